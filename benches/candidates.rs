@@ -0,0 +1,47 @@
+//! Times both `CandidateSet` backends filtering the Super Mastermind space.
+//!
+//! Run with `cargo bench --bench candidates`.
+
+use std::time::{Duration, Instant};
+
+use mastermind::deduction::{Backend, CandidateSet};
+use mastermind::{Code, GameConfig, Rng};
+
+const GAMES: usize = 20;
+
+fn main() {
+    let config = GameConfig::super_mastermind();
+    let mut rng = Rng::seeded(1);
+    let games: Vec<(Code, Vec<Code>)> = (0..GAMES)
+        .map(|_| {
+            let hidden = Code::random(&config, &mut rng);
+            let guesses = (0..4).map(|_| Code::random(&config, &mut rng)).collect();
+            (hidden, guesses)
+        })
+        .collect();
+
+    for backend in [Backend::Sparse, Backend::Dense] {
+        let mut setup = Duration::ZERO;
+        let mut filtering = Duration::ZERO;
+        let mut remaining = 0;
+        for (hidden, guesses) in &games {
+            let start = Instant::now();
+            let mut candidates = CandidateSet::with_backend(&config, backend);
+            setup += start.elapsed();
+
+            let start = Instant::now();
+            for guess in guesses {
+                candidates.observe(guess, &hidden.score(guess));
+            }
+            remaining += candidates.len();
+            filtering += start.elapsed();
+        }
+        println!(
+            "{:?}: {:?} to set up, {:?} to filter four guesses, {} candidates left on average",
+            backend,
+            setup / GAMES as u32,
+            filtering / GAMES as u32,
+            remaining / GAMES
+        );
+    }
+}
@@ -0,0 +1,36 @@
+//! Measures the heap `CodeSpace` and `LazyCodeSpace` need to walk every code
+//! of 6 pegs in 8 colors, profiling allocations with dhat.
+//!
+//! Run with `cargo bench --bench code_space --features dhat-heap`. The full
+//! profile is written to `dhat-heap.json`, for dhat's viewer.
+
+use mastermind::{CodeSpace, Color, GameConfig, LazyCodeSpace};
+
+#[global_allocator]
+static ALLOCATOR: dhat::Alloc = dhat::Alloc;
+
+/// Returns the most bytes held at once since the profiler started.
+fn peak() -> usize {
+    dhat::HeapStats::get().max_bytes
+}
+
+fn main() {
+    let _profiler = dhat::Profiler::new_heap();
+    let config = GameConfig {
+        colors: (0..8).map(Color::from_index).collect(),
+        code_length: 6,
+        ..GameConfig::default()
+    };
+    let before = dhat::HeapStats::get().curr_bytes;
+    // dhat only keeps the peak of the whole run, so the lazy walk, which
+    // should hold the least, goes first.
+    let lazy_count = LazyCodeSpace::new(&config).count();
+    let lazy = peak() - before;
+    let eager_count = CodeSpace::new(&config).iter().count();
+    let eager = peak() - before;
+    assert_eq!(eager_count, lazy_count);
+    assert!(lazy < eager, "the lazy walk held {} bytes", lazy);
+    println!("{} codes of 6 pegs in 8 colors", lazy_count);
+    println!("CodeSpace: {} bytes at peak", eager);
+    println!("LazyCodeSpace: {} bytes at peak", lazy);
+}
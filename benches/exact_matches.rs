@@ -0,0 +1,60 @@
+//! Times `Code::count_exact_matches` against `Code::count_exact_matches_simd`
+//! over every pair of classic codes, and over pairs of random 16-peg codes,
+//! which fill a whole SSE register.
+//!
+//! The SSE4.1 count is only built when the target enables the feature, so
+//! run with `RUSTFLAGS="-C target-feature=+sse4.1" cargo bench --bench
+//! exact_matches` to time it; without the flag both sides count one peg at a
+//! time.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use mastermind::{Code, CodeSpace, GameConfig, Rng};
+
+const ROUNDS: u32 = 10;
+
+/// Runs `count` over every pair of `codes` `ROUNDS` times, returning the
+/// average time a round took and the matches it counted.
+fn time(codes: &[Code], count: impl Fn(&Code, &Code) -> usize) -> (Duration, usize) {
+    let mut matches = 0;
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        matches = 0;
+        for a in codes {
+            for b in codes {
+                matches += count(black_box(a), black_box(b));
+            }
+        }
+    }
+    (start.elapsed() / ROUNDS, matches)
+}
+
+/// Times both counts over every pair of `codes` and prints the results.
+fn compare(name: &str, codes: &[Code]) {
+    let (scalar, scalar_matches) = time(codes, Code::count_exact_matches);
+    let (simd, simd_matches) = time(codes, Code::count_exact_matches_simd);
+    assert_eq!(scalar_matches, simd_matches);
+    println!("{}: {} pairs of codes", name, codes.len() * codes.len());
+    println!("  scalar: {:?}", scalar);
+    println!("  simd: {:?}", simd);
+}
+
+fn main() {
+    let simd_built = cfg!(all(target_arch = "x86_64", target_feature = "sse4.1"));
+    println!("sse4.1 count built: {}", simd_built);
+
+    let classic: Vec<Code> = CodeSpace::new(&GameConfig::default())
+        .iter()
+        .cloned()
+        .collect();
+    compare("classic", &classic);
+
+    let config = GameConfig {
+        code_length: 16,
+        ..GameConfig::super_mastermind()
+    };
+    let mut rng = Rng::seeded(1);
+    let long: Vec<Code> = (0..1000).map(|_| Code::random(&config, &mut rng)).collect();
+    compare("16 pegs", &long);
+}
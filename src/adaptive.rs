@@ -0,0 +1,230 @@
+//! Adaptive difficulty: the rules of the next game follow how the last few
+//! went.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::code::Color;
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::history::{GameSession, Outcome, Variant};
+
+/// Wins in a row, each quick, that make the next game harder.
+const QUICK_WINS_TO_STEP_UP: usize = 3;
+
+/// Losses in a row that make the next game easier.
+const LOSSES_TO_STEP_DOWN: usize = 2;
+
+/// The rules adaptive difficulty adjusts: how many pegs a code has and how
+/// many colors they are drawn from, always the first colors of `Color::ALL`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Settings {
+    pub code_length: usize,
+    pub color_count: usize,
+}
+
+impl Settings {
+    /// Returns the settings `config` is played with.
+    pub fn of(config: &GameConfig) -> Settings {
+        Settings {
+            code_length: config.code_length,
+            color_count: config.colors.len(),
+        }
+    }
+
+    /// Returns `config` changed to these settings.
+    pub fn apply(&self, config: GameConfig) -> GameConfig {
+        GameConfig {
+            code_length: self.code_length,
+            colors: Color::ALL[..self.color_count].to_vec(),
+            ..config
+        }
+    }
+
+    /// Returns the most guesses a win may take and still count as quick.
+    pub fn quick_win_guesses(&self) -> usize {
+        self.code_length + 2
+    }
+}
+
+/// Formats settings the way `FromStr` reads them, like `4x6` for four pegs
+/// in six colors.
+impl fmt::Display for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.code_length, self.color_count)
+    }
+}
+
+impl FromStr for Settings {
+    type Err = GameError;
+
+    /// Parses `PEGSxCOLORS`, such as `4x6`.
+    ///
+    /// ```
+    /// use mastermind::adaptive::Settings;
+    ///
+    /// let settings: Settings = "5x8".parse().unwrap();
+    /// assert_eq!((settings.code_length, settings.color_count), (5, 8));
+    /// assert_eq!(settings.to_string(), "5x8");
+    /// assert!("5x9".parse::<Settings>().is_err());
+    /// assert!("0x4".parse::<Settings>().is_err());
+    /// assert!("five".parse::<Settings>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            GameError::ParseError(format!(
+                "\"{}\" is not PEGSxCOLORS, with 1 to {} colors",
+                s,
+                Color::ALL.len()
+            ))
+        };
+        let (pegs, colors) = s.split_once('x').ok_or_else(invalid)?;
+        let settings = Settings {
+            code_length: pegs.parse().map_err(|_| invalid())?,
+            color_count: colors.parse().map_err(|_| invalid())?,
+        };
+        if settings.code_length == 0 || !(1..=Color::ALL.len()).contains(&settings.color_count) {
+            return Err(invalid());
+        }
+        Ok(settings)
+    }
+}
+
+/// The easiest and hardest settings adaptive difficulty may choose.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bounds {
+    pub floor: Settings,
+    pub ceiling: Settings,
+}
+
+impl Default for Bounds {
+    /// From three pegs in three colors up to Super Mastermind.
+    fn default() -> Self {
+        Bounds {
+            floor: Settings {
+                code_length: 3,
+                color_count: 3,
+            },
+            ceiling: Settings {
+                code_length: 5,
+                color_count: Color::ALL.len(),
+            },
+        }
+    }
+}
+
+impl Bounds {
+    /// Returns `settings` moved inside these bounds.
+    pub fn clamp(&self, settings: Settings) -> Settings {
+        Settings {
+            code_length: settings
+                .code_length
+                .clamp(self.floor.code_length, self.ceiling.code_length),
+            color_count: settings
+                .color_count
+                .clamp(self.floor.color_count, self.ceiling.color_count),
+        }
+    }
+}
+
+/// The part of a finished game adaptive difficulty looks at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameResult {
+    pub settings: Settings,
+    pub outcome: Outcome,
+    pub guesses: usize,
+}
+
+impl GameResult {
+    /// Returns the result of a recorded classic game, or `None` for other
+    /// variants, which play by different rules.
+    pub fn from_session(session: &GameSession) -> Option<GameResult> {
+        (session.variant == Variant::Classic).then(|| GameResult {
+            settings: Settings::of(&session.config),
+            outcome: session.outcome,
+            guesses: session.rounds.len(),
+        })
+    }
+
+    fn is_quick_win(&self) -> bool {
+        self.outcome == Outcome::Won && self.guesses <= self.settings.quick_win_guesses()
+    }
+}
+
+/// Chooses the settings for the next game from `history`, oldest first.
+///
+/// Only the latest games played at `current` count, so every change starts
+/// afresh. After three quick wins in a row (see `Settings::quick_win_guesses`)
+/// a color is added, or a peg once the colors are at the ceiling. After two
+/// losses in a row a peg is taken away, or a color once the pegs are at the
+/// floor. The result always lies within `bounds`.
+///
+/// ```
+/// use mastermind::adaptive::{next_settings, Bounds, GameResult, Settings};
+/// use mastermind::Outcome::{self, Abandoned, Lost, Won};
+///
+/// let s = |s: &str| s.parse::<Settings>().unwrap();
+/// let game = |settings: &str, outcome: Outcome, guesses: usize| GameResult {
+///     settings: s(settings),
+///     outcome,
+///     guesses,
+/// };
+/// let bounds = Bounds::default();
+/// let cases = [
+///     // (history, current, expected)
+///     (vec![], "4x4", "4x4"),
+///     (vec![game("4x4", Won, 4), game("4x4", Won, 5)], "4x4", "4x4"),
+///     (vec![game("4x4", Won, 4), game("4x4", Won, 5), game("4x4", Won, 6)], "4x4", "4x5"),
+///     // One slow win in the run is not enough.
+///     (vec![game("4x4", Won, 4), game("4x4", Won, 7), game("4x4", Won, 6)], "4x4", "4x4"),
+///     // Wins at earlier settings do not count towards the current ones.
+///     (vec![game("4x4", Won, 3), game("4x4", Won, 3), game("4x5", Won, 3)], "4x5", "4x5"),
+///     // With every color in play, a peg is added instead.
+///     (vec![game("4x8", Won, 5); 3], "4x8", "5x8"),
+///     (vec![game("5x8", Won, 5); 3], "5x8", "5x8"),
+///     (vec![game("4x6", Lost, 10), game("4x6", Lost, 10)], "4x6", "3x6"),
+///     (vec![game("4x6", Lost, 10), game("4x6", Won, 9), game("4x6", Lost, 10)], "4x6", "4x6"),
+///     // Leaving a game is neither a win nor a loss, but it breaks a run.
+///     (vec![game("4x6", Lost, 3), game("4x6", Abandoned, 2), game("4x6", Lost, 10)], "4x6", "4x6"),
+///     // At the floor for pegs, a color goes instead.
+///     (vec![game("3x5", Lost, 10); 2], "3x5", "3x4"),
+///     (vec![game("3x3", Lost, 10); 2], "3x3", "3x3"),
+///     // Settings outside the bounds are brought inside first.
+///     (vec![], "6x2", "5x3"),
+/// ];
+/// for (history, current, expected) in cases {
+///     assert_eq!(
+///         next_settings(&history, s(current), &bounds),
+///         s(expected),
+///         "{:?} at {}",
+///         history,
+///         current
+///     );
+/// }
+/// ```
+pub fn next_settings(history: &[GameResult], current: Settings, bounds: &Bounds) -> Settings {
+    let current = bounds.clamp(current);
+    let recent: Vec<&GameResult> = history
+        .iter()
+        .rev()
+        .take_while(|game| game.settings == current)
+        .collect();
+    let run = |test: &dyn Fn(&GameResult) -> bool| recent.iter().take_while(|g| test(g)).count();
+
+    let mut next = current;
+    if run(&GameResult::is_quick_win) >= QUICK_WINS_TO_STEP_UP {
+        if current.color_count < bounds.ceiling.color_count {
+            next.color_count += 1;
+        } else if current.code_length < bounds.ceiling.code_length {
+            next.code_length += 1;
+        }
+    } else if run(&|game| game.outcome.is_loss()) >= LOSSES_TO_STEP_DOWN {
+        if current.code_length > bounds.floor.code_length {
+            next.code_length -= 1;
+        } else if current.color_count > bounds.floor.color_count {
+            next.color_count -= 1;
+        }
+    }
+    next
+}
@@ -0,0 +1,458 @@
+//! Measuring how good guesses are, for solvers and for finished games.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::board::{Board, Round};
+use crate::code::{Code, CodeSpace, LazyCodeSpace};
+use crate::config::GameConfig;
+use crate::score::Score;
+use crate::solver::{score_buckets, MostPartsSolver, OpeningSolver, Solver};
+
+/// How a solver fared over every hidden code of a configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolverReport {
+    /// The forced first guess, if the solver was not left to choose its own.
+    pub first_guess: Option<Code>,
+    pub games: usize,
+    /// The most guesses any solved game took.
+    pub worst_case: usize,
+    /// The mean number of guesses over solved games.
+    pub average: f64,
+    /// Games the solver gave up on because it had no guess left.
+    pub unsolved: usize,
+}
+
+/// Plays a fresh solver from `new_solver` against every code valid under
+/// `config`, opening with `first_guess` when one is given.
+///
+/// Solvers are deterministic, so games that have seen the same scores so far
+/// get the same next guess. The games are played together as a tree of such
+/// histories, asking a solver once per history instead of once per game and
+/// round, which keeps spaces as large as Super Mastermind's practical.
+///
+/// ```
+/// use mastermind::analysis::analyze;
+/// use mastermind::{Code, GameConfig, MostPartsSolver};
+///
+/// let config = GameConfig {
+///     code_length: 3,
+///     ..GameConfig::default()
+/// };
+/// let solver = || MostPartsSolver::new(&config);
+/// let chosen = analyze(&config, None, solver);
+/// let rrr = Code::parse(String::from("rrr"), &config).unwrap();
+/// let forced = analyze(&config, Some(&rrr), solver);
+///
+/// assert_eq!(chosen.unsolved, 0);
+/// assert!(forced.worst_case >= chosen.worst_case);
+/// assert!(forced.average > chosen.average);
+/// ```
+pub fn analyze<S, F>(
+    config: &GameConfig,
+    first_guess: Option<&Code>,
+    mut new_solver: F,
+) -> SolverReport
+where
+    S: Solver,
+    F: FnMut() -> S,
+{
+    let hidden: Vec<Code> = LazyCodeSpace::new(config).collect();
+    let mut tally = Tally::default();
+    let mut new_solver = || OpeningSolver::new(new_solver(), first_guess.cloned());
+    play_together(&mut new_solver, &mut Vec::new(), hidden, &mut tally);
+    let solved = tally.games - tally.unsolved;
+    SolverReport {
+        first_guess: first_guess.cloned(),
+        games: tally.games,
+        worst_case: tally.worst_case,
+        average: if solved == 0 {
+            0.0
+        } else {
+            tally.total as f64 / solved as f64
+        },
+        unsolved: tally.unsolved,
+    }
+}
+
+/// Running totals for `analyze`.
+#[derive(Default)]
+struct Tally {
+    games: usize,
+    worst_case: usize,
+    total: usize,
+    unsolved: usize,
+}
+
+/// Plays every code of `hidden`, all of which scored the same against the
+/// guesses of `rounds`, to the end.
+fn play_together<S, F>(
+    new_solver: &mut F,
+    rounds: &mut Vec<Round>,
+    hidden: Vec<Code>,
+    tally: &mut Tally,
+) where
+    S: Solver,
+    F: FnMut() -> S,
+{
+    let Some(guess) = new_solver().next_guess(rounds) else {
+        tally.games += hidden.len();
+        tally.unsolved += hidden.len();
+        return;
+    };
+    let mut groups: HashMap<Score, Vec<Code>> = HashMap::new();
+    for code in hidden {
+        let score = code.score(&guess);
+        if score.is_win(code.len()) {
+            let guesses = rounds.len() + 1;
+            tally.games += 1;
+            tally.worst_case = tally.worst_case.max(guesses);
+            tally.total += guesses;
+        } else {
+            groups.entry(score).or_default().push(code);
+        }
+    }
+    for (score, group) in groups {
+        rounds.push(Round::new(guess.clone(), score));
+        play_together(new_solver, rounds, group, tally);
+        rounds.pop();
+    }
+}
+
+/// How well a first guess splits the codes of a configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpeningReport {
+    pub guess: Code,
+    /// The most codes that could remain after any score this guess can get.
+    pub worst_case: usize,
+    /// The number of codes expected to remain, with every hidden code equally
+    /// likely.
+    pub expected: f64,
+}
+
+/// Scores every possible first guess under `config` by the candidates it would
+/// leave, best first: smallest worst case, then smallest expected size, then
+/// code order.
+///
+/// ```
+/// use mastermind::analysis::rank_openings;
+/// use mastermind::{Color, GameConfig};
+///
+/// // With four colors, one repeated color plus two others, like "rrgb",
+/// // splits the codes best.
+/// let ranking = rank_openings(&GameConfig::default());
+/// assert_eq!(
+///     ranking[0].guess.colors(),
+///     [Color::Red, Color::Red, Color::Green, Color::Blue]
+/// );
+/// assert_eq!(ranking[0].worst_case, 46);
+/// ```
+pub fn rank_openings(config: &GameConfig) -> Vec<OpeningReport> {
+    let codes: Vec<Code> = LazyCodeSpace::new(config).collect();
+    let mut reports: Vec<OpeningReport> = codes
+        .iter()
+        .map(|guess| {
+            let buckets = score_buckets(guess, &codes);
+            let worst_case = buckets.iter().map(|(_, n)| *n).max().unwrap_or(0);
+            let squares: usize = buckets.iter().map(|(_, n)| n * n).sum();
+            OpeningReport {
+                guess: guess.clone(),
+                worst_case,
+                expected: squares as f64 / codes.len() as f64,
+            }
+        })
+        .collect();
+    reports.sort_by(|a, b| {
+        a.worst_case
+            .cmp(&b.worst_case)
+            .then(a.expected.total_cmp(&b.expected))
+            .then_with(|| a.guess.cmp(&b.guess))
+    });
+    reports
+}
+
+/// A judgement of one guess in a finished game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// No guess could have guaranteed fewer remaining codes.
+    Fine,
+    /// Another guess would have left fewer codes in the worst case.
+    Inefficient,
+    /// The guess could not have been the code, given the earlier feedback.
+    AlreadyImpossible,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Verdict::Fine => "fine",
+            Verdict::Inefficient => "inefficient",
+            Verdict::AlreadyImpossible => "already impossible",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// How one round of a game compares with the best play available at the time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoundReview {
+    /// Codes still possible before the guess.
+    pub candidates_before: usize,
+    /// The most codes the guess could have left, over every possible score.
+    pub worst_case: usize,
+    /// The guess that minimizes the worst case, earliest in code order on ties.
+    pub best_guess: Code,
+    /// The most codes `best_guess` could have left.
+    pub best_worst_case: usize,
+    pub verdict: Verdict,
+}
+
+/// Reviews every round of `board` against a minimax guess. A guess that wins
+/// is never called inefficient.
+///
+/// ```
+/// use mastermind::analysis::{review_game, Verdict};
+/// use mastermind::{Board, Code, GameConfig};
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let mut board = Board::new(config.clone(), code("rgby"));
+/// for guess in ["rrrr", "rggg", "gggg", "rgby"] {
+///     board.guess(code(guess));
+/// }
+///
+/// let review = review_game(&board);
+/// let summary: Vec<_> = review
+///     .iter()
+///     .map(|r| (r.candidates_before, r.worst_case, r.best_worst_case, r.verdict))
+///     .collect();
+/// assert_eq!(
+///     summary,
+///     [
+///         (256, 108, 46, Verdict::Inefficient),
+///         (108, 24, 16, Verdict::Inefficient),
+///         (12, 12, 2, Verdict::AlreadyImpossible),
+///         (12, 3, 2, Verdict::Fine),
+///     ]
+/// );
+/// ```
+pub fn review_game(board: &Board) -> Vec<RoundReview> {
+    let space = CodeSpace::new(board.config());
+    let rounds = board.rounds();
+    let mut candidates: Vec<&Code> = space.iter().collect();
+    let mut reviews = Vec::new();
+    for round in rounds {
+        let worst = |guess: &Code| {
+            score_buckets(guess, candidates.iter().copied())
+                .iter()
+                .map(|(_, n)| *n)
+                .max()
+                .unwrap_or(0)
+        };
+        let worst_case = worst(round.guess());
+        let (best_guess, best_worst_case) = space
+            .iter()
+            .map(|code| (code, worst(code)))
+            .min_by_key(|(_, n)| *n)
+            .map(|(code, n)| (code.clone(), n))
+            .expect("the code space is never empty");
+        let verdict = if !candidates.contains(&round.guess()) {
+            Verdict::AlreadyImpossible
+        } else if worst_case > best_worst_case && !round.wins() {
+            Verdict::Inefficient
+        } else {
+            Verdict::Fine
+        };
+        reviews.push(RoundReview {
+            candidates_before: candidates.len(),
+            worst_case,
+            best_guess,
+            best_worst_case,
+            verdict,
+        });
+        candidates.retain(|code| code.is_consistent_with(round));
+    }
+    reviews
+}
+
+/// How much one round of a game could be expected to reveal, next to the most
+/// any guess could have.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntropyReview {
+    /// Codes still possible before the guess.
+    pub candidates_before: usize,
+    /// The bits the guess was expected to reveal, averaged over every score
+    /// it could have received.
+    pub expected_bits: f64,
+    /// The guess expected to reveal the most, earliest in code order on ties.
+    pub best_guess: Code,
+    /// The bits `best_guess` was expected to reveal.
+    pub best_expected_bits: f64,
+}
+
+impl EntropyReview {
+    /// Returns whether the guess was expected to reveal less than half of
+    /// what the best guess would have.
+    pub fn is_mistake(&self) -> bool {
+        self.expected_bits < self.best_expected_bits / 2.0
+    }
+}
+
+/// Returns the bits scoring `guess` is expected to reveal about which of
+/// `candidates` is the hidden code: the entropy of how the scores split them.
+///
+/// ```
+/// use mastermind::analysis::expected_bits;
+/// use mastermind::{Code, CodeSpace, GameConfig};
+///
+/// let config = GameConfig::default();
+/// let space = CodeSpace::new(&config);
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+///
+/// assert_eq!(expected_bits(&code("rrrr"), [&code("rgby")]), 0.0);
+/// assert_eq!(expected_bits(&code("rrrr"), [&code("rgby"), &code("gggg")]), 1.0);
+/// assert!(expected_bits(&code("rgby"), space.iter()) > expected_bits(&code("rrrr"), space.iter()));
+/// ```
+pub fn expected_bits<'a>(guess: &Code, candidates: impl IntoIterator<Item = &'a Code>) -> f64 {
+    let buckets = score_buckets(guess, candidates);
+    let total: usize = buckets.iter().map(|(_, n)| n).sum();
+    buckets
+        .iter()
+        .map(|(_, n)| {
+            let p = *n as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Reviews every round of `board` against the guess expected to reveal the
+/// most about the hidden code.
+///
+/// ```
+/// use mastermind::analysis::entropy_review;
+/// use mastermind::{Board, Code, GameConfig};
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let mut board = Board::new(config.clone(), code("rgby"));
+/// for guess in ["rrrr", "rggg", "gggg", "rgby"] {
+///     board.guess(code(guess));
+/// }
+///
+/// let review = entropy_review(&board);
+/// let mistakes: Vec<bool> = review.iter().map(|r| r.is_mistake()).collect();
+/// assert_eq!(mistakes, [false, false, true, false]);
+/// assert_eq!(review[0].best_guess, code("rrgb"));
+/// ```
+pub fn entropy_review(board: &Board) -> Vec<EntropyReview> {
+    let space = CodeSpace::new(board.config());
+    let mut candidates: Vec<&Code> = space.iter().collect();
+    let mut reviews = Vec::new();
+    for round in board {
+        let expected = |guess: &Code| expected_bits(guess, candidates.iter().copied());
+        let (best_guess, best_expected_bits) = space
+            .iter()
+            .map(|code| (code, expected(code)))
+            .fold(
+                None,
+                |best: Option<(&Code, f64)>, (code, bits)| match best {
+                    Some((_, most)) if most >= bits => best,
+                    _ => Some((code, bits)),
+                },
+            )
+            .map(|(code, bits)| (code.clone(), bits))
+            .expect("the code space is never empty");
+        reviews.push(EntropyReview {
+            candidates_before: candidates.len(),
+            expected_bits: expected(round.guess()),
+            best_guess,
+            best_expected_bits,
+        });
+        candidates.retain(|code| code.is_consistent_with(round));
+    }
+    reviews
+}
+
+/// Rates how hard a hidden code is to crack.
+pub struct GameDifficulty;
+
+impl GameDifficulty {
+    /// Rates `code` under `config` from 0.0, the easiest, to 1.0, the hardest,
+    /// weighing together:
+    ///
+    /// - how many distinct colors it uses, out of as many as it could;
+    /// - how many guesses `MostPartsSolver` takes to crack it, between one
+    ///   and the most it takes for any code;
+    /// - whether it is one of the codes that take the solver the most guesses.
+    ///
+    /// This plays the solver against every code of `config`, so it is only
+    /// quick for small games.
+    ///
+    /// ```
+    /// use mastermind::analysis::GameDifficulty;
+    /// use mastermind::{Code, GameConfig};
+    ///
+    /// let config = GameConfig {
+    ///     code_length: 3,
+    ///     ..GameConfig::default()
+    /// };
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let easy = GameDifficulty::score(&code("rrr"), &config);
+    /// let hard = GameDifficulty::score(&code("rgb"), &config);
+    /// assert!(easy < hard);
+    /// assert!((0.0..=1.0).contains(&easy) && (0.0..=1.0).contains(&hard));
+    /// ```
+    pub fn score(code: &Code, config: &GameConfig) -> f64 {
+        let fraction = |n: usize, most: usize| {
+            if most <= 1 {
+                0.0
+            } else {
+                (n.min(most) - 1) as f64 / (most - 1) as f64
+            }
+        };
+        let distinct = code.colors().iter().collect::<HashSet<_>>();
+        let colors = fraction(distinct.len(), config.code_length.min(config.colors.len()));
+
+        let report = analyze(config, None, || MostPartsSolver::new(config));
+        let guesses = solve(code, MostPartsSolver::new(config));
+        let (guesses, worst_case) = match guesses {
+            Some(n) => (fraction(n, report.worst_case), n >= report.worst_case),
+            None => (1.0, true),
+        };
+
+        0.4 * colors + 0.4 * guesses + if worst_case { 0.2 } else { 0.0 }
+    }
+}
+
+/// Returns how many guesses `solver` takes to crack `hidden`, or `None` if it
+/// runs out of guesses to make.
+fn solve(hidden: &Code, mut solver: impl Solver) -> Option<usize> {
+    let mut rounds = Vec::new();
+    loop {
+        let round = Round::from_guess(solver.next_guess(&rounds)?, hidden);
+        let wins = round.wins();
+        rounds.push(round);
+        if wins {
+            return Some(rounds.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code::Color;
+
+    #[test]
+    fn codes_the_solver_struggles_with_rate_harder() {
+        let config = GameConfig {
+            colors: Color::ALL[..5].to_vec(),
+            ..GameConfig::default()
+        };
+        let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+        let easy = GameDifficulty::score(&code("rrrr"), &config);
+        let hard = GameDifficulty::score(&code("rpgb"), &config);
+        assert!(easy < hard);
+        assert!((0.0..=1.0).contains(&easy) && (0.0..=1.0).contains(&hard));
+    }
+}
@@ -0,0 +1,267 @@
+//! Saving the game in progress when the player presses Ctrl-C, and, with
+//! `--autosave`, after every round so a game survives a crash.
+//!
+//! Autosaved games are kept in a `recovery` directory, one file per game, and
+//! removed when the game ends normally. Any left behind belong to a run that
+//! died, and are offered back on the next interactive launch.
+
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::board::Board;
+use crate::paths::profile_dir;
+use crate::Result;
+
+/// The exit code used when a game is interrupted, whether or not it was saved.
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// Returns `autosave.json` in `player`'s profile directory.
+pub fn autosave_path(player: Option<&str>) -> Option<PathBuf> {
+    Some(profile_dir(player)?.join("autosave.json"))
+}
+
+/// Returns the directory `player`'s autosaved games are kept in.
+pub fn recovery_dir(player: Option<&str>) -> Option<PathBuf> {
+    Some(profile_dir(player)?.join("recovery"))
+}
+
+/// Names a game by when it started, in seconds since the epoch, and the seed
+/// it was set up with, so two games never share an autosave file.
+pub fn game_id(started_at: u64, seed: u64) -> String {
+    format!("{}-{:016x}", started_at, seed)
+}
+
+/// The file one game is autosaved to after every round.
+///
+/// ```
+/// use mastermind::autosave::{game_id, leftover_games, RecoveryFile};
+/// use mastermind::{Board, Code, GameConfig};
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let dir = dir.path().join("recovery");
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let mut board = Board::new(config.clone(), code("rgby"));
+/// board.guess(code("rrgg"));
+///
+/// let file = RecoveryFile::new(&dir, &game_id(1_700_000_000, 42));
+/// file.save(&board).unwrap();
+/// let leftovers = leftover_games(&dir).unwrap();
+/// assert_eq!(leftovers.len(), 1);
+/// assert_eq!(leftovers[0].board.rounds(), board.rounds());
+/// assert_eq!(leftovers[0].file, file);
+/// assert!(leftovers[0].describe().starts_with("4 pegs, 4 colors, 1 round played, "));
+///
+/// file.remove().unwrap();
+/// assert!(leftover_games(&dir).unwrap().is_empty());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecoveryFile {
+    path: PathBuf,
+}
+
+impl RecoveryFile {
+    /// Returns the file in `dir` for the game named `id`.
+    pub fn new(dir: &Path, id: &str) -> Self {
+        Self {
+            path: dir.join(format!("{}.json", id)),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Writes `board` in the format `--load` reads. The game is written to a
+    /// file beside this one first and moved into place, so a crash part way
+    /// through leaves the last round's save whole.
+    pub fn save(&self, board: &Board) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let partial = self.path.with_extension("json.partial");
+        std::fs::write(&partial, serde_json::to_string(board)?)?;
+        std::fs::rename(&partial, &self.path)?;
+        Ok(())
+    }
+
+    /// Removes the file, for when the game has ended. A file that was never
+    /// written is not an error.
+    pub fn remove(&self) -> Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An autosaved game left behind by a run that did not finish it.
+pub struct Leftover {
+    pub file: RecoveryFile,
+    pub board: Board,
+    /// How long ago the game was last saved.
+    pub age: Duration,
+}
+
+impl Leftover {
+    /// Describes the game's settings, progress and age on one line, like
+    /// `4 pegs, 6 colors, 2 rounds played, 5 minutes ago`.
+    pub fn describe(&self) -> String {
+        let config = self.board.config();
+        let rounds = self.board.rounds().len();
+        format!(
+            "{} pegs, {} colors, {} round{} played, {}",
+            config.code_length,
+            config.colors.len(),
+            rounds,
+            if rounds == 1 { "" } else { "s" },
+            describe_age(self.age)
+        )
+    }
+}
+
+/// Returns every game autosaved in `dir`, most recently saved first. Files
+/// that cannot be read as a game are skipped.
+pub fn leftover_games(dir: &Path) -> Result<Vec<Leftover>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut leftovers = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(board) = serde_json::from_str::<Board>(&text) else {
+            continue;
+        };
+        let age = std::fs::metadata(&path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .unwrap_or_default();
+        leftovers.push(Leftover {
+            file: RecoveryFile { path },
+            board,
+            age,
+        });
+    }
+    leftovers.sort_by_key(|leftover| leftover.age);
+    Ok(leftovers)
+}
+
+/// Describes `age` in the largest whole unit that fits.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use mastermind::autosave::describe_age;
+///
+/// assert_eq!(describe_age(Duration::from_secs(20)), "just now");
+/// assert_eq!(describe_age(Duration::from_secs(60)), "1 minute ago");
+/// assert_eq!(describe_age(Duration::from_secs(7_300)), "2 hours ago");
+/// assert_eq!(describe_age(Duration::from_secs(3 * 86_400)), "3 days ago");
+/// ```
+pub fn describe_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    let (count, unit) = match secs {
+        0..=59 => return String::from("just now"),
+        60..=3_599 => (secs / 60, "minute"),
+        3_600..=86_399 => (secs / 3_600, "hour"),
+        _ => (secs / 86_400, "day"),
+    };
+    format!(
+        "{} {}{} ago",
+        count,
+        unit,
+        if count == 1 { "" } else { "s" }
+    )
+}
+
+/// The game to save if play is interrupted, shared with the thread that
+/// watches for Ctrl-C.
+#[derive(Clone, Default)]
+pub struct Autosave {
+    pending: Arc<Mutex<Option<String>>>,
+}
+
+impl Autosave {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remembers `board` as the game to save.
+    pub fn update(&self, board: &Board) {
+        *self.pending.lock().unwrap() = serde_json::to_string(board).ok();
+    }
+
+    /// Forgets the game, for when it has finished.
+    pub fn clear(&self) {
+        *self.pending.lock().unwrap() = None;
+    }
+
+    /// Writes the remembered game to `path` in the same format `--load` reads,
+    /// returning whether there was a game to write.
+    pub fn save(&self, path: &Path) -> Result<bool> {
+        let Some(game) = self.pending.lock().unwrap().clone() else {
+            return Ok(false);
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, game)?;
+        Ok(true)
+    }
+
+    /// Sets the Ctrl-C handler to restore the terminal, save the game to `path`
+    /// and exit with `EXIT_INTERRUPTED`. With no game to save it asks for a
+    /// second Ctrl-C, and one within two seconds of another always quits.
+    pub fn install(&self, path: PathBuf) -> Result<()> {
+        use std::time::Instant;
+
+        const FORCE_QUIT_WINDOW: Duration = Duration::from_secs(2);
+
+        let autosave = self.clone();
+        let mut last: Option<Instant> = None;
+        ctrlc::set_handler(move || {
+            restore_terminal();
+            if last.is_some_and(|at| at.elapsed() < FORCE_QUIT_WINDOW) {
+                std::process::exit(EXIT_INTERRUPTED);
+            }
+            last = Some(Instant::now());
+            match autosave.save(&path) {
+                Ok(true) => {
+                    eprintln!(
+                        "\ngame saved to {0}, resume with `mastermind --load {0}`",
+                        path.display()
+                    );
+                    std::process::exit(EXIT_INTERRUPTED);
+                }
+                Ok(false) => eprintln!("\npress Ctrl-C again to quit"),
+                Err(e) => eprintln!(
+                    "\ncould not save the game: {}; press Ctrl-C again to quit",
+                    e
+                ),
+            }
+        })?;
+        Ok(())
+    }
+}
+
+/// Leaves raw mode so the shell is usable after exiting from the handler.
+#[cfg(feature = "color")]
+fn restore_terminal() {
+    if crossterm::terminal::is_raw_mode_enabled().unwrap_or(false) {
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
+
+#[cfg(not(feature = "color"))]
+fn restore_terminal() {}
@@ -0,0 +1,245 @@
+//! A fixed set of timed workloads for spotting performance regressions with
+//! `mastermind bench`, without any benchmarking crate.
+//!
+//! Every workload is seeded or scripted, so runs on the same machine time the
+//! same work and can be compared against a saved baseline.
+
+use std::fmt::Write;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::analysis::analyze;
+use crate::code::{Code, LazyCodeSpace};
+use crate::config::GameConfig;
+use crate::deduction::CandidateSet;
+use crate::random::Rng;
+use crate::solver::MostPartsSolver;
+
+/// A piece of work to time. `run` returns a value derived from everything it
+/// computed so none of it can be optimized away.
+pub struct Workload {
+    pub name: &'static str,
+    pub run: fn() -> u64,
+}
+
+/// Returns the workloads `mastermind bench` times, in the order it prints
+/// them.
+pub fn workloads() -> Vec<Workload> {
+    vec![
+        Workload {
+            name: "score table",
+            run: score_table,
+        },
+        Workload {
+            name: "10k scores",
+            run: random_scores,
+        },
+        Workload {
+            name: "full solve",
+            run: full_solve,
+        },
+        Workload {
+            name: "candidate filtering",
+            run: candidate_filtering,
+        },
+    ]
+}
+
+/// Scores every classic code against every other, keeping each score as the
+/// compact pair of peg counts.
+fn score_table() -> u64 {
+    let codes: Vec<Code> = LazyCodeSpace::new(&GameConfig::default()).collect();
+    let table: Vec<(u8, u8)> = codes
+        .iter()
+        .flat_map(|guess| {
+            codes
+                .iter()
+                .map(move |hidden| hidden.score(guess).as_compact_u8())
+        })
+        .collect();
+    table.iter().map(|&(blacks, _)| u64::from(blacks)).sum()
+}
+
+/// Scores 10,000 seeded random pairs of classic codes.
+fn random_scores() -> u64 {
+    let config = GameConfig::default();
+    let mut rng = Rng::seeded(1);
+    (0..10_000)
+        .map(|_| {
+            let guess = Code::random(&config, &mut rng);
+            let hidden = Code::random(&config, &mut rng);
+            hidden.score(&guess).white_count() as u64
+        })
+        .sum()
+}
+
+/// Plays the solver `--analyze` uses against all 1296 classic codes.
+fn full_solve() -> u64 {
+    let config = GameConfig::default();
+    let report = analyze(&config, None, || MostPartsSolver::new(&config));
+    (report.average * report.games as f64) as u64
+}
+
+/// Narrows the Super Mastermind candidates with a scripted game.
+fn candidate_filtering() -> u64 {
+    let config = GameConfig::super_mastermind();
+    let code = |s: &str| Code::parse(String::from(s), &config).expect("the script is valid");
+    let hidden = code("pkwnb");
+    let mut candidates = CandidateSet::new(&config);
+    for guess in ["rrggb", "yyppn", "wwkkr", "bpkwn"] {
+        let guess = code(guess);
+        candidates.observe(&guess, &hidden.score(&guess));
+    }
+    candidates.len() as u64
+}
+
+/// The median time of one workload.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Measurement {
+    pub name: String,
+    pub median: Duration,
+}
+
+/// Runs `workload` `warmup` times untimed, then `iterations` times timed, and
+/// returns the median.
+pub fn measure(workload: &Workload, warmup: usize, iterations: usize) -> Measurement {
+    for _ in 0..warmup {
+        black_box((workload.run)());
+    }
+    let mut times: Vec<Duration> = (0..iterations.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            black_box((workload.run)());
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+    Measurement {
+        name: workload.name.to_string(),
+        median: times[times.len() / 2],
+    }
+}
+
+/// Formats `measurements` as a table, with a column comparing each to the
+/// same workload in `baseline` if one is given.
+///
+/// ```
+/// use mastermind::bench::{report, Measurement};
+/// use std::time::Duration;
+///
+/// let measured = |name: &str, ms| Measurement {
+///     name: name.to_string(),
+///     median: Duration::from_millis(ms),
+/// };
+/// let now = [measured("score table", 30), measured("full solve", 400)];
+/// let before = [measured("score table", 40)];
+///
+/// assert_eq!(
+///     report(&now, None),
+///     "workload                    median\n\
+///      score table               30.000ms\n\
+///      full solve               400.000ms\n"
+/// );
+/// assert_eq!(
+///     report(&now, Some(&before)),
+///     "workload                    median   baseline   change\n\
+///      score table               30.000ms   40.000ms   -25.0%\n\
+///      full solve               400.000ms          -        -\n"
+/// );
+/// ```
+pub fn report(measurements: &[Measurement], baseline: Option<&[Measurement]>) -> String {
+    let ms = |d: Duration| format!("{:.3}ms", d.as_secs_f64() * 1000.0);
+    let mut out = format!("{:<20} {:>13}", "workload", "median");
+    if baseline.is_some() {
+        write!(out, " {:>10} {:>8}", "baseline", "change").unwrap();
+    }
+    out.push('\n');
+    for measurement in measurements {
+        write!(
+            out,
+            "{:<20} {:>13}",
+            measurement.name,
+            ms(measurement.median)
+        )
+        .unwrap();
+        if let Some(baseline) = baseline {
+            match baseline.iter().find(|b| b.name == measurement.name) {
+                Some(before) => {
+                    let change = measurement.median.as_secs_f64() / before.median.as_secs_f64();
+                    write!(
+                        out,
+                        " {:>10} {:>7.1}%",
+                        ms(before.median),
+                        (change - 1.0) * 100.0
+                    )
+                    .unwrap();
+                }
+                None => write!(out, " {:>10} {:>8}", "-", "-").unwrap(),
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Medians saved by `--save-baseline` to compare later runs against with
+/// `--baseline`.
+///
+/// ```
+/// use mastermind::bench::{Measurement, Baseline};
+/// use std::time::Duration;
+///
+/// let baseline = Baseline(vec![Measurement {
+///     name: String::from("10k scores"),
+///     median: Duration::from_nanos(1_234_567),
+/// }]);
+/// let json = serde_json::to_string(&baseline).unwrap();
+/// assert_eq!(json, r#"{"workloads":[{"name":"10k scores","median_ns":1234567}]}"#);
+/// assert_eq!(serde_json::from_str::<Baseline>(&json).unwrap(), baseline);
+/// assert!(serde_json::from_str::<Baseline>("{}").is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Baseline(pub Vec<Measurement>);
+
+impl serde::Serialize for Baseline {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SavedBaseline {
+            workloads: self
+                .0
+                .iter()
+                .map(|m| SavedMeasurement {
+                    name: m.name.clone(),
+                    median_ns: m.median.as_nanos() as u64,
+                })
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Baseline {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let saved = SavedBaseline::deserialize(deserializer)?;
+        Ok(Baseline(
+            saved
+                .workloads
+                .into_iter()
+                .map(|w| Measurement {
+                    name: w.name,
+                    median: Duration::from_nanos(w.median_ns),
+                })
+                .collect(),
+        ))
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedBaseline {
+    workloads: Vec<SavedMeasurement>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedMeasurement {
+    name: String,
+    median_ns: u64,
+}
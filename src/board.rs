@@ -0,0 +1,1836 @@
+use std::fmt;
+use std::io::Write;
+use std::sync::mpsc::{Receiver, RecvError, RecvTimeoutError};
+use std::time::Duration;
+
+use termcolor::{ColorChoice, StandardStream, WriteColor};
+
+#[cfg(feature = "async")]
+use tokio::io::{AsyncBufRead, BufReader};
+
+#[cfg(feature = "color")]
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    terminal,
+};
+
+use crate::analysis::entropy_review;
+use crate::builder::BoardBuilder;
+use crate::coach::Coach;
+use crate::code::{Code, CodeSpace, Color, LazyCodeSpace};
+use crate::codemaker::Codemaker;
+use crate::config::{DisplayMode, GameConfig, OutputLevel};
+use crate::deduction::{describe_progress, explain_inconsistency, CandidateSet, ProgressScale};
+use crate::display::{color_enabled, dim, key_spec, labelled_block_spec, underline};
+use crate::error::GameError;
+use crate::events::{EventRecord, EventSink, GameEvent, GameEventHandler, GameState, StepRecorder};
+use crate::format::OutputFormatter;
+use crate::history::{self, GameSession, GameSummary, Outcome};
+#[cfg(feature = "async")]
+use crate::input::read_line_limited_async;
+use crate::input::{expand_macro, normalize_input, read_line_limited, DEFAULT_MAX_LINE_LENGTH};
+use crate::layout::{truncate, width, wrap};
+use crate::palette::{self, Palette};
+#[cfg(feature = "serde")]
+use crate::save::SavedRound;
+use crate::score::{Score, ScoreDetail};
+#[cfg(feature = "tui")]
+use crate::selector::ColorSelector;
+use crate::solver::{describe_buckets, describe_position_colors, score_buckets, Solver};
+use crate::Result;
+
+/// A game in progress: the hidden code and every round guessed against it.
+///
+/// ```
+/// use mastermind::{Board, Code, GameConfig};
+///
+/// let hidden: Code = String::from("rgby").try_into().unwrap();
+/// let mut board = Board::new(GameConfig::default(), hidden);
+/// for guess in ["rrgg", "rgyb", "rgby"] {
+///     board.guess(String::from(guess).try_into().unwrap());
+/// }
+///
+/// assert_eq!(board.rounds().len(), 3);
+/// assert!(board.last_round().unwrap().wins());
+/// assert_eq!(board.best_round().unwrap().score().black_count(), 4);
+/// for round in &board {
+///     println!("{} scored {}", round.guess(), round.score());
+/// }
+/// ```
+pub struct Board {
+    config: GameConfig,
+    hidden_code: Code,
+    /// What became of every guess used, played or skipped, in order.
+    outcomes: Vec<RoundOutcome>,
+    /// The rounds of `outcomes` that were played, also kept on their own so
+    /// they can be handed out as a slice. Only `record` adds to either.
+    rounds: Vec<Round>,
+    formatter: Option<OutputFormatter>,
+    max_line_length: usize,
+    wide_width: Option<usize>,
+    diff_guesses: bool,
+    show_position_candidates: bool,
+    show_candidates: Option<usize>,
+    progress: Option<ProgressScale>,
+    progress_width: Option<usize>,
+    no_prompt: bool,
+    events: Option<Box<dyn EventSink>>,
+    next_event: u64,
+    handler: Option<Box<dyn GameEventHandler>>,
+    /// Picks the code each guess is scored against, when the code is not
+    /// fixed from the start.
+    codemaker: Option<Box<dyn Codemaker>>,
+    /// Tips on wasted guesses, when the config asks for coaching.
+    coaching: Option<Coach>,
+    /// When the board was made, in seconds since the Unix epoch.
+    started_at: u64,
+    /// Whether the player gave the game up; see `forfeit`.
+    forfeited: bool,
+    /// Where `get_input_async` reads guesses from; stdin unless set.
+    #[cfg(feature = "async")]
+    async_input: Option<Box<dyn AsyncBufRead + Unpin + Send>>,
+}
+
+/// How far `show_candidates` indents each code it lists.
+const CANDIDATE_INDENT: &str = "  ";
+
+/// The narrowest terminal `Board::print_wide` draws color squares for.
+pub const WIDE_MIN_WIDTH: usize = 60;
+
+/// How many characters wide each peg is drawn by `Board::print_wide`.
+const WIDE_CELL_WIDTH: usize = 3;
+
+/// The most colors listed for one position after each round; beyond this only
+/// their number is shown.
+const POSITION_COLORS_LISTED: usize = 4;
+
+impl Board {
+    pub fn new(config: GameConfig, hidden_code: Code) -> Self {
+        let coaching = config.coach.then(|| Coach::new(&config));
+        Self {
+            config,
+            hidden_code,
+            outcomes: Vec::new(),
+            rounds: Vec::new(),
+            formatter: None,
+            max_line_length: DEFAULT_MAX_LINE_LENGTH,
+            wide_width: None,
+            diff_guesses: false,
+            show_position_candidates: false,
+            show_candidates: None,
+            progress: None,
+            progress_width: None,
+            no_prompt: false,
+            events: None,
+            next_event: 1,
+            handler: None,
+            codemaker: None,
+            coaching,
+            started_at: history::now(),
+            forfeited: false,
+            #[cfg(feature = "async")]
+            async_input: None,
+        }
+    }
+
+    pub(crate) fn from_parts(
+        config: GameConfig,
+        hidden_code: Code,
+        outcomes: Vec<RoundOutcome>,
+    ) -> Self {
+        let coaching = config.coach.then(|| {
+            let mut coach = Coach::new(&config);
+            for round in outcomes.iter().filter_map(RoundOutcome::round) {
+                coach.review(round);
+            }
+            coach
+        });
+        Self {
+            config,
+            hidden_code,
+            rounds: outcomes
+                .iter()
+                .filter_map(RoundOutcome::round)
+                .cloned()
+                .collect(),
+            outcomes,
+            formatter: None,
+            max_line_length: DEFAULT_MAX_LINE_LENGTH,
+            wide_width: None,
+            diff_guesses: false,
+            show_position_candidates: false,
+            show_candidates: None,
+            progress: None,
+            progress_width: None,
+            no_prompt: false,
+            events: None,
+            next_event: 1,
+            handler: None,
+            codemaker: None,
+            coaching,
+            started_at: history::now(),
+            forfeited: false,
+            #[cfg(feature = "async")]
+            async_input: None,
+        }
+    }
+
+    /// Starts setting up a game one setting at a time; see `BoardBuilder`.
+    pub fn builder() -> BoardBuilder {
+        BoardBuilder::new()
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn hidden_code(&self) -> &Code {
+        &self.hidden_code
+    }
+
+    /// Prints each round through `formatter` instead of redrawing the board.
+    pub fn set_output_format(&mut self, formatter: Option<OutputFormatter>) {
+        self.formatter = formatter;
+    }
+
+    pub fn output_format(&self) -> Option<&OutputFormatter> {
+        self.formatter.as_ref()
+    }
+
+    /// Sets the longest guess line, in bytes, that `get_input` will read.
+    pub fn set_max_line_length(&mut self, limit: usize) {
+        self.max_line_length = limit;
+    }
+
+    /// Draws the board with `print_wide` for a terminal `width` columns wide,
+    /// or as plain text when `None`.
+    pub fn set_wide(&mut self, width: Option<usize>) {
+        self.wide_width = width;
+    }
+
+    /// Draws the pegs of every guess, including those already played, in `mode`
+    /// from now on.
+    ///
+    /// ```
+    /// use mastermind::{Board, Code, DisplayMode, GameConfig};
+    ///
+    /// let hidden: Code = String::from("rgby").try_into().unwrap();
+    /// let mut board = Board::new(GameConfig::default(), hidden);
+    /// board.guess(String::from("rrgg").try_into().unwrap());
+    /// let letters = board.to_string();
+    /// let rounds = board.rounds().to_vec();
+    ///
+    /// board.set_display_mode(DisplayMode::Emoji);
+    /// assert_eq!(board.config().display_mode, DisplayMode::Emoji);
+    /// assert!(board.to_string().contains("🔴 🔴 🟢 🟢"));
+    /// board.set_display_mode(DisplayMode::Letters);
+    /// assert_eq!(board.to_string(), letters);
+    /// assert_eq!(board.rounds(), rounds);
+    /// ```
+    pub fn set_display_mode(&mut self, mode: DisplayMode) {
+        self.config.display_mode = mode;
+    }
+
+    /// Marks the pegs of each guess that differ from the guess before it: underlined
+    /// with color support, in brackets without.
+    pub fn set_diff_guesses(&mut self, diff: bool) {
+        self.diff_guesses = diff;
+    }
+
+    /// Leaves out the board and the `guess: ` prompt before each guess, and
+    /// instead prints just the new round after it, so that every line of
+    /// input gets one line of output.
+    pub fn set_no_prompt(&mut self, no_prompt: bool) {
+        self.no_prompt = no_prompt;
+    }
+
+    /// Shows after each guess how much of the code space has been ruled out,
+    /// as a bar on a `scale` drawn to fit a terminal `width` columns wide, or
+    /// as plain counts when `width` is `None`. Not shown with an output
+    /// format set or with assists off.
+    pub fn set_progress(&mut self, scale: Option<ProgressScale>, width: Option<usize>) {
+        self.progress = scale;
+        self.progress_width = width;
+    }
+
+    /// After each round, prints the colors still possible at every position.
+    pub fn set_show_position_candidates(&mut self, show: bool) {
+        self.show_position_candidates = show;
+    }
+
+    /// After each round, prints how many codes are still possible and the
+    /// first `shown` of them, one per line; with `shown` at 0 only the count.
+    pub fn set_show_candidates(&mut self, shown: Option<usize>) {
+        self.show_candidates = shown;
+    }
+
+    /// Returns the positions to mark in the guess of round `index`, which are
+    /// none unless `set_diff_guesses` is on and there is an earlier round.
+    fn changed_positions(&self, index: usize) -> Vec<usize> {
+        match index.checked_sub(1) {
+            Some(previous) if self.diff_guesses => self.rounds[index]
+                .guess()
+                .diff_positions(self.rounds[previous].guess()),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the guess of round `index` as drawn by `Display`, with changed
+    /// pegs marked when `set_diff_guesses` is on.
+    fn guess_text(&self, index: usize) -> String {
+        let mode = self.config.display_mode;
+        let colors = self.rounds[index].guess().colors();
+        if self.diff_guesses {
+            diff_guess(
+                colors,
+                &self.changed_positions(index),
+                &self.config.palette,
+                mode,
+            )
+        } else {
+            let pegs: Vec<String> = colors
+                .iter()
+                .map(|color| color.glyph(&self.config.palette, mode))
+                .collect();
+            pegs.join(" ")
+        }
+    }
+
+    /// Draws a skipped guess as a row of dashes, with the reason where the
+    /// score would be.
+    fn skipped_row(&self, reason: SkipReason) -> String {
+        let dashes = vec!["-"; self.config.code_length].join(" ");
+        let score_width = if self.config.anagram_mode {
+            1
+        } else {
+            2 * self.config.code_length - 1
+        };
+        format!(
+            "| {:<guess$} | {:<score$} |",
+            dashes,
+            reason.name(),
+            guess = self.guess_width(),
+            score = score_width
+        )
+    }
+
+    /// Returns how many characters wide `guess_text` is.
+    fn guess_width(&self) -> usize {
+        let peg_width = self.config.display_mode.peg_width();
+        if self.diff_guesses {
+            diff_guess_width(self.config.code_length, peg_width)
+        } else {
+            (peg_width + 1) * self.config.code_length - 1
+        }
+    }
+
+    /// Writes the board to stdout the way `set_wide` asked for.
+    pub fn print(&self) -> std::io::Result<()> {
+        let choice = if color_enabled() {
+            ColorChoice::Always
+        } else {
+            ColorChoice::Never
+        };
+        let stdout = StandardStream::stdout(choice);
+        let mut out = stdout.lock();
+        match self.wide_width {
+            Some(width) => self.print_wide(&mut out, width),
+            None => writeln!(out, "{}", self),
+        }
+    }
+
+    /// Writes the board with each peg drawn as a square of color, two lines tall
+    /// and `WIDE_CELL_WIDTH` characters wide, with the score beside the top line.
+    /// A `writer` that does not support color gets letters instead of squares.
+    ///
+    /// Terminals narrower than `WIDE_MIN_WIDTH` get the plain `Display` output.
+    ///
+    /// ```
+    /// use termcolor::{Ansi, NoColor};
+    ///
+    /// use mastermind::{Board, Code, GameConfig};
+    ///
+    /// let hidden: Code = String::from("rgby").try_into().unwrap();
+    /// let mut board = Board::new(GameConfig::default(), hidden);
+    /// board.guess(String::from("rrgg").try_into().unwrap());
+    ///
+    /// let mut narrow = NoColor::new(Vec::new());
+    /// board.print_wide(&mut narrow, 40).unwrap();
+    /// assert_eq!(String::from_utf8(narrow.into_inner()).unwrap(), format!("{}\n", board));
+    ///
+    /// let mut wide = NoColor::new(Vec::new());
+    /// board.print_wide(&mut wide, 100).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(wide.into_inner()).unwrap(),
+    ///     "\n\
+    ///      =============================\n\
+    ///      | rrr rrr ggg ggg | b w - - |\n\
+    ///      | rrr rrr ggg ggg |         |\n\
+    ///      =============================\n"
+    /// );
+    ///
+    /// // In color, each peg is a block with its letter in the middle.
+    /// let mut painted = Ansi::new(Vec::new());
+    /// board.print_wide(&mut painted, 100).unwrap();
+    /// let painted = String::from_utf8(painted.into_inner()).unwrap();
+    /// assert!(painted.contains("\x1b[48;5;9m r "), "{:?}", painted);
+    /// assert!(painted.contains("\x1b[48;5;14m \x1b[0m"), "{:?}", painted);
+    /// ```
+    pub fn print_wide<W: WriteColor>(
+        &self,
+        writer: &mut W,
+        terminal_width: usize,
+    ) -> std::io::Result<()> {
+        if terminal_width < WIDE_MIN_WIDTH {
+            return writeln!(writer, "{}", self);
+        }
+        writeln!(writer)?;
+        if self.rounds.is_empty() {
+            return Ok(());
+        }
+        let length = self.config.code_length;
+        let score_width = if self.config.anagram_mode {
+            length.to_string().len()
+        } else {
+            2 * length - 1
+        };
+        let border = "=".repeat((WIDE_CELL_WIDTH + 1) * length + score_width + 6);
+        writeln!(writer, "{}", border)?;
+        for (index, round) in self.rounds.iter().enumerate() {
+            let changed = self.changed_positions(index);
+            for top in [true, false] {
+                write!(writer, "|")?;
+                for (i, color) in round.guess().colors().iter().enumerate() {
+                    write!(writer, " ")?;
+                    let marked = !top && changed.contains(&i);
+                    write_wide_cell(writer, color, &self.config.palette, marked)?;
+                }
+                write!(writer, " | ")?;
+                if !top {
+                    write!(writer, "{}", " ".repeat(score_width))?;
+                } else if self.config.anagram_mode {
+                    let common = round.guess().common_peg_count(&self.hidden_code);
+                    write!(writer, "{:>width$}", common, width = score_width)?;
+                } else {
+                    for (i, detail) in round.score().details(length).iter().enumerate() {
+                        if i > 0 {
+                            write!(writer, " ")?;
+                        }
+                        write_key_peg(writer, detail)?;
+                    }
+                }
+                writeln!(writer, " |")?;
+            }
+        }
+        writeln!(writer, "{}", border)
+    }
+
+    /// Sends this game's events to `sink` as they happen, or stops logging when
+    /// `None`.
+    pub fn set_event_sink(&mut self, sink: Option<Box<dyn EventSink>>) {
+        self.events = sink;
+    }
+
+    /// Logs `event` to the event sink, if there is one. A sink that fails is
+    /// dropped with a warning rather than ending the game.
+    pub fn emit(&mut self, event: GameEvent) {
+        let Some(sink) = self.events.as_mut() else {
+            return;
+        };
+        let record = EventRecord::new(self.next_event, event);
+        self.next_event += 1;
+        if let Err(e) = sink.emit(&record) {
+            eprintln!("event log disabled: {}", e);
+            self.events = None;
+        }
+    }
+
+    /// Calls `handler` back after every round played through `get_input`,
+    /// `get_input_timeout` or `get_input_async`, and once more when the game
+    /// ends.
+    pub fn set_event_handler(&mut self, handler: Box<dyn GameEventHandler>) {
+        self.handler = Some(handler);
+    }
+
+    /// Lets `codemaker` choose the code each guess is scored against, in place
+    /// of the hidden code the board was made with.
+    pub fn set_codemaker(&mut self, codemaker: Box<dyn Codemaker>) {
+        self.codemaker = Some(codemaker);
+    }
+
+    /// Returns where the game stands.
+    pub fn state(&self) -> GameState {
+        GameState {
+            guesses_used: self.guesses_used(),
+            guesses_left: self
+                .config
+                .max_guesses
+                .map(|max| max.saturating_sub(self.guesses_used())),
+            won: self.last_round().is_some_and(Round::wins),
+        }
+    }
+
+    /// Tells the event handler, if there is one, that a round was played,
+    /// if `round` is set, and that the game is over if it is.
+    fn notify_handler(&self, round: Option<&Round>) {
+        let Some(handler) = &self.handler else {
+            return;
+        };
+        let state = self.state();
+        if let Some(round) = round {
+            handler.on_round_complete(round, &state);
+        }
+        let outcome = if state.won {
+            Outcome::Won
+        } else if state.guesses_left == Some(0) {
+            Outcome::Lost
+        } else {
+            return;
+        };
+        let session = GameSession::from_board(self, self.started_at, outcome);
+        handler.on_game_over(&GameSummary::of(0, &session));
+    }
+
+    /// Scores `code` against the hidden code and records it as a new round.
+    pub fn guess(&mut self, code: Code) -> &Round {
+        if let Some(codemaker) = &mut self.codemaker {
+            self.hidden_code = codemaker.hidden_for(&code);
+        }
+        let round = Round::from_guess(code, &self.hidden_code);
+        self.emit(GameEvent::ScoreComputed {
+            guess: round.input_code.clone(),
+            score: round.score.clone(),
+        });
+        if let Some(coach) = &mut self.coaching {
+            coach.review(&round);
+        }
+        self.record(RoundOutcome::Guessed(round));
+        self.last_round().expect("a round was just played")
+    }
+
+    /// Plays `code` as `guess` does, then tells the event handler, if there is
+    /// one, about the round as `get_input` would.
+    pub fn play_guess(&mut self, code: Code) -> &Round {
+        self.guess(code);
+        self.notify_handler(self.last_round());
+        self.last_round().expect("a round was just played")
+    }
+
+    /// Lets `solver` play this board, returning it with a recorder that is
+    /// already its event handler; play rounds with `StepRecorder::play`.
+    pub fn with_step_recorder(mut self, solver: impl Solver + 'static) -> (Board, StepRecorder) {
+        let recorder = StepRecorder::new(solver);
+        self.set_event_handler(Box::new(recorder.clone()));
+        (self, recorder)
+    }
+
+    /// Returns the coach watching this game, if the config asks for coaching.
+    pub fn coaching(&self) -> Option<&Coach> {
+        self.coaching.as_ref()
+    }
+
+    /// Returns every round played so far, oldest first, leaving out skipped
+    /// guesses.
+    pub fn rounds(&self) -> &[Round] {
+        &self.rounds
+    }
+
+    /// Adds what became of the next guess.
+    fn record(&mut self, outcome: RoundOutcome) {
+        if let RoundOutcome::Guessed(round) = &outcome {
+            self.rounds.push(round.clone());
+        }
+        self.outcomes.push(outcome);
+    }
+
+    /// Ends the game and returns every round played, oldest first. Iterating
+    /// over a `Board` by value does the same one round at a time.
+    ///
+    /// ```
+    /// use mastermind::{Board, Code, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let new_board = || {
+    ///     let mut board = Board::new(config.clone(), code("rgby"));
+    ///     for guess in ["rrgg", "bbyy", "rgby"] {
+    ///         board.guess(code(guess));
+    ///     }
+    ///     board
+    /// };
+    ///
+    /// let board = new_board();
+    /// let mut guesses = Vec::new();
+    /// for round in &board {
+    ///     guesses.push(round.guess().clone());
+    /// }
+    /// assert_eq!(guesses, [code("rrgg"), code("bbyy"), code("rgby")]);
+    ///
+    /// let rounds = new_board().into_rounds();
+    /// assert_eq!(rounds, board.rounds());
+    /// let blacks: Vec<usize> = new_board()
+    ///     .into_iter()
+    ///     .map(|round| round.score().black_count())
+    ///     .collect();
+    /// assert_eq!(blacks, [1, 1, 4]);
+    /// ```
+    pub fn into_rounds(self) -> Vec<Round> {
+        self.rounds
+    }
+
+    /// Returns the most recently played round.
+    pub fn last_round(&self) -> Option<&Round> {
+        self.rounds.last()
+    }
+
+    /// Returns the round with the best score, by `Score`'s order, preferring
+    /// the earliest on ties.
+    pub fn best_round(&self) -> Option<&Round> {
+        self.rounds.iter().rev().max_by_key(|round| &round.score)
+    }
+
+    /// Returns how many guesses have been used: every round played plus every
+    /// guess skipped.
+    pub fn guesses_used(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    /// Gives the game up, as typing `forfeit` or `quit` at the guess prompt
+    /// does unless the word is also a code, and returns its summary. No guess
+    /// is used: the game ends where it stands, and is counted as lost, though
+    /// not as run out of guesses.
+    ///
+    /// ```
+    /// use std::sync::mpsc;
+    ///
+    /// use mastermind::{Board, Code, GameConfig, Outcome, OutputLevel, Palette};
+    ///
+    /// let config = GameConfig {
+    ///     output_level: OutputLevel::Quiet,
+    ///     ..GameConfig::default()
+    /// };
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let mut board = Board::new(config.clone(), code("rgby"));
+    /// board.guess(code("rrgg"));
+    /// let summary = board.forfeit();
+    /// assert_eq!(summary.outcome, Outcome::Forfeit);
+    /// assert!(summary.outcome.is_loss());
+    /// assert_eq!(summary.guesses, 1);
+    /// assert!(board.is_forfeit());
+    /// assert_eq!(board.guesses_used(), 1);
+    ///
+    /// // At the prompt, either word gives up without playing a round.
+    /// for word in ["forfeit", "quit"] {
+    ///     let mut board = Board::new(config.clone(), code("rgby"));
+    ///     let (sender, lines) = mpsc::channel();
+    ///     sender.send(Ok(String::from(word))).unwrap();
+    ///     assert!(!board.get_input_timeout(&lines, None).unwrap());
+    ///     assert!(board.is_forfeit());
+    ///     assert_eq!(board.guesses_used(), 0);
+    /// }
+    ///
+    /// // A word the palette can spell is played as a guess instead.
+    /// let palette = Palette::from_letters("quit").unwrap();
+    /// let spelled = GameConfig {
+    ///     colors: palette.values(),
+    ///     palette,
+    ///     ..config.clone()
+    /// };
+    /// let hidden = Code::from_letters("quit", &spelled).unwrap();
+    /// let mut board = Board::new(spelled, hidden);
+    /// let (sender, lines) = mpsc::channel();
+    /// sender.send(Ok(String::from("quit"))).unwrap();
+    /// assert!(board.get_input_timeout(&lines, None).unwrap());
+    /// assert!(!board.is_forfeit());
+    /// ```
+    pub fn forfeit(&mut self) -> GameSummary {
+        self.forfeited = true;
+        let session = GameSession::from_board(self, self.started_at, Outcome::Forfeit);
+        let summary = GameSummary::of(0, &session);
+        if let Some(handler) = &self.handler {
+            handler.on_game_over(&summary);
+        }
+        summary
+    }
+
+    /// Returns whether the game was given up with `forfeit`.
+    pub fn is_forfeit(&self) -> bool {
+        self.forfeited
+    }
+
+    /// Uses up a guess without playing it. A skipped guess tells nothing about
+    /// the code, so it is left out of `rounds` and everything worked out from
+    /// them.
+    ///
+    /// ```
+    /// use mastermind::board::SkipReason;
+    /// use mastermind::{Board, Code, GameConfig, GameSession, GameSummary, Outcome};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let mut board = Board::new(config.clone(), code("rgby"));
+    /// board.skip(SkipReason::Timeout);
+    /// board.guess(code("rgby"));
+    /// assert_eq!(board.rounds().len(), 1);
+    ///
+    /// // History keeps skipped guesses where they happened.
+    /// let session = GameSession::from_board(&board, 0, Outcome::Won);
+    /// assert_eq!(session.skipped, [(0, SkipReason::Timeout)]);
+    /// assert_eq!(GameSummary::of(1, &session).guesses, 2);
+    /// ```
+    pub fn skip(&mut self, reason: SkipReason) {
+        self.record(RoundOutcome::Skipped { reason });
+    }
+
+    /// Returns every guess skipped, with how many rounds had been played
+    /// before it.
+    pub fn skipped(&self) -> Vec<(usize, SkipReason)> {
+        let mut played = 0;
+        let mut skipped = Vec::new();
+        for outcome in &self.outcomes {
+            match outcome {
+                RoundOutcome::Guessed(_) => played += 1,
+                RoundOutcome::Skipped { reason } => skipped.push((played, *reason)),
+            }
+        }
+        skipped
+    }
+
+    /// Returns what became of every guess used, in the order they were used:
+    /// the rounds played with skipped guesses between them.
+    ///
+    /// ```
+    /// use mastermind::analysis::review_game;
+    /// use mastermind::board::{RoundOutcome, SkipReason};
+    /// use mastermind::{Board, Code, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let mut board = Board::new(config.clone(), code("rgby"));
+    /// board.guess(code("rrgg"));
+    /// let candidates = board.candidates().len();
+    /// board.skip(SkipReason::Timeout);
+    /// board.guess(code("rgby"));
+    ///
+    /// assert_eq!(board.guesses_used(), 3);
+    /// assert_eq!(board.rounds().len(), 2);
+    /// assert!(matches!(board.outcomes()[0], RoundOutcome::Guessed(_)));
+    /// assert_eq!(
+    ///     board.outcomes()[1],
+    ///     RoundOutcome::Skipped { reason: SkipReason::Timeout }
+    /// );
+    /// assert_eq!(board.outcomes()[2].guess(), Some(&code("rgby")));
+    ///
+    /// // Skipping narrows nothing, and only played rounds are reviewed.
+    /// let mut skipped_only = Board::new(config.clone(), code("rgby"));
+    /// skipped_only.guess(code("rrgg"));
+    /// skipped_only.skip(SkipReason::Timeout);
+    /// assert_eq!(skipped_only.candidates().len(), candidates);
+    /// assert_eq!(review_game(&board).len(), 2);
+    ///
+    /// // The board draws a skipped guess as a dashed row.
+    /// let drawn = board.to_string();
+    /// assert!(drawn.contains("| - - - - | timeout |"), "{}", drawn);
+    /// ```
+    pub fn outcomes(&self) -> &[RoundOutcome] {
+        &self.outcomes
+    }
+
+    pub fn get_input(&mut self) -> Result<bool> {
+        self.prompt()?;
+        let mut buffer = String::new();
+        let stdin = std::io::stdin();
+        match read_line_limited(&mut stdin.lock(), &mut buffer, self.max_line_length) {
+            Ok(0) => return Err(GameError::Io(std::io::ErrorKind::UnexpectedEof.into()).into()),
+            Ok(_) => {}
+            Err(e @ GameError::LineTooLong { .. }) => {
+                println!("{}", e);
+                return Ok(false);
+            }
+            Err(e) => return Err(e.into()),
+        }
+        self.handle_input(buffer)
+    }
+
+    /// Like `get_input`, but takes the guess from `lines` (see
+    /// `input::spawn_line_reader`) and gives up after `timeout`. A guess that
+    /// runs out of time is skipped and still counts towards
+    /// `config.max_guesses`. The color selector is not offered, since it would
+    /// compete with `lines` for the terminal.
+    ///
+    /// ```
+    /// use std::sync::mpsc;
+    /// use std::time::Duration;
+    ///
+    /// use mastermind::{Board, Code, GameConfig, OutputLevel};
+    ///
+    /// let config = GameConfig {
+    ///     output_level: OutputLevel::Quiet,
+    ///     ..GameConfig::default()
+    /// };
+    /// let hidden = Code::parse(String::from("rgby"), &config).unwrap();
+    /// let mut board = Board::new(config, hidden);
+    /// let (sender, lines) = mpsc::channel();
+    /// let timeout = Some(Duration::from_millis(20));
+    ///
+    /// // Nothing typed in time.
+    /// assert!(!board.get_input_timeout(&lines, timeout).unwrap());
+    /// assert_eq!((board.rounds().len(), board.guesses_used()), (0, 1));
+    ///
+    /// sender.send(Ok(String::from("rgby"))).unwrap();
+    /// assert!(board.get_input_timeout(&lines, timeout).unwrap());
+    /// assert_eq!(board.guesses_used(), 2);
+    ///
+    /// // The end of input is an error, as with `get_input`.
+    /// drop(sender);
+    /// assert!(board.get_input_timeout(&lines, None).is_err());
+    /// ```
+    pub fn get_input_timeout(
+        &mut self,
+        lines: &Receiver<std::result::Result<String, GameError>>,
+        timeout: Option<Duration>,
+    ) -> Result<bool> {
+        self.prompt()?;
+        let line = match timeout {
+            Some(timeout) => lines.recv_timeout(timeout).map_err(|e| match e {
+                RecvTimeoutError::Timeout => None,
+                RecvTimeoutError::Disconnected => Some(RecvError),
+            }),
+            None => lines.recv().map_err(Some),
+        };
+        let buffer = match line {
+            Ok(Ok(line)) => line,
+            Ok(Err(e @ GameError::LineTooLong { .. })) => {
+                println!("{}", e);
+                return Ok(false);
+            }
+            Ok(Err(e)) => return Err(e.into()),
+            Err(None) => {
+                self.skip(SkipReason::Timeout);
+                println!("\ntime's up, that guess is skipped");
+                self.notify_handler(None);
+                return Ok(false);
+            }
+            Err(Some(RecvError)) => {
+                return Err(GameError::Io(std::io::ErrorKind::UnexpectedEof.into()).into())
+            }
+        };
+        #[cfg(feature = "tui")]
+        if normalize_input(&buffer) == "select" {
+            println!("select is not available with a guess timeout");
+            return Ok(false);
+        }
+        self.handle_input(buffer)
+    }
+
+    /// Reads guesses for `get_input_async` from `input` rather than stdin.
+    #[cfg(feature = "async")]
+    pub fn set_async_input(&mut self, input: Box<dyn AsyncBufRead + Unpin + Send>) {
+        self.async_input = Some(input);
+    }
+
+    /// Hands back what `get_input_async` has been reading, so whatever was
+    /// typed after the last guess can still be read.
+    #[cfg(feature = "async")]
+    pub fn take_async_input(&mut self) -> Option<Box<dyn AsyncBufRead + Unpin + Send>> {
+        self.async_input.take()
+    }
+
+    /// Like `get_input_timeout`, but reads the guess asynchronously, from stdin
+    /// or the input given to `set_async_input`, and returns where the game
+    /// stands after it. A guess that runs out of `timeout` is skipped and still
+    /// counts towards `config.max_guesses`.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use tokio::io::{AsyncWriteExt, BufReader};
+    ///
+    /// use mastermind::{Board, Code, GameConfig, OutputLevel};
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = GameConfig {
+    ///     output_level: OutputLevel::Quiet,
+    ///     ..GameConfig::default()
+    /// };
+    /// let hidden = Code::parse(String::from("rgby"), &config).unwrap();
+    /// let mut board = Board::new(config, hidden);
+    /// let (mut keyboard, stdin) = tokio::io::duplex(64);
+    /// board.set_async_input(Box::new(BufReader::new(stdin)));
+    /// let timeout = Some(Duration::from_millis(20));
+    ///
+    /// // Nothing typed in time.
+    /// let state = board.get_input_async(timeout).await.unwrap();
+    /// assert_eq!((state.guesses_used, board.rounds().len()), (1, 0));
+    ///
+    /// keyboard.write_all(b"rrgg\nrgby\n").await.unwrap();
+    /// let state = board.get_input_async(timeout).await.unwrap();
+    /// assert_eq!((state.guesses_used, state.won), (2, false));
+    /// let state = board.get_input_async(None).await.unwrap();
+    /// assert_eq!((state.guesses_used, state.won), (3, true));
+    ///
+    /// // The end of input is an error, as with `get_input`.
+    /// drop(keyboard);
+    /// assert!(board.get_input_async(None).await.is_err());
+    /// # });
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn get_input_async(&mut self, timeout: Option<Duration>) -> Result<GameState> {
+        self.prompt()?;
+        let limit = self.max_line_length;
+        let input = self
+            .async_input
+            .get_or_insert_with(|| Box::new(BufReader::new(tokio::io::stdin())));
+        let mut buffer = String::new();
+        let read = read_line_limited_async(input, &mut buffer, limit);
+        let read = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, read).await,
+            None => Ok(read.await),
+        };
+        match read {
+            Ok(Ok(0)) => return Err(GameError::Io(std::io::ErrorKind::UnexpectedEof.into()).into()),
+            Ok(Ok(_)) => {}
+            Ok(Err(e @ GameError::LineTooLong { .. })) => {
+                println!("{}", e);
+                return Ok(self.state());
+            }
+            Ok(Err(e)) => return Err(e.into()),
+            Err(_) => {
+                self.skip(SkipReason::Timeout);
+                println!("\ntime's up, that guess is skipped");
+                self.notify_handler(None);
+                return Ok(self.state());
+            }
+        }
+        #[cfg(feature = "tui")]
+        if normalize_input(&buffer) == "select" {
+            println!("select is not available with a guess timeout");
+            return Ok(self.state());
+        }
+        self.handle_input(buffer)?;
+        Ok(self.state())
+    }
+
+    /// Shows the board and the guess prompt, unless output is quiet.
+    fn prompt(&self) -> Result<()> {
+        if self.no_prompt {
+            return Ok(());
+        }
+        let level = self.config.output_level;
+        if self.formatter.is_none() && level > OutputLevel::Quiet {
+            self.print()?;
+        }
+        if level > OutputLevel::Quiet {
+            print!("guess: ");
+            std::io::stdout().flush()?;
+        }
+        Ok(())
+    }
+
+    /// Acts on one line typed at the guess prompt, returning whether it was a
+    /// winning guess.
+    fn handle_input(&mut self, buffer: String) -> Result<bool> {
+        let level = self.config.output_level;
+        let input = normalize_input(&buffer).to_string();
+        if let Some(mode) = input.strip_prefix("mode ") {
+            match mode.parse() {
+                Ok(mode) => self.set_display_mode(mode),
+                Err(e) => println!("{}", e),
+            }
+            return Ok(false);
+        }
+        let is_code = || Code::parse(input.clone(), &self.config).is_ok();
+        if (input == "forfeit" || input == "quit") && !is_code() {
+            self.forfeit();
+            return Ok(false);
+        }
+        if let Some(code) = input.strip_prefix("what-if ") {
+            self.what_if(code);
+            return Ok(false);
+        }
+        if let Some(position) = input.strip_prefix("peek ") {
+            self.peek(position);
+            return Ok(false);
+        }
+        #[cfg(feature = "tui")]
+        let buffer = if input == "select" {
+            match ColorSelector::new(&self.config).run()? {
+                Some(code) => code.letters(&self.config.palette),
+                None => return Ok(false),
+            }
+        } else {
+            buffer
+        };
+        let last_guess = self.last_round().map(Round::guess);
+        let buffer = match expand_macro(&input, last_guess, &self.config) {
+            Ok(expanded) if expanded != input => {
+                if level > OutputLevel::Quiet {
+                    println!("playing {}", expanded);
+                }
+                expanded
+            }
+            Ok(_) => buffer,
+            Err(e) => {
+                self.emit(GameEvent::GuessRejected {
+                    input,
+                    reason: e.to_string(),
+                });
+                println!("{}", e);
+                return Ok(false);
+            }
+        };
+        match Code::parse(buffer, &self.config) {
+            Ok(code) => {
+                self.emit(GameEvent::GuessSubmitted {
+                    input,
+                    guess: code.clone(),
+                });
+                if self.config.coach {
+                    self.coach(&code);
+                }
+                let wins = self.guess(code).wins();
+                let round = self.last_round().expect("a round was just played");
+                if let Some(formatter) = &self.formatter {
+                    println!(
+                        "{}",
+                        formatter.format_round(round, self.rounds.len(), &self.config.palette)
+                    );
+                } else if self.no_prompt && level > OutputLevel::Quiet {
+                    if self.config.anagram_mode {
+                        let common = round.guess().common_peg_count(&self.hidden_code);
+                        let guess = round.guess().display(&self.config.palette);
+                        println!("{} | {}", guess, common);
+                    } else {
+                        println!("{}", round.display(&self.config.palette));
+                    }
+                }
+                if self.config.explain && self.formatter.is_none() && level > OutputLevel::Quiet {
+                    for line in wrap(&round.explain(&self.hidden_code), width()) {
+                        println!("{}", line);
+                    }
+                }
+                let verbose = level == OutputLevel::Verbose;
+                let progress = match (self.progress, &self.formatter, self.config.assists) {
+                    (Some(scale), None, true) => Some(scale),
+                    _ => None,
+                };
+                if verbose || progress.is_some() {
+                    // Both lines count the same codes, so count them once.
+                    let remaining = self.candidates().len();
+                    let total = CandidateSet::new(&self.config).len();
+                    if verbose {
+                        println!("{} of {} codes remain possible", remaining, total);
+                    }
+                    if let Some(scale) = progress {
+                        println!(
+                            "{}",
+                            describe_progress(remaining, total, self.progress_width, scale)
+                        );
+                    }
+                }
+                if self.show_position_candidates && self.config.assists {
+                    let positions = self.candidates().position_colors();
+                    let line = describe_position_colors(
+                        &positions,
+                        POSITION_COLORS_LISTED,
+                        &self.config.palette,
+                    );
+                    println!("{}", truncate(&line, width()));
+                }
+                if let (Some(shown), true) = (self.show_candidates, self.config.assists) {
+                    self.print_candidates(shown);
+                }
+                if let Some(coach) = &self.coaching {
+                    let played = self.rounds.len();
+                    for (_, tip) in coach.tips().iter().filter(|(round, _)| *round == played) {
+                        println!("tip: {}", tip.describe(&self.config.palette));
+                    }
+                }
+                self.notify_handler(self.last_round());
+                Ok(wins)
+            }
+            Err(e) => {
+                self.emit(GameEvent::GuessRejected {
+                    input,
+                    reason: e.to_string(),
+                });
+                println!("{}", e);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Returns the codes still consistent with every round played.
+    pub fn candidates(&self) -> CandidateSet {
+        CandidateSet::from_rounds(&self.config, &self.rounds)
+    }
+
+    /// Returns the codes of `all_codes` consistent with every round played,
+    /// in the order `all_codes` has them.
+    ///
+    /// ```
+    /// use mastermind::{Board, Code, CodeSpace, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let all_codes = CodeSpace::new(&config);
+    /// assert_eq!(Board::new(config.clone(), code("gbyg")).remaining_candidates(&all_codes).len(), 256);
+    ///
+    /// // No red at all leaves the other 3 colors in each of 4 positions.
+    /// let mut board = Board::new(config.clone(), code("gbyg"));
+    /// board.guess(code("rrrr"));
+    /// assert_eq!(board.remaining_candidates(&all_codes).len(), 3 * 3 * 3 * 3);
+    ///
+    /// // Exactly one red: 4 places for it, 3 colors for each other peg.
+    /// let mut board = Board::new(config.clone(), code("gbrg"));
+    /// board.guess(code("rrrr"));
+    /// assert_eq!(board.remaining_candidates(&all_codes).len(), 4 * 3 * 3 * 3);
+    ///
+    /// // Two whites for rrgg: red kept to the last half and green to the
+    /// // first, with blue or yellow elsewhere. One red and one green can be
+    /// // placed 2 * 2 ways with 2 * 2 fillings; two reds, or two greens, fill
+    /// // their half with the other half's 4 fillings.
+    /// let mut board = Board::new(config.clone(), code("gbry"));
+    /// board.guess(code("rrgg"));
+    /// let remaining = board.remaining_candidates(&all_codes);
+    /// assert_eq!(remaining.len(), 2 * 2 * 2 * 2 + 4 + 4);
+    /// assert!(remaining.contains(&code("gbry")));
+    /// ```
+    pub fn remaining_candidates(&self, all_codes: &CodeSpace) -> Vec<Code> {
+        all_codes
+            .iter()
+            .filter(|code| {
+                self.rounds
+                    .iter()
+                    .all(|round| code.is_consistent_with(round))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Prints how many codes are still possible, then the first `shown` of
+    /// them indented, one per line. The codes are generated one at a time, and
+    /// only the ones shown are kept.
+    fn print_candidates(&self, shown: usize) {
+        let mut count = 0;
+        let mut first = Vec::with_capacity(shown);
+        let remaining = LazyCodeSpace::new(&self.config).filter(|code| {
+            self.rounds
+                .iter()
+                .all(|round| code.is_consistent_with(round))
+        });
+        for code in remaining {
+            if count < shown {
+                first.push(code);
+            }
+            count += 1;
+        }
+        println!(
+            "{} possible code{}",
+            count,
+            if count == 1 { "" } else { "s" }
+        );
+        for code in &first {
+            println!("{}{}", CANDIDATE_INDENT, code.display(&self.config.palette));
+        }
+        if shown > 0 && count > shown {
+            println!("{}and {} more", CANDIDATE_INDENT, count - shown);
+        }
+    }
+
+    /// Writes the game out in the style of chess's Portable Game Notation:
+    /// tag pairs describing the game, then each guess and its score as a
+    /// numbered move, ending with the result. The hidden code is only given
+    /// once the game is over.
+    ///
+    /// A guess expected to reveal less than half of what the best guess would
+    /// have, by `analysis::entropy_review`, is marked as a mistake with `$2`.
+    ///
+    /// ```
+    /// use mastermind::{Board, Code, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let mut board = Board::new(config.clone(), code("rgby"));
+    /// for guess in ["rrrr", "rggg", "gggg"] {
+    ///     board.guess(code(guess));
+    /// }
+    /// assert!(board.export_pgn_game().contains("[Result \"*\"]"));
+    /// assert!(!board.export_pgn_game().contains("[Code \""));
+    ///
+    /// board.guess(code("rgby"));
+    /// assert_eq!(
+    ///     board.export_pgn_game(),
+    ///     "[Event \"Mastermind\"]\n\
+    ///      [Colors \"rgby\"]\n\
+    ///      [CodeLength \"4\"]\n\
+    ///      [Duplicates \"yes\"]\n\
+    ///      [MaxGuesses \"-\"]\n\
+    ///      [Code \"rgby\"]\n\
+    ///      [Result \"1-0\"]\n\
+    ///      \n\
+    ///      1. rrrr 1b0w 2. rggg 2b0w 3. gggg 1b0w $2 4. rgby 4b0w 1-0\n"
+    /// );
+    /// ```
+    pub fn export_pgn_game(&self) -> String {
+        let letters = |code: &Code| code.letters(&self.config.palette);
+        let state = self.state();
+        let result = if state.won {
+            "1-0"
+        } else if state.guesses_left == Some(0) {
+            "0-1"
+        } else {
+            "*"
+        };
+        let mut tags = vec![
+            ("Event", String::from("Mastermind")),
+            (
+                "Colors",
+                self.config
+                    .colors
+                    .iter()
+                    .map(|color| self.config.palette.style(color).letter)
+                    .collect(),
+            ),
+            ("CodeLength", self.config.code_length.to_string()),
+            (
+                "Duplicates",
+                String::from(if self.config.allow_duplicates {
+                    "yes"
+                } else {
+                    "no"
+                }),
+            ),
+            (
+                "MaxGuesses",
+                self.config
+                    .max_guesses
+                    .map_or(String::from("-"), |max| max.to_string()),
+            ),
+        ];
+        if result != "*" {
+            tags.push(("Code", letters(&self.hidden_code)));
+        }
+        tags.push(("Result", String::from(result)));
+
+        let mut moves = Vec::new();
+        let reviews = entropy_review(self);
+        for (number, (round, review)) in self.rounds.iter().zip(&reviews).enumerate() {
+            let score = round.score();
+            moves.push(format!(
+                "{}. {} {}b{}w",
+                number + 1,
+                letters(round.guess()),
+                score.black_count(),
+                score.white_count()
+            ));
+            if review.is_mistake() && !round.wins() {
+                moves.push(String::from("$2"));
+            }
+        }
+        moves.push(String::from(result));
+
+        let mut pgn = String::new();
+        for (name, value) in tags {
+            pgn.push_str(&format!("[{} \"{}\"]\n", name, value));
+        }
+        pgn.push('\n');
+        // PGN keeps lines of move text under 80 characters.
+        for line in wrap(&moves.join(" "), 79) {
+            pgn.push_str(&line);
+            pgn.push('\n');
+        }
+        pgn
+    }
+
+    /// Shows how the remaining candidates would split if `code` were guessed,
+    /// without using up a guess.
+    fn what_if(&self, code: &str) {
+        if !self.config.assists {
+            println!("what-if is disabled in this game");
+            return;
+        }
+        match Code::parse(code.to_string(), &self.config) {
+            Ok(code) => {
+                let candidates: Vec<Code> = self.candidates().iter().collect();
+                println!("{}", describe_buckets(&score_buckets(&code, &candidates)));
+            }
+            Err(e) => println!("{}", e),
+        }
+    }
+
+    /// Returns the color at `pos`, counting from 0, of the hidden code.
+    ///
+    /// # Panics
+    ///
+    /// Unless the board's config has `cheat_mode` set, or if `pos` is past
+    /// the end of the code.
+    ///
+    /// ```
+    /// use mastermind::{Board, Color, GameConfig};
+    /// use std::panic::{self, AssertUnwindSafe};
+    ///
+    /// let config = GameConfig {
+    ///     cheat_mode: true,
+    ///     ..GameConfig::default()
+    /// };
+    /// let board = Board::new(config, String::from("rgby").try_into().unwrap());
+    /// assert_eq!(board.peek_position(2), Color::Blue);
+    ///
+    /// let board = Board::new(GameConfig::default(), String::from("rgby").try_into().unwrap());
+    /// let peeked = panic::catch_unwind(AssertUnwindSafe(|| board.peek_position(0)));
+    /// assert!(peeked.is_err());
+    /// ```
+    pub fn peek_position(&self, pos: usize) -> Color {
+        assert!(
+            self.config.cheat_mode,
+            "peek_position needs a board whose config has cheat_mode set"
+        );
+        self.hidden_code.colors()[pos].clone()
+    }
+
+    /// Reveals the peg at `position`, counting from 1, dimmed to set it apart
+    /// from the game.
+    fn peek(&self, position: &str) {
+        if !self.config.cheat_mode {
+            println!("peek needs --cheat");
+            return;
+        }
+        match position.parse::<usize>() {
+            Ok(n) if (1..=self.config.code_length).contains(&n) => {
+                let color = self.peek_position(n - 1);
+                let name = &self.config.palette.style(&color).name;
+                println!("{}", dim(&format!("Position {} is {}", n, name)));
+            }
+            _ => println!(
+                "peek takes a position from 1 to {}",
+                self.config.code_length
+            ),
+        }
+    }
+
+    /// Warns about colors in `guess` that no remaining possibility contains, and
+    /// explains the first earlier round that rules `guess` out.
+    fn coach(&self, guess: &Code) {
+        for color in self.candidates().eliminated_colors() {
+            if guess.contains(&color) {
+                println!(
+                    "heads up: no remaining possibility contains {}",
+                    self.config.palette.style(&color).name
+                );
+            }
+        }
+        let explained = self.rounds.iter().enumerate().find_map(|(i, round)| {
+            explain_inconsistency(guess, round, &self.config.palette).map(|why| (i + 1, why))
+        });
+        if let Some((number, why)) = explained {
+            println!("heads up: round {}: {} - impossible", number, why);
+        }
+    }
+
+    /// Asks for the hidden code on the terminal, then starts a game with it.
+    pub fn init(config: GameConfig) -> Result<Self> {
+        let mut buffer = String::new();
+        if config.output_level > OutputLevel::Quiet {
+            Self::print_intro(&config)?;
+        }
+        read_hidden(&mut buffer)?;
+
+        let hidden_code = Code::parse(buffer, &config)?;
+        Ok(Self::start(config, hidden_code))
+    }
+
+    /// Starts a game against `hidden_code`, explaining the scoring unless quiet.
+    pub fn start(config: GameConfig, hidden_code: Code) -> Self {
+        if config.output_level > OutputLevel::Quiet {
+            Self::print_legend();
+        }
+        Self::new(config, hidden_code)
+    }
+
+    fn print_intro(config: &GameConfig) -> Result<()> {
+        let letters: Vec<String> = config
+            .colors
+            .iter()
+            .map(|color| color.glyph(&config.palette, DisplayMode::Letters))
+            .collect();
+        println!(" to begin you will need to input hidden code.");
+        println!(
+            " codes are {} pegs, each one of these letters:\n {}",
+            config.code_length,
+            letters.join(" ")
+        );
+        println!(
+            " or their positions in that list, 1 through {}",
+            config.colors.len()
+        );
+        print!("hidden code: ");
+        std::io::stdout().flush()?;
+        Ok(())
+    }
+
+    fn print_legend() {
+        println!("\n great.\n");
+        println!("{}", legend(width()));
+    }
+}
+
+/// Explains the key pegs, with each line wrapped to fit `width` columns.
+///
+/// ```
+/// use mastermind::board::legend;
+/// use mastermind::layout::visible_width;
+///
+/// assert_eq!(legend(80).lines().count(), 6);
+/// assert!(legend(24).lines().count() > 6);
+/// assert!(legend(24).lines().all(|line| visible_width(line) <= 24));
+/// ```
+pub fn legend(width: usize) -> String {
+    let text = format!(
+        r#"score is represented with three different colors:
+
+ correct color, correct position: {}
+ correct color, wrong position: {}
+ wrong color, wrong position: {}
+ good luck!"#,
+        ScoreDetail::ColorAndPositionCorrect,
+        ScoreDetail::ColorCorrect,
+        ScoreDetail::Empty,
+    );
+    let lines: Vec<String> = text.lines().flat_map(|line| wrap(line, width)).collect();
+    lines.join("\n")
+}
+
+/// Draws one peg of `palette` for `Board::print_wide` as a block of its
+/// color with its letter in the middle, underlined if `changed`, or, when
+/// `writer` has no color, as its letter repeated, in brackets if `changed`.
+fn write_wide_cell<W: WriteColor>(
+    writer: &mut W,
+    color: &Color,
+    palette: &Palette,
+    changed: bool,
+) -> std::io::Result<()> {
+    let letter = palette.style(color).letter;
+    if !writer.supports_color() {
+        return if changed {
+            write!(writer, "[{}]", letter)
+        } else {
+            write!(writer, "{}", letter.to_string().repeat(WIDE_CELL_WIDTH))
+        };
+    }
+    writer.set_color(labelled_block_spec(color, palette).set_underline(changed))?;
+    write!(writer, "{:^width$}", letter, width = WIDE_CELL_WIDTH)?;
+    writer.reset()
+}
+
+/// Draws the key peg `detail` for `Board::print_wide` as a colored blank, or
+/// as its letter when `writer` has no color.
+fn write_key_peg<W: WriteColor>(writer: &mut W, detail: &ScoreDetail) -> std::io::Result<()> {
+    if !writer.supports_color() {
+        return write!(writer, "{}", detail.letter());
+    }
+    writer.set_color(&key_spec(detail))?;
+    write!(writer, " ")?;
+    writer.reset()
+}
+
+/// Draws the pegs `colors` of `palette` in `mode` with those at `changed`
+/// underlined, or without color in brackets, with the rest padded to the
+/// same width so the columns still line up.
+fn diff_guess(colors: &[Color], changed: &[usize], palette: &Palette, mode: DisplayMode) -> String {
+    if !color_enabled() {
+        return colors
+            .iter()
+            .enumerate()
+            .map(|(i, color)| {
+                if changed.contains(&i) {
+                    format!("[{}]", color.glyph(palette, mode))
+                } else {
+                    format!(" {} ", color.glyph(palette, mode))
+                }
+            })
+            .collect();
+    }
+    let pegs: Vec<String> = colors
+        .iter()
+        .enumerate()
+        .map(|(i, color)| {
+            if changed.contains(&i) {
+                underline(&color.glyph(palette, mode))
+            } else {
+                color.glyph(palette, mode)
+            }
+        })
+        .collect();
+    pegs.join(" ")
+}
+
+/// How many characters wide `diff_guess` draws `length` pegs each
+/// `peg_width` wide.
+fn diff_guess_width(length: usize, peg_width: usize) -> usize {
+    if color_enabled() {
+        (peg_width + 1) * length - 1
+    } else {
+        (peg_width + 2) * length
+    }
+}
+
+/// Reads a line from the terminal without echoing it.
+#[cfg(feature = "color")]
+fn read_hidden(buffer: &mut String) -> Result<()> {
+    terminal::enable_raw_mode()?;
+    while let Event::Key(KeyEvent {
+        code, modifiers, ..
+    }) = event::read()?
+    {
+        match code {
+            KeyCode::Enter => {
+                break;
+            }
+            // Raw mode turns Ctrl-C into a key press rather than a signal.
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                terminal::disable_raw_mode()?;
+                return Err(GameError::Io(std::io::ErrorKind::Interrupted.into()).into());
+            }
+            KeyCode::Char(c) => {
+                buffer.push(c);
+            }
+            _ => {}
+        }
+    }
+    terminal::disable_raw_mode()?;
+    Ok(())
+}
+
+/// Reads a line from stdin; without terminal support the input is echoed.
+#[cfg(not(feature = "color"))]
+fn read_hidden(buffer: &mut String) -> Result<()> {
+    std::io::stdin().read_line(buffer)?;
+    Ok(())
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut lines = Vec::new();
+        let mut index = 0;
+        for outcome in &self.outcomes {
+            let round = match outcome {
+                RoundOutcome::Guessed(round) => round,
+                RoundOutcome::Skipped { reason } => {
+                    lines.push(self.skipped_row(*reason));
+                    continue;
+                }
+            };
+            let guess = self.guess_text(index);
+            index += 1;
+            let s = if self.config.anagram_mode {
+                let common = round.guess().common_peg_count(&self.hidden_code);
+                format!("| {} | {} |", guess, common)
+            } else {
+                let pegs: Vec<String> = round
+                    .score()
+                    .details(round.guess().len())
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+                format!("| {} | {} |", guess, pegs.join(" "))
+            };
+            lines.push(s);
+        }
+        if !lines.is_empty() {
+            let width = if self.config.anagram_mode {
+                self.guess_width() + 8
+            } else {
+                self.guess_width() + 2 * self.config.code_length + 6
+            };
+            writeln!(f, "\n{}", "=".repeat(width))?;
+            writeln!(f, "{}", lines.join("\n"))?;
+            writeln!(f, "{}", "=".repeat(width))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a Board {
+    type Item = &'a Round;
+    type IntoIter = std::slice::Iter<'a, Round>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rounds.iter()
+    }
+}
+
+impl IntoIterator for Board {
+    type Item = Round;
+    type IntoIter = std::vec::IntoIter<Round>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_rounds().into_iter()
+    }
+}
+
+/// Why a guess was used up without being played.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum SkipReason {
+    /// The time for the guess ran out.
+    Timeout,
+}
+
+impl SkipReason {
+    /// Returns the name saves and the board use for the reason.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SkipReason::Timeout => "timeout",
+        }
+    }
+
+    /// Returns the reason called `name`, if there is one.
+    pub fn from_name(name: &str) -> Option<SkipReason> {
+        match name {
+            "timeout" => Some(SkipReason::Timeout),
+            _ => None,
+        }
+    }
+}
+
+/// What became of one guess: played and scored, or skipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RoundOutcome {
+    Guessed(Round),
+    Skipped { reason: SkipReason },
+}
+
+impl RoundOutcome {
+    /// Returns the round played, or `None` for a skipped guess.
+    pub fn round(&self) -> Option<&Round> {
+        match self {
+            RoundOutcome::Guessed(round) => Some(round),
+            RoundOutcome::Skipped { .. } => None,
+        }
+    }
+
+    /// Returns the code guessed, or `None` for a skipped guess.
+    pub fn guess(&self) -> Option<&Code> {
+        self.round().map(Round::guess)
+    }
+
+    /// Returns the score, or `None` for a skipped guess.
+    pub fn score(&self) -> Option<&Score> {
+        self.round().map(Round::score)
+    }
+}
+
+/// A single guess and the score it received.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Round {
+    input_code: Code,
+    score: Score,
+}
+
+impl Round {
+    pub(crate) fn new(input_code: Code, score: Score) -> Self {
+        Self { input_code, score }
+    }
+
+    /// Scores `guess` against `hidden`.
+    ///
+    /// ```
+    /// use mastermind::{Code, Round};
+    ///
+    /// let hidden: Code = String::from("rgby").try_into().unwrap();
+    /// let round = Round::from_guess(hidden.clone(), &hidden);
+    /// assert!(round.wins());
+    ///
+    /// let round = Round::from_guess(String::from("rgyb").try_into().unwrap(), &hidden);
+    /// assert!(!round.wins());
+    /// assert_eq!(round.score().black_count(), 2);
+    /// assert_eq!(round.score().white_count(), 2);
+    /// ```
+    pub fn from_guess(guess: Code, hidden: &Code) -> Round {
+        let score = hidden.score(&guess);
+        Self::new(guess, score)
+    }
+
+    /// Returns the code that was guessed.
+    pub fn guess(&self) -> &Code {
+        &self.input_code
+    }
+
+    /// Returns the score the guess received.
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    pub fn wins(&self) -> bool {
+        self.score.is_win(self.input_code.len())
+    }
+
+    /// Returns something that writes this round with the guess in `palette`'s
+    /// letters, the way `Display` writes it in the classic ones.
+    ///
+    /// ```
+    /// use mastermind::display::set_color;
+    /// use mastermind::{Code, GameConfig, Palette, Round};
+    ///
+    /// set_color(false);
+    /// let palette = Palette::from_letters("0123").unwrap();
+    /// let config = GameConfig {
+    ///     colors: palette.values(),
+    ///     palette,
+    ///     ..GameConfig::default()
+    /// };
+    /// let code = |s: &str| Code::from_letters(s, &config).unwrap();
+    /// let round = Round::from_guess(code("0011"), &code("0123"));
+    /// assert_eq!(round.display(&config.palette).to_string(), "0 0 1 1 | b w - -");
+    /// assert_eq!(round.to_string(), "r r g g | b w - -");
+    /// ```
+    pub fn display<'a>(&'a self, palette: &'a Palette) -> RoundDisplay<'a> {
+        RoundDisplay {
+            round: self,
+            palette,
+        }
+    }
+
+    /// Says in words what the score means, given the `hidden` code it was
+    /// scored against. Pegs left over once blacks and whites are counted are
+    /// either colors the code lacks or repeats of a color it holds fewer of.
+    ///
+    /// ```
+    /// use mastermind::{Code, Round};
+    ///
+    /// let explain = |guess: &str, hidden: &str| {
+    ///     let hidden: Code = hidden.parse().unwrap();
+    ///     Round::from_guess(guess.parse().unwrap(), &hidden).explain(&hidden)
+    /// };
+    /// assert_eq!(
+    ///     explain("rgbp", "rbgy"),
+    ///     "1 peg is the right color in the right place; 2 more are correct colors \
+    ///      in the wrong place; 1 color doesn't appear in the code at all"
+    /// );
+    /// assert_eq!(explain("rgby", "rgby"), "all 4 pegs are the right color in the right place");
+    /// assert_eq!(
+    ///     explain("pnwk", "rgby"),
+    ///     "no peg is the right color in the right place; 4 colors don't appear in the code at all"
+    /// );
+    /// assert_eq!(
+    ///     explain("gbyr", "rgby"),
+    ///     "no peg is the right color in the right place; 4 are correct colors in the wrong place"
+    /// );
+    ///
+    /// // Repeats never earn more pegs than the code has of that color.
+    /// assert_eq!(
+    ///     explain("rrrr", "rgby"),
+    ///     "1 peg is the right color in the right place; \
+    ///      3 more repeat a color the code has fewer of"
+    /// );
+    /// assert_eq!(
+    ///     explain("grrg", "rgbb"),
+    ///     "no peg is the right color in the right place; 2 are correct colors in the wrong \
+    ///      place; 2 more repeat a color the code has fewer of"
+    /// );
+    /// ```
+    pub fn explain(&self, hidden: &Code) -> String {
+        let blacks = self.score.black_count();
+        let whites = self.score.white_count();
+        let length = self.input_code.len();
+        if blacks == length {
+            return format!("all {} pegs are the right color in the right place", length);
+        }
+        let guess = self.input_code.colors();
+        let missing = |color: &&Color| !hidden.colors().contains(color);
+        let absent_pegs = guess.iter().filter(missing).count();
+        let mut absent_colors: Vec<&Color> = guess.iter().filter(missing).collect();
+        absent_colors.sort();
+        absent_colors.dedup();
+        let repeats = length - blacks - whites - absent_pegs;
+
+        let mut parts = vec![match blacks {
+            0 => String::from("no peg is the right color in the right place"),
+            1 => String::from("1 peg is the right color in the right place"),
+            n => format!("{} pegs are the right color in the right place", n),
+        }];
+        let more = if blacks == 0 { "" } else { " more" };
+        match whites {
+            0 => {}
+            1 => parts.push(format!("1{} is a correct color in the wrong place", more)),
+            n => parts.push(format!(
+                "{}{} are correct colors in the wrong place",
+                n, more
+            )),
+        }
+        match absent_colors.len() {
+            0 => {}
+            1 => parts.push(String::from("1 color doesn't appear in the code at all")),
+            n => parts.push(format!("{} colors don't appear in the code at all", n)),
+        }
+        match repeats {
+            0 => {}
+            1 => parts.push(String::from("1 more repeats a color the code has fewer of")),
+            n => parts.push(format!("{} more repeat a color the code has fewer of", n)),
+        }
+        parts.join("; ")
+    }
+}
+
+/// Writes the guess in the classic letters; `Round::display` writes it in
+/// another palette's.
+impl fmt::Display for Round {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.display(palette::classic()))
+    }
+}
+
+/// Written as `{"guess": "rrgg", "score": {"blacks": 1, "whites": 1}}`, with
+/// the classic letters. Reading one back refuses a score with more key pegs
+/// than the guess has pegs.
+///
+/// ```
+/// use mastermind::{Code, Round};
+///
+/// let round = Round::from_guess("rrgg".parse().unwrap(), &"rgby".parse::<Code>().unwrap());
+/// let json = serde_json::to_string(&round).unwrap();
+/// assert_eq!(json, r#"{"guess":"rrgg","score":{"blacks":1,"whites":1}}"#);
+/// assert_eq!(serde_json::from_str::<Round>(&json).unwrap(), round);
+/// let overscored = r#"{"guess":"rg","score":{"blacks":2,"whites":1}}"#;
+/// assert!(serde_json::from_str::<Round>(overscored).is_err());
+/// ```
+#[cfg(feature = "serde")]
+impl serde::Serialize for Round {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        SavedRound::new(self, palette::classic()).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Round {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        SavedRound::deserialize(deserializer)?
+            .read(palette::classic())
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A round with its guess written in the letters of a palette, made by
+/// `Round::display`.
+pub struct RoundDisplay<'a> {
+    round: &'a Round,
+    palette: &'a Palette,
+}
+
+impl fmt::Display for RoundDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let guess = self.round.guess();
+        write!(f, "{} | ", guess.display(self.palette))?;
+        self.round.score.fmt_pegs(f, guess.len())
+    }
+}
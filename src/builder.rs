@@ -0,0 +1,212 @@
+//! Setting up a game from code, with every setting checked against the others
+//! in one place.
+
+use crate::board::Board;
+use crate::code::{Code, Color, LazyCodeSpace, MAX_CODE_LENGTH};
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::random::Rng;
+
+/// Where the hidden code of a built game comes from.
+#[derive(Clone, Debug)]
+enum Secret {
+    Given(Code),
+    /// Picked when the game is built, from a generator seeded when the
+    /// builder was asked for a random code.
+    Random(Rng),
+}
+
+/// Builds a `Board` from settings given one at a time, checking at `build`
+/// that they and the hidden code fit together. Start one with
+/// `Board::builder`; unset settings keep their `GameConfig::default` values.
+///
+/// ```
+/// use mastermind::{Board, Color, GameError};
+///
+/// # fn main() -> Result<(), GameError> {
+/// let mut board = Board::builder()
+///     .length(5)
+///     .colors(6)
+///     .allow_duplicates(false)
+///     .max_guesses(8)
+///     .secret_from_str("rgbpy")?
+///     .build()?;
+/// assert_eq!(board.config().colors, &Color::ALL[..6]);
+///
+/// for guess in ["rgbyp", "rgbpn", "rgbpy"] {
+///     board.guess(guess.parse()?);
+/// }
+/// let state = board.state();
+/// assert!(state.won);
+/// assert_eq!(state.guesses_left, Some(5));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// A random hidden code follows the rules the game is built with.
+///
+/// ```
+/// use mastermind::{Board, Rng};
+///
+/// let mut rng = Rng::seeded(3);
+/// for _ in 0..20 {
+///     let board = Board::builder()
+///         .length(4)
+///         .colors(4)
+///         .allow_duplicates(false)
+///         .secret_random(&mut rng)
+///         .build()
+///         .unwrap();
+///     let mut colors = board.hidden_code().colors().to_vec();
+///     colors.sort();
+///     colors.dedup();
+///     assert_eq!(colors.len(), 4);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct BoardBuilder {
+    config: GameConfig,
+    /// The number of colors asked for, checked at `build`.
+    colors: Option<usize>,
+    secret: Option<Secret>,
+}
+
+impl BoardBuilder {
+    pub fn new() -> Self {
+        Self {
+            config: GameConfig::default(),
+            colors: None,
+            secret: None,
+        }
+    }
+
+    /// Sets how many pegs a code has.
+    pub fn length(mut self, length: usize) -> Self {
+        self.config.code_length = length;
+        self
+    }
+
+    /// Plays with the first `count` colors of `Color::ALL`.
+    pub fn colors(mut self, count: usize) -> Self {
+        self.colors = Some(count);
+        self
+    }
+
+    /// Sets whether a code may use the same color more than once.
+    pub fn allow_duplicates(mut self, allow: bool) -> Self {
+        self.config.allow_duplicates = allow;
+        self
+    }
+
+    /// Sets how many guesses the player gets before losing.
+    pub fn max_guesses(mut self, max: usize) -> Self {
+        self.config.max_guesses = Some(max);
+        self
+    }
+
+    /// Hides `code`, which must fit the settings when the game is built.
+    pub fn secret(mut self, code: Code) -> Self {
+        self.secret = Some(Secret::Given(code));
+        self
+    }
+
+    /// Hides the code written in `s`, in any of the ways `Code` parses from a
+    /// string. Letters that name no color are an error here; whether the code
+    /// fits the settings is checked when the game is built.
+    pub fn secret_from_str(self, s: &str) -> Result<Self, GameError> {
+        Ok(self.secret(s.parse()?))
+    }
+
+    /// Hides a code picked at random, when the game is built, from those
+    /// valid under its settings.
+    pub fn secret_random(mut self, rng: &mut Rng) -> Self {
+        self.secret = Some(Secret::Random(Rng::seeded(rng.next_u64())));
+        self
+    }
+
+    /// Checks the settings and hidden code against each other, then starts a
+    /// game with them.
+    ///
+    /// ```
+    /// use mastermind::{Board, BoardBuilder, Color, GameError};
+    ///
+    /// let builder = || Board::builder().secret_from_str("rgby").unwrap();
+    /// let setting = |built: Result<Board, GameError>| match built {
+    ///     Err(GameError::InvalidSetting { setting, .. }) => setting,
+    ///     other => panic!("{:?}", other.map(|board| board.to_string())),
+    /// };
+    /// assert_eq!(setting(builder().length(0).build()), "length");
+    /// assert_eq!(setting(builder().colors(0).build()), "colors");
+    /// assert_eq!(setting(builder().colors(9).build()), "colors");
+    /// assert_eq!(setting(builder().max_guesses(0).build()), "max_guesses");
+    /// assert_eq!(setting(builder().length(40).build()), "length");
+    /// assert_eq!(setting(builder().length(256).colors(1).build()), "length");
+    /// assert_eq!(setting(BoardBuilder::new().build()), "secret");
+    ///
+    /// let too_few = Board::builder()
+    ///     .length(5)
+    ///     .colors(4)
+    ///     .allow_duplicates(false)
+    ///     .secret_from_str("rgbyr")
+    ///     .unwrap()
+    ///     .build();
+    /// assert_eq!(setting(too_few), "colors");
+    ///
+    /// let short = builder().length(5).build();
+    /// assert!(matches!(short, Err(GameError::WrongLength { expected: 5, found: 4, .. })));
+    /// let unused = builder().colors(3).build();
+    /// assert!(matches!(unused, Err(GameError::InvalidCharacter { position: 3, found: 'y', .. })));
+    /// let repeated = Board::builder()
+    ///     .allow_duplicates(false)
+    ///     .secret_from_str("rgbr")
+    ///     .unwrap()
+    ///     .build();
+    /// assert!(matches!(
+    ///     repeated,
+    ///     Err(GameError::RepeatedColor { color: Color::Red, .. })
+    /// ));
+    /// assert!(matches!(
+    ///     Board::builder().secret_from_str("rgbx"),
+    ///     Err(GameError::InvalidCharacter { found: 'x', .. })
+    /// ));
+    /// ```
+    pub fn build(self) -> Result<Board, GameError> {
+        let mut config = self.config;
+        let invalid = |setting, reason: String| Err(GameError::InvalidSetting { setting, reason });
+        if !(1..=MAX_CODE_LENGTH).contains(&config.code_length) {
+            return invalid("length", format!("must be from 1 to {}", MAX_CODE_LENGTH));
+        }
+        if let Some(count) = self.colors {
+            if !(1..=Color::ALL.len()).contains(&count) {
+                return invalid("colors", format!("must be from 1 to {}", Color::ALL.len()));
+            }
+            config.colors = Color::ALL[..count].to_vec();
+        }
+        if !config.allow_duplicates && config.colors.len() < config.code_length {
+            return invalid(
+                "colors",
+                format!(
+                    "{} are too few for {} pegs without repeats",
+                    config.colors.len(),
+                    config.code_length
+                ),
+            );
+        }
+        if config.max_guesses == Some(0) {
+            return invalid("max_guesses", String::from("must be at least 1"));
+        }
+        LazyCodeSpace::try_new(&config)?;
+        let hidden_code = match self.secret {
+            Some(Secret::Given(code)) => Code::try_new(code.colors().to_vec(), &config)?,
+            Some(Secret::Random(mut rng)) => Code::random(&config, &mut rng),
+            None => return invalid("secret", String::from("no hidden code was given")),
+        };
+        Ok(Board::new(config, hidden_code))
+    }
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,302 @@
+//! Challenge codes: a whole puzzle, settings and hidden code, packed into a
+//! short string that can be sent to someone else to play.
+//!
+//! The hidden code is only scrambled so it cannot be read at a glance; anyone
+//! with this crate can decode it.
+//!
+//! A code is these bytes in base32:
+//!
+//! | bytes | meaning                                                    |
+//! |-------|------------------------------------------------------------|
+//! | 1     | format version, currently 1                                |
+//! | 1     | salt for scrambling the hidden code                        |
+//! | 1     | colors in play, one bit per `Color` in `Color::ALL` order  |
+//! | 1     | code length                                                |
+//! | 1     | guesses allowed, 0 for unlimited                           |
+//! | 1     | flags: 1 for repeated colors, 2 for anagram mode           |
+//! | n     | the hidden code, one scrambled color index per peg         |
+//! | 2     | Fletcher-16 checksum of everything before it               |
+
+use crate::code::{Code, Color};
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::random::Rng;
+
+/// The format written by `Challenge::encode`.
+const VERSION: u8 = 1;
+
+const ALLOW_DUPLICATES: u8 = 1;
+const ANAGRAM_MODE: u8 = 2;
+
+/// The bytes before the hidden code, and the checksum after it.
+const HEADER_LEN: usize = 6;
+const CHECKSUM_LEN: usize = 2;
+
+const BASE32: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// A puzzle to pass on: the rules it is played under and its hidden code.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Challenge {
+    /// Only the colors, code length, guess limit, whether colors may repeat
+    /// and anagram mode are kept; everything else is left at its default.
+    pub config: GameConfig,
+    pub hidden: Code,
+}
+
+impl Challenge {
+    pub fn new(config: GameConfig, hidden: Code) -> Self {
+        Self { config, hidden }
+    }
+
+    /// Packs the challenge into a lowercase base32 string. `salt` changes how
+    /// the hidden code is scrambled, so the same puzzle can be sent in
+    /// different-looking codes.
+    ///
+    /// Fails if the code is longer than 255 pegs or the guess limit is above
+    /// 255, which do not fit the format.
+    ///
+    /// ```
+    /// use mastermind::challenge::Challenge;
+    /// use mastermind::{Code, GameConfig, GameError, Rng};
+    /// use std::collections::HashSet;
+    ///
+    /// let mut rng = Rng::seeded(7);
+    /// for _ in 0..500 {
+    ///     let colors: Vec<_> = mastermind::Color::ALL
+    ///         .into_iter()
+    ///         .filter(|_| rng.below(2) == 0)
+    ///         .collect();
+    ///     if colors.is_empty() {
+    ///         continue;
+    ///     }
+    ///     let mut config = GameConfig {
+    ///         colors,
+    ///         code_length: 1 + rng.below(8),
+    ///         max_guesses: [None, Some(1 + rng.below(20))][rng.below(2)],
+    ///         anagram_mode: rng.below(2) == 0,
+    ///         ..GameConfig::default()
+    ///     };
+    ///     let hidden = Code::random(&config, &mut rng);
+    ///     let distinct: HashSet<_> = hidden.colors().iter().collect();
+    ///     config.allow_duplicates = distinct.len() < hidden.len() || rng.below(2) == 0;
+    ///
+    ///     let challenge = Challenge::new(config, hidden);
+    ///     let code = challenge.encode(rng.below(256) as u8).unwrap();
+    ///     assert_eq!(Challenge::decode(&code).unwrap(), challenge);
+    ///     assert_eq!(Challenge::decode(&code.to_uppercase()).unwrap(), challenge);
+    ///
+    ///     // Any one mistyped character is caught.
+    ///     let mut typo: Vec<char> = code.chars().collect();
+    ///     let at = rng.below(typo.len());
+    ///     typo[at] = if typo[at] == 'a' { 'b' } else { 'a' };
+    ///     let typo: String = typo.into_iter().collect();
+    ///     assert!(matches!(Challenge::decode(&typo), Err(GameError::InvalidChallenge)));
+    /// }
+    /// ```
+    pub fn encode(&self, salt: u8) -> Result<String, GameError> {
+        let byte = |n: usize, what: &str| {
+            u8::try_from(n).map_err(|_| {
+                GameError::ParseError(format!("{} {} does not fit in a challenge code", what, n))
+            })
+        };
+        if !self.config.palette.is_classic() {
+            return Err(GameError::ParseError(String::from(
+                "only games with the classic colors fit in a challenge code",
+            )));
+        }
+        let colors = Color::ALL
+            .iter()
+            .enumerate()
+            .filter(|(_, color)| self.config.colors.contains(color))
+            .fold(0u8, |mask, (i, _)| mask | 1 << i);
+        let mut flags = 0;
+        if self.config.allow_duplicates {
+            flags |= ALLOW_DUPLICATES;
+        }
+        if self.config.anagram_mode {
+            flags |= ANAGRAM_MODE;
+        }
+        let mut bytes = vec![
+            VERSION,
+            salt,
+            colors,
+            byte(self.config.code_length, "a code length of")?,
+            byte(self.config.max_guesses.unwrap_or(0), "a limit of")?,
+            flags,
+        ];
+        bytes.extend(
+            self.hidden
+                .colors()
+                .iter()
+                .zip(keystream(salt))
+                .map(|(color, key)| color.index() as u8 ^ key),
+        );
+        bytes.extend(fletcher16(&bytes).to_be_bytes());
+        Ok(base32_encode(&bytes))
+    }
+
+    /// Unpacks a code made by `encode`, ignoring case and surrounding space.
+    ///
+    /// ```
+    /// use mastermind::challenge::Challenge;
+    /// use mastermind::{Code, GameConfig};
+    ///
+    /// let config = GameConfig::super_mastermind();
+    /// let hidden = Code::parse(String::from("pkwnb"), &config).unwrap();
+    /// let code = Challenge::new(config, hidden.clone()).encode(42).unwrap();
+    /// assert!(!code.contains("pkwnb"));
+    ///
+    /// let challenge = Challenge::decode(&format!("  {}\n", code)).unwrap();
+    /// assert_eq!(challenge.config.colors.len(), 8);
+    /// assert_eq!(challenge.hidden, hidden);
+    /// assert_eq!(Challenge::decode("hello").unwrap_err().to_string(), "invalid challenge code");
+    /// ```
+    pub fn decode(code: &str) -> Result<Challenge, GameError> {
+        let bytes = base32_decode(code.trim()).ok_or(GameError::InvalidChallenge)?;
+        if bytes.len() < HEADER_LEN + CHECKSUM_LEN {
+            return Err(GameError::InvalidChallenge);
+        }
+        let (body, checksum) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+        if fletcher16(body).to_be_bytes() != checksum {
+            return Err(GameError::InvalidChallenge);
+        }
+        let (header, pegs) = body.split_at(HEADER_LEN);
+        let [version, salt, colors, code_length, max_guesses, flags] = header else {
+            unreachable!("the header is split off at its length");
+        };
+        if *version != VERSION {
+            return Err(GameError::ParseError(format!(
+                "challenge code format {} is not supported, only {}",
+                version, VERSION
+            )));
+        }
+        let config = GameConfig {
+            colors: Color::ALL
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| colors & 1 << i != 0)
+                .map(|(_, color)| color)
+                .collect(),
+            code_length: usize::from(*code_length),
+            max_guesses: Some(usize::from(*max_guesses)).filter(|n| *n > 0),
+            allow_duplicates: flags & ALLOW_DUPLICATES != 0,
+            anagram_mode: flags & ANAGRAM_MODE != 0,
+            ..GameConfig::default()
+        };
+        let hidden = pegs
+            .iter()
+            .zip(keystream(*salt))
+            .map(|(peg, key)| Color::ALL.get(usize::from(peg ^ key)).cloned())
+            .collect::<Option<Vec<Color>>>()
+            .ok_or(GameError::InvalidChallenge)?;
+        if config.colors.is_empty() || config.code_length == 0 {
+            return Err(GameError::InvalidChallenge);
+        }
+        let hidden = Code::try_new(hidden, &config).map_err(|_| GameError::InvalidChallenge)?;
+        Ok(Challenge { config, hidden })
+    }
+}
+
+/// Returns the bytes the hidden code is scrambled with for `salt`.
+fn keystream(salt: u8) -> impl Iterator<Item = u8> {
+    let mut rng = Rng::seeded(u64::from(salt));
+    std::iter::repeat_with(move || (rng.next_u64() % 256) as u8)
+}
+
+/// The Fletcher-16 checksum of `bytes`. Unlike a plain sum it also changes
+/// when two bytes are swapped.
+fn fletcher16(bytes: &[u8]) -> u16 {
+    let (mut low, mut high) = (0u16, 0u16);
+    for &byte in bytes {
+        low = (low + u16::from(byte)) % 255;
+        high = (high + low) % 255;
+    }
+    high << 8 | low
+}
+
+/// Encodes `bytes` as lowercase RFC 4648 base32 without padding.
+///
+/// ```
+/// use mastermind::challenge::{base32_decode, base32_encode};
+///
+/// assert_eq!(base32_encode(b"foobar"), "mzxw6ytboi");
+/// assert_eq!(base32_decode("MZXW6ytboi").unwrap(), b"foobar");
+/// assert_eq!(base32_decode("mzxw1"), None);
+/// ```
+pub fn base32_encode(bytes: &[u8]) -> String {
+    encode_bits(bytes, 5, BASE32)
+}
+
+/// Decodes base32 written by `base32_encode`, in either case, or returns
+/// `None` if `text` has other characters or leftover bits.
+pub fn base32_decode(text: &str) -> Option<Vec<u8>> {
+    decode_bits(text, 5, |c| {
+        BASE32
+            .iter()
+            .position(|&b| char::from(b) == c.to_ascii_lowercase())
+    })
+}
+
+/// Encodes `bytes` as RFC 4648 base64url without padding.
+///
+/// ```
+/// use mastermind::challenge::{base64url_decode, base64url_encode};
+///
+/// assert_eq!(base64url_encode(b"\xfb\xffmm"), "-_9tbQ");
+/// assert_eq!(base64url_decode("-_9tbQ").unwrap(), b"\xfb\xffmm");
+/// assert_eq!(base64url_decode("-_9tb+"), None);
+/// ```
+pub fn base64url_encode(bytes: &[u8]) -> String {
+    encode_bits(bytes, 6, BASE64URL)
+}
+
+/// Decodes base64url written by `base64url_encode`, or returns `None` if
+/// `text` has other characters or leftover bits.
+pub fn base64url_decode(text: &str) -> Option<Vec<u8>> {
+    decode_bits(text, 6, |c| {
+        BASE64URL.iter().position(|&b| char::from(b) == c)
+    })
+}
+
+/// Writes `bytes` `width` bits at a time as characters of `alphabet`, padding
+/// the last character with zero bits.
+fn encode_bits(bytes: &[u8], width: u32, alphabet: &[u8]) -> String {
+    let mut out = String::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = buffer << 8 | u32::from(byte);
+        bits += 8;
+        while bits >= width {
+            bits -= width;
+            out.push(char::from(
+                alphabet[(buffer >> bits) as usize & ((1 << width) - 1)],
+            ));
+        }
+    }
+    if bits > 0 {
+        out.push(char::from(
+            alphabet[(buffer << (width - bits)) as usize & ((1 << width) - 1)],
+        ));
+    }
+    out
+}
+
+/// Reverses `encode_bits`, looking each character up with `digit`. Padding
+/// bits must be zero and less than a whole character.
+fn decode_bits(text: &str, width: u32, digit: impl Fn(char) -> Option<usize>) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in text.chars() {
+        buffer = buffer << width | digit(c)? as u32;
+        bits += width;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    (bits < width && buffer == 0).then_some(out)
+}
@@ -0,0 +1,252 @@
+//! Tips on wasted guesses for `--coach`, given as each guess is scored.
+//!
+//! Each kind of waste has its own check, and every check errs on the side of
+//! saying nothing: a tip is only given when the guess is certain to have been
+//! wasted, never when it merely could have been better.
+
+use std::fmt;
+
+use crate::board::Round;
+use crate::code::Code;
+use crate::config::GameConfig;
+use crate::deduction::CandidateSet;
+use crate::palette::{self, Palette};
+
+/// A way a guess was wasted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Tip {
+    /// The guess had been played before, in the round given.
+    Repeated(usize),
+    /// The score ruled out none of the codes still possible.
+    RuledOutNothing,
+    /// The guess could not be the code, but this rearrangement of its pegs
+    /// could have been.
+    Rearrange(Code),
+}
+
+impl Tip {
+    /// What guesses earning this kind of tip are called in the post-game
+    /// summary.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Tip::Repeated(_) => "repeated guesses",
+            Tip::RuledOutNothing => "guesses that ruled nothing out",
+            Tip::Rearrange(_) => "guesses a rearrangement would have beaten",
+        }
+    }
+
+    /// Words the tip, writing any code in `palette`'s letters.
+    ///
+    /// ```
+    /// use mastermind::coach::Tip;
+    /// use mastermind::display::set_color;
+    /// use mastermind::{Code, GameConfig, Palette};
+    ///
+    /// set_color(false);
+    /// let palette = Palette::from_letters("0123").unwrap();
+    /// let config = GameConfig {
+    ///     colors: palette.values(),
+    ///     palette,
+    ///     ..GameConfig::default()
+    /// };
+    /// let tip = Tip::Rearrange(Code::from_letters("1032", &config).unwrap());
+    /// assert_eq!(
+    ///     tip.describe(&config.palette),
+    ///     "those pegs could not be the code, but rearranged as 1 0 3 2 they could"
+    /// );
+    /// assert_eq!(
+    ///     tip.to_string(),
+    ///     "those pegs could not be the code, but rearranged as g r y b they could"
+    /// );
+    /// ```
+    pub fn describe(&self, palette: &Palette) -> String {
+        match self {
+            Tip::Repeated(round) => format!("you already guessed this in round {}", round),
+            Tip::RuledOutNothing => String::from("that guess ruled out no remaining codes"),
+            Tip::Rearrange(code) => format!(
+                "those pegs could not be the code, but rearranged as {} they could",
+                code.display(palette)
+            ),
+        }
+    }
+}
+
+/// Words the tip in the classic letters; `Tip::describe` uses another
+/// palette's.
+impl fmt::Display for Tip {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.describe(palette::classic()))
+    }
+}
+
+/// Returns the round, counting from 1, in which `guess` was already played
+/// among `earlier`.
+///
+/// ```
+/// use mastermind::coach::repeated_in;
+/// use mastermind::{Code, GameConfig, Round};
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let hidden = code("rgby");
+/// let earlier = [
+///     Round::from_guess(code("rrgg"), &hidden),
+///     Round::from_guess(code("bbyy"), &hidden),
+/// ];
+/// assert_eq!(repeated_in(&code("bbyy"), &earlier), Some(2));
+/// assert_eq!(repeated_in(&code("yybb"), &earlier), None);
+/// ```
+pub fn repeated_in(guess: &Code, earlier: &[Round]) -> Option<usize> {
+    earlier
+        .iter()
+        .position(|round| round.guess() == guess)
+        .map(|i| i + 1)
+}
+
+/// Returns whether a guess that left `after` of the `before` possible codes,
+/// without winning, ruled out nothing. With one code left there was nothing
+/// to rule out, so that is not counted.
+///
+/// ```
+/// use mastermind::coach::ruled_out_nothing;
+///
+/// assert!(ruled_out_nothing(12, 12));
+/// assert!(!ruled_out_nothing(12, 3));
+/// assert!(!ruled_out_nothing(1, 1));
+/// ```
+pub fn ruled_out_nothing(before: usize, after: usize) -> bool {
+    before > 1 && after == before
+}
+
+/// Returns the first of `candidates`, in code order, that uses exactly the
+/// pegs of `guess` in another order, if `guess` is not a candidate itself.
+///
+/// ```
+/// use mastermind::coach::better_rearrangement;
+/// use mastermind::deduction::CandidateSet;
+/// use mastermind::{Code, GameConfig};
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let mut candidates = CandidateSet::new(&config);
+/// let first = code("rgby");
+/// candidates.observe(&first, &code("gbyr").score(&first));
+///
+/// // Only the codes sharing no position with "rgby" are left.
+/// assert_eq!(better_rearrangement(&code("rgby"), &candidates), Some(code("gryb")));
+/// assert_eq!(better_rearrangement(&code("gbyr"), &candidates), None);
+/// assert_eq!(better_rearrangement(&code("rrgg"), &candidates), None);
+/// ```
+pub fn better_rearrangement(guess: &Code, candidates: &CandidateSet) -> Option<Code> {
+    if candidates.contains(guess) {
+        return None;
+    }
+    candidates
+        .iter()
+        .find(|code| code.common_peg_count(guess) == guess.len())
+}
+
+/// Watches a game's rounds and keeps the tips each one earned.
+///
+/// ```
+/// use mastermind::coach::{Coach, Tip};
+/// use mastermind::{Code, GameConfig, Round};
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let hidden = code("rgby");
+/// let mut coach = Coach::new(&config);
+///
+/// assert_eq!(coach.review(&Round::from_guess(code("rrgg"), &hidden)), []);
+/// assert_eq!(
+///     coach.review(&Round::from_guess(code("rrgg"), &hidden)),
+///     [Tip::Repeated(1)]
+/// );
+/// assert_eq!(coach.review(&Round::from_guess(code("rgyb"), &hidden)), []);
+/// // Two pegs in place and two swapped: "rbgy" moves three.
+/// assert_eq!(
+///     coach.review(&Round::from_guess(code("rbgy"), &hidden)),
+///     [Tip::Rearrange(code("rgby"))]
+/// );
+/// assert_eq!(coach.review(&Round::from_guess(code("rgby"), &hidden)), []);
+/// assert_eq!(
+///     coach.summary().unwrap(),
+///     "coach tips:\n  repeated guesses: 1\n  guesses a rearrangement would have beaten: 1"
+/// );
+///
+/// let mut coach = Coach::new(&config);
+/// for guess in ["rrgg", "bbyy"] {
+///     coach.review(&Round::from_guess(code(guess), &hidden));
+/// }
+/// assert_eq!(
+///     coach.review(&Round::from_guess(code("ggrr"), &hidden)),
+///     [Tip::RuledOutNothing]
+/// );
+/// assert_eq!(coach.tips(), [(3, Tip::RuledOutNothing)]);
+/// ```
+pub struct Coach {
+    candidates: CandidateSet,
+    rounds: Vec<Round>,
+    tips: Vec<(usize, Tip)>,
+}
+
+impl Coach {
+    pub fn new(config: &GameConfig) -> Self {
+        Self {
+            candidates: CandidateSet::new(config),
+            rounds: Vec::new(),
+            tips: Vec::new(),
+        }
+    }
+
+    /// Narrows the possible codes by `round` and returns the tips it earned.
+    /// A repeated guess only gets the tip for repeating it.
+    pub fn review(&mut self, round: &Round) -> Vec<Tip> {
+        let guess = round.guess();
+        let before = self.candidates.len();
+        let rearranged = better_rearrangement(guess, &self.candidates);
+        self.candidates.observe(guess, round.score());
+        let mut tips = Vec::new();
+        if let Some(earlier) = repeated_in(guess, &self.rounds) {
+            tips.push(Tip::Repeated(earlier));
+        } else if !round.wins() {
+            if ruled_out_nothing(before, self.candidates.len()) {
+                tips.push(Tip::RuledOutNothing);
+            }
+            if let Some(code) = rearranged {
+                tips.push(Tip::Rearrange(code));
+            }
+        }
+        self.rounds.push(round.clone());
+        let number = self.rounds.len();
+        self.tips
+            .extend(tips.iter().map(|tip| (number, tip.clone())));
+        tips
+    }
+
+    /// Returns every tip given so far, with the round, counting from 1, it
+    /// was given for.
+    pub fn tips(&self) -> &[(usize, Tip)] {
+        &self.tips
+    }
+
+    /// Counts the tips given by kind, a line each, for the post-game review,
+    /// or returns `None` if there were none.
+    pub fn summary(&self) -> Option<String> {
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for (_, tip) in &self.tips {
+            match counts.iter_mut().find(|(kind, _)| *kind == tip.kind()) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((tip.kind(), 1)),
+            }
+        }
+        if counts.is_empty() {
+            return None;
+        }
+        let lines: Vec<String> = counts
+            .iter()
+            .map(|(kind, count)| format!("  {}: {}", kind, count))
+            .collect();
+        Some(format!("coach tips:\n{}", lines.join("\n")))
+    }
+}
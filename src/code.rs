@@ -0,0 +1,1553 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use crate::board::Round;
+use crate::config::{DisplayMode, GameConfig};
+use crate::deduction::ColorConstraint;
+use crate::display::{color_enabled, paint_color};
+use crate::embedded::common_pegs;
+use crate::error::GameError;
+use crate::exact;
+use crate::input::normalize_input;
+use crate::palette::{self, Palette, PegValue};
+use crate::random::Rng;
+use crate::score::Score;
+use crate::Result;
+
+/// A peg named by the classic color it is in the default palette. Under
+/// another palette the same values stand for that palette's pegs.
+pub type Color = PegValue;
+
+/// The number of classic colors.
+const COLOR_COUNT: usize = 8;
+
+/// The most pegs a code may have. Scoring counts pegs in a `usize`, but
+/// `Code::anagram_score` and `Score::as_compact_u8` hand counts out as a `u8`,
+/// and would saturate for longer codes.
+pub const MAX_CODE_LENGTH: usize = u8::MAX as usize;
+
+#[allow(non_upper_case_globals)]
+impl PegValue {
+    pub const Red: Color = PegValue::new(0);
+    pub const Green: Color = PegValue::new(1);
+    pub const Blue: Color = PegValue::new(2);
+    pub const Yellow: Color = PegValue::new(3);
+    pub const Pink: Color = PegValue::new(4);
+    pub const Brown: Color = PegValue::new(5);
+    pub const White: Color = PegValue::new(6);
+    pub const Black: Color = PegValue::new(7);
+
+    /// Every classic color, in value order.
+    pub const ALL: [Color; COLOR_COUNT] = [
+        Color::Red,
+        Color::Green,
+        Color::Blue,
+        Color::Yellow,
+        Color::Pink,
+        Color::Brown,
+        Color::White,
+        Color::Black,
+    ];
+
+    /// Returns how different the two colors look in `palette`: the Euclidean
+    /// distance between them in CIE L*a*b* space, where equal distances are
+    /// meant to look about equally different. Pegs the palette gives no color
+    /// to are as different as black and white from every other peg.
+    ///
+    /// ```
+    /// use mastermind::{Color, Palette};
+    ///
+    /// let classic = Palette::classic();
+    /// let distance = |a: Color, b: Color| a.perceptual_distance(&b, &classic);
+    /// assert_eq!(distance(Color::Red, Color::Red), 0.0);
+    /// assert!(distance(Color::Red, Color::Pink) < distance(Color::Red, Color::Green));
+    /// assert!(distance(Color::Red, Color::Brown) < distance(Color::Red, Color::Blue));
+    /// assert_eq!(distance(Color::White, Color::Black), 100.0);
+    /// for a in Color::ALL {
+    ///     for b in Color::ALL {
+    ///         assert_eq!(distance(a.clone(), b.clone()), distance(b, a.clone()));
+    ///     }
+    /// }
+    ///
+    /// let digits = Palette::from_letters("0123").unwrap();
+    /// assert_eq!(Color::Red.perceptual_distance(&Color::Pink, &digits), 100.0);
+    /// ```
+    pub fn perceptual_distance(&self, other: &Color, palette: &Palette) -> f64 {
+        if self == other {
+            return 0.0;
+        }
+        let lab = |color: &Color| palette.style(color).lab;
+        let (Some(ours), Some(theirs)) = (lab(self), lab(other)) else {
+            return 100.0;
+        };
+        ours.iter()
+            .zip(theirs)
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Returns the color of `palette` closest to this one turned inside out:
+    /// lightness flipped and hue turned halfway round, in CIE L*a*b* space.
+    /// Red's is black, blue's is yellow, green's is pink, and white's is
+    /// black. Pegs the palette gives no color to take the first other peg;
+    /// with no other peg, a color is its own complement.
+    ///
+    /// ```
+    /// use mastermind::{Color, Palette};
+    ///
+    /// let classic = Palette::classic();
+    /// assert_eq!(Color::Red.complementary(&classic), Color::Black);
+    /// assert_eq!(Color::Blue.complementary(&classic), Color::Yellow);
+    /// assert_eq!(Color::Green.complementary(&classic), Color::Pink);
+    /// assert_eq!(Color::White.complementary(&classic), Color::Black);
+    /// for color in Color::ALL {
+    ///     let complement = color.complementary(&classic);
+    ///     assert_ne!(complement, color);
+    ///     assert!(Color::ALL.contains(&complement));
+    ///     assert!(Color::ALL.contains(&complement.complementary(&classic)));
+    /// }
+    ///
+    /// // Pegs with no color stay within their palette.
+    /// let digits = Palette::from_letters("123").unwrap();
+    /// for value in digits.values() {
+    ///     assert_ne!(value.complementary(&digits), value);
+    ///     assert!(digits.values().contains(&value.complementary(&digits)));
+    /// }
+    /// let one = Palette::from_letters("1").unwrap();
+    /// assert_eq!(Color::from_index(0).complementary(&one), Color::from_index(0));
+    /// ```
+    pub fn complementary(&self, palette: &Palette) -> Color {
+        let others: Vec<Color> = palette
+            .values()
+            .into_iter()
+            .filter(|value| value != self)
+            .collect();
+        let lab = |color: &Color| palette.style(color).lab;
+        let Some([l, a, b]) = lab(self) else {
+            return others.first().unwrap_or(self).clone();
+        };
+        let opposite = [100.0 - l, -a, -b];
+        let distance = |color: &Color| {
+            lab(color).map_or(f64::INFINITY, |lab| {
+                lab.iter()
+                    .zip(opposite)
+                    .map(|(x, y)| (x - y).powi(2))
+                    .sum::<f64>()
+            })
+        };
+        others
+            .iter()
+            .min_by(|x, y| distance(x).total_cmp(&distance(y)))
+            .unwrap_or(self)
+            .clone()
+    }
+
+    /// Returns this color drawn as a peg of `palette` in `mode`.
+    pub fn glyph(&self, palette: &Palette, mode: DisplayMode) -> String {
+        let style = palette.style(self);
+        match mode {
+            DisplayMode::Letters => self.paint(palette, &style.letter.to_string()),
+            DisplayMode::Emoji => style.emoji.clone(),
+            DisplayMode::Symbols => self.paint(palette, &style.symbol.to_string()),
+        }
+    }
+
+    /// Returns `text` in this color of `palette`, in the current theme, or as
+    /// it is when color is off.
+    fn paint(&self, palette: &Palette, text: &str) -> String {
+        if color_enabled() {
+            paint_color(self, palette, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Writes the classic letter for the color.
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.glyph(palette::classic(), DisplayMode::Letters)
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Code {
+    positional: Vec<Color>,
+    set: HashSet<Color>,
+}
+
+impl Code {
+    pub(crate) fn new(positional: Vec<Color>) -> Self {
+        let set = positional.iter().cloned().collect();
+        Self { positional, set }
+    }
+
+    /// Builds a code from `positions`, checking that it is valid under `config`:
+    /// the right length, only colors in play, and no repeats unless
+    /// `config.allow_duplicates` is set.
+    ///
+    /// ```
+    /// use mastermind::{Code, Color, GameConfig, GameError};
+    ///
+    /// let config = GameConfig {
+    ///     colors: vec![Color::Red, Color::Green, Color::Blue],
+    ///     code_length: 3,
+    ///     allow_duplicates: false,
+    ///     ..GameConfig::default()
+    /// };
+    /// let code = Code::try_new(vec![Color::Blue, Color::Red, Color::Green], &config).unwrap();
+    /// assert_eq!(code.colors(), [Color::Blue, Color::Red, Color::Green]);
+    ///
+    /// let short = Code::try_new(vec![Color::Red, Color::Green], &config);
+    /// assert!(matches!(
+    ///     short,
+    ///     Err(GameError::WrongLength { expected: 3, found: 2, .. })
+    /// ));
+    /// let long = Code::try_new(vec![Color::Red; 4], &config);
+    /// assert!(matches!(
+    ///     long,
+    ///     Err(GameError::WrongLength { expected: 3, found: 4, .. })
+    /// ));
+    /// let unused = Code::try_new(vec![Color::Red, Color::Yellow, Color::Blue], &config);
+    /// assert!(matches!(
+    ///     unused,
+    ///     Err(GameError::InvalidCharacter { position: 1, found: 'y', .. })
+    /// ));
+    /// let repeated = Code::try_new(vec![Color::Red, Color::Green, Color::Red], &config);
+    /// assert!(matches!(
+    ///     repeated,
+    ///     Err(GameError::RepeatedColor { color: Color::Red, .. })
+    /// ));
+    /// assert_eq!(repeated.unwrap_err().to_string(), "red is used more than once");
+    ///
+    /// let config = GameConfig {
+    ///     allow_duplicates: true,
+    ///     ..config
+    /// };
+    /// assert!(Code::try_new(vec![Color::Red, Color::Green, Color::Red], &config).is_ok());
+    /// ```
+    pub fn try_new(
+        positions: Vec<Color>,
+        config: &GameConfig,
+    ) -> std::result::Result<Code, GameError> {
+        let letter = |color: &Color| config.palette.style(color).letter;
+        let input: String = positions.iter().map(letter).collect();
+        if positions.len() != config.code_length {
+            return Err(GameError::WrongLength {
+                position: positions.len().min(config.code_length),
+                expected: config.code_length,
+                found: positions.len(),
+                input,
+            });
+        }
+        if let Some(position) = positions.iter().position(|c| !config.colors.contains(c)) {
+            return Err(GameError::InvalidCharacter {
+                position,
+                found: letter(&positions[position]),
+                expected: config.colors.iter().map(letter).collect(),
+                input,
+            });
+        }
+        let code = Self::new(positions);
+        if !config.allow_duplicates && code.set.len() < code.positional.len() {
+            let repeated = code
+                .positional
+                .iter()
+                .enumerate()
+                .find(|(i, c)| code.positional[..*i].contains(c))
+                .map(|(_, c)| c.clone())
+                .expect("a shorter set means some color repeats");
+            return Err(GameError::RepeatedColor {
+                name: config.palette.style(&repeated).name.clone(),
+                color: repeated,
+            });
+        }
+        Ok(code)
+    }
+
+    /// Builds a code from the colors `colors` yields, checking it against
+    /// `config` as `try_new` does.
+    ///
+    /// ```
+    /// use mastermind::{Code, Color, GameConfig, GameError};
+    ///
+    /// let config = GameConfig::default();
+    /// let red = Code::from_iterator(std::iter::repeat(Color::Red).take(4), &config).unwrap();
+    /// assert_eq!(red, Code::all_of(Color::Red, 4));
+    ///
+    /// let colors = vec![Color::Yellow, Color::Blue, Color::Green, Color::Red];
+    /// let reversed = Code::from_iterator(colors.iter().rev().cloned(), &config).unwrap();
+    /// assert_eq!(reversed, "rgby".parse().unwrap());
+    ///
+    /// let short = Code::from_iterator(std::iter::repeat(Color::Red).take(3), &config);
+    /// assert!(matches!(short, Err(GameError::WrongLength { expected: 4, found: 3, .. })));
+    /// let long = Code::from_iterator(colors.into_iter().cycle().take(5), &config);
+    /// assert!(matches!(long, Err(GameError::WrongLength { expected: 4, found: 5, .. })));
+    /// ```
+    pub fn from_iterator(
+        colors: impl IntoIterator<Item = Color>,
+        config: &GameConfig,
+    ) -> std::result::Result<Code, GameError> {
+        Self::try_new(colors.into_iter().collect(), config)
+    }
+
+    /// Builds a code from `positions` without checking it against any
+    /// configuration, for callers that already know it is valid.
+    pub fn unchecked_new(positions: Vec<Color>) -> Code {
+        Self::new(positions)
+    }
+
+    /// Picks a code uniformly at random from those valid under `config`,
+    /// drawing colors without replacement when it does not allow repeats. That
+    /// takes at least as many colors as pegs.
+    ///
+    /// ```
+    /// use mastermind::{Code, GameConfig, Rng};
+    ///
+    /// let config = GameConfig {
+    ///     allow_duplicates: false,
+    ///     ..GameConfig::default()
+    /// };
+    /// let mut rng = Rng::seeded(7);
+    /// for _ in 0..100 {
+    ///     let code = Code::random(&config, &mut rng);
+    ///     assert!(Code::try_new(code.colors().to_vec(), &config).is_ok());
+    /// }
+    /// ```
+    pub fn random(config: &GameConfig, rng: &mut Rng) -> Code {
+        if !config.allow_duplicates {
+            let mut left = config.colors.clone();
+            let positional = (0..config.code_length)
+                .map(|_| left.swap_remove(rng.below(left.len())))
+                .collect();
+            return Self::new(positional);
+        }
+        let positional = (0..config.code_length)
+            .map(|_| config.colors[rng.below(config.colors.len())].clone())
+            .collect();
+        Self::new(positional)
+    }
+
+    /// Returns a code of `length` pegs, all `color`.
+    ///
+    /// ```
+    /// use mastermind::{Code, Color};
+    ///
+    /// let red = Code::all_of(Color::Red, 4);
+    /// assert!(red.is_all_same());
+    /// assert_eq!(red.score(&Code::all_of(Color::Green, 4)).black_count(), 0);
+    /// ```
+    pub fn all_of(color: Color, length: usize) -> Code {
+        Self::new(vec![color; length])
+    }
+
+    /// Returns a code of `length` pegs alternating between `a` and `b`,
+    /// starting with `a`.
+    ///
+    /// ```
+    /// use mastermind::{Code, Color};
+    ///
+    /// let code = Code::alternating(Color::Red, Color::Blue, 5);
+    /// assert_eq!(
+    ///     code.colors(),
+    ///     [Color::Red, Color::Blue, Color::Red, Color::Blue, Color::Red]
+    /// );
+    /// assert!(!code.is_all_same());
+    /// ```
+    pub fn alternating(a: Color, b: Color, length: usize) -> Code {
+        Self::new(
+            (0..length)
+                .map(|i| if i % 2 == 0 { a.clone() } else { b.clone() })
+                .collect(),
+        )
+    }
+
+    /// Returns the codes valid under `config` that satisfy `predicate`, in
+    /// the order of `CodeSpace`. Codes are built one at a time, so the whole
+    /// space is never held at once however few codes match.
+    ///
+    /// ```
+    /// use mastermind::{Code, CodeSpace, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let same = Code::generate_all_with_constraint(&config, Code::is_all_same);
+    /// assert_eq!(same.count(), config.colors.len());
+    ///
+    /// let two_colors = |code: &Code| code.colors().iter().collect::<std::collections::HashSet<_>>().len() == 2;
+    /// let constrained: Vec<Code> = Code::generate_all_with_constraint(&config, two_colors).collect();
+    /// let filtered: Vec<Code> = CodeSpace::new(&config).iter().filter(|c| two_colors(c)).cloned().collect();
+    /// assert_eq!(constrained, filtered);
+    /// assert_eq!(constrained.len(), 84);
+    /// ```
+    pub fn generate_all_with_constraint(
+        config: &GameConfig,
+        predicate: impl Fn(&Code) -> bool,
+    ) -> impl Iterator<Item = Code> {
+        LazyCodeSpace::new(config).filter(move |code| predicate(code))
+    }
+
+    /// Returns a code with `f` applied to the color at each position.
+    ///
+    /// ```
+    /// use mastermind::{Code, Color, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = Code::parse(String::from("rgby"), &config).unwrap();
+    /// assert_eq!(code.map(|c| c.clone()), code);
+    ///
+    /// let reds = code.map(|c| if *c == Color::Green { Color::Red } else { c.clone() });
+    /// assert_eq!(reds.colors(), [Color::Red, Color::Red, Color::Blue, Color::Yellow]);
+    /// ```
+    pub fn map(&self, f: impl Fn(&Color) -> Color) -> Code {
+        Self::new(self.positional.iter().map(f).collect())
+    }
+
+    /// Returns a code with every color replaced by the one `mapping` gives for
+    /// it, failing with `GameError::UnmappedColor` for a color it leaves out.
+    ///
+    /// Relabeling both codes with the same one-to-one mapping never changes the
+    /// score between them.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use mastermind::{Code, Color, GameConfig, GameError};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let swap = HashMap::from([
+    ///     (Color::Red, Color::Green),
+    ///     (Color::Green, Color::Red),
+    ///     (Color::Blue, Color::Blue),
+    ///     (Color::Yellow, Color::Yellow),
+    /// ]);
+    /// let (hidden, guess) = (code("rgby"), code("rrgb"));
+    /// let relabeled = hidden.relabel(&swap).unwrap();
+    /// assert_eq!(relabeled, code("grby"));
+    /// assert_eq!(relabeled.score(&guess.relabel(&swap).unwrap()), hidden.score(&guess));
+    ///
+    /// let partial = HashMap::from([(Color::Red, Color::Blue)]);
+    /// assert!(matches!(
+    ///     hidden.relabel(&partial),
+    ///     Err(GameError::UnmappedColor(Color::Green))
+    /// ));
+    /// ```
+    pub fn relabel(&self, mapping: &HashMap<Color, Color>) -> std::result::Result<Code, GameError> {
+        let positional = self
+            .positional
+            .iter()
+            .map(|color| {
+                mapping
+                    .get(color)
+                    .cloned()
+                    .ok_or_else(|| GameError::UnmappedColor(color.clone()))
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self::new(positional))
+    }
+
+    /// Parses `s` one peg per character, skipping whitespace, parentheses and
+    /// commas between pegs.
+    fn parse_pegs(
+        s: &str,
+        config: &GameConfig,
+        expected: Vec<char>,
+        peg: impl Fn(char) -> Option<Color>,
+    ) -> std::result::Result<Code, GameError> {
+        let mut positional = Vec::new();
+        let mut end = 0;
+        for (position, c) in s.chars().enumerate() {
+            if c.is_whitespace() || matches!(c, '(' | ')' | ',') {
+                continue;
+            }
+            let color = peg(c).ok_or_else(|| GameError::InvalidCharacter {
+                input: s.to_string(),
+                position,
+                found: c,
+                expected: expected.clone(),
+            })?;
+            if positional.len() == config.code_length {
+                return Err(GameError::WrongLength {
+                    input: s.to_string(),
+                    position,
+                    expected: config.code_length,
+                    found: s.chars().filter(|&c| peg(c).is_some()).count(),
+                });
+            }
+            positional.push(color);
+            end = position + 1;
+        }
+        if positional.len() < config.code_length {
+            return Err(GameError::WrongLength {
+                input: s.to_string(),
+                position: end,
+                expected: config.code_length,
+                found: positional.len(),
+            });
+        }
+        Ok(Self::new(positional))
+    }
+
+    /// Parse a code where each peg is a 1-based index into `config.colors`,
+    /// one digit per peg. With more than nine colors, pegs written as numbers
+    /// separated by spaces or commas reach the colors past the ninth.
+    ///
+    /// A digit or number that names no color is a `GameError::ParseError`
+    /// saying where it is, however the pegs are written.
+    ///
+    /// ```
+    /// use mastermind::{Code, Color, GameConfig, GameError};
+    ///
+    /// let config = GameConfig::default();
+    /// let letters = |s| Code::from_letters(s, &config).unwrap();
+    /// assert_eq!(Code::from_numeric_string("1234", &config).unwrap(), letters("rgby"));
+    /// assert_eq!(Code::from_numeric_string("1223", &config).unwrap(), letters("rggb"));
+    /// assert!(matches!(
+    ///     Code::from_numeric_string("5", &config),
+    ///     Err(GameError::ParseError(_))
+    /// ));
+    /// assert_eq!(
+    ///     Code::from_numeric_string("1254", &config).unwrap_err().to_string(),
+    ///     "parse error: 5 at position 3 is not a color number from 1 to 4"
+    /// );
+    /// assert!(matches!(
+    ///     Code::from_numeric_string("123", &config),
+    ///     Err(GameError::WrongLength { found: 3, .. })
+    /// ));
+    ///
+    /// let twelve = GameConfig {
+    ///     colors: (0..12).map(Color::from_index).collect(),
+    ///     ..GameConfig::default()
+    /// };
+    /// let code = Code::from_numeric_string("10 2, 12 1", &twelve).unwrap();
+    /// let indexes: Vec<usize> = code.colors().iter().map(Color::index).collect();
+    /// assert_eq!(indexes, [9, 1, 11, 0]);
+    /// assert_eq!(Code::parse(String::from("10 2 12 1"), &twelve).unwrap(), code);
+    /// assert!(matches!(
+    ///     Code::from_numeric_string("10 2 13 1", &twelve),
+    ///     Err(GameError::ParseError(_))
+    /// ));
+    /// assert_eq!(
+    ///     Code::from_numeric_string("10 2 13 1", &twelve).unwrap_err().to_string(),
+    ///     "parse error: 13 at position 6 is not a color number from 1 to 12"
+    /// );
+    /// ```
+    pub fn from_numeric_string(
+        s: &str,
+        config: &GameConfig,
+    ) -> std::result::Result<Code, GameError> {
+        if config.colors.len() > 9 && s.trim().contains(|c: char| c.is_whitespace() || c == ',') {
+            return Self::parse_numbers(s, config);
+        }
+        let expected = (1..=config.colors.len().min(9))
+            .filter_map(|i| char::from_digit(i as u32, 10))
+            .collect();
+        Self::parse_pegs(s, config, expected, |c| {
+            c.to_digit(10)
+                .and_then(|d| (d as usize).checked_sub(1))
+                .and_then(|i| config.colors.get(i))
+                .cloned()
+        })
+        .map_err(|error| match error {
+            GameError::InvalidCharacter {
+                position, found, ..
+            } => not_a_color_number(&found.to_string(), position, config),
+            other => other,
+        })
+    }
+
+    /// Parses `s` as color numbers separated by whitespace or commas.
+    fn parse_numbers(s: &str, config: &GameConfig) -> std::result::Result<Code, GameError> {
+        let mut tokens = Vec::new();
+        let mut start = None;
+        for (i, c) in s.char_indices().chain([(s.len(), ',')]) {
+            if !(c.is_whitespace() || c == ',') {
+                start = start.or(Some(i));
+            } else if let Some(begin) = start.take() {
+                tokens.push((s[..begin].chars().count(), &s[begin..i]));
+            }
+        }
+        if tokens.len() != config.code_length {
+            let position = match tokens.get(config.code_length) {
+                Some((column, _)) => *column,
+                None => s.trim_end().chars().count(),
+            };
+            return Err(GameError::WrongLength {
+                input: s.to_string(),
+                position,
+                expected: config.code_length,
+                found: tokens.len(),
+            });
+        }
+        let positional = tokens
+            .into_iter()
+            .map(|(column, token)| {
+                token
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| n.checked_sub(1))
+                    .and_then(|i| config.colors.get(i))
+                    .cloned()
+                    .ok_or_else(|| not_a_color_number(token, column, config))
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self::new(positional))
+    }
+
+    /// Parse a code written as color letters, ignoring whitespace, parentheses and
+    /// commas between them. Letters are looked up in `config.palette`.
+    ///
+    /// ```
+    /// use mastermind::{Code, GameConfig, GameError, Palette};
+    ///
+    /// let classic = GameConfig::default();
+    /// let code = Code::from_letters("r g (b, y)", &classic).unwrap();
+    /// assert_eq!(code, Code::from_letters("rgby", &classic).unwrap());
+    ///
+    /// let palette = Palette::from_letters("0123456789").unwrap();
+    /// let digits = GameConfig {
+    ///     colors: palette.values(),
+    ///     palette,
+    ///     ..GameConfig::default()
+    /// };
+    /// let hidden = Code::from_letters("9052", &digits).unwrap();
+    /// assert_eq!(hidden.colors()[0].index(), 9);
+    /// // Digits are pegs of this palette, not indexes into the colors.
+    /// assert_eq!(Code::parse(String::from("9052"), &digits).unwrap(), hidden);
+    /// assert!(matches!(
+    ///     Code::from_letters("905r", &digits),
+    ///     Err(GameError::InvalidCharacter { found: 'r', .. })
+    /// ));
+    /// ```
+    pub fn from_letters(s: &str, config: &GameConfig) -> std::result::Result<Code, GameError> {
+        let letter = |color: &Color| config.palette.style(color).letter;
+        let expected = config.colors.iter().map(letter).collect();
+        Self::parse_pegs(s, config, expected, |c| {
+            config
+                .colors
+                .iter()
+                .find(|color| letter(color) == c)
+                .cloned()
+        })
+    }
+
+    /// Parse user input, treating all-digit input as color indices unless the
+    /// palette types its own pegs as digits.
+    pub fn parse(s: String, config: &GameConfig) -> Result<Code> {
+        Ok(Self::parse_input(&s, config)?)
+    }
+
+    fn parse_input(s: &str, config: &GameConfig) -> std::result::Result<Code, GameError> {
+        let trimmed = normalize_input(s);
+        let digit_pegs = config.palette.letters().chars().any(|c| c.is_ascii_digit());
+        let numeric = trimmed.starts_with(|c: char| c.is_ascii_digit())
+            && trimmed
+                .chars()
+                .all(|c| c.is_ascii_digit() || c.is_whitespace() || c == ',');
+        if !digit_pegs && numeric {
+            return Code::from_numeric_string(trimmed, config);
+        }
+        Code::from_letters(trimmed, config)
+    }
+
+    /// Parses the code stored in the environment variable `var_name`, the same
+    /// way typed input is parsed.
+    ///
+    /// ```
+    /// use mastermind::{Code, GameConfig, GameError};
+    ///
+    /// let config = GameConfig::default();
+    /// std::env::set_var("MASTERMIND_DOC_CODE", "rgby");
+    /// let code = Code::from_env("MASTERMIND_DOC_CODE", &config).unwrap();
+    /// assert_eq!(code.to_string(), Code::parse(String::from("rgby"), &config).unwrap().to_string());
+    ///
+    /// std::env::remove_var("MASTERMIND_DOC_CODE");
+    /// assert!(matches!(
+    ///     Code::from_env("MASTERMIND_DOC_CODE", &config),
+    ///     Err(GameError::MissingEnvVar(_))
+    /// ));
+    /// ```
+    pub fn from_env(var_name: &str, config: &GameConfig) -> std::result::Result<Code, GameError> {
+        match std::env::var(var_name) {
+            Ok(value) => Self::parse_input(&value, config),
+            Err(std::env::VarError::NotPresent) => {
+                Err(GameError::MissingEnvVar(var_name.to_string()))
+            }
+            Err(std::env::VarError::NotUnicode(_)) => Err(GameError::ParseError(format!(
+                "${} is not valid UTF-8",
+                var_name
+            ))),
+        }
+    }
+
+    /// Returns the number of pegs in this code.
+    pub fn len(&self) -> usize {
+        self.positional.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positional.is_empty()
+    }
+
+    /// Score `other` against this code using the standard black/white peg rules.
+    ///
+    /// Each peg of `other` earns at most one key: black pegs for exact matches come
+    /// first, then white pegs for colors that are present but misplaced, counting
+    /// repeated colors no more often than they appear in both codes.
+    ///
+    /// ```
+    /// use mastermind::{Code, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let score = |hidden: &str, guess: &str| {
+    ///     let score = code(hidden).score(&code(guess));
+    ///     (score.black_count(), score.white_count())
+    /// };
+    /// assert_eq!(score("rgby", "rgby"), (4, 0));
+    /// assert_eq!(score("rgby", "ybgr"), (0, 4));
+    /// // A repeated color earns no more keys than the hidden code has of it.
+    /// assert_eq!(score("rgby", "rrrr"), (1, 0));
+    /// assert_eq!(score("rgby", "gggr"), (1, 1));
+    /// assert_eq!(score("rrgg", "grrr"), (1, 2));
+    /// // Nor more than the guess has of it.
+    /// assert_eq!(score("rrrb", "brgy"), (1, 1));
+    /// ```
+    pub fn score(&self, other: &Code) -> Score {
+        let blacks = self.count_exact_matches(other);
+        let whites = self.common_peg_count(other) - blacks;
+
+        Score::from_counts(blacks, whites, self.len())
+            .expect("a score never has more pegs than the code")
+    }
+
+    /// Returns the number of positions where both codes have the same color, which
+    /// is the black peg count of `score` without the work of counting whites.
+    ///
+    /// ```
+    /// use mastermind::{Code, GameConfig, Rng};
+    ///
+    /// let mut rng = Rng::seeded(7);
+    /// for length in [1, 4, 6] {
+    ///     let config = GameConfig {
+    ///         code_length: length,
+    ///         ..GameConfig::super_mastermind()
+    ///     };
+    ///     for _ in 0..1000 {
+    ///         let a = Code::random(&config, &mut rng);
+    ///         let b = Code::random(&config, &mut rng);
+    ///         assert_eq!(a.count_exact_matches(&b), a.score(&b).black_count());
+    ///         assert_eq!(a.count_exact_matches(&b), b.count_exact_matches(&a));
+    ///     }
+    /// }
+    /// ```
+    pub fn count_exact_matches(&self, other: &Code) -> usize {
+        self.positional
+            .iter()
+            .zip(other.positional.iter())
+            .filter(|(s, o)| s == o)
+            .count()
+    }
+
+    /// Returns the same count as `count_exact_matches`, comparing 16 pegs at
+    /// a time in one SSE instruction when the crate is built for x86_64 with
+    /// SSE4.1 enabled, as `RUSTFLAGS="-C target-cpu=native"` does on most
+    /// processors. Other builds count one peg at a time.
+    ///
+    /// ```
+    /// use mastermind::{Code, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// assert_eq!(code("rgby").count_exact_matches_simd(&code("rgyb")), 2);
+    /// assert_eq!(code("rrrr").count_exact_matches_simd(&code("gggg")), 0);
+    /// ```
+    #[inline]
+    pub fn count_exact_matches_simd(&self, other: &Code) -> usize {
+        exact::count_exact_matches(&self.positional, &other.positional)
+    }
+
+    /// Returns how many pegs the two codes have in common when position is ignored,
+    /// counting a repeated color no more often than it appears in both. This is
+    /// the black plus white peg count of `score`. The count stops at `u8::MAX`
+    /// for codes longer than `MAX_CODE_LENGTH`.
+    ///
+    /// ```
+    /// use mastermind::{Code, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// // A rearrangement has every peg in common, wherever they sit.
+    /// assert_eq!(code("rgby").anagram_score(&code("ybgr")), 4);
+    /// // Only one of the two reds in "rrgg" has a partner in "rgby".
+    /// assert_eq!(code("rrgg").anagram_score(&code("rgby")), 2);
+    /// assert_eq!(code("rrrr").anagram_score(&code("gggg")), 0);
+    ///
+    /// let (guess, hidden) = (code("rrgb"), code("gryy"));
+    /// let score = guess.score(&hidden);
+    /// let total = score.black_count() + score.white_count();
+    /// assert_eq!(usize::from(guess.anagram_score(&hidden)), total);
+    /// ```
+    pub fn anagram_score(&self, other: &Code) -> u8 {
+        u8::try_from(self.common_peg_count(other)).unwrap_or(u8::MAX)
+    }
+
+    /// Returns the count of `anagram_score` without narrowing it to a byte.
+    pub(crate) fn common_peg_count(&self, other: &Code) -> usize {
+        let classic = |code: &Code| code.positional.iter().all(|c| c.index() < COLOR_COUNT);
+        let pegs = self
+            .positional
+            .iter()
+            .zip(other.positional.iter())
+            .map(|(s, o)| (s.index(), o.index()));
+        if classic(self) && classic(other) {
+            common_pegs::<COLOR_COUNT>(pegs)
+        } else {
+            common_pegs::<256>(pegs)
+        }
+    }
+
+    /// Returns the positions where `other` has a different color from this
+    /// code, in ascending order. If the codes differ in length, every position
+    /// past the end of the shorter one counts as different.
+    ///
+    /// ```
+    /// use mastermind::{Code, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// assert_eq!(code("rgby").diff_positions(&code("rgyy")), [2]);
+    /// assert_eq!(code("rgby").diff_positions(&code("grby")), [0, 1]);
+    /// assert!(code("rgby").diff_positions(&code("rgby")).is_empty());
+    ///
+    /// let short = Code::parse(String::from("rgb"), &GameConfig {
+    ///     code_length: 3,
+    ///     ..GameConfig::default()
+    /// })
+    /// .unwrap();
+    /// assert_eq!(code("rgyy").diff_positions(&short), [2, 3]);
+    /// assert_eq!(short.diff_positions(&code("rgyy")), [2, 3]);
+    /// ```
+    pub fn diff_positions(&self, other: &Code) -> Vec<usize> {
+        let longest = self.len().max(other.len());
+        (0..longest)
+            .filter(|&i| self.positional.get(i) != other.positional.get(i))
+            .collect()
+    }
+
+    /// Returns the fewest pegs that must be inserted, removed, or recolored to
+    /// turn this code into `other`, so codes of different lengths can be
+    /// compared.
+    ///
+    /// It never exceeds the number of `diff_positions`, but can be less even
+    /// for codes of one length: shifting `rgrg` along by one peg is two edits,
+    /// though all four positions differ.
+    ///
+    /// ```
+    /// use mastermind::{Code, GameConfig, Rng};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// assert_eq!(code("rgby").levenshtein_distance(&code("rgyy")), 1);
+    /// assert_eq!(code("rgrg").levenshtein_distance(&code("grgr")), 2);
+    ///
+    /// let short = Code::parse(String::from("gby"), &GameConfig {
+    ///     code_length: 3,
+    ///     ..GameConfig::default()
+    /// })
+    /// .unwrap();
+    /// assert_eq!(code("rgby").levenshtein_distance(&short), 1);
+    /// assert_eq!(short.levenshtein_distance(&code("rgby")), 1);
+    ///
+    /// let mut rng = Rng::seeded(3);
+    /// for _ in 0..200 {
+    ///     let configs: Vec<GameConfig> = (0..3)
+    ///         .map(|_| GameConfig {
+    ///             code_length: 1 + rng.below(6),
+    ///             ..GameConfig::default()
+    ///         })
+    ///         .collect();
+    ///     let [a, b, c] = [0, 1, 2].map(|i| Code::random(&configs[i], &mut rng));
+    ///     assert_eq!(a.levenshtein_distance(&a), 0);
+    ///     assert_eq!(a.levenshtein_distance(&b), b.levenshtein_distance(&a));
+    ///     assert!(a.levenshtein_distance(&c) <= a.levenshtein_distance(&b) + b.levenshtein_distance(&c));
+    ///     assert!(a.levenshtein_distance(&b) <= a.diff_positions(&b).len());
+    ///     assert!(a.levenshtein_distance(&b) >= a.len().abs_diff(b.len()));
+    /// }
+    /// ```
+    pub fn levenshtein_distance(&self, other: &Code) -> usize {
+        // Distances from the prefixes of `self` to the prefix of `other` seen
+        // so far, one row at a time.
+        let mut row: Vec<usize> = (0..=self.len()).collect();
+        for (j, theirs) in other.positional.iter().enumerate() {
+            let mut diagonal = row[0];
+            row[0] = j + 1;
+            for (i, ours) in self.positional.iter().enumerate() {
+                let substitute = diagonal + usize::from(ours != theirs);
+                diagonal = row[i + 1];
+                row[i + 1] = substitute.min(row[i] + 1).min(diagonal + 1);
+            }
+        }
+        row[self.len()]
+    }
+
+    /// Returns whether `other` starts with this code, peg for peg.
+    ///
+    /// ```
+    /// use mastermind::Code;
+    ///
+    /// let code = |s: &str| s.parse::<Code>().unwrap();
+    /// assert!(code("rgg").is_prefix_of(&code("rggb")));
+    /// assert!(!code("rgg").is_prefix_of(&code("rgrb")));
+    /// assert!(code("rggb").is_prefix_of(&code("rggb")));
+    /// assert!(!code("rggbr").is_prefix_of(&code("rggb")));
+    /// ```
+    pub fn is_prefix_of(&self, other: &Code) -> bool {
+        other.positional.starts_with(&self.positional)
+    }
+
+    /// Returns how many leading pegs this code and `other` share.
+    ///
+    /// ```
+    /// use mastermind::Code;
+    ///
+    /// let code = |s: &str| s.parse::<Code>().unwrap();
+    /// assert_eq!(code("rggb").common_prefix_length(&code("rgrb")), 2);
+    /// assert_eq!(code("rggb").common_prefix_length(&code("rggb")), 4);
+    /// assert_eq!(code("rggb").common_prefix_length(&code("rg")), 2);
+    /// assert_eq!(code("rggb").common_prefix_length(&code("grgb")), 0);
+    ///
+    /// let (a, b) = (code("rgyb"), code("rgybw"));
+    /// assert_eq!(a.is_prefix_of(&b), a.common_prefix_length(&b) == a.len());
+    /// ```
+    pub fn common_prefix_length(&self, other: &Code) -> usize {
+        self.positional
+            .iter()
+            .zip(&other.positional)
+            .take_while(|(ours, theirs)| ours == theirs)
+            .count()
+    }
+
+    /// Returns the colors of this code in position order.
+    pub fn colors(&self) -> &[Color] {
+        &self.positional
+    }
+
+    /// Returns something that writes this code in `palette`'s letters, the
+    /// way `Display` writes it in the classic ones.
+    ///
+    /// ```
+    /// use mastermind::display::set_color;
+    /// use mastermind::{Code, GameConfig, Palette};
+    ///
+    /// set_color(false);
+    /// let palette = Palette::from_letters("qwerty").unwrap();
+    /// let config = GameConfig {
+    ///     colors: palette.values(),
+    ///     palette,
+    ///     ..GameConfig::default()
+    /// };
+    /// let code = Code::from_letters("tweq", &config).unwrap();
+    /// assert_eq!(code.display(&config.palette).to_string(), "t w e q");
+    /// assert_eq!(code.letters(&config.palette), "tweq");
+    /// // The same pegs in the classic palette.
+    /// assert_eq!(code.to_string(), "p g b r");
+    /// ```
+    pub fn display<'a>(&'a self, palette: &'a Palette) -> CodeDisplay<'a> {
+        CodeDisplay {
+            code: self,
+            palette,
+        }
+    }
+
+    /// Returns the letters `palette` types this code with, run together and
+    /// never painted, as saves and exports write it.
+    pub fn letters(&self, palette: &Palette) -> String {
+        self.positional
+            .iter()
+            .map(|color| palette.style(color).letter)
+            .collect()
+    }
+
+    /// Reads a code written the way `letters` writes it for `palette`, as
+    /// saves store it: one letter per peg, with nothing between them.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_saved_letters(
+        s: &str,
+        palette: &Palette,
+    ) -> std::result::Result<Code, GameError> {
+        let colors = s
+            .chars()
+            .map(|c| {
+                palette.value_of(c).ok_or_else(|| {
+                    GameError::ParseError(format!("'{}' in \"{}\" is not a color", c, s))
+                })
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        if !(1..=MAX_CODE_LENGTH).contains(&colors.len()) {
+            return Err(GameError::ParseError(format!(
+                "a code must have from 1 to {} pegs, not {}",
+                MAX_CODE_LENGTH,
+                colors.len()
+            )));
+        }
+        Ok(Code::new(colors))
+    }
+
+    /// Returns whether `color` appears anywhere in this code.
+    pub fn contains(&self, color: &Color) -> bool {
+        self.set.contains(color)
+    }
+
+    /// Returns whether every peg is the same color.
+    pub fn is_all_same(&self) -> bool {
+        self.set.len() <= 1
+    }
+
+    /// Returns whether this code could be the hidden code given the score in `round`.
+    pub fn is_consistent_with(&self, round: &Round) -> bool {
+        self.score(round.guess()) == *round.score()
+    }
+
+    /// Returns whether this code meets every one of `constraints`.
+    ///
+    /// ```
+    /// use mastermind::deduction::ColorConstraint;
+    /// use mastermind::{Code, Color};
+    ///
+    /// let code: Code = "rgby".parse().unwrap();
+    /// assert!(code.satisfies(&[]));
+    /// assert!(code.satisfies(&[ColorConstraint::Present(Color::Red)]));
+    /// assert!(!code.satisfies(&[
+    ///     ColorConstraint::Present(Color::Red),
+    ///     ColorConstraint::Absent(Color::Blue),
+    /// ]));
+    /// ```
+    pub fn satisfies(&self, constraints: &[ColorConstraint]) -> bool {
+        constraints.iter().all(|constraint| constraint.allows(self))
+    }
+
+    /// Returns how many bits of information are still needed to pick the hidden
+    /// code out of `candidates`, i.e. `log2` of their count. No candidates at
+    /// all leave nothing to pick, so that is 0 bits too.
+    ///
+    /// ```
+    /// use mastermind::{Code, CodeSpace, GameConfig};
+    ///
+    /// let candidates: Vec<Code> = CodeSpace::new(&GameConfig::default()).into_iter().collect();
+    /// assert_eq!(Code::information_content(&candidates), 8.0);
+    /// assert_eq!(Code::information_content(&candidates[..1]), 0.0);
+    /// assert_eq!(Code::information_content(&[]), 0.0);
+    /// ```
+    pub fn information_content(candidates: &[Code]) -> f64 {
+        if candidates.is_empty() {
+            return 0.0;
+        }
+        (candidates.len() as f64).log2()
+    }
+
+    /// Returns how evenly scoring this code as a guess splits `candidates`: the
+    /// entropy, in bits, of the scores it would get, `-Σ p log2 p` over the
+    /// share `p` of candidates giving each score. The more bits, the more the
+    /// guess is expected to reveal; see `analysis::expected_bits`.
+    ///
+    /// ```
+    /// use mastermind::{Code, CodeSpace, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let candidates: Vec<Code> = CodeSpace::new(&config).into_iter().collect();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    ///
+    /// // Two pegs of one color and one each of two others is the best opening.
+    /// let best = code("rrgb").scoring_entropy(&candidates);
+    /// for guess in &candidates {
+    ///     let entropy = guess.scoring_entropy(&candidates);
+    ///     assert!(entropy <= best + 1e-12);
+    ///     assert!(entropy <= Code::information_content(&candidates));
+    /// }
+    /// assert!(code("rrrr").scoring_entropy(&candidates) < best);
+    ///
+    /// // One candidate leaves nothing to learn.
+    /// assert_eq!(code("rrgg").scoring_entropy(&[code("rgby")]), 0.0);
+    /// assert_eq!(code("rgby").scoring_entropy(&[code("rgby")]), 0.0);
+    /// ```
+    pub fn scoring_entropy(&self, candidates: &[Code]) -> f64 {
+        crate::analysis::expected_bits(self, candidates)
+    }
+
+    /// Returns how many bits learning that `guess` scored `score` removes: the
+    /// information in `candidates` less the information in those still
+    /// consistent with the score.
+    ///
+    /// ```
+    /// use mastermind::{Code, CodeSpace, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let candidates: Vec<Code> = CodeSpace::new(&config).into_iter().collect();
+    /// let guess = Code::parse(String::from("rrgb"), &config).unwrap();
+    ///
+    /// // A winning score leaves one candidate, so every bit is eliminated.
+    /// let win = guess.score(&guess);
+    /// assert_eq!(Code::bits_eliminated(&guess, &win, &candidates), 8.0);
+    ///
+    /// for hidden in &candidates {
+    ///     let score = hidden.score(&guess);
+    ///     assert!(Code::bits_eliminated(&guess, &score, &candidates) >= 0.0);
+    /// }
+    /// ```
+    pub fn bits_eliminated(guess: &Code, score: &Score, candidates: &[Code]) -> f64 {
+        let remaining = candidates
+            .iter()
+            .filter(|candidate| candidate.score(guess) == *score)
+            .count();
+        // A score no candidate could give rules everything out; count that as
+        // all of the information rather than an infinite amount.
+        let left = if remaining == 0 {
+            0.0
+        } else {
+            (remaining as f64).log2()
+        };
+        Self::information_content(candidates) - left
+    }
+}
+
+/// The error for `token`, found at 0-based `column`, naming no color of
+/// `config` by its 1-based number.
+fn not_a_color_number(token: &str, column: usize, config: &GameConfig) -> GameError {
+    GameError::ParseError(format!(
+        "{} at position {} is not a color number from 1 to {}",
+        token,
+        column + 1,
+        config.colors.len()
+    ))
+}
+
+impl PartialEq for Code {
+    fn eq(&self, other: &Self) -> bool {
+        self.positional == other.positional
+    }
+}
+
+impl Eq for Code {}
+
+impl Hash for Code {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.positional.hash(state);
+    }
+}
+
+impl PartialOrd for Code {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Code {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.positional.cmp(&other.positional)
+    }
+}
+
+/// Writes the code in the classic letters; `Code::display` writes it in
+/// another palette's.
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.display(palette::classic()))
+    }
+}
+
+/// Written in its compact form, the classic letters run together.
+///
+/// ```
+/// use mastermind::Code;
+///
+/// let code: Code = "rgby".parse().unwrap();
+/// assert_eq!(serde_json::to_string(&code).unwrap(), r#""rgby""#);
+/// assert_eq!(serde_json::from_str::<Code>(r#""rgby""#).unwrap(), code);
+/// assert!(serde_json::from_str::<Code>(r#""rgbx""#).is_err());
+/// assert!(serde_json::from_str::<Code>(r#""""#).is_err());
+/// ```
+#[cfg(feature = "serde")]
+impl serde::Serialize for Code {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.letters(palette::classic()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Code {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let letters = String::deserialize(deserializer)?;
+        Code::from_saved_letters(&letters, palette::classic()).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A code written in the letters of a palette, made by `Code::display`.
+pub struct CodeDisplay<'a> {
+    code: &'a Code,
+    palette: &'a Palette,
+}
+
+impl fmt::Display for CodeDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pegs: Vec<String> = self
+            .code
+            .positional
+            .iter()
+            .map(|color| color.glyph(self.palette, DisplayMode::Letters))
+            .collect();
+        write!(f, "{}", pegs.join(" "))
+    }
+}
+
+impl FromStr for Code {
+    type Err = GameError;
+
+    /// Parses a code of any length in any of the colors, written the way
+    /// `Display` writes one or as bare letters, optionally between
+    /// parentheses and separated by commas or whitespace.
+    ///
+    /// ```
+    /// use mastermind::{Code, Color, GameConfig, Rng};
+    ///
+    /// let code: Code = "(r, g, b, y)".parse().unwrap();
+    /// assert_eq!(code, "rgby".parse().unwrap());
+    /// assert_eq!(code.colors(), [Color::Red, Color::Green, Color::Blue, Color::Yellow]);
+    /// assert!("rgbx".parse::<Code>().is_err());
+    /// assert!("".parse::<Code>().is_err());
+    ///
+    /// let mut rng = Rng::seeded(5);
+    /// for (code_length, color_count) in [(4, 4), (5, 8), (1, 2), (7, 6)] {
+    ///     let config = GameConfig {
+    ///         code_length,
+    ///         colors: Color::ALL[..color_count].to_vec(),
+    ///         ..GameConfig::default()
+    ///     };
+    ///     for _ in 0..50 {
+    ///         let code = Code::random(&config, &mut rng);
+    ///         let letters: Vec<String> = code.colors().iter().map(|c| c.letter().to_string()).collect();
+    ///         let spaced = code.to_string();
+    ///         let compact = letters.concat();
+    ///         let tuple = format!("({})", letters.join(", "));
+    ///         for text in [spaced, compact, tuple] {
+    ///             assert_eq!(text.parse::<Code>().unwrap(), code, "{:?}", text);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let values = Color::ALL;
+        let mut positional = Vec::new();
+        let mut rest = s;
+        while let Some(c) = rest.chars().next() {
+            if c.is_whitespace() || matches!(c, '(' | ')' | ',') {
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+            // `Display` may have painted the letter, so try that first.
+            let painted = values.iter().cloned().find_map(|color| {
+                let text = color.to_string();
+                rest.starts_with(&text).then_some((color, text.len()))
+            });
+            let (color, len) = painted
+                .or_else(|| Color::from_letter(c).map(|color| (color, c.len_utf8())))
+                .ok_or_else(|| GameError::InvalidCharacter {
+                    input: s.to_string(),
+                    position: s[..s.len() - rest.len()].chars().count(),
+                    found: c,
+                    expected: values.iter().map(Color::letter).collect(),
+                })?;
+            positional.push(color);
+            rest = &rest[len..];
+        }
+        if positional.is_empty() {
+            return Err(GameError::ParseError(String::from(
+                "a code needs at least one peg",
+            )));
+        }
+        Ok(Code::new(positional))
+    }
+}
+
+impl TryFrom<String> for Code {
+    type Error = Box<dyn std::error::Error>;
+
+    /// Parses a code the way `str::parse` does, kept for code written before
+    /// `FromStr`. Unlike `str::parse`, it only accepts the pegs of a
+    /// `GameConfig::default()` code, as it always has.
+    ///
+    /// ```
+    /// use mastermind::Code;
+    ///
+    /// let code: Code = String::from("rgby").try_into().unwrap();
+    /// assert_eq!(code, "rgby".parse().unwrap());
+    /// assert!(Code::try_from(String::from("rgb")).is_err());
+    /// assert!(Code::try_from(String::from("rgbyr")).is_err());
+    /// assert!("rgbyr".parse::<Code>().is_ok());
+    /// ```
+    fn try_from(s: String) -> Result<Self> {
+        let code = s.parse::<Code>()?;
+        let expected = GameConfig::default().code_length;
+        if code.len() != expected {
+            // Where the first missing or extra peg is, counting every character
+            // that is not a separator as a peg.
+            let mut pegs = s
+                .chars()
+                .enumerate()
+                .filter(|(_, c)| !(c.is_whitespace() || matches!(c, '(' | ')' | ',')));
+            let position = match pegs.nth(expected) {
+                Some((position, _)) => position,
+                None => s.trim_end().chars().count(),
+            };
+            return Err(GameError::WrongLength {
+                input: s,
+                position,
+                expected,
+                found: code.len(),
+            }
+            .into());
+        }
+        Ok(code)
+    }
+}
+
+/// Every code that is valid under a `GameConfig`, in lexicographic order.
+///
+/// ```
+/// use mastermind::{CodeSpace, Color, GameConfig};
+///
+/// assert_eq!(CodeSpace::new(&GameConfig::default()).len(), 4usize.pow(4));
+///
+/// let custom = GameConfig {
+///     colors: vec![Color::Red, Color::Green, Color::Blue],
+///     code_length: 5,
+///     ..GameConfig::default()
+/// };
+/// assert_eq!(CodeSpace::new(&custom).len(), 3usize.pow(5));
+/// ```
+pub struct CodeSpace {
+    codes: Vec<Code>,
+}
+
+impl CodeSpace {
+    pub fn new(config: &GameConfig) -> Self {
+        Self {
+            codes: LazyCodeSpace::new(config).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Code> {
+        self.codes.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a CodeSpace {
+    type Item = &'a Code;
+    type IntoIter = std::slice::Iter<'a, Code>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.codes.iter()
+    }
+}
+
+impl IntoIterator for CodeSpace {
+    type Item = Code;
+    type IntoIter = std::vec::IntoIter<Code>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.codes.into_iter()
+    }
+}
+
+/// The same codes as `CodeSpace`, in the same order, generated one at a time.
+///
+/// Each code is computed from its index like a counter whose digits are colors, so
+/// only the current position is stored and skipping ahead with `nth` costs
+/// `O(code_length)` rather than walking every code in between.
+#[derive(Clone)]
+pub struct LazyCodeSpace {
+    colors: Vec<Color>,
+    code_length: usize,
+    next: usize,
+    end: usize,
+}
+
+impl LazyCodeSpace {
+    /// Returns every code of `config`.
+    ///
+    /// # Panics
+    ///
+    /// If there are more codes than a `usize` can count; `try_new` returns an
+    /// error instead.
+    pub fn new(config: &GameConfig) -> Self {
+        Self::try_new(config).expect("the code space of a checked config fits in a usize")
+    }
+
+    /// Returns every code of `config`, or an error if there are more than a
+    /// `usize` can count.
+    ///
+    /// ```
+    /// use mastermind::{Color, GameConfig, GameError, LazyCodeSpace};
+    ///
+    /// let config = GameConfig {
+    ///     colors: (0..8).map(Color::from_index).collect(),
+    ///     code_length: 6,
+    ///     ..GameConfig::default()
+    /// };
+    /// let mut space = LazyCodeSpace::try_new(&config).unwrap();
+    /// assert_eq!(space.len(), 262_144);
+    /// assert_eq!(space.nth(262_143).unwrap().colors(), vec![Color::from_index(7); 6]);
+    ///
+    /// let long = GameConfig {
+    ///     code_length: 40,
+    ///     ..GameConfig::default()
+    /// };
+    /// assert!(matches!(
+    ///     LazyCodeSpace::try_new(&long),
+    ///     Err(GameError::InvalidSetting { setting: "length", .. })
+    /// ));
+    /// ```
+    pub fn try_new(config: &GameConfig) -> std::result::Result<Self, GameError> {
+        let mut colors = config.colors.clone();
+        colors.sort();
+        colors.dedup();
+        let end = u32::try_from(config.code_length)
+            .ok()
+            .and_then(|length| colors.len().checked_pow(length))
+            .ok_or_else(|| GameError::InvalidSetting {
+                setting: "length",
+                reason: format!(
+                    "{} pegs in {} colors make more codes than can be counted",
+                    config.code_length,
+                    colors.len()
+                ),
+            })?;
+        Ok(Self {
+            colors,
+            code_length: config.code_length,
+            next: 0,
+            end,
+        })
+    }
+
+    /// Returns the colors codes are drawn from, in `Color` order.
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    pub fn code_length(&self) -> usize {
+        self.code_length
+    }
+
+    /// Returns where `code` is in the whole space, the inverse of `code_at`, or
+    /// `None` if it is not a code of this space.
+    pub fn index_of(&self, code: &Code) -> Option<usize> {
+        if code.len() != self.code_length {
+            return None;
+        }
+        code.colors().iter().try_fold(0, |index, color| {
+            let digit = self.colors.iter().position(|c| c == color)?;
+            Some(index * self.colors.len() + digit)
+        })
+    }
+
+    /// Returns the `n`th code of the whole space, regardless of iteration progress.
+    pub fn code_at(&self, n: usize) -> Option<Code> {
+        if n >= self.end {
+            return None;
+        }
+        let base = self.colors.len();
+        let mut rest = n;
+        let mut positional = Vec::with_capacity(self.code_length);
+        for _ in 0..self.code_length {
+            positional.push(self.colors[rest % base].clone());
+            rest /= base;
+        }
+        positional.reverse();
+        Some(Code::new(positional))
+    }
+}
+
+impl Iterator for LazyCodeSpace {
+    type Item = Code;
+
+    fn next(&mut self) -> Option<Code> {
+        let code = self.code_at(self.next)?;
+        self.next += 1;
+        Some(code)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Code> {
+        self.next = self.next.saturating_add(n).min(self.end);
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for LazyCodeSpace {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_codes_count_common_pegs_past_a_byte() {
+        let red = Code::all_of(Color::Red, 300);
+        let score = red.score(&red);
+        assert_eq!((score.black_count(), score.white_count()), (300, 0));
+        assert_eq!(red.anagram_score(&red), u8::MAX);
+
+        let parsed: Code = "r".repeat(256).parse().unwrap();
+        let mixed = Code::alternating(Color::Green, Color::Red, 256);
+        assert_eq!(parsed.anagram_score(&mixed), 128);
+        let score = parsed.score(&mixed);
+        assert_eq!((score.black_count(), score.white_count()), (128, 0));
+    }
+
+    #[test]
+    fn simd_exact_matches_agree_with_the_scalar_count() {
+        let space = CodeSpace::new(&GameConfig::default());
+        for a in &space {
+            for b in &space {
+                assert_eq!(a.count_exact_matches_simd(b), a.count_exact_matches(b));
+            }
+        }
+        let mut rng = Rng::seeded(7);
+        for length in 1..=40 {
+            let config = GameConfig {
+                code_length: length,
+                ..GameConfig::super_mastermind()
+            };
+            for _ in 0..1000 {
+                let a = Code::random(&config, &mut rng);
+                let b = Code::random(&config, &mut rng);
+                assert_eq!(a.count_exact_matches_simd(&b), a.count_exact_matches(&b));
+            }
+        }
+    }
+}
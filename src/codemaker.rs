@@ -0,0 +1,113 @@
+//! Who sets the code a game is played against. By default a board keeps the
+//! code it was made with; a `Codemaker` can instead pick the code each guess
+//! is scored against, as long as every score it gives stays true of it.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::code::{Code, LazyCodeSpace};
+use crate::config::GameConfig;
+use crate::score::Score;
+
+/// Picks the code each guess is scored against.
+pub trait Codemaker {
+    /// Returns the code `guess` is to be scored against. It must score the
+    /// same as the code returned last time against every earlier guess.
+    fn hidden_for(&mut self, guess: &Code) -> Code;
+}
+
+/// A codemaker that never commits to a code while more than one is left:
+/// each guess gets the score that keeps the most codes possible, so the
+/// player always faces the worst case. Scores stay honest, since the code
+/// returned is always one that earns every score given so far.
+///
+/// A winning score is only given when no other is left, so the game is won
+/// by naming the last code standing.
+///
+/// ```
+/// use mastermind::codemaker::{AdversarialCodemaker, Codemaker};
+/// use mastermind::random::Rng;
+/// use mastermind::{Board, Code, GameConfig};
+///
+/// let config = GameConfig {
+///     code_length: 3,
+///     ..GameConfig::default()
+/// };
+/// for seed in 0..20 {
+///     let mut rng = Rng::seeded(seed);
+///     let codemaker = AdversarialCodemaker::new(&config);
+///     let mut board = Board::new(config.clone(), codemaker.hidden());
+///     board.set_codemaker(Box::new(codemaker));
+///     while !board.last_round().is_some_and(|round| round.wins()) {
+///         let candidates = board.candidates();
+///         let guess = if candidates.len() == 1 {
+///             board.hidden_code().clone()
+///         } else {
+///             Code::random(&config, &mut rng)
+///         };
+///         let won = board.guess(guess.clone()).wins();
+///         // The code revealed at the end earns every score given.
+///         for round in &board {
+///             assert!(board.hidden_code().is_consistent_with(round));
+///         }
+///         // Nothing is won while another code could still be the answer.
+///         assert!(!won || candidates.len() == 1 && candidates.contains(&guess));
+///     }
+/// }
+///
+/// // With one code left, only that code wins.
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let mut codemaker = AdversarialCodemaker::new(&config);
+/// for guess in ["rrg", "gbr", "byy", "yrb"] {
+///     codemaker.hidden_for(&code(guess));
+/// }
+/// assert_eq!(codemaker.remaining(), 1);
+/// let survivor = codemaker.hidden();
+/// let other = code(if survivor == code("rrr") { "ggg" } else { "rrr" });
+/// assert_eq!(codemaker.hidden_for(&other), survivor);
+/// assert_eq!(codemaker.hidden_for(&survivor), survivor);
+/// ```
+pub struct AdversarialCodemaker {
+    candidates: Vec<Code>,
+}
+
+impl AdversarialCodemaker {
+    /// Starts with every code valid under `config` possible.
+    pub fn new(config: &GameConfig) -> Self {
+        Self {
+            candidates: LazyCodeSpace::new(config).collect(),
+        }
+    }
+
+    /// Returns the code the game is played against for now: the first of
+    /// those still possible.
+    pub fn hidden(&self) -> Code {
+        self.candidates[0].clone()
+    }
+
+    /// Returns how many codes are still possible.
+    pub fn remaining(&self) -> usize {
+        self.candidates.len()
+    }
+}
+
+impl Codemaker for AdversarialCodemaker {
+    /// Keeps the codes that share the most common score for `guess`,
+    /// preferring, among equally many, any score but a win and then the lower
+    /// score.
+    fn hidden_for(&mut self, guess: &Code) -> Code {
+        let mut buckets: HashMap<Score, Vec<Code>> = HashMap::new();
+        for code in self.candidates.drain(..) {
+            buckets.entry(code.score(guess)).or_default().push(code);
+        }
+        let length = guess.len();
+        let (_, codes) = buckets
+            .into_iter()
+            .max_by_key(|(score, codes)| {
+                (codes.len(), !score.is_win(length), Reverse(score.clone()))
+            })
+            .expect("some code is always consistent with every score given");
+        self.candidates = codes;
+        self.hidden()
+    }
+}
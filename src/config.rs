@@ -0,0 +1,251 @@
+use std::str::FromStr;
+
+use crate::code::Color;
+use crate::error::GameError;
+use crate::palette::Palette;
+
+/// How much a game prints while it is played.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OutputLevel {
+    /// Only the final outcome.
+    Quiet,
+    /// The board before every guess.
+    #[default]
+    Normal,
+    /// The board plus how many codes are still possible after each guess.
+    Verbose,
+}
+
+/// How the pegs of guesses are drawn on the board.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DisplayMode {
+    /// The letter typed for each color.
+    #[default]
+    Letters,
+    /// A colored circle emoji per color.
+    Emoji,
+    /// A different shape per color, for players who cannot tell the colors
+    /// apart.
+    Symbols,
+}
+
+impl DisplayMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DisplayMode::Letters => "letters",
+            DisplayMode::Emoji => "emoji",
+            DisplayMode::Symbols => "symbols",
+        }
+    }
+
+    /// Returns how many terminal columns one peg takes up.
+    pub fn peg_width(&self) -> usize {
+        match self {
+            DisplayMode::Emoji => 2,
+            DisplayMode::Letters | DisplayMode::Symbols => 1,
+        }
+    }
+}
+
+impl FromStr for DisplayMode {
+    type Err = GameError;
+
+    /// Parses a mode by its `name`.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    ///
+    /// use mastermind::DisplayMode;
+    ///
+    /// assert_eq!("emoji".parse::<DisplayMode>().unwrap(), DisplayMode::Emoji);
+    /// assert_eq!(DisplayMode::from_str("symbols").unwrap(), DisplayMode::Symbols);
+    /// assert!("pictures".parse::<DisplayMode>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            DisplayMode::Letters,
+            DisplayMode::Emoji,
+            DisplayMode::Symbols,
+        ]
+        .into_iter()
+        .find(|mode| mode.name() == s)
+        .ok_or_else(|| {
+            GameError::ParseError(format!(
+                "unknown display mode '{}', expected one of: letters emoji symbols",
+                s
+            ))
+        })
+    }
+}
+
+/// Settings that determine which codes are valid for a game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    pub colors: Vec<Color>,
+    pub code_length: usize,
+    pub output_level: OutputLevel,
+    /// Point out guesses that use colors the feedback has already ruled out,
+    /// and give tips on wasted guesses.
+    pub coach: bool,
+    /// Say in words what each round's score means, for players still
+    /// learning the pegs.
+    pub explain: bool,
+    /// Show only how many colors each guess has in common with the hidden code,
+    /// ignoring position.
+    pub anagram_mode: bool,
+    /// How many guesses the player gets before losing; `None` means unlimited.
+    pub max_guesses: Option<usize>,
+    /// Allow helpers such as `what-if` at the guess prompt.
+    pub assists: bool,
+    /// Whether a code may use the same color more than once.
+    pub allow_duplicates: bool,
+    /// How guesses are drawn on the board.
+    pub display_mode: DisplayMode,
+    /// Allow `Board::peek_position`, and `peek` at the guess prompt, to
+    /// reveal pegs of the hidden code, for debugging solvers.
+    pub cheat_mode: bool,
+    /// The pegs `colors` are drawn from, and how each is typed and drawn.
+    pub palette: Palette,
+}
+
+impl GameConfig {
+    /// Returns the rules of the commercial Super Mastermind: five pegs in any
+    /// of eight colors, for 32768 possible codes.
+    ///
+    /// ```
+    /// use mastermind::{GameConfig, LazyCodeSpace};
+    ///
+    /// let config = GameConfig::super_mastermind();
+    /// assert_eq!(config.colors.len(), 8);
+    /// assert_eq!(config.code_length, 5);
+    /// assert_eq!(LazyCodeSpace::new(&config).len(), 32768);
+    /// ```
+    pub fn super_mastermind() -> GameConfig {
+        GameConfig {
+            colors: vec![
+                Color::Red,
+                Color::Green,
+                Color::Blue,
+                Color::Yellow,
+                Color::Pink,
+                Color::Brown,
+                Color::White,
+                Color::Black,
+            ],
+            code_length: 5,
+            ..GameConfig::default()
+        }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            colors: vec![Color::Red, Color::Green, Color::Blue, Color::Yellow],
+            code_length: 4,
+            output_level: OutputLevel::default(),
+            coach: false,
+            explain: false,
+            anagram_mode: false,
+            max_guesses: None,
+            assists: true,
+            allow_duplicates: true,
+            display_mode: DisplayMode::default(),
+            cheat_mode: false,
+            palette: Palette::classic(),
+        }
+    }
+}
+
+/// Writes the colors in play and the code length, and the palette, as its
+/// letters, only when it is not the classic one, so older readers still
+/// understand classic games. Everything else is how this player likes to
+/// play, not part of the game, and is left out.
+///
+/// ```
+/// use mastermind::palette::Palette;
+/// use mastermind::GameConfig;
+///
+/// let classic = GameConfig::default();
+/// let json = serde_json::to_string(&classic).unwrap();
+/// assert_eq!(json, r#"{"colors":["r","g","b","y"],"code_length":4}"#);
+/// assert_eq!(serde_json::from_str::<GameConfig>(&json).unwrap(), classic);
+///
+/// let palette = Palette::from_letters("0123456789").unwrap();
+/// let digits = GameConfig {
+///     colors: palette.values()[..6].to_vec(),
+///     palette,
+///     ..GameConfig::default()
+/// };
+/// let json = serde_json::to_string(&digits).unwrap();
+/// assert_eq!(
+///     json,
+///     r#"{"colors":["0","1","2","3","4","5"],"code_length":4,"palette":"0123456789"}"#
+/// );
+/// assert_eq!(serde_json::from_str::<GameConfig>(&json).unwrap(), digits);
+///
+/// let empty = r#"{"colors":["r"],"code_length":0}"#;
+/// assert!(serde_json::from_str::<GameConfig>(empty).is_err());
+/// ```
+#[cfg(feature = "serde")]
+impl serde::Serialize for GameConfig {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SavedConfig {
+            colors: self
+                .colors
+                .iter()
+                .map(|color| self.palette.style(color).letter.to_string())
+                .collect(),
+            code_length: self.code_length,
+            palette: (!self.palette.is_classic()).then(|| self.palette.letters()),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Reads a config, with the classic palette if none is written.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for GameConfig {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let saved = SavedConfig::deserialize(deserializer)?;
+        let palette = match &saved.palette {
+            None => Palette::classic(),
+            Some(letters) => Palette::from_letters(letters).map_err(D::Error::custom)?,
+        };
+        let colors = saved
+            .colors
+            .iter()
+            .map(|letter| {
+                let mut chars = letter.chars();
+                match (chars.next().and_then(|c| palette.value_of(c)), chars.next()) {
+                    (Some(color), None) => Ok(color),
+                    _ => Err(D::Error::custom(format!("\"{}\" is not a color", letter))),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if !(1..=crate::code::MAX_CODE_LENGTH).contains(&saved.code_length) {
+            return Err(D::Error::custom(format!(
+                "\"code_length\" must be from 1 to {}",
+                crate::code::MAX_CODE_LENGTH
+            )));
+        }
+        Ok(GameConfig {
+            colors,
+            code_length: saved.code_length,
+            palette,
+            ..GameConfig::default()
+        })
+    }
+}
+
+/// A config as saves write it, with each color as its letter in the palette.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedConfig {
+    colors: Vec<String>,
+    code_length: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    palette: Option<String>,
+}
@@ -0,0 +1,254 @@
+//! One puzzle per calendar day, the same for everyone playing with the same
+//! rules, and a record of which days have been played.
+
+#[cfg(feature = "serde")]
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::{Days, NaiveDate};
+
+use crate::code::Code;
+use crate::config::GameConfig;
+#[cfg(feature = "serde")]
+use crate::error::GameError;
+use crate::history::{Outcome, Streak};
+use crate::paths::profile_dir;
+use crate::random::Rng;
+use crate::Result;
+
+/// Mixed into each day's seed so daily codes differ from `--seed <day>` games.
+const DAILY_SALT: u64 = 0x6461_696c_7970_757a;
+
+/// Returns the hidden code of the puzzle for `date` under `config`.
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use mastermind::daily::daily_code;
+/// use mastermind::GameConfig;
+///
+/// let config = GameConfig::default();
+/// let day = NaiveDate::from_ymd_opt(2024, 5, 17).unwrap();
+/// assert_eq!(daily_code(&config, day), daily_code(&config, day));
+/// ```
+pub fn daily_code(config: &GameConfig, date: NaiveDate) -> Code {
+    let seed = (i64::from(date.to_epoch_days()) as u64) ^ DAILY_SALT;
+    Code::random(config, &mut Rng::seeded(seed))
+}
+
+/// How the puzzle for one day went.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DailyRecord {
+    /// The day whose puzzle was played.
+    pub date: NaiveDate,
+    /// The day it was actually played on.
+    pub played_on: NaiveDate,
+    pub outcome: Outcome,
+    pub guesses: usize,
+}
+
+impl DailyRecord {
+    /// Returns whether the puzzle was caught up on after its own day.
+    pub fn is_backfilled(&self) -> bool {
+        self.played_on != self.date
+    }
+}
+
+/// Every daily puzzle played, in the order they were finished.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct DailyLog {
+    records: Vec<DailyRecord>,
+}
+
+impl DailyLog {
+    /// Returns `daily.json` in `player`'s profile directory.
+    pub fn default_path(player: Option<&str>) -> Option<PathBuf> {
+        Some(profile_dir(player)?.join("daily.json"))
+    }
+
+    /// Reads the log saved at `path`; a missing file is an empty log.
+    #[cfg(feature = "serde")]
+    pub fn load(path: &Path) -> Result<DailyLog> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(serde_json::from_str(&text).map_err(GameError::from)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(DailyLog::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes the log to `path`, creating its directory if needed.
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn records(&self) -> &[DailyRecord] {
+        &self.records
+    }
+
+    pub fn add(&mut self, record: DailyRecord) {
+        self.records.push(record);
+    }
+
+    /// Returns how the puzzle for `date` went, if it has been played.
+    pub fn get(&self, date: NaiveDate) -> Option<&DailyRecord> {
+        self.records.iter().find(|record| record.date == date)
+    }
+
+    /// Checks that the puzzle for `date` may be played on `today`: it is not
+    /// in the future and has not been played before.
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use mastermind::daily::{DailyLog, DailyRecord};
+    /// use mastermind::Outcome;
+    ///
+    /// let today = NaiveDate::from_ymd_opt(2024, 5, 20).unwrap();
+    /// let mut log = DailyLog::default();
+    /// assert!(log.check_playable(today, today).is_ok());
+    /// assert!(log.check_playable(NaiveDate::from_ymd_opt(2024, 5, 17).unwrap(), today).is_ok());
+    /// assert!(log.check_playable(NaiveDate::from_ymd_opt(2024, 5, 21).unwrap(), today).is_err());
+    ///
+    /// log.add(DailyRecord {
+    ///     date: NaiveDate::from_ymd_opt(2024, 5, 17).unwrap(),
+    ///     played_on: today,
+    ///     outcome: Outcome::Lost,
+    ///     guesses: 10,
+    /// });
+    /// let replay = log.check_playable(NaiveDate::from_ymd_opt(2024, 5, 17).unwrap(), today);
+    /// assert_eq!(
+    ///     replay.unwrap_err().to_string(),
+    ///     "the puzzle for 2024-05-17 has already been played"
+    /// );
+    /// ```
+    pub fn check_playable(&self, date: NaiveDate, today: NaiveDate) -> Result<()> {
+        if date > today {
+            return Err(format!("the puzzle for {} is not out yet", date).into());
+        }
+        if self.get(date).is_some() {
+            return Err(format!("the puzzle for {} has already been played", date).into());
+        }
+        Ok(())
+    }
+
+    /// Counts consecutive days won on the day itself. The current streak ends
+    /// `today`, or yesterday while today's puzzle is still unplayed; puzzles
+    /// caught up on later neither extend nor break a streak.
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use mastermind::daily::{DailyLog, DailyRecord};
+    /// use mastermind::history::Streak;
+    /// use mastermind::Outcome;
+    ///
+    /// let day = |d| NaiveDate::from_ymd_opt(2024, 5, d).unwrap();
+    /// let played = |date, played_on, outcome| DailyRecord {
+    ///     date,
+    ///     played_on,
+    ///     outcome,
+    ///     guesses: 4,
+    /// };
+    /// let mut log = DailyLog::default();
+    /// for d in [1, 2, 3, 5, 6] {
+    ///     log.add(played(day(d), day(d), Outcome::Won));
+    /// }
+    /// // Catching up on the 4th later does not join the two runs.
+    /// log.add(played(day(4), day(6), Outcome::Won));
+    /// assert_eq!(log.streak(day(6)), Streak { current: 2, best: 3 });
+    /// // Today's puzzle can still be played, so yesterday's streak stands.
+    /// assert_eq!(log.streak(day(7)).current, 2);
+    /// assert_eq!(log.streak(day(8)).current, 0);
+    ///
+    /// log.add(played(day(7), day(7), Outcome::Lost));
+    /// assert_eq!(log.streak(day(7)), Streak { current: 0, best: 3 });
+    /// ```
+    pub fn streak(&self, today: NaiveDate) -> Streak {
+        let mut won: Vec<NaiveDate> = self
+            .records
+            .iter()
+            .filter(|record| !record.is_backfilled() && record.outcome == Outcome::Won)
+            .map(|record| record.date)
+            .collect();
+        won.sort();
+        won.dedup();
+
+        let mut best = 0;
+        let mut run = 0;
+        let mut previous = None;
+        for &day in &won {
+            run = if previous.is_some_and(|previous| day.pred_opt() == Some(previous)) {
+                run + 1
+            } else {
+                1
+            };
+            best = usize::max(best, run);
+            previous = Some(day);
+        }
+
+        let played_today = self.get(today).is_some();
+        let mut day = if won.contains(&today) || played_today {
+            Some(today)
+        } else {
+            today.pred_opt()
+        };
+        let mut current = 0;
+        while let Some(won_on) = day.filter(|day| won.contains(day)) {
+            current += 1;
+            day = won_on.pred_opt();
+        }
+        Streak { current, best }
+    }
+
+    /// Describes the `days` days up to `today`, newest first, one per line: a
+    /// `✓` for a win, `✗` for a loss and `–` for a day not played.
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use mastermind::daily::{DailyLog, DailyRecord};
+    /// use mastermind::Outcome;
+    ///
+    /// let today = NaiveDate::from_ymd_opt(2024, 5, 20).unwrap();
+    /// let mut log = DailyLog::default();
+    /// log.add(DailyRecord {
+    ///     date: NaiveDate::from_ymd_opt(2024, 5, 19).unwrap(),
+    ///     played_on: today,
+    ///     outcome: Outcome::Won,
+    ///     guesses: 5,
+    /// });
+    /// assert_eq!(
+    ///     log.calendar(today, 3),
+    ///     "2024-05-20  –\n2024-05-19  ✓  5 guesses, caught up\n2024-05-18  –"
+    /// );
+    /// ```
+    pub fn calendar(&self, today: NaiveDate, days: usize) -> String {
+        let lines: Vec<String> = (0..days as u64)
+            .map_while(|ago| today.checked_sub_days(Days::new(ago)))
+            .map(|date| match self.get(date) {
+                None => format!("{}  –", date),
+                Some(record) => {
+                    let mark = if record.outcome == Outcome::Won {
+                        '✓'
+                    } else {
+                        '✗'
+                    };
+                    let late = if record.is_backfilled() {
+                        ", caught up"
+                    } else {
+                        ""
+                    };
+                    format!("{}  {}  {} guesses{}", date, mark, record.guesses, late)
+                }
+            })
+            .collect();
+        lines.join("\n")
+    }
+}
@@ -0,0 +1,25 @@
+//! Calendar days, as `chrono::NaiveDate`s, which are written `YYYY-MM-DD`.
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Returns today's date in UTC.
+pub fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+/// Returns the UTC date `seconds` seconds after the Unix epoch.
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use mastermind::date::day_of;
+///
+/// assert_eq!(day_of(0), NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
+/// assert_eq!(day_of(86_400 * 366 - 1), NaiveDate::from_ymd_opt(1971, 1, 1).unwrap());
+/// ```
+pub fn day_of(seconds: u64) -> NaiveDate {
+    i64::try_from(seconds)
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .unwrap_or_default()
+        .date_naive()
+}
@@ -0,0 +1,726 @@
+//! What the feedback so far says about the hidden code.
+
+use crate::board::Round;
+use crate::code::{Code, Color, LazyCodeSpace};
+use crate::config::GameConfig;
+use crate::palette::Palette;
+use crate::score::Score;
+use crate::solver::position_colors;
+
+/// How a `CandidateSet` stores its codes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// The candidates themselves, in a list that shrinks as they are ruled
+    /// out. Cheap to filter for small spaces, but every code of the space is
+    /// built up front.
+    Sparse,
+    /// One bit per code of the space, with codes built only while filtering.
+    /// Eight codes fit in a byte, so large spaces stay small.
+    Dense,
+}
+
+/// Spaces with more codes than this use `Backend::Dense`.
+pub const DENSE_THRESHOLD: usize = 4096;
+
+#[derive(Clone)]
+enum Storage {
+    Sparse(Vec<Code>),
+    Dense(Vec<u64>),
+}
+
+/// The codes of a configuration still consistent with every guess and score
+/// observed. Small spaces keep the candidates in a list and large ones, past
+/// `DENSE_THRESHOLD` codes, one bit per code of its `LazyCodeSpace`; both give
+/// the same answers.
+///
+/// ```
+/// use mastermind::deduction::CandidateSet;
+/// use mastermind::{Code, GameConfig};
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let hidden = code("rgby");
+///
+/// let mut candidates = CandidateSet::new(&config);
+/// for guess in [code("rrgb"), code("rgyy"), code("bbbb")] {
+///     candidates.observe(&guess, &hidden.score(&guess));
+/// }
+/// assert!(candidates.contains(&hidden));
+/// assert!(!candidates.contains(&code("rrrr")));
+/// assert!(!candidates.is_contradictory());
+/// ```
+#[derive(Clone)]
+pub struct CandidateSet {
+    space: LazyCodeSpace,
+    storage: Storage,
+}
+
+impl CandidateSet {
+    /// Starts with every code valid under `config`, choosing the backend by
+    /// the size of the space.
+    pub fn new(config: &GameConfig) -> Self {
+        let backend = if LazyCodeSpace::new(config).len() > DENSE_THRESHOLD {
+            Backend::Dense
+        } else {
+            Backend::Sparse
+        };
+        Self::with_backend(config, backend)
+    }
+
+    /// Starts with every code valid under `config`, stored as `backend`.
+    pub fn with_backend(config: &GameConfig, backend: Backend) -> Self {
+        let space = LazyCodeSpace::new(config);
+        let storage = match backend {
+            Backend::Sparse => Storage::Sparse(space.clone().collect()),
+            Backend::Dense => {
+                let len = space.len();
+                let mut bits = vec![u64::MAX; len.div_ceil(64)];
+                if let Some(last) = bits.last_mut() {
+                    if !len.is_multiple_of(64) {
+                        *last = (1 << (len % 64)) - 1;
+                    }
+                }
+                Storage::Dense(bits)
+            }
+        };
+        Self { space, storage }
+    }
+
+    /// Returns how the candidates are stored.
+    pub fn backend(&self) -> Backend {
+        match self.storage {
+            Storage::Sparse(_) => Backend::Sparse,
+            Storage::Dense(_) => Backend::Dense,
+        }
+    }
+
+    /// Starts with the codes valid under `config` that fit every round in
+    /// `rounds`.
+    pub fn from_rounds(config: &GameConfig, rounds: &[Round]) -> Self {
+        let mut candidates = Self::new(config);
+        for round in rounds {
+            candidates.observe(round.guess(), round.score());
+        }
+        candidates
+    }
+
+    /// Drops every candidate that would not have given `score` for `guess`.
+    pub fn observe(&mut self, guess: &Code, score: &Score) {
+        match &mut self.storage {
+            Storage::Sparse(codes) => codes.retain(|code| code.score(guess) == *score),
+            Storage::Dense(bits) => {
+                for (word, bits) in bits.iter_mut().enumerate() {
+                    let mut rest = *bits;
+                    while rest != 0 {
+                        let bit = rest.trailing_zeros() as usize;
+                        rest &= rest - 1;
+                        let code = code_at(&self.space, word * 64 + bit);
+                        if code.score(guess) != *score {
+                            *bits &= !(1 << bit);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Sparse(codes) => codes.len(),
+            Storage::Dense(bits) => bits.iter().map(|word| word.count_ones() as usize).sum(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match &self.storage {
+            Storage::Sparse(codes) => codes.is_empty(),
+            Storage::Dense(bits) => bits.iter().all(|word| *word == 0),
+        }
+    }
+
+    /// Returns whether `code` is still a candidate.
+    pub fn contains(&self, code: &Code) -> bool {
+        match &self.storage {
+            Storage::Sparse(codes) => self.space.index_of(code).is_some_and(|index| {
+                codes
+                    .binary_search_by_key(&Some(index), |c| self.space.index_of(c))
+                    .is_ok()
+            }),
+            Storage::Dense(bits) => self
+                .space
+                .index_of(code)
+                .is_some_and(|index| bits[index / 64] & (1 << (index % 64)) != 0),
+        }
+    }
+
+    /// Returns the candidates in code order.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = Code> + '_> {
+        match &self.storage {
+            Storage::Sparse(codes) => Box::new(codes.iter().cloned()),
+            Storage::Dense(bits) => {
+                Box::new(bits.iter().enumerate().flat_map(move |(word, bits)| {
+                    (0..64)
+                        .filter(move |bit| bits & (1 << bit) != 0)
+                        .map(move |bit| code_at(&self.space, word * 64 + bit))
+                }))
+            }
+        }
+    }
+
+    /// Returns whether no code fits everything observed, meaning some score
+    /// was given wrongly.
+    pub fn is_contradictory(&self) -> bool {
+        self.is_empty()
+    }
+
+    /// Returns the colors of the configuration that no candidate contains.
+    ///
+    /// A color can be ruled out by rounds taken together even when each on its
+    /// own leaves it possible:
+    ///
+    /// ```
+    /// use mastermind::deduction::CandidateSet;
+    /// use mastermind::{Code, Color, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let hidden = code("rrgg");
+    /// let mut candidates = CandidateSet::new(&config);
+    ///
+    /// // Blue is in a guess that earns white pegs, so it might be one of them.
+    /// candidates.observe(&code("rgrb"), &hidden.score(&code("rgrb")));
+    /// assert!(candidates.eliminated_colors().is_empty());
+    ///
+    /// // A second round without blue leaves only codes without it.
+    /// candidates.observe(&code("grrr"), &hidden.score(&code("grrr")));
+    /// assert_eq!(candidates.eliminated_colors(), [Color::Blue]);
+    /// assert_eq!(candidates.len(), 4);
+    /// ```
+    pub fn eliminated_colors(&self) -> Vec<Color> {
+        let mut seen = vec![false; self.space.colors().len()];
+        for code in self.iter() {
+            for color in code.colors() {
+                if let Some(i) = self.space.colors().iter().position(|c| c == color) {
+                    seen[i] = true;
+                }
+            }
+        }
+        self.space
+            .colors()
+            .iter()
+            .zip(seen)
+            .filter(|(_, seen)| !seen)
+            .map(|(color, _)| color.clone())
+            .collect()
+    }
+
+    /// Returns, for each position, the color every candidate has there, if
+    /// they all agree. With no candidates nothing is locked.
+    ///
+    /// ```
+    /// use mastermind::deduction::CandidateSet;
+    /// use mastermind::{Code, Color, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let mut candidates = CandidateSet::new(&config);
+    /// let guess = code("rrrr");
+    /// candidates.observe(&guess, &code("rgby").score(&guess));
+    /// let guess = code("rgbb");
+    /// candidates.observe(&guess, &code("rgby").score(&guess));
+    ///
+    /// assert_eq!(candidates.locked_positions()[0], Some(Color::Red));
+    /// assert_eq!(candidates.eliminated_colors(), []);
+    /// ```
+    pub fn locked_positions(&self) -> Vec<Option<Color>> {
+        let mut codes = self.iter();
+        let Some(first) = codes.next() else {
+            return vec![None; self.space.code_length()];
+        };
+        let mut locked: Vec<Option<Color>> = first.colors().iter().cloned().map(Some).collect();
+        for code in codes {
+            for (lock, color) in locked.iter_mut().zip(code.colors()) {
+                if lock.as_ref() != Some(color) {
+                    *lock = None;
+                }
+            }
+        }
+        locked
+    }
+
+    /// Returns, for each position, the colors some candidate has there.
+    pub fn position_colors(&self) -> Vec<Vec<Color>> {
+        let codes: Vec<Code> = self.iter().collect();
+        position_colors(&codes, self.space.code_length())
+    }
+}
+
+/// The colors each position could still hold, narrowed by reasoning about
+/// one round at a time rather than by checking every code. It is cheap for
+/// any size of game, but can miss what only the rounds taken together
+/// prove, which `CandidateSet::locked_positions` would find.
+///
+/// ```
+/// use mastermind::deduction::PositionConstraints;
+/// use mastermind::{Code, Color, GameConfig, Round};
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let hidden = code("rgby");
+/// let mut constraints = PositionConstraints::new(4, &config.colors);
+/// let mut play = |guess: &str| {
+///     constraints.update(&Round::from_guess(code(guess), &hidden));
+///     constraints.forced_positions()
+/// };
+///
+/// // No black pegs: red is not at 2, 3 or 4, so the one red black peg of
+/// // "rrrr" must be at 1.
+/// assert_eq!(play("grrr"), [None, None, None, None]);
+/// assert_eq!(play("rrrr"), [Some(Color::Red), None, None, None]);
+/// // Likewise blue is not at 2 or 4, and 1 is red, so blue is at 3.
+/// assert_eq!(play("ybyb"), [Some(Color::Red), None, None, None]);
+/// assert_eq!(play("bbbb"), [Some(Color::Red), None, Some(Color::Blue), None]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PositionConstraints {
+    /// The colors still possible at each position.
+    possible: Vec<Vec<Color>>,
+    rounds: Vec<Round>,
+}
+
+impl PositionConstraints {
+    /// Starts with any of `colors` possible at each of `code_length`
+    /// positions.
+    pub fn new(code_length: usize, colors: &[Color]) -> Self {
+        Self {
+            possible: vec![colors.to_vec(); code_length],
+            rounds: Vec::new(),
+        }
+    }
+
+    /// Narrows the possible colors by `round`, then again by every earlier
+    /// round in light of what it showed, until nothing more follows.
+    pub fn update(&mut self, round: &Round) {
+        self.rounds.push(round.clone());
+        let rounds = std::mem::take(&mut self.rounds);
+        while rounds.iter().any(|round| self.apply(round)) {}
+        self.rounds = rounds;
+    }
+
+    /// Narrows the possible colors by what `round` alone proves, returning
+    /// whether anything changed.
+    fn apply(&mut self, round: &Round) -> bool {
+        let guess = round.guess().colors();
+        let blacks = round.score().black_count();
+        let pegs = blacks + round.score().white_count();
+        let before = self.possible.clone();
+        // Which positions could have earned a black peg, and which must have.
+        let could: Vec<bool> = guess
+            .iter()
+            .zip(&self.possible)
+            .map(|(color, possible)| possible.contains(color))
+            .collect();
+        let must = guess
+            .iter()
+            .zip(&self.possible)
+            .filter(|(color, possible)| possible.as_slice() == [(*color).clone()])
+            .count();
+        for (i, possible) in self.possible.iter_mut().enumerate() {
+            if pegs == 0 {
+                possible.retain(|color| !guess.contains(color));
+            }
+            let known = possible.as_slice() == [guess[i].clone()];
+            if blacks == 0 || (must == blacks && !known) {
+                possible.retain(|color| *color != guess[i]);
+            } else if could[i] && could.iter().filter(|c| **c).count() == blacks {
+                possible.retain(|color| *color == guess[i]);
+            }
+        }
+        self.possible != before
+    }
+
+    /// Returns, for each position, its color if only one is still possible.
+    pub fn forced_positions(&self) -> Vec<Option<Color>> {
+        self.possible
+            .iter()
+            .map(|possible| match possible.as_slice() {
+                [color] => Some(color.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+fn code_at(space: &LazyCodeSpace, index: usize) -> Code {
+    space
+        .code_at(index)
+        .expect("candidate indices are within the code space")
+}
+
+/// How `progress_fill` turns the codes ruled out into a bar.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProgressScale {
+    /// The bar shows the share of codes ruled out. The first guess usually
+    /// rules out most of them, so the bar fills up early.
+    #[default]
+    Linear,
+    /// The bar shows how far the count has come down on a log scale, so each
+    /// guess that cuts the candidates by the same factor adds the same amount.
+    Log,
+}
+
+impl std::str::FromStr for ProgressScale {
+    type Err = crate::error::GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(ProgressScale::Linear),
+            "log" => Ok(ProgressScale::Log),
+            _ => Err(crate::error::GameError::ParseError(format!(
+                "unknown progress scale '{}', expected linear or log",
+                s
+            ))),
+        }
+    }
+}
+
+/// Returns how many of `width` cells of a progress bar to fill when
+/// `remaining` of `total` codes are still candidates. The bar is full once a
+/// single code is left, and also when none is, since nothing is left to rule
+/// out; it only fills up before that by rounding down to `width`.
+///
+/// ```
+/// use mastermind::deduction::{progress_fill, ProgressScale::{Linear, Log}};
+///
+/// let cases = [
+///     // (remaining, total, scale, fill of 10)
+///     (1296, 1296, Linear, 0),
+///     (42, 1296, Linear, 9),
+///     (42, 1296, Log, 4),
+///     (100, 1296, Log, 3),
+///     (2, 1296, Linear, 9),
+///     (2, 1296, Log, 9),
+///     (1, 1296, Linear, 10),
+///     (1, 1296, Log, 10),
+///     // A contradiction leaves nothing to rule out.
+///     (0, 1296, Linear, 10),
+///     (0, 1296, Log, 10),
+///     (1, 1, Linear, 10),
+///     (1, 1, Log, 10),
+/// ];
+/// for (remaining, total, scale, fill) in cases {
+///     assert_eq!(
+///         progress_fill(remaining, total, 10, scale),
+///         fill,
+///         "{} of {} on {:?}",
+///         remaining,
+///         total,
+///         scale
+///     );
+/// }
+/// ```
+pub fn progress_fill(remaining: usize, total: usize, width: usize, scale: ProgressScale) -> usize {
+    if remaining <= 1 || total <= 1 {
+        return width;
+    }
+    let done = match scale {
+        ProgressScale::Linear => (total - remaining) as f64 / (total - 1) as f64,
+        ProgressScale::Log => 1.0 - (remaining as f64).ln() / (total as f64).ln(),
+    };
+    ((done.clamp(0.0, 1.0) * width as f64) as usize).min(width)
+}
+
+/// The widest `describe_progress` draws its bar.
+const PROGRESS_MAX_WIDTH: usize = 30;
+
+/// Describes `remaining` of `total` codes left, like `[#########-] 1296 → 42`,
+/// with the bar sized to a terminal `width` columns wide. Without a terminal,
+/// or one too narrow for a bar, only the counts are given, in plain ASCII.
+///
+/// ```
+/// use mastermind::deduction::{describe_progress, ProgressScale};
+///
+/// assert_eq!(
+///     describe_progress(42, 1296, Some(80), ProgressScale::Linear),
+///     format!("[{}-] 1296 → 42", "#".repeat(29))
+/// );
+/// assert_eq!(
+///     describe_progress(42, 1296, Some(25), ProgressScale::Log),
+///     "[######-------] 1296 → 42"
+/// );
+/// assert_eq!(describe_progress(42, 1296, None, ProgressScale::Log), "codes: 1296 -> 42");
+/// assert_eq!(describe_progress(42, 1296, Some(15), ProgressScale::Log), "codes: 1296 -> 42");
+/// ```
+pub fn describe_progress(
+    remaining: usize,
+    total: usize,
+    width: Option<usize>,
+    scale: ProgressScale,
+) -> String {
+    let counts = format!("{} → {}", total, remaining);
+    // The brackets and the space after them.
+    let bar_width = width
+        .map(|width| width.saturating_sub(counts.chars().count() + 3))
+        .unwrap_or(0)
+        .min(PROGRESS_MAX_WIDTH);
+    if bar_width < 5 {
+        return format!("codes: {} -> {}", total, remaining);
+    }
+    let fill = progress_fill(remaining, total, bar_width, scale);
+    format!(
+        "[{}{}] {}",
+        "#".repeat(fill),
+        "-".repeat(bar_width - fill),
+        counts
+    )
+}
+
+/// Explains why `candidate` cannot be the hidden code given the score of
+/// `round`, or returns `None` if it could be. The guess of `round` is written
+/// in `palette`'s letters.
+///
+/// The explanation names the first broken constraint: how many positions the
+/// two codes share, which bounds the black pegs, or how many colors they share
+/// counting repeats no more often than both codes have them, which bounds the
+/// black and white pegs together.
+///
+/// ```
+/// use mastermind::deduction::explain_inconsistency;
+/// use mastermind::{Code, GameConfig, Round};
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let round = Round::from_guess(code("rgby"), &code("ryyg"));
+///
+/// assert_eq!(explain_inconsistency(&code("ryyg"), &round, &config.palette), None);
+/// assert_eq!(
+///     explain_inconsistency(&code("rgbb"), &round, &config.palette).unwrap(),
+///     "you scored 1 black and 2 white against r g b y; your new guess matches it \
+///      in 3 positions, which would need at least 3 black pegs"
+/// );
+/// assert_eq!(
+///     explain_inconsistency(&code("yrgg"), &round, &config.palette).unwrap(),
+///     "you scored 1 black and 2 white against r g b y; your new guess matches it \
+///      in 0 positions, which would give 0 black pegs, not 1"
+/// );
+/// // "rrrr" shares only one red with "rgby", however many reds it has.
+/// assert_eq!(
+///     explain_inconsistency(&code("rrrr"), &round, &config.palette).unwrap(),
+///     "you scored 1 black and 2 white against r g b y; your new guess shares 1 \
+///      color with it, which would give 1 peg in all, not 3"
+/// );
+/// assert_eq!(
+///     explain_inconsistency(&code("rbgy"), &Round::from_guess(code("rgby"), &code("rggg")), &config.palette).unwrap(),
+///     "you scored 2 black and 0 white against r g b y; your new guess shares 4 \
+///      colors with it, which would give 4 pegs in all, not 2"
+/// );
+/// ```
+pub fn explain_inconsistency(candidate: &Code, round: &Round, palette: &Palette) -> Option<String> {
+    let guess = round.guess();
+    let score = round.score();
+    let blacks = score.black_count();
+    let pegs = blacks + score.white_count();
+    let exact = candidate.count_exact_matches(guess);
+    let common = candidate.common_peg_count(guess);
+    let problem = if exact > blacks {
+        format!(
+            "matches it in {} {}, which would need at least {} {}",
+            exact,
+            plural(exact, "position"),
+            exact,
+            plural(exact, "black peg")
+        )
+    } else if exact < blacks {
+        format!(
+            "matches it in {} {}, which would give {} {}, not {}",
+            exact,
+            plural(exact, "position"),
+            exact,
+            plural(exact, "black peg"),
+            blacks
+        )
+    } else if common != pegs {
+        format!(
+            "shares {} {} with it, which would give {} {} in all, not {}",
+            common,
+            plural(common, "color"),
+            common,
+            plural(common, "peg"),
+            pegs
+        )
+    } else {
+        return None;
+    };
+    let letters: Vec<String> = guess
+        .colors()
+        .iter()
+        .map(|c| palette.style(c).letter.to_string())
+        .collect();
+    Some(format!(
+        "you scored {} black and {} white against {}; your new guess {}",
+        blacks,
+        score.white_count(),
+        letters.join(" "),
+        problem
+    ))
+}
+
+/// Something a score says about one color, without regard to position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ColorConstraint {
+    /// The hidden code has no peg of this color.
+    Absent(Color),
+    /// The hidden code has at least one peg of this color.
+    Present(Color),
+}
+
+impl ColorConstraint {
+    /// Returns what `round` proves about individual colors: none of the
+    /// guess's colors are in the code if it scored no pegs at all, and all of
+    /// them are if every peg of the guess scored. A guess of one color that
+    /// scored anything proves that color is present. Other scores prove
+    /// nothing about any single color.
+    ///
+    /// ```
+    /// use mastermind::deduction::ColorConstraint;
+    /// use mastermind::{Code, Color, GameConfig, LazyCodeSpace, Round};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    ///
+    /// let miss = Round::from_guess(code("rrgg"), &code("bybb"));
+    /// let constraints = ColorConstraint::from_round(&miss);
+    /// assert_eq!(
+    ///     constraints,
+    ///     [ColorConstraint::Absent(Color::Red), ColorConstraint::Absent(Color::Green)]
+    /// );
+    /// let left: Vec<Code> = LazyCodeSpace::new(&config)
+    ///     .filter(|c| c.satisfies(&constraints))
+    ///     .collect();
+    /// assert_eq!(left.len(), 16);
+    /// assert!(left.iter().all(|c| !c.contains(&Color::Red) && !c.contains(&Color::Green)));
+    ///
+    /// let all = Round::from_guess(code("rgby"), &code("ybgr"));
+    /// assert!(ColorConstraint::from_round(&all)
+    ///     .iter()
+    ///     .all(|c| matches!(c, ColorConstraint::Present(_))));
+    ///
+    /// let reds = Round::from_guess(code("rrrr"), &code("rgbr"));
+    /// assert_eq!(ColorConstraint::from_round(&reds), [ColorConstraint::Present(Color::Red)]);
+    ///
+    /// let some = Round::from_guess(code("rrgg"), &code("rbyy"));
+    /// assert_eq!(ColorConstraint::from_round(&some), []);
+    /// ```
+    pub fn from_round(round: &Round) -> Vec<ColorConstraint> {
+        let guess = round.guess();
+        let pegs = round.score().total_pegs();
+        let mut colors: Vec<Color> = Vec::new();
+        for color in guess.colors() {
+            if !colors.contains(color) {
+                colors.push(color.clone());
+            }
+        }
+        if pegs == 0 {
+            colors.into_iter().map(ColorConstraint::Absent).collect()
+        } else if pegs == guess.len() || colors.len() == 1 {
+            colors.into_iter().map(ColorConstraint::Present).collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Returns whether `code` meets this constraint.
+    pub fn allows(&self, code: &Code) -> bool {
+        match self {
+            ColorConstraint::Absent(color) => !code.contains(color),
+            ColorConstraint::Present(color) => code.contains(color),
+        }
+    }
+}
+
+/// Returns `word`, with an `s` unless `n` is one.
+fn plural(n: usize, word: &str) -> String {
+    if n == 1 {
+        word.to_string()
+    } else {
+        format!("{}s", word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Rng;
+
+    #[test]
+    fn honest_scores_never_rule_out_the_hidden_code() {
+        let config = GameConfig::default();
+        let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+        let guesses = [code("rrgb"), code("rgyy"), code("bbbb"), code("yrgb")];
+        for hidden in LazyCodeSpace::new(&config) {
+            let mut candidates = CandidateSet::new(&config);
+            for guess in &guesses {
+                candidates.observe(guess, &hidden.score(guess));
+                assert!(candidates.contains(&hidden));
+                assert!(!candidates.is_contradictory());
+            }
+            assert!(candidates
+                .iter()
+                .all(|c| guesses.iter().all(|g| c.score(g) == hidden.score(g))));
+        }
+    }
+
+    #[test]
+    fn both_backends_agree_on_random_games() {
+        let mut rng = Rng::seeded(11);
+        for _ in 0..40 {
+            let config = GameConfig {
+                colors: Color::ALL[..2 + rng.below(5)].to_vec(),
+                code_length: 1 + rng.below(4),
+                ..GameConfig::default()
+            };
+            let hidden = Code::random(&config, &mut rng);
+            let mut sparse = CandidateSet::with_backend(&config, Backend::Sparse);
+            let mut dense = CandidateSet::with_backend(&config, Backend::Dense);
+            for _ in 0..1 + rng.below(5) {
+                let guess = Code::random(&config, &mut rng);
+                // Now and then a score that is a lie, to reach contradictions too.
+                let other = Code::random(&config, &mut rng);
+                let score = if rng.below(4) == 0 {
+                    other.score(&guess)
+                } else {
+                    hidden.score(&guess)
+                };
+                sparse.observe(&guess, &score);
+                dense.observe(&guess, &score);
+                assert_eq!(sparse.len(), dense.len());
+                assert!(sparse.iter().eq(dense.iter()));
+                assert_eq!(sparse.contains(&hidden), dense.contains(&hidden));
+                assert_eq!(sparse.locked_positions(), dense.locked_positions());
+            }
+        }
+    }
+
+    #[test]
+    fn forced_positions_are_right_and_locked_among_the_candidates() {
+        let config = GameConfig::default();
+        let mut rng = Rng::seeded(11);
+        for _ in 0..50 {
+            let hidden = Code::random(&config, &mut rng);
+            let mut constraints = PositionConstraints::new(4, &config.colors);
+            let mut candidates = CandidateSet::new(&config);
+            for _ in 0..6 {
+                let guess = Code::random(&config, &mut rng);
+                constraints.update(&Round::from_guess(guess.clone(), &hidden));
+                candidates.observe(&guess, &hidden.score(&guess));
+                let locked = candidates.locked_positions();
+                for (i, forced) in constraints.forced_positions().iter().enumerate() {
+                    if let Some(color) = forced {
+                        assert_eq!(*color, hidden.colors()[i]);
+                        assert_eq!(locked[i].as_ref(), Some(color));
+                    }
+                }
+            }
+        }
+    }
+}
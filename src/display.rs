@@ -0,0 +1,466 @@
+//! Color themes: the terminal colors pegs are drawn in.
+//!
+//! Everything painted with color support goes through `paint_color`,
+//! `paint_color_block`, `paint_labelled_block` and `paint_key`, which use the
+//! theme chosen with `set_theme`. Themes give each shade as 24-bit RGB for terminals that
+//! announce truecolor support in `COLORTERM`, with one of the 256 ANSI colors
+//! to fall back on.
+//!
+//! Whether to paint at all is decided once, with `ColorChoice::resolve`, and
+//! handed to `set_color`; with it off, everything is drawn as plain letters.
+
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+use termcolor::ColorSpec;
+
+use crate::code::Color;
+use crate::error::GameError;
+use crate::palette;
+use crate::score::ScoreDetail;
+
+/// One terminal color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shade {
+    /// The exact color, for truecolor terminals; `None` to always use `ansi`.
+    pub rgb: Option<[u8; 3]>,
+    /// The color among the 256 ANSI colors.
+    pub ansi: u8,
+}
+
+const fn ansi(ansi: u8) -> Shade {
+    Shade { rgb: None, ansi }
+}
+
+const fn rgb(r: u8, g: u8, b: u8, ansi: u8) -> Shade {
+    Shade {
+        rgb: Some([r, g, b]),
+        ansi,
+    }
+}
+
+impl Shade {
+    /// Returns `text` written in this shade, as RGB if `truecolor` allows.
+    pub fn foreground(&self, text: &str, truecolor: bool) -> String {
+        format!("\x1b[38;{}m{}\x1b[39m", self.sgr(truecolor), text)
+    }
+
+    /// Returns `text` on a background of this shade, as RGB if `truecolor`
+    /// allows.
+    pub fn background(&self, text: &str, truecolor: bool) -> String {
+        format!("\x1b[48;{}m{}\x1b[49m", self.sgr(truecolor), text)
+    }
+
+    /// Returns this shade for a `termcolor` writer, as RGB if `truecolor`
+    /// allows.
+    pub fn termcolor(&self, truecolor: bool) -> termcolor::Color {
+        match self.rgb {
+            Some([r, g, b]) if truecolor => termcolor::Color::Rgb(r, g, b),
+            _ => termcolor::Color::Ansi256(self.ansi),
+        }
+    }
+
+    fn sgr(&self, truecolor: bool) -> String {
+        match self.rgb {
+            Some([r, g, b]) if truecolor => format!("2;{};{};{}", r, g, b),
+            _ => format!("5;{}", self.ansi),
+        }
+    }
+}
+
+/// The shades of one theme: a code peg per `Color`, in `Color::ALL` order,
+/// then the key pegs.
+struct Palette {
+    colors: [Shade; Color::ALL.len()],
+    black_key: Shade,
+    white_key: Shade,
+    empty_key: Shade,
+}
+
+/// Every theme's shades, in `Theme::ALL` order.
+const PALETTES: [Palette; 5] = [
+    // Default: the terminal's own bright colors.
+    Palette {
+        colors: [
+            ansi(9),
+            ansi(10),
+            ansi(12),
+            ansi(11),
+            ansi(13),
+            ansi(3),
+            ansi(15),
+            ansi(8),
+        ],
+        black_key: ansi(14),
+        white_key: ansi(15),
+        empty_key: ansi(9),
+    },
+    // Light: deep colors that stand out on a pale background.
+    Palette {
+        colors: [
+            rgb(190, 0, 0, 124),
+            rgb(0, 128, 0, 28),
+            rgb(0, 60, 200, 20),
+            rgb(175, 135, 0, 136),
+            rgb(190, 0, 140, 127),
+            rgb(120, 70, 20, 94),
+            rgb(140, 140, 140, 245),
+            rgb(0, 0, 0, 16),
+        ],
+        black_key: rgb(0, 0, 0, 16),
+        white_key: rgb(200, 200, 200, 251),
+        empty_key: rgb(230, 180, 180, 224),
+    },
+    // Dark: soft, light colors for a dark background.
+    Palette {
+        colors: [
+            rgb(255, 95, 95, 203),
+            rgb(95, 255, 95, 83),
+            rgb(95, 135, 255, 69),
+            rgb(255, 255, 95, 227),
+            rgb(255, 135, 215, 212),
+            rgb(215, 135, 95, 173),
+            rgb(255, 255, 255, 231),
+            rgb(128, 128, 128, 244),
+        ],
+        black_key: rgb(0, 215, 255, 45),
+        white_key: rgb(255, 255, 255, 231),
+        empty_key: rgb(88, 88, 88, 240),
+    },
+    // High contrast: fully saturated colors.
+    Palette {
+        colors: [
+            rgb(255, 0, 0, 196),
+            rgb(0, 255, 0, 46),
+            rgb(0, 0, 255, 21),
+            rgb(255, 255, 0, 226),
+            rgb(255, 0, 255, 201),
+            rgb(135, 75, 0, 94),
+            rgb(255, 255, 255, 231),
+            rgb(108, 108, 108, 242),
+        ],
+        black_key: rgb(0, 255, 255, 51),
+        white_key: rgb(255, 255, 255, 231),
+        empty_key: rgb(255, 0, 0, 196),
+    },
+    // Solarized: Ethan Schoonover's accent colors, with orange for brown.
+    Palette {
+        colors: [
+            rgb(220, 50, 47, 160),
+            rgb(133, 153, 0, 100),
+            rgb(38, 139, 210, 33),
+            rgb(181, 137, 0, 136),
+            rgb(211, 54, 130, 125),
+            rgb(203, 75, 22, 166),
+            rgb(238, 232, 213, 254),
+            rgb(7, 54, 66, 235),
+        ],
+        black_key: rgb(42, 161, 152, 37),
+        white_key: rgb(147, 161, 161, 247),
+        empty_key: rgb(88, 110, 117, 240),
+    },
+];
+
+/// A named set of colors to draw pegs in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Default,
+    Light,
+    Dark,
+    HighContrast,
+    Solarized,
+}
+
+impl Theme {
+    /// Every theme, in the order `mastermind themes` lists them.
+    pub const ALL: [Theme; 5] = [
+        Theme::Default,
+        Theme::Light,
+        Theme::Dark,
+        Theme::HighContrast,
+        Theme::Solarized,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Default => "default",
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::HighContrast => "high-contrast",
+            Theme::Solarized => "solarized",
+        }
+    }
+
+    fn palette(&self) -> &'static Palette {
+        &PALETTES[*self as usize]
+    }
+
+    /// Returns the shade this theme draws `color` in.
+    ///
+    /// ```
+    /// use mastermind::display::Theme;
+    /// use mastermind::{Color, ScoreDetail};
+    ///
+    /// // The default theme keeps to the 256 ANSI colors.
+    /// let red = Theme::Default.color(&Color::Red);
+    /// assert_eq!(red.foreground("r", true), "\x1b[38;5;9mr\x1b[39m");
+    ///
+    /// let blue = Theme::Solarized.color(&Color::Blue);
+    /// assert_eq!(blue.foreground("b", true), "\x1b[38;2;38;139;210mb\x1b[39m");
+    /// assert_eq!(blue.foreground("b", false), "\x1b[38;5;33mb\x1b[39m");
+    ///
+    /// let black = Theme::Light.key(&ScoreDetail::ColorAndPositionCorrect);
+    /// assert_eq!(black.background(" ", true), "\x1b[48;2;0;0;0m \x1b[49m");
+    /// let empty = Theme::HighContrast.key(&ScoreDetail::Empty);
+    /// assert_eq!(empty.background(" ", false), "\x1b[48;5;196m \x1b[49m");
+    /// ```
+    pub fn color(&self, color: &Color) -> Shade {
+        self.palette().colors[color.index() % Color::ALL.len()]
+    }
+
+    /// Returns the shade `pegs` gives `color`, or the one this theme draws
+    /// it in when `pegs` leaves it to the theme.
+    pub fn peg(&self, color: &Color, pegs: &palette::Palette) -> Shade {
+        pegs.style(color).shade.unwrap_or(self.color(color))
+    }
+
+    /// Returns the shade this theme draws the key peg `detail` in.
+    pub fn key(&self, detail: &ScoreDetail) -> Shade {
+        let palette = self.palette();
+        match detail {
+            ScoreDetail::ColorAndPositionCorrect => palette.black_key,
+            ScoreDetail::ColorCorrect => palette.white_key,
+            ScoreDetail::Empty => palette.empty_key,
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = GameError;
+
+    /// Parses a theme by its `name`.
+    ///
+    /// ```
+    /// use mastermind::display::Theme;
+    ///
+    /// assert_eq!("high-contrast".parse::<Theme>().unwrap(), Theme::HighContrast);
+    /// let error = "sepia".parse::<Theme>().unwrap_err().to_string();
+    /// assert!(error.ends_with(
+    ///     "unknown theme 'sepia', expected one of: default light dark high-contrast solarized"
+    /// ));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Theme::ALL
+            .into_iter()
+            .find(|theme| theme.name() == s)
+            .ok_or_else(|| {
+                GameError::ParseError(format!(
+                    "unknown theme '{}', expected one of: {}",
+                    s,
+                    Theme::ALL.map(|theme| theme.name()).join(" ")
+                ))
+            })
+    }
+}
+
+/// The theme in use, as its index in `Theme::ALL`.
+static THEME: AtomicUsize = AtomicUsize::new(0);
+
+/// Draws everything from now on in `theme`.
+pub fn set_theme(theme: Theme) {
+    THEME.store(theme as usize, Ordering::Relaxed);
+}
+
+/// Returns the theme set with `set_theme`.
+pub fn theme() -> Theme {
+    Theme::ALL[THEME.load(Ordering::Relaxed)]
+}
+
+/// When to draw in color.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Only on a terminal, and not if `NO_COLOR` is set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorChoice::Auto => "auto",
+            ColorChoice::Always => "always",
+            ColorChoice::Never => "never",
+        }
+    }
+
+    /// Returns whether to draw in color, given whether output goes to a
+    /// terminal and the value of `NO_COLOR`, which turns color off unless
+    /// empty.
+    ///
+    /// ```
+    /// use mastermind::display::ColorChoice;
+    ///
+    /// assert!(ColorChoice::Auto.resolve(true, None));
+    /// assert!(!ColorChoice::Auto.resolve(false, None));
+    /// assert!(!ColorChoice::Auto.resolve(true, Some("1")));
+    /// assert!(ColorChoice::Auto.resolve(true, Some("")));
+    /// assert!(ColorChoice::Always.resolve(false, Some("1")));
+    /// assert!(!ColorChoice::Never.resolve(true, None));
+    /// ```
+    pub fn resolve(&self, terminal: bool, no_color: Option<&str>) -> bool {
+        match self {
+            ColorChoice::Auto => terminal && no_color.is_none_or(str::is_empty),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = GameError;
+
+    /// Parses a choice by its `name`.
+    ///
+    /// ```
+    /// use mastermind::display::ColorChoice;
+    ///
+    /// assert_eq!("never".parse::<ColorChoice>().unwrap(), ColorChoice::Never);
+    /// assert!("sometimes".parse::<ColorChoice>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [ColorChoice::Auto, ColorChoice::Always, ColorChoice::Never]
+            .into_iter()
+            .find(|choice| choice.name() == s)
+            .ok_or_else(|| {
+                GameError::ParseError(format!(
+                    "unknown color choice '{}', expected one of: auto always never",
+                    s
+                ))
+            })
+    }
+}
+
+/// Whether to paint, as set with `set_color`.
+static COLOR: AtomicBool = AtomicBool::new(true);
+
+/// Turns painting on or off from now on. Without color support it is always
+/// off.
+pub fn set_color(enabled: bool) {
+    COLOR.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether output is painted: on unless turned off with `set_color`,
+/// and never without color support.
+///
+/// ```
+/// use mastermind::display::{color_enabled, dim, set_color};
+///
+/// set_color(true);
+/// assert_eq!(color_enabled(), cfg!(feature = "color"));
+/// assert_eq!(dim("x") != "x", cfg!(feature = "color"));
+/// set_color(false);
+/// assert!(!color_enabled());
+/// assert_eq!(dim("x"), "x");
+/// ```
+pub fn color_enabled() -> bool {
+    cfg!(feature = "color") && COLOR.load(Ordering::Relaxed)
+}
+
+/// Returns `text` dimmed, or as it is when color is off.
+pub fn dim(text: &str) -> String {
+    if color_enabled() {
+        format!("\x1b[2m{}\x1b[22m", text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Returns `text` underlined.
+pub fn underline(text: &str) -> String {
+    format!("\x1b[4m{}\x1b[24m", text)
+}
+
+/// Returns whether the terminal says it shows 24-bit color.
+fn truecolor() -> bool {
+    static TRUECOLOR: OnceLock<bool> = OnceLock::new();
+    *TRUECOLOR.get_or_init(|| {
+        std::env::var("COLORTERM").is_ok_and(|value| value == "truecolor" || value == "24bit")
+    })
+}
+
+/// Returns `text` in `color` of `pegs`, in the current theme.
+pub fn paint_color(color: &Color, pegs: &palette::Palette, text: &str) -> String {
+    theme().peg(color, pegs).foreground(text, truecolor())
+}
+
+/// Returns `text` on a block of `color` of `pegs`, in the current theme.
+pub fn paint_color_block(color: &Color, pegs: &palette::Palette, text: &str) -> String {
+    theme().peg(color, pegs).background(text, truecolor())
+}
+
+/// Returns the color of `pegs` to write text in on a block of `bg` so it
+/// stays readable: `bg`'s complementary color.
+pub fn text_color_for(bg: &Color, pegs: &palette::Palette) -> Color {
+    bg.complementary(pegs)
+}
+
+/// Returns `text` on a block of `color` of `pegs`, written in
+/// `text_color_for(color, pegs)`, in the current theme.
+pub fn paint_labelled_block(color: &Color, pegs: &palette::Palette, text: &str) -> String {
+    let theme = theme();
+    let text = theme
+        .peg(&text_color_for(color, pegs), pegs)
+        .foreground(text, truecolor());
+    theme.peg(color, pegs).background(&text, truecolor())
+}
+
+/// Returns the spec `paint_labelled_block` paints `color` of `pegs` with, for
+/// a `termcolor` writer.
+pub fn labelled_block_spec(color: &Color, pegs: &palette::Palette) -> ColorSpec {
+    let theme = theme();
+    let text = theme.peg(&text_color_for(color, pegs), pegs);
+    let mut spec = ColorSpec::new();
+    spec.set_fg(Some(text.termcolor(truecolor())))
+        .set_bg(Some(theme.peg(color, pegs).termcolor(truecolor())));
+    spec
+}
+
+/// Returns the spec `paint_key` paints the key peg `detail` with, for a
+/// `termcolor` writer.
+pub fn key_spec(detail: &ScoreDetail) -> ColorSpec {
+    let mut spec = ColorSpec::new();
+    spec.set_bg(Some(theme().key(detail).termcolor(truecolor())));
+    spec
+}
+
+/// Returns `text` on the background of the key peg `detail`, in the current
+/// theme.
+pub fn paint_key(detail: &ScoreDetail, text: &str) -> String {
+    theme().key(detail).background(text, truecolor())
+}
+
+/// Returns a line showing every color and key peg in `theme`.
+pub fn swatch(theme: Theme) -> String {
+    let truecolor = truecolor();
+    let colors: Vec<String> = Color::ALL
+        .iter()
+        .map(|color| {
+            theme
+                .color(color)
+                .foreground(&color.letter().to_string(), truecolor)
+        })
+        .collect();
+    let keys: Vec<String> = [
+        ScoreDetail::ColorAndPositionCorrect,
+        ScoreDetail::ColorCorrect,
+        ScoreDetail::Empty,
+    ]
+    .iter()
+    .map(|detail| theme.key(detail).background(" ", truecolor))
+    .collect();
+    format!("{} | {}", colors.join(" "), keys.join(" "))
+}
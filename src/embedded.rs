@@ -0,0 +1,370 @@
+//! The rules of the game without `std`: colors, codes, key pegs and scoring,
+//! kept in fixed-capacity `heapless` collections so nothing is allocated.
+//!
+//! Capacities are const generics, so a `Code<4>` holds up to four pegs, and
+//! no capacity may be over `MAX_LENGTH`. Colors are the eight classic ones,
+//! typed by the same letters as `crate::Color`.
+//!
+//! `crate::Code` keeps its pegs on the heap so it can take any palette and
+//! any length, so the two are separate types, but they score alike: both
+//! count the pegs they have in common with the same function.
+//!
+//! ```
+//! use mastermind::embedded::{Code, Color};
+//!
+//! let hidden: Code<4> = Code::parse("rgby").unwrap();
+//! let score = hidden.score(&Code::parse("rygg").unwrap());
+//! assert_eq!((score.black_count(), score.white_count()), (1, 2));
+//! assert_eq!(score.to_text::<4>(4), "bww-");
+//! assert_eq!(hidden.pegs()[3], Color::from_letter('y').unwrap());
+//! ```
+
+use core::fmt;
+
+use heapless::{String, Vec};
+
+use crate::random::Rng;
+
+/// How many colors there are: the eight classic ones.
+pub const COLOR_COUNT: usize = 8;
+
+/// The most pegs a code may have, as a `Score` keeps its counts in a `u8`.
+/// A `Code` with a larger capacity does not compile.
+///
+/// ```compile_fail
+/// let code = mastermind::embedded::Code::<256>::parse("rgby");
+/// ```
+pub const MAX_LENGTH: usize = u8::MAX as usize;
+
+/// The letter typed for each color, in color order.
+const LETTERS: [char; COLOR_COUNT] = ['r', 'g', 'b', 'y', 'p', 'n', 'w', 'k'];
+
+/// One of the classic colors, by its place in `r g b y p n w k`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Color(u8);
+
+impl Color {
+    /// Returns the color at `index`, if there is one.
+    pub const fn new(index: u8) -> Option<Color> {
+        if (index as usize) < COLOR_COUNT {
+            Some(Color(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn index(self) -> usize {
+        usize::from(self.0)
+    }
+
+    /// Returns the letter used to type this color.
+    pub fn letter(self) -> char {
+        LETTERS[self.index()]
+    }
+
+    /// Returns the color typed as `c`, if any.
+    pub fn from_letter(c: char) -> Option<Color> {
+        LETTERS
+            .iter()
+            .position(|letter| *letter == c)
+            .map(|i| Color(i as u8))
+    }
+}
+
+/// A key peg: black for the right color in the right place, white for the
+/// right color in the wrong place.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Key {
+    Black,
+    White,
+}
+
+impl Key {
+    /// Returns the letter the key peg is written as.
+    pub fn letter(self) -> char {
+        match self {
+            Key::Black => 'b',
+            Key::White => 'w',
+        }
+    }
+}
+
+/// The key pegs a guess earned.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Score {
+    blacks: u8,
+    whites: u8,
+}
+
+impl Score {
+    pub fn black_count(self) -> usize {
+        usize::from(self.blacks)
+    }
+
+    pub fn white_count(self) -> usize {
+        usize::from(self.whites)
+    }
+
+    /// Returns whether the score is all black for a code of `length` pegs.
+    pub fn wins(self, length: usize) -> bool {
+        self.black_count() == length
+    }
+
+    /// Returns the key pegs, blacks first, keeping no more than `N`.
+    pub fn keys<const N: usize>(self) -> Vec<Key, N> {
+        let blacks = core::iter::repeat_n(Key::Black, self.black_count());
+        let whites = core::iter::repeat_n(Key::White, self.white_count());
+        blacks.chain(whites).take(N).collect()
+    }
+
+    /// Writes the score as one letter per peg of a `length`-peg code: `b`s,
+    /// then `w`s, then `-` for pegs that earned nothing. Stops at `N` bytes.
+    pub fn to_text<const N: usize>(self, length: usize) -> String<N> {
+        let mut text = String::new();
+        let keys = self.keys::<N>();
+        let letters = keys
+            .iter()
+            .map(|key| key.letter())
+            .chain(core::iter::repeat('-'))
+            .take(length);
+        for letter in letters {
+            if text.push(letter).is_err() {
+                break;
+            }
+        }
+        text
+    }
+}
+
+/// Why a code could not be made.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CodeError {
+    /// The code has no pegs.
+    Empty,
+    /// The code has more pegs than its capacity.
+    TooLong { capacity: usize },
+    /// A character that names no color, counting from 0.
+    InvalidCharacter { position: usize, found: char },
+}
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodeError::Empty => write!(f, "a code needs at least one peg"),
+            CodeError::TooLong { capacity } => {
+                write!(f, "a code can have at most {} pegs", capacity)
+            }
+            CodeError::InvalidCharacter { position, found } => {
+                write!(f, "invalid character '{}' at position {}", found, position)
+            }
+        }
+    }
+}
+
+/// A code of up to `N` pegs.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Code<const N: usize> {
+    pegs: Vec<Color, N>,
+}
+
+impl<const N: usize> Code<N> {
+    /// Refuses, when a code is made, a capacity too large to score.
+    const FITS: () = assert!(N <= MAX_LENGTH, "a code can have at most 255 pegs");
+
+    /// Makes a code of `pegs`.
+    pub fn new(pegs: &[Color]) -> Result<Self, CodeError> {
+        let () = Self::FITS;
+        if pegs.is_empty() {
+            return Err(CodeError::Empty);
+        }
+        let pegs = Vec::from_slice(pegs).map_err(|_| CodeError::TooLong { capacity: N })?;
+        Ok(Code { pegs })
+    }
+
+    /// Parses a code written as color letters, skipping whitespace,
+    /// parentheses and commas between them, as `crate::Code` does.
+    ///
+    /// ```
+    /// use mastermind::embedded::{Code, CodeError};
+    ///
+    /// assert_eq!(Code::<4>::parse("(r, g, b, y)"), Code::parse("rgby"));
+    /// assert_eq!(Code::<4>::parse("rgbx"), Err(CodeError::InvalidCharacter { position: 3, found: 'x' }));
+    /// assert_eq!(Code::<4>::parse("rgbyr"), Err(CodeError::TooLong { capacity: 4 }));
+    /// assert_eq!(Code::<4>::parse(" "), Err(CodeError::Empty));
+    /// ```
+    pub fn parse(s: &str) -> Result<Self, CodeError> {
+        let () = Self::FITS;
+        let mut pegs = Vec::new();
+        for (position, c) in s.chars().enumerate() {
+            if c.is_whitespace() || matches!(c, '(' | ')' | ',') {
+                continue;
+            }
+            let color =
+                Color::from_letter(c).ok_or(CodeError::InvalidCharacter { position, found: c })?;
+            pegs.push(color)
+                .map_err(|_| CodeError::TooLong { capacity: N })?;
+        }
+        if pegs.is_empty() {
+            return Err(CodeError::Empty);
+        }
+        Ok(Code { pegs })
+    }
+
+    /// Returns the `index`th of the codes of `length` pegs drawn from the
+    /// first `colors` colors, counting with the last peg changing fastest, the
+    /// order `crate::LazyCodeSpace` uses. `None` past the last code, or if
+    /// the code would not fit.
+    ///
+    /// ```
+    /// use mastermind::embedded::Code;
+    ///
+    /// let codes = (0..).map_while(|i| Code::<4>::from_index(i, 4, 4));
+    /// assert_eq!(codes.clone().count(), 256);
+    /// assert_eq!(codes.clone().nth(1), Code::parse("rrrg").ok());
+    /// assert_eq!(codes.last(), Code::parse("yyyy").ok());
+    /// assert_eq!(Code::<4>::from_index(0, 4, 5), None);
+    /// ```
+    pub fn from_index(index: usize, colors: usize, length: usize) -> Option<Self> {
+        let () = Self::FITS;
+        if !(1..=COLOR_COUNT).contains(&colors) || length == 0 || length > N {
+            return None;
+        }
+        let mut pegs: Vec<Color, N> = Vec::new();
+        let mut rest = index;
+        for _ in 0..length {
+            pegs.push(Color((rest % colors) as u8)).ok()?;
+            rest /= colors;
+        }
+        if rest != 0 {
+            return None;
+        }
+        pegs.reverse();
+        Some(Code { pegs })
+    }
+
+    /// Picks a code of `length` pegs from the first `colors` colors.
+    ///
+    /// # Panics
+    ///
+    /// If `colors` is not from 1 to `COLOR_COUNT`, or `length` is not from 1
+    /// to `N`.
+    pub fn random(rng: &mut Rng, colors: usize, length: usize) -> Self {
+        let () = Self::FITS;
+        assert!((1..=COLOR_COUNT).contains(&colors), "no such colors");
+        assert!((1..=N).contains(&length), "the code does not fit");
+        let pegs = (0..length)
+            .map(|_| Color(rng.below(colors) as u8))
+            .collect();
+        Code { pegs }
+    }
+
+    pub fn pegs(&self) -> &[Color] {
+        &self.pegs
+    }
+
+    pub fn len(&self) -> usize {
+        self.pegs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pegs.is_empty()
+    }
+
+    /// Scores `other` against this code, counting a repeated color as white
+    /// no more often than both codes have it.
+    ///
+    /// ```
+    /// use mastermind::embedded::Code;
+    ///
+    /// let code = |s| Code::<4>::parse(s).unwrap();
+    /// let score = code("rgby").score(&code("rrrr"));
+    /// assert_eq!((score.black_count(), score.white_count()), (1, 0));
+    /// assert!(code("rgby").score(&code("rgby")).wins(4));
+    /// ```
+    pub fn score(&self, other: &Self) -> Score {
+        let pairs = || self.pegs.iter().zip(other.pegs.iter());
+        let blacks = pairs().filter(|(a, b)| a == b).count();
+        let common = common_pegs::<COLOR_COUNT>(pairs().map(|(a, b)| (a.index(), b.index())));
+        // No code is longer than `MAX_LENGTH`, so both counts fit.
+        Score {
+            blacks: blacks as u8,
+            whites: (common - blacks) as u8,
+        }
+    }
+}
+
+/// Counts the pegs two codes have in common when position is ignored, given
+/// the value of each code's peg at every position, with room for `N` values. A
+/// repeated value counts no more often than both codes have it.
+pub(crate) fn common_pegs<const N: usize>(pegs: impl Iterator<Item = (usize, usize)>) -> usize {
+    let mut ours = [0usize; N];
+    let mut theirs = [0usize; N];
+    for (a, b) in pegs {
+        ours[a] += 1;
+        theirs[b] += 1;
+    }
+    ours.iter().zip(theirs.iter()).map(|(a, b)| *a.min(b)).sum()
+}
+
+impl<const N: usize> fmt::Display for Code<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for peg in &self.pegs {
+            write!(f, "{}", peg.letter())?;
+        }
+        Ok(())
+    }
+}
+
+/// The same pegs as a `crate::Code`, for handing a game over to the full
+/// crate.
+///
+/// ```
+/// use mastermind::embedded::{Code, MAX_LENGTH};
+/// use mastermind::Rng;
+///
+/// fn agree<const N: usize>(a: &Code<N>, b: &Code<N>) {
+///     let ours = a.score(b);
+///     let theirs = mastermind::Code::from(a).score(&mastermind::Code::from(b));
+///     assert_eq!(ours.black_count(), theirs.black_count(), "{} {}", a, b);
+///     assert_eq!(ours.white_count(), theirs.white_count(), "{} {}", a, b);
+/// }
+///
+/// // Both score every 4-peg, 4-color pair alike.
+/// let codes: Vec<Code<4>> = (0..).map_while(|i| Code::from_index(i, 4, 4)).collect();
+/// for a in &codes {
+///     for b in &codes {
+///         agree(a, b);
+///     }
+/// }
+///
+/// // And longer codes, heavy with repeated colors.
+/// let code = |s| Code::<12>::parse(s).unwrap();
+/// agree(&code("rrrrgggbbbyy"), &code("ggrrrrbyyyyk"));
+/// agree(&code("rrrrrrrrrrrr"), &code("rgrgrgrgrgrg"));
+/// agree(&code("kwnpybgrrgby"), &code("rrrrrrrrrrrr"));
+/// let mut rng = Rng::seeded(11);
+/// for _ in 0..2000 {
+///     let colors = 1 + rng.below(8);
+///     let length = 1 + rng.below(12);
+///     let a = Code::<12>::random(&mut rng, colors, length);
+///     agree(&a, &Code::random(&mut rng, colors, length));
+/// }
+///
+/// // Right up to the longest code there is.
+/// let red = Code::<MAX_LENGTH>::random(&mut rng, 1, MAX_LENGTH);
+/// let mixed = Code::random(&mut rng, 2, MAX_LENGTH);
+/// agree(&red, &red);
+/// agree(&red, &mixed);
+/// assert_eq!(red.score(&red).black_count(), 255);
+/// ```
+#[cfg(feature = "std")]
+impl<const N: usize> From<&Code<N>> for crate::Code {
+    fn from(code: &Code<N>) -> crate::Code {
+        crate::Code::unchecked_new(
+            code.pegs
+                .iter()
+                .map(|peg| crate::Color::ALL[peg.index()].clone())
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+use std::io;
+
+use crate::code::Color;
+
+/// Errors produced while setting up or playing a game.
+///
+/// Errors about typed codes echo the input with a caret under the character
+/// at fault, counting characters rather than bytes.
+///
+/// ```
+/// use mastermind::{Code, GameConfig};
+///
+/// let config = GameConfig::default();
+/// let error = |s: &str| Code::parse(String::from(s), &config).unwrap_err().to_string();
+/// assert_eq!(
+///     error("rgxy"),
+///     "invalid character 'x' at position 3, expected one of: r g b y\n  rgxy\n    ^"
+/// );
+/// assert_eq!(
+///     error("réby"),
+///     "invalid character 'é' at position 2, expected one of: r g b y\n  réby\n   ^"
+/// );
+/// assert_eq!(
+///     error("r, g, b"),
+///     "not enough pegs: expected 4 but found 3\n  r, g, b\n         ^"
+/// );
+/// assert_eq!(
+///     error("rgbyr"),
+///     "too many pegs: expected 4 but found 5\n  rgbyr\n      ^"
+/// );
+/// ```
+#[derive(Debug)]
+pub enum GameError {
+    ParseError(String),
+    /// A character that is not valid at `position` (counted in characters) of `input`.
+    InvalidCharacter {
+        input: String,
+        position: usize,
+        found: char,
+        expected: Vec<char>,
+    },
+    /// A code with `found` pegs where `expected` were needed; `position` is where
+    /// the first missing or extra peg is in `input`.
+    WrongLength {
+        input: String,
+        position: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A line of input longer than `limit` bytes.
+    LineTooLong {
+        limit: usize,
+    },
+    /// A code that uses `color`, called `name` in the game's palette, more
+    /// than once where repeats are not allowed.
+    RepeatedColor {
+        color: Color,
+        name: String,
+    },
+    /// A color that a relabeling mapping gives no replacement for.
+    UnmappedColor(Color),
+    /// An environment variable that was expected to hold a code is not set.
+    MissingEnvVar(String),
+    /// A challenge code that is mistyped, truncated or otherwise unreadable.
+    InvalidChallenge,
+    /// A game setting that is out of range or does not fit with the others.
+    InvalidSetting {
+        setting: &'static str,
+        reason: String,
+    },
+    Io(io::Error),
+}
+
+/// Echoes `input` with a caret under the character at `position`.
+fn write_caret(f: &mut fmt::Formatter, input: &str, position: usize) -> fmt::Result {
+    if input.is_empty() {
+        return Ok(());
+    }
+    write!(f, "\n  {}\n  {}^", input, " ".repeat(position))
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::ParseError(msg) => write!(f, "parse error: {}", msg),
+            GameError::InvalidCharacter {
+                input,
+                position,
+                found,
+                expected,
+            } => {
+                let expected: Vec<String> = expected.iter().map(char::to_string).collect();
+                write!(
+                    f,
+                    "invalid character '{}' at position {}, expected one of: {}",
+                    found,
+                    position + 1,
+                    expected.join(" ")
+                )?;
+                write_caret(f, input, *position)
+            }
+            GameError::WrongLength {
+                input,
+                position,
+                expected,
+                found,
+            } => {
+                let problem = if found < expected {
+                    "not enough"
+                } else {
+                    "too many"
+                };
+                write!(
+                    f,
+                    "{} pegs: expected {} but found {}",
+                    problem, expected, found
+                )?;
+                write_caret(f, input, *position)
+            }
+            GameError::LineTooLong { limit } => {
+                write!(f, "input line is longer than {} bytes", limit)
+            }
+            GameError::RepeatedColor { name, .. } => {
+                write!(f, "{} is used more than once", name)
+            }
+            GameError::UnmappedColor(color) => {
+                write!(f, "no replacement is given for {}", color.name())
+            }
+            GameError::MissingEnvVar(name) => {
+                write!(f, "environment variable ${} is not set", name)
+            }
+            GameError::InvalidChallenge => write!(f, "invalid challenge code"),
+            GameError::InvalidSetting { setting, reason } => {
+                write!(f, "invalid {}: {}", setting, reason)
+            }
+            GameError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for GameError {
+    fn from(e: serde_json::Error) -> Self {
+        GameError::ParseError(e.to_string())
+    }
+}
@@ -0,0 +1,510 @@
+//! A log of everything that happens during a game, for tools that analyse play.
+
+use std::cell::RefCell;
+use std::io;
+#[cfg(feature = "serde")]
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::board::{Board, Round};
+use crate::code::Code;
+use crate::config::GameConfig;
+use crate::deduction::CandidateSet;
+use crate::error::GameError;
+use crate::history::{GameSummary, Outcome};
+use crate::score::Score;
+use crate::solver::Solver;
+
+/// Something that happened during a game. Logs name each kind of event in
+/// an `"event"` field beside its details.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "event", rename_all = "snake_case")
+)]
+pub enum GameEvent {
+    /// A game began; `seed` is set when the hidden code was chosen at random.
+    GameStarted {
+        config: GameConfig,
+        hidden_code: Code,
+        #[cfg_attr(feature = "serde", serde(with = "seed_string"))]
+        seed: Option<u64>,
+    },
+    /// The player typed `input`, which parsed as `guess`.
+    GuessSubmitted {
+        input: String,
+        guess: Code,
+    },
+    /// The player typed `input`, which was not a valid guess.
+    GuessRejected {
+        input: String,
+        reason: String,
+    },
+    /// `guess` was scored against the hidden code.
+    ScoreComputed {
+        guess: Code,
+        score: Score,
+    },
+    GameOver {
+        outcome: Outcome,
+        guesses: usize,
+    },
+}
+
+/// Seeds are written as strings, as JSON readers that hold every number as
+/// a double would round the larger ones.
+#[cfg(feature = "serde")]
+mod seed_string {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(seed: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+        match seed {
+            Some(seed) => serializer.collect_str(seed),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u64>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|seed| {
+                seed.parse()
+                    .map_err(|_| serde::de::Error::custom("\"seed\" must be a string of digits"))
+            })
+            .transpose()
+    }
+}
+
+/// An event with its place in the log: `sequence` counts from 1 within a game
+/// and `timestamp_ms` is milliseconds since the Unix epoch.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventRecord {
+    pub sequence: u64,
+    pub timestamp_ms: u64,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub event: GameEvent,
+}
+
+impl EventRecord {
+    /// Stamps `event` with `sequence` and the current time.
+    pub fn new(sequence: u64, event: GameEvent) -> Self {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        Self {
+            sequence,
+            timestamp_ms,
+            event,
+        }
+    }
+}
+
+/// Somewhere to send events as they happen.
+pub trait EventSink {
+    fn emit(&mut self, record: &EventRecord) -> io::Result<()>;
+}
+
+/// Writes each event as one line of JSON.
+#[cfg(feature = "serde")]
+pub struct JsonLinesSink<W> {
+    writer: W,
+}
+
+#[cfg(feature = "serde")]
+impl<W: Write> JsonLinesSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<W: Write> EventSink for JsonLinesSink<W> {
+    fn emit(&mut self, record: &EventRecord) -> io::Result<()> {
+        writeln!(self.writer, "{}", serde_json::to_string(record)?)?;
+        self.writer.flush()
+    }
+}
+
+/// Keeps events in memory; clones share the same list, so one can be handed to
+/// a board while another reads what it logged.
+#[derive(Clone, Default)]
+pub struct MemorySink {
+    records: Rc<RefCell<Vec<EventRecord>>>,
+}
+
+impl MemorySink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn records(&self) -> Vec<EventRecord> {
+        self.records.borrow().clone()
+    }
+}
+
+impl EventSink for MemorySink {
+    fn emit(&mut self, record: &EventRecord) -> io::Result<()> {
+        self.records.borrow_mut().push(record.clone());
+        Ok(())
+    }
+}
+
+/// Where a game stands after a round.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameState {
+    /// Rounds played plus guesses that ran out of time.
+    pub guesses_used: usize,
+    /// `None` when there is no limit on guesses.
+    pub guesses_left: Option<usize>,
+    pub won: bool,
+}
+
+/// Code called back by a `Board` as its game goes on, for programs that embed
+/// one and want to react to play rather than log it.
+///
+/// ```
+/// use mastermind::events::{GameEventHandler, GameState};
+/// use mastermind::{Board, GameConfig, GameSummary, Outcome, Round};
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+/// use std::sync::mpsc::channel;
+///
+/// #[derive(Clone, Default)]
+/// struct CountingHandler {
+///     rounds: Rc<Cell<usize>>,
+///     games: Rc<Cell<usize>>,
+///     outcome: Rc<Cell<Option<Outcome>>>,
+/// }
+///
+/// impl GameEventHandler for CountingHandler {
+///     fn on_round_complete(&self, _round: &Round, _state: &GameState) {
+///         self.rounds.set(self.rounds.get() + 1);
+///     }
+///     fn on_game_over(&self, summary: &GameSummary) {
+///         self.games.set(self.games.get() + 1);
+///         self.outcome.set(Some(summary.outcome));
+///     }
+/// }
+///
+/// let mut board = Board::new(GameConfig::default(), String::from("rgby").try_into().unwrap());
+/// let counter = CountingHandler::default();
+/// board.set_event_handler(Box::new(counter.clone()));
+/// let (sender, lines) = channel();
+/// for line in ["rrgg", "not a code", "rgbb", "rgby"] {
+///     sender.send(Ok(String::from(line))).unwrap();
+///     board.get_input_timeout(&lines, None).unwrap();
+/// }
+/// // The line that was not a code played no round.
+/// assert_eq!(counter.rounds.get(), 3);
+/// assert_eq!(counter.games.get(), 1);
+/// assert_eq!(counter.outcome.get(), Some(Outcome::Won));
+///
+/// // Running out of guesses ends the game too.
+/// let config = GameConfig {
+///     max_guesses: Some(2),
+///     ..GameConfig::default()
+/// };
+/// let mut board = Board::new(config, String::from("rgby").try_into().unwrap());
+/// let counter = CountingHandler::default();
+/// board.set_event_handler(Box::new(counter.clone()));
+/// for line in ["rrgg", "yyyy"] {
+///     sender.send(Ok(String::from(line))).unwrap();
+///     board.get_input_timeout(&lines, None).unwrap();
+/// }
+/// assert_eq!(counter.rounds.get(), 2);
+/// assert_eq!(counter.outcome.get(), Some(Outcome::Lost));
+/// ```
+pub trait GameEventHandler {
+    /// Called after each guess is scored.
+    fn on_round_complete(&self, round: &Round, state: &GameState);
+    /// Called once, when the code is guessed or the guesses run out. The
+    /// summary's `id` is 0, as the game has not been recorded.
+    fn on_game_over(&self, summary: &GameSummary);
+}
+
+/// Writes each callback to stderr as a line of JSON.
+#[cfg(feature = "serde")]
+pub struct LoggingHandler;
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct RoundLog<'a> {
+    round: &'a Round,
+    guesses_used: usize,
+    guesses_left: Option<usize>,
+    won: bool,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct GameOverLog {
+    outcome: Outcome,
+    guesses: usize,
+    seconds: u64,
+}
+
+#[cfg(feature = "serde")]
+impl GameEventHandler for LoggingHandler {
+    fn on_round_complete(&self, round: &Round, state: &GameState) {
+        let log = RoundLog {
+            round,
+            guesses_used: state.guesses_used,
+            guesses_left: state.guesses_left,
+            won: state.won,
+        };
+        if let Ok(json) = serde_json::to_string(&log) {
+            eprintln!("{}", json);
+        }
+    }
+
+    fn on_game_over(&self, summary: &GameSummary) {
+        let log = GameOverLog {
+            outcome: summary.outcome,
+            guesses: summary.guesses,
+            seconds: summary.ended_at.saturating_sub(summary.started_at),
+        };
+        if let Ok(json) = serde_json::to_string(&log) {
+            eprintln!("{}", json);
+        }
+    }
+}
+
+/// One guess a solver made: what it guessed, how many codes were possible
+/// before and after the score came back, and how long it took to decide.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolverStep {
+    pub guess: Code,
+    pub candidates_before: usize,
+    pub candidates_after: usize,
+    pub score: Score,
+    pub decision_time: Duration,
+}
+
+/// A guess chosen but not yet scored.
+struct PendingStep {
+    guess: Code,
+    candidates: CandidateSet,
+    decision_time: Duration,
+}
+
+struct Recording {
+    solver: Box<dyn Solver>,
+    pending: Option<PendingStep>,
+    steps: Vec<SolverStep>,
+}
+
+/// Lets a solver play a board and records each of its steps as the board
+/// reports the round back. Clones share the same recording, so one can be
+/// handed to the board while another plays and reads the steps.
+///
+/// Rounds played some other way, such as typed through `get_input`, are not
+/// the solver's and are not recorded.
+///
+/// ```
+/// use mastermind::{Board, Code, GameConfig, Round, Solver};
+///
+/// // Plays a fixed list of guesses, one per round.
+/// struct Script(Vec<Code>);
+///
+/// impl Solver for Script {
+///     fn next_guess(&mut self, rounds: &[Round]) -> Option<Code> {
+///         self.0.get(rounds.len()).cloned()
+///     }
+///
+///     fn candidates(&self) -> Vec<&Code> {
+///         Vec::new()
+///     }
+/// }
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let script = Script(vec![code("rrgg"), code("rgbb"), code("rgyb"), code("rgby")]);
+/// let (mut board, recorder) =
+///     Board::new(config.clone(), code("rgby")).with_step_recorder(script);
+/// recorder.play(&mut board);
+///
+/// let steps = recorder.steps();
+/// assert_eq!(steps.len(), 4);
+/// assert_eq!(steps[0].guess, code("rrgg"));
+/// assert_eq!(steps[0].candidates_before, 256);
+/// assert_eq!((steps[0].score.black_count(), steps[0].score.white_count()), (1, 1));
+/// for (step, round) in steps.iter().zip(board.rounds()) {
+///     assert_eq!(&step.guess, round.guess());
+///     assert_eq!(&step.score, round.score());
+///     assert!((1..=step.candidates_before).contains(&step.candidates_after));
+/// }
+/// for pair in steps.windows(2) {
+///     assert_eq!(pair[0].candidates_after, pair[1].candidates_before);
+/// }
+/// assert_eq!(steps[3].candidates_after, 1);
+/// assert!(steps.iter().all(|step| step.decision_time < std::time::Duration::from_secs(1)));
+/// assert_eq!(recorder.summary().lines().count(), 5);
+/// ```
+#[derive(Clone)]
+pub struct StepRecorder {
+    recording: Rc<RefCell<Recording>>,
+}
+
+impl StepRecorder {
+    pub fn new(solver: impl Solver + 'static) -> Self {
+        Self {
+            recording: Rc::new(RefCell::new(Recording {
+                solver: Box::new(solver),
+                pending: None,
+                steps: Vec::new(),
+            })),
+        }
+    }
+
+    /// Returns the steps recorded so far, oldest first.
+    pub fn steps(&self) -> Vec<SolverStep> {
+        self.recording.borrow().steps.clone()
+    }
+
+    /// Asks the solver for a guess and plays it on `board`, or returns `None`
+    /// if the solver has no guess to make.
+    pub fn play_round(&self, board: &mut Board) -> Option<Round> {
+        let mut recording = self.recording.borrow_mut();
+        let started = Instant::now();
+        let guess = recording.solver.next_guess(board.rounds())?;
+        recording.pending = Some(PendingStep {
+            guess: guess.clone(),
+            candidates: board.candidates(),
+            decision_time: started.elapsed(),
+        });
+        // The board calls back into this recorder.
+        drop(recording);
+        Some(board.play_guess(guess).clone())
+    }
+
+    /// Plays rounds until the game is won or lost, or the solver gives up.
+    pub fn play(&self, board: &mut Board) {
+        while !board.state().won && board.state().guesses_left != Some(0) {
+            if self.play_round(board).is_none() {
+                break;
+            }
+        }
+    }
+
+    /// Returns the steps as a table, one row per guess.
+    pub fn summary(&self) -> String {
+        let mut table = format!(
+            "{:>5}  {:<16} {:>6} {:>10} {:>10} {:>10}\n",
+            "round", "guess", "score", "before", "after", "time"
+        );
+        for (n, step) in self.recording.borrow().steps.iter().enumerate() {
+            table.push_str(&format!(
+                "{:>5}  {:<16} {:>6} {:>10} {:>10} {:>8.1}ms\n",
+                n + 1,
+                step.guess.to_string(),
+                format!("{}b{}w", step.score.black_count(), step.score.white_count()),
+                step.candidates_before,
+                step.candidates_after,
+                step.decision_time.as_secs_f64() * 1000.0
+            ));
+        }
+        table
+    }
+}
+
+impl GameEventHandler for StepRecorder {
+    fn on_round_complete(&self, round: &Round, _state: &GameState) {
+        let mut recording = self.recording.borrow_mut();
+        let Some(mut pending) = recording.pending.take() else {
+            return;
+        };
+        let candidates_before = pending.candidates.len();
+        pending.candidates.observe(round.guess(), round.score());
+        recording.steps.push(SolverStep {
+            guess: pending.guess,
+            candidates_before,
+            candidates_after: pending.candidates.len(),
+            score: round.score().clone(),
+            decision_time: pending.decision_time,
+        });
+    }
+
+    fn on_game_over(&self, _summary: &GameSummary) {}
+}
+
+/// Reads a log written by `JsonLinesSink`.
+#[cfg(feature = "serde")]
+pub fn read_events<R: BufRead>(reader: R) -> crate::Result<Vec<EventRecord>> {
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            records.push(serde_json::from_str(&line).map_err(GameError::from)?);
+        }
+    }
+    Ok(records)
+}
+
+/// Rebuilds the board a log describes: the game it starts and every guess
+/// scored after that.
+///
+/// ```
+/// use mastermind::events::{replay, GameEvent, MemorySink};
+/// use mastermind::{Board, Code, GameConfig};
+///
+/// let hidden: Code = String::from("rgby").try_into().unwrap();
+/// let mut board = Board::new(GameConfig::default(), hidden.clone());
+/// let sink = MemorySink::new();
+/// board.set_event_sink(Some(Box::new(sink.clone())));
+/// board.emit(GameEvent::GameStarted {
+///     config: GameConfig::default(),
+///     hidden_code: hidden,
+///     seed: None,
+/// });
+/// for guess in ["rrgg", "rgby"] {
+///     board.guess(String::from(guess).try_into().unwrap());
+/// }
+///
+/// let replayed = replay(&sink.records()).unwrap();
+/// assert_eq!(replayed.rounds(), board.rounds());
+///
+/// // The log reads back from its JSON lines form as it was written.
+/// #[cfg(feature = "serde")]
+/// {
+///     use mastermind::events::{read_events, EventSink, JsonLinesSink};
+///
+///     let mut log = Vec::new();
+///     let mut writer = JsonLinesSink::new(&mut log);
+///     for record in sink.records() {
+///         writer.emit(&record).unwrap();
+///     }
+///     assert_eq!(read_events(log.as_slice()).unwrap(), sink.records());
+/// }
+/// ```
+pub fn replay(records: &[EventRecord]) -> Result<Board, GameError> {
+    let mut board: Option<Board> = None;
+    for record in records {
+        match &record.event {
+            GameEvent::GameStarted {
+                config,
+                hidden_code,
+                ..
+            } => board = Some(Board::new(config.clone(), hidden_code.clone())),
+            GameEvent::ScoreComputed { guess, score } => {
+                let board = board.as_mut().ok_or_else(|| {
+                    GameError::ParseError(String::from(
+                        "a guess was scored before the game started",
+                    ))
+                })?;
+                if board.guess(guess.clone()).score() != score {
+                    return Err(GameError::ParseError(format!(
+                        "event {} does not match the hidden code's score",
+                        record.sequence
+                    )));
+                }
+            }
+            _ => {}
+        }
+    }
+    board.ok_or_else(|| GameError::ParseError(String::from("the log has no game in it")))
+}
@@ -0,0 +1,12 @@
+//! Counting the pegs two codes share in place, one peg at a time.
+//!
+//! Builds for x86_64 with SSE4.1 enabled use `exact_sse41.rs` instead.
+
+use crate::code::Color;
+
+/// Returns the number of positions where `ours` and `theirs` hold the same
+/// value.
+pub(crate) fn count_exact_matches(ours: &[Color], theirs: &[Color]) -> usize {
+    let (ours, theirs) = (Color::as_bytes(ours), Color::as_bytes(theirs));
+    ours.iter().zip(theirs).filter(|(a, b)| a == b).count()
+}
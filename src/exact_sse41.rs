@@ -0,0 +1,89 @@
+//! Counting the pegs two codes share in place, 16 pegs at a time with
+//! SSE4.1.
+//!
+//! Only built for x86_64 with the `sse4.1` target feature enabled at compile
+//! time; other builds use `exact_scalar.rs`.
+//!
+//! Pegs are loaded straight from the codes, never copied into a padded
+//! buffer: full runs of 16 are loaded whole, and a shorter tail as its first
+//! and last 8 or 4 pegs side by side, which may overlap. Only positions
+//! that exist are counted, and only once.
+
+use std::arch::x86_64::{
+    __m128i, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_movemask_epi8, _mm_set_epi32, _mm_set_epi64x,
+};
+
+use crate::code::Color;
+
+/// The pegs compared by one instruction.
+const LANES: usize = 16;
+
+/// Returns the number of positions where `ours` and `theirs` hold the same
+/// value.
+#[inline]
+pub(crate) fn count_exact_matches(ours: &[Color], theirs: &[Color]) -> usize {
+    let length = ours.len().min(theirs.len());
+    let (ours, theirs) = (
+        Color::as_bytes(&ours[..length]),
+        Color::as_bytes(&theirs[..length]),
+    );
+    let mut ours = ours.chunks_exact(LANES);
+    let mut theirs = theirs.chunks_exact(LANES);
+    let mut count = 0;
+    for (a, b) in ours.by_ref().zip(theirs.by_ref()) {
+        // SAFETY: each chunk is exactly the 16 bytes read.
+        let (a, b) = unsafe {
+            (
+                _mm_loadu_si128(a.as_ptr() as *const __m128i),
+                _mm_loadu_si128(b.as_ptr() as *const __m128i),
+            )
+        };
+        count += movemask(a, b).count_ones() as usize;
+    }
+    count + count_tail(ours.remainder(), theirs.remainder())
+}
+
+/// Counts the equal bytes of `a` and `b`, which have the same length, fewer
+/// than 16.
+#[inline]
+fn count_tail(a: &[u8], b: &[u8]) -> usize {
+    let length = a.len();
+    let width = match length {
+        8.. => 8,
+        4.. => 4,
+        _ => return a.iter().zip(b).filter(|(a, b)| a == b).count(),
+    };
+    let mask = if width == 8 {
+        movemask(ends64(a), ends64(b))
+    } else {
+        movemask(ends32(a), ends32(b))
+    };
+    // The second `width` bits stand for the last `width` positions; drop the
+    // ones the first `width` bits already counted.
+    let first = (1 << width) - 1;
+    let last = ((1 << (2 * width)) - 1) & !((1 << (3 * width - length)) - 1);
+    (mask & (first | last)).count_ones() as usize
+}
+
+/// Returns the first and last 8 bytes of `bytes`, at least 8 long.
+#[inline]
+fn ends64(bytes: &[u8]) -> __m128i {
+    let word = |at: usize| i64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+    // SAFETY: this module is only built with SSE4.1, and with it SSE2.
+    unsafe { _mm_set_epi64x(word(bytes.len() - 8), word(0)) }
+}
+
+/// Returns the first and last 4 bytes of `bytes`, at least 4 long.
+#[inline]
+fn ends32(bytes: &[u8]) -> __m128i {
+    let word = |at: usize| i32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+    // SAFETY: this module is only built with SSE4.1, and with it SSE2.
+    unsafe { _mm_set_epi32(0, 0, word(bytes.len() - 4), word(0)) }
+}
+
+/// Returns a bit for each of the 16 bytes, set where `a` and `b` are equal.
+#[inline]
+fn movemask(a: __m128i, b: __m128i) -> i32 {
+    // SAFETY: this module is only built with SSE4.1, and with it SSE2.
+    unsafe { _mm_movemask_epi8(_mm_cmpeq_epi8(a, b)) }
+}
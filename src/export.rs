@@ -0,0 +1,135 @@
+//! Writing recorded games out for spreadsheets and other tools.
+
+use std::io::{self, Write};
+
+use crate::date::day_of;
+use crate::history::GameSession;
+
+/// The columns written by `write_csv`, and the keys written by `write_json`.
+pub const COLUMNS: [&str; 12] = [
+    "date",
+    "started_at",
+    "code_length",
+    "color_count",
+    "outcome",
+    "guesses",
+    "adjusted_guesses",
+    "duration_seconds",
+    "hidden_code",
+    "assists",
+    "coach",
+    "cheat",
+];
+
+/// One game's values for `COLUMNS`, in the same order.
+#[derive(serde::Serialize)]
+struct Row {
+    date: String,
+    started_at: u64,
+    code_length: usize,
+    color_count: usize,
+    outcome: &'static str,
+    guesses: usize,
+    adjusted_guesses: f64,
+    duration_seconds: u64,
+    hidden_code: String,
+    assists: bool,
+    coach: bool,
+    cheat: bool,
+}
+
+impl Row {
+    fn of(session: &GameSession) -> Row {
+        Row {
+            date: day_of(session.started_at).to_string(),
+            started_at: session.started_at,
+            code_length: session.config.code_length,
+            color_count: session.config.colors.len(),
+            outcome: session.outcome.name(),
+            guesses: session.guesses(),
+            // Two places are plenty to compare games by.
+            adjusted_guesses: (session.adjusted_guesses() * 100.0).round() / 100.0,
+            duration_seconds: session.ended_at.saturating_sub(session.started_at),
+            hidden_code: session.hidden_code.letters(&session.config.palette),
+            assists: session.config.assists,
+            coach: session.config.coach,
+            cheat: session.config.cheat_mode,
+        }
+    }
+}
+
+/// Writes one CSV row per game, after a header row.
+///
+/// ```
+/// use mastermind::board::SkipReason;
+/// use mastermind::export::{write_csv, COLUMNS};
+/// use mastermind::history::{GameSession, History, Outcome, Variant};
+/// use mastermind::{Code, GameConfig, Round};
+///
+/// let hidden: Code = String::from("rgby").try_into().unwrap();
+/// let miss = Round::from_guess(String::from("rrgg").try_into().unwrap(), &hidden);
+/// let game = |outcome, started_at, guesses| GameSession {
+///     started_at,
+///     ended_at: started_at + 95,
+///     outcome,
+///     variant: Variant::Classic,
+///     config: GameConfig::default(),
+///     hidden_code: hidden.clone(),
+///     rounds: vec![miss.clone(); guesses],
+///     skipped: Vec::new(),
+/// };
+/// let dir = tempfile::tempdir().unwrap();
+/// let history = History::open(&dir.path().join("history.jsonl"));
+/// for session in [
+///     game(Outcome::Won, 0, 3),
+///     game(Outcome::Lost, 86_400, 10),
+///     GameSession {
+///         skipped: vec![(1, SkipReason::Timeout)],
+///         config: GameConfig {
+///             coach: true,
+///             ..GameConfig::default()
+///         },
+///         ..game(Outcome::Won, 2 * 86_400, 4)
+///     },
+/// ] {
+///     history.record(&session).unwrap();
+/// }
+///
+/// let mut csv = Vec::new();
+/// write_csv(&history.sessions().unwrap(), &mut csv).unwrap();
+/// let mut reader = csv::Reader::from_reader(csv.as_slice());
+/// assert_eq!(reader.headers().unwrap(), COLUMNS.as_slice());
+/// let rows: Vec<csv::StringRecord> = reader.records().collect::<Result<_, _>>().unwrap();
+/// let column = |name| COLUMNS.iter().position(|&c| c == name).unwrap();
+/// let sum = |name| rows.iter().map(|row| row[column(name)].parse::<u64>().unwrap()).sum::<u64>();
+///
+/// // What was read back adds up to the history's totals.
+/// let totals = history.totals().unwrap();
+/// assert_eq!(rows.len(), totals.games);
+/// let wins = rows.iter().filter(|row| &row[column("outcome")] == "won").count();
+/// assert_eq!(wins, totals.wins);
+/// assert_eq!(sum("guesses"), totals.guesses as u64);
+/// assert_eq!(sum("duration_seconds"), totals.seconds);
+/// assert_eq!(
+///     &rows[2],
+///     vec!["1970-01-03", "172800", "4", "4", "won", "5", "5.0", "95", "rgby", "true", "true", "false"]
+/// );
+/// ```
+pub fn write_csv<W: Write>(sessions: &[GameSession], writer: &mut W) -> io::Result<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+    for session in sessions {
+        csv.serialize(Row::of(session))?;
+    }
+    // With no rows to take the header from, it is written by hand.
+    if sessions.is_empty() {
+        csv.write_record(COLUMNS)?;
+    }
+    csv.flush()
+}
+
+/// Writes the same data as `write_csv` as a JSON array of objects, with
+/// numbers as numbers.
+pub fn write_json<W: Write>(sessions: &[GameSession], writer: &mut W) -> io::Result<()> {
+    let games: Vec<Row> = sessions.iter().map(Row::of).collect();
+    writeln!(writer, "{}", serde_json::to_string(&games)?)
+}
@@ -0,0 +1,157 @@
+use std::fmt::Write;
+
+use crate::board::Round;
+use crate::error::GameError;
+use crate::palette::Palette;
+
+#[derive(Clone, Copy)]
+enum Field {
+    Round,
+    Guess,
+    Black,
+    White,
+    ScoreCompact,
+}
+
+/// The placeholders a template may use.
+const FIELDS: [(&str, Field); 5] = [
+    ("round", Field::Round),
+    ("guess", Field::Guess),
+    ("black", Field::Black),
+    ("white", Field::White),
+    ("score_compact", Field::ScoreCompact),
+];
+
+enum Segment {
+    Literal(String),
+    Field(Field),
+}
+
+/// Renders rounds through a user supplied template such as
+/// `"{round},{guess},{black},{white}"`.
+///
+/// Placeholders are `{round}`, `{guess}`, `{black}`, `{white}` and
+/// `{score_compact}`; `{{` and `}}` produce literal braces.
+pub struct OutputFormatter {
+    segments: Vec<Segment>,
+}
+
+impl OutputFormatter {
+    /// Parses `template`, rejecting unknown placeholders and unbalanced braces.
+    ///
+    /// ```
+    /// use mastermind::OutputFormatter;
+    ///
+    /// assert!(OutputFormatter::new("{round} {{literal}}").is_ok());
+    /// let error = |template| OutputFormatter::new(template).err().unwrap().to_string();
+    /// assert_eq!(
+    ///     error("{round},{colour}"),
+    ///     "parse error: unknown placeholder {colour} in format, expected one of: \
+    ///      {round} {guess} {black} {white} {score_compact}"
+    /// );
+    /// assert_eq!(error("{round"), "parse error: unclosed placeholder {round in format");
+    /// assert_eq!(
+    ///     error("round}"),
+    ///     "parse error: unmatched '}' in format, use '}}' for a literal brace"
+    /// );
+    /// ```
+    pub fn new(template: &str) -> Result<OutputFormatter, GameError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => {
+                                return Err(GameError::ParseError(format!(
+                                    "unclosed placeholder {{{} in format",
+                                    name
+                                )))
+                            }
+                        }
+                    }
+                    let (_, field) = FIELDS.iter().find(|(f, _)| *f == name).ok_or_else(|| {
+                        GameError::ParseError(format!(
+                            "unknown placeholder {{{}}} in format, expected one of: {}",
+                            name,
+                            FIELDS.map(|(f, _)| format!("{{{}}}", f)).join(" ")
+                        ))
+                    })?;
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Field(*field));
+                }
+                '}' => {
+                    return Err(GameError::ParseError(String::from(
+                        "unmatched '}' in format, use '}}' for a literal brace",
+                    )))
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(OutputFormatter { segments })
+    }
+
+    /// Renders `round`, which was the `number`th round of its game, writing
+    /// the guess in `palette`'s letters.
+    ///
+    /// ```
+    /// use mastermind::{Board, Code, GameConfig, OutputFormatter};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let mut board = Board::new(config.clone(), code("rgby"));
+    /// for guess in ["rrgg", "ybgr", "rgby"] {
+    ///     board.guess(code(guess));
+    /// }
+    /// let render = |template| {
+    ///     let formatter = OutputFormatter::new(template).unwrap();
+    ///     let lines: Vec<String> = board
+    ///         .rounds()
+    ///         .iter()
+    ///         .enumerate()
+    ///         .map(|(i, round)| formatter.format_round(round, i + 1, &config.palette))
+    ///         .collect();
+    ///     lines.join("\n")
+    /// };
+    /// assert_eq!(
+    ///     render("{round},{guess},{black},{white}"),
+    ///     "1,rrgg,1,1\n2,ybgr,0,4\n3,rgby,4,0"
+    /// );
+    /// assert_eq!(render("{{{score_compact}}}"), "{1b1w}\n{0b4w}\n{4b0w}");
+    /// ```
+    pub fn format_round(&self, round: &Round, number: usize, palette: &Palette) -> String {
+        let score = round.score();
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(s) => out.push_str(s),
+                Segment::Field(Field::Round) => write!(out, "{}", number).unwrap(),
+                Segment::Field(Field::Guess) => out.push_str(&round.guess().letters(palette)),
+                Segment::Field(Field::Black) => write!(out, "{}", score.black_count()).unwrap(),
+                Segment::Field(Field::White) => write!(out, "{}", score.white_count()).unwrap(),
+                Segment::Field(Field::ScoreCompact) => {
+                    write!(out, "{}b{}w", score.black_count(), score.white_count()).unwrap()
+                }
+            }
+        }
+        out
+    }
+}
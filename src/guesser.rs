@@ -0,0 +1,184 @@
+//! The computer guesses a code the player is thinking of, with the player
+//! scoring each guess by hand.
+
+use std::io::{BufRead, Write};
+
+use crate::board::Round;
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::input::prompt;
+use crate::score::Score;
+use crate::solver::{find_contradiction, Solver};
+use crate::Result;
+
+/// How a game in guesser mode ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GuesserOutcome {
+    /// The computer found the code with this many guesses.
+    Solved(usize),
+    /// The player stopped scoring, either by giving up on a contradiction or by
+    /// closing input.
+    GaveUp,
+}
+
+/// Plays guesser mode: guesses from `new_solver` are written to `output` and the
+/// player's scores, typed as black and white peg counts like `2 1`, are read
+/// from `input`.
+///
+/// When the scores stop fitting any code, the smallest set of rounds that
+/// conflict is named and the player may correct one of them. Play then resumes
+/// with a fresh solver from `new_solver`.
+///
+/// ```
+/// use mastermind::guesser::{run_guesser, GuesserOutcome};
+/// use mastermind::{GameConfig, MostPartsSolver};
+///
+/// let config = GameConfig::default();
+/// // The player is thinking of "rgby" but wrongly scores the second guess,
+/// // "rgry", as no pegs at all, then corrects it to 3 blacks.
+/// let script = "1 2\n0 0\n2\n3 0\n1 1\n4 0\n";
+/// let mut output = Vec::new();
+/// let outcome = run_guesser(&mut script.as_bytes(), &mut output, &config, || {
+///     MostPartsSolver::new(&config)
+/// })
+/// .unwrap();
+///
+/// let output = String::from_utf8(output).unwrap();
+/// assert!(output.contains("rounds 1 and 2 cannot both be correct for any code"));
+/// assert_eq!(outcome, GuesserOutcome::Solved(4));
+/// ```
+pub fn run_guesser<R, W, S, F>(
+    input: &mut R,
+    output: &mut W,
+    config: &GameConfig,
+    mut new_solver: F,
+) -> Result<GuesserOutcome>
+where
+    R: BufRead,
+    W: Write,
+    S: Solver,
+    F: FnMut() -> S,
+{
+    let mut solver = new_solver();
+    let mut rounds: Vec<Round> = Vec::new();
+    loop {
+        let Some(guess) = solver.next_guess(&rounds) else {
+            let culprits =
+                find_contradiction(config, &rounds).unwrap_or_else(|| (0..rounds.len()).collect());
+            writeln!(output, "{}", describe_contradiction(&culprits))?;
+            let Some(index) = pick_round(input, output, &culprits)? else {
+                return Ok(GuesserOutcome::GaveUp);
+            };
+            let guess = rounds[index].guess().clone();
+            let question = format!(
+                "score for round {} ({}): ",
+                index + 1,
+                guess.display(&config.palette)
+            );
+            let Some(score) = ask_score(input, output, config, &question)? else {
+                return Ok(GuesserOutcome::GaveUp);
+            };
+            rounds[index] = Round::new(guess, score);
+            solver = new_solver();
+            continue;
+        };
+
+        let question = format!(
+            "guess {}: {}\nscore: ",
+            rounds.len() + 1,
+            guess.display(&config.palette)
+        );
+        let Some(score) = ask_score(input, output, config, &question)? else {
+            return Ok(GuesserOutcome::GaveUp);
+        };
+        let solved = score.is_win(config.code_length);
+        rounds.push(Round::new(guess, score));
+        if solved {
+            return Ok(GuesserOutcome::Solved(rounds.len()));
+        }
+    }
+}
+
+/// Explains that the rounds at the zero-based `culprits` cannot all be right.
+fn describe_contradiction(culprits: &[usize]) -> String {
+    let numbers: Vec<String> = culprits.iter().map(|i| (i + 1).to_string()).collect();
+    match numbers.as_slice() {
+        [] => String::from("no code is possible - please re-check the scores"),
+        [one] => format!(
+            "round {} cannot be correct for any code - please re-check it",
+            one
+        ),
+        [first, second] => format!(
+            "rounds {} and {} cannot both be correct for any code - please re-check them",
+            first, second
+        ),
+        [rest @ .., last] => format!(
+            "rounds {} and {} cannot all be correct for any code - please re-check them",
+            rest.join(", "),
+            last
+        ),
+    }
+}
+
+/// Asks which of the `culprits` to re-score, or `None` to give up.
+fn pick_round<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    culprits: &[usize],
+) -> Result<Option<usize>> {
+    let numbers: Vec<String> = culprits.iter().map(|i| (i + 1).to_string()).collect();
+    let question = format!(
+        "re-enter the score for round [{}, blank to give up]: ",
+        numbers.join(", ")
+    );
+    loop {
+        let Some(line) = prompt(input, output, &question)? else {
+            return Ok(None);
+        };
+        if line.is_empty() {
+            return Ok(None);
+        }
+        match line.parse::<usize>() {
+            Ok(n) if n > 0 && culprits.contains(&(n - 1)) => return Ok(Some(n - 1)),
+            _ => writeln!(output, "please pick one of: {}", numbers.join(", "))?,
+        }
+    }
+}
+
+/// Asks for a score until a valid one is typed, or `None` at the end of input.
+fn ask_score<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    config: &GameConfig,
+    question: &str,
+) -> Result<Option<Score>> {
+    loop {
+        let Some(line) = prompt(input, output, question)? else {
+            return Ok(None);
+        };
+        match parse_score(&line, config.code_length) {
+            Ok(score) => return Ok(Some(score)),
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+    }
+}
+
+/// Parses black and white peg counts separated by whitespace or a comma.
+fn parse_score(line: &str, length: usize) -> std::result::Result<Score, GameError> {
+    let counts: Vec<&str> = line
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .collect();
+    let invalid = || {
+        GameError::ParseError(format!(
+            "expected black and white peg counts like \"2 1\" but found \"{}\"",
+            line
+        ))
+    };
+    let [blacks, whites] = counts.as_slice() else {
+        return Err(invalid());
+    };
+    let blacks = blacks.parse().map_err(|_| invalid())?;
+    let whites = whites.parse().map_err(|_| invalid())?;
+    Score::from_counts(blacks, whites, length)
+}
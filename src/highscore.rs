@@ -0,0 +1,255 @@
+//! Personal best results, kept per code length and number of colors.
+
+#[cfg(feature = "serde")]
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+
+use crate::config::GameConfig;
+#[cfg(feature = "serde")]
+use crate::error::GameError;
+use crate::paths::profile_dir;
+use crate::timetrial::TimeTrialResult;
+#[cfg(feature = "serde")]
+use crate::Result;
+
+/// A winning game worth remembering.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HighScore {
+    pub name: String,
+    pub guesses: u32,
+    pub code_length: usize,
+    pub color_count: usize,
+    pub date: NaiveDate,
+}
+
+/// Every recorded high score, in the order they were set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct HighScoreBoard {
+    scores: Vec<HighScore>,
+}
+
+impl HighScoreBoard {
+    /// Returns `highscores.json` in `player`'s profile directory.
+    pub fn default_path(player: Option<&str>) -> Option<PathBuf> {
+        Some(profile_dir(player)?.join("highscores.json"))
+    }
+
+    /// Reads the board saved at `path`; a missing file is an empty board.
+    #[cfg(feature = "serde")]
+    pub fn load(path: &Path) -> Result<HighScoreBoard> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(serde_json::from_str(&text).map_err(GameError::from)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HighScoreBoard::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes the board to `path`, creating its directory if needed.
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use mastermind::{HighScore, HighScoreBoard};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("scores/highscores.json");
+    /// assert_eq!(HighScoreBoard::load(&path).unwrap(), HighScoreBoard::default());
+    ///
+    /// let mut board = HighScoreBoard::default();
+    /// board.add(HighScore {
+    ///     name: String::from("dana"),
+    ///     guesses: 4,
+    ///     code_length: 4,
+    ///     color_count: 6,
+    ///     date: NaiveDate::from_ymd_opt(2024, 5, 17).unwrap(),
+    /// });
+    /// board.save(&path).unwrap();
+    /// assert_eq!(HighScoreBoard::load(&path).unwrap(), board);
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn scores(&self) -> &[HighScore] {
+        &self.scores
+    }
+
+    pub fn add(&mut self, score: HighScore) {
+        self.scores.push(score);
+    }
+
+    /// Returns the `n` fewest-guess scores for codes of `code_length` pegs in
+    /// `color_count` colors, earliest first among equals.
+    pub fn top(&self, code_length: usize, color_count: usize, n: usize) -> Vec<&HighScore> {
+        let mut scores: Vec<&HighScore> = self
+            .scores
+            .iter()
+            .filter(|s| s.code_length == code_length && s.color_count == color_count)
+            .collect();
+        scores.sort_by_key(|s| (s.guesses, s.date));
+        scores.truncate(n);
+        scores
+    }
+
+    /// Returns whether winning in `guesses` would beat every score for `config`.
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use mastermind::{GameConfig, HighScore, HighScoreBoard};
+    ///
+    /// let config = GameConfig::default();
+    /// let mut board = HighScoreBoard::default();
+    /// assert!(board.is_new_best(&config, 10));
+    /// board.add(HighScore {
+    ///     name: String::from("dana"),
+    ///     guesses: 5,
+    ///     code_length: config.code_length,
+    ///     color_count: config.colors.len(),
+    ///     date: NaiveDate::from_ymd_opt(2024, 5, 17).unwrap(),
+    /// });
+    /// assert!(board.is_new_best(&config, 4));
+    /// // Tying the best is not a new best.
+    /// assert!(!board.is_new_best(&config, 5));
+    /// assert!(!board.is_new_best(&config, 6));
+    /// // Scores for other settings don't count.
+    /// let other = GameConfig { code_length: 5, ..config };
+    /// assert!(board.is_new_best(&other, 6));
+    /// ```
+    pub fn is_new_best(&self, config: &GameConfig, guesses: u32) -> bool {
+        self.top(config.code_length, config.colors.len(), 1)
+            .first()
+            .is_none_or(|best| guesses < best.guesses)
+    }
+
+    /// Returns each distinct `(code_length, color_count)` with a score, sorted.
+    pub fn configs(&self) -> Vec<(usize, usize)> {
+        let mut configs: Vec<_> = self
+            .scores
+            .iter()
+            .map(|s| (s.code_length, s.color_count))
+            .collect();
+        configs.sort();
+        configs.dedup();
+        configs
+    }
+}
+
+/// A time trial worth remembering; `seconds` is how long the trial lasted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeTrialScore {
+    pub name: String,
+    pub solved: usize,
+    pub guesses: usize,
+    pub seconds: u64,
+    pub code_length: usize,
+    pub color_count: usize,
+    pub date: NaiveDate,
+}
+
+/// Every recorded time trial, kept apart from the normal high scores.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct TimeTrialBoard {
+    scores: Vec<TimeTrialScore>,
+}
+
+impl TimeTrialBoard {
+    /// Returns `timetrials.json` in `player`'s profile directory.
+    pub fn default_path(player: Option<&str>) -> Option<PathBuf> {
+        Some(profile_dir(player)?.join("timetrials.json"))
+    }
+
+    /// Reads the board saved at `path`; a missing file is an empty board.
+    #[cfg(feature = "serde")]
+    pub fn load(path: &Path) -> Result<TimeTrialBoard> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(serde_json::from_str(&text).map_err(GameError::from)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(TimeTrialBoard::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes the board to `path`, creating its directory if needed.
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn scores(&self) -> &[TimeTrialScore] {
+        &self.scores
+    }
+
+    pub fn add(&mut self, score: TimeTrialScore) {
+        self.scores.push(score);
+    }
+
+    /// Returns the `n` best trials of `seconds` on codes of `code_length` pegs
+    /// in `color_count` colors: most codes solved, then fewest guesses, then
+    /// earliest.
+    pub fn top(
+        &self,
+        code_length: usize,
+        color_count: usize,
+        seconds: u64,
+        n: usize,
+    ) -> Vec<&TimeTrialScore> {
+        let mut scores: Vec<&TimeTrialScore> = self
+            .scores
+            .iter()
+            .filter(|s| {
+                s.code_length == code_length && s.color_count == color_count && s.seconds == seconds
+            })
+            .collect();
+        scores.sort_by_key(|s| (std::cmp::Reverse(s.solved), s.guesses, s.date));
+        scores.truncate(n);
+        scores
+    }
+
+    /// Returns whether `result`, played under `config`, would beat every
+    /// trial of the same length. A trial that solved nothing never does.
+    pub fn is_new_best(&self, config: &GameConfig, result: &TimeTrialResult) -> bool {
+        let seconds = result.limit.as_secs();
+        result.solved > 0
+            && self
+                .top(config.code_length, config.colors.len(), seconds, 1)
+                .first()
+                .is_none_or(|best| {
+                    (result.solved, std::cmp::Reverse(result.guesses))
+                        > (best.solved, std::cmp::Reverse(best.guesses))
+                })
+    }
+
+    /// Returns each distinct `(code_length, color_count, seconds)` with a
+    /// trial, sorted.
+    pub fn configs(&self) -> Vec<(usize, usize, u64)> {
+        let mut configs: Vec<_> = self
+            .scores
+            .iter()
+            .map(|s| (s.code_length, s.color_count, s.seconds))
+            .collect();
+        configs.sort();
+        configs.dedup();
+        configs
+    }
+}
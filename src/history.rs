@@ -0,0 +1,933 @@
+//! A record of every finished game, kept as one JSON object per line or,
+//! with the `sqlite` feature, in an SQLite database.
+
+#[cfg(feature = "sqlite")]
+use std::cell::OnceCell;
+use std::fmt;
+#[cfg(all(feature = "serde", not(feature = "sqlite")))]
+use std::fs::OpenOptions;
+#[cfg(all(feature = "serde", not(feature = "sqlite")))]
+use std::io::Write;
+#[cfg(feature = "serde")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "serde")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "sqlite")]
+use rusqlite::OptionalExtension;
+
+#[cfg(feature = "sqlite")]
+use crate::board::RoundOutcome;
+use crate::board::{Board, Round, SkipReason};
+use crate::code::Code;
+use crate::config::GameConfig;
+#[cfg(feature = "serde")]
+use crate::error::GameError;
+#[cfg(feature = "serde")]
+use crate::paths::profile_dir;
+#[cfg(feature = "sqlite")]
+use crate::save::with_skipped;
+#[cfg(feature = "serde")]
+use crate::save::{read_skips, saved_skips, SavedRound, SavedSkip};
+#[cfg(feature = "sqlite")]
+use crate::score::Score;
+#[cfg(feature = "serde")]
+use crate::Result;
+
+/// How many games a history keeps unless changed with `set_history_cap`.
+#[cfg(feature = "serde")]
+pub const DEFAULT_HISTORY_CAP: usize = 5000;
+
+#[cfg(feature = "serde")]
+static HISTORY_CAP: AtomicUsize = AtomicUsize::new(DEFAULT_HISTORY_CAP);
+
+/// Keeps only the newest `cap` games in every history recorded to from now
+/// on. Older games are dropped, but still count in its `Totals`.
+#[cfg(feature = "serde")]
+pub fn set_history_cap(cap: usize) {
+    HISTORY_CAP.store(cap, Ordering::Relaxed);
+}
+
+/// Returns how many games a history keeps: `DEFAULT_HISTORY_CAP` unless
+/// changed with `set_history_cap`.
+#[cfg(feature = "serde")]
+pub fn history_cap() -> usize {
+    HISTORY_CAP.load(Ordering::Relaxed)
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// How a game ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Outcome {
+    Won,
+    Lost,
+    /// The player left before the game was decided.
+    Abandoned,
+    /// The player gave up and took the loss, with guesses still to spare, or
+    /// a tournament's guess budget ran out first.
+    Forfeit,
+}
+
+impl Outcome {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Outcome::Won => "won",
+            Outcome::Lost => "lost",
+            Outcome::Abandoned => "abandoned",
+            Outcome::Forfeit => "forfeit",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Outcome> {
+        match name {
+            "won" => Some(Outcome::Won),
+            "lost" => Some(Outcome::Lost),
+            "abandoned" => Some(Outcome::Abandoned),
+            "forfeit" => Some(Outcome::Forfeit),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the game counts as lost: out of guesses or forfeit.
+    pub fn is_loss(&self) -> bool {
+        matches!(self, Outcome::Lost | Outcome::Forfeit)
+    }
+
+    /// Returns how a game that ended this way went for the player who made
+    /// the code, in a duel: a code cracked is a loss for them, and one that
+    /// held out a win.
+    pub fn for_codemaker(&self) -> Outcome {
+        match self {
+            Outcome::Won => Outcome::Lost,
+            Outcome::Lost | Outcome::Forfeit => Outcome::Won,
+            Outcome::Abandoned => Outcome::Abandoned,
+        }
+    }
+}
+
+/// Which rules of play a game followed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Variant {
+    /// Each guess is scored before the next is made.
+    #[default]
+    Classic,
+    /// Every guess is written down before any is scored, then the code is
+    /// named in one final answer; see `static_game`.
+    Static,
+}
+
+impl Variant {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variant::Classic => "classic",
+            Variant::Static => "static",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Variant> {
+        match name {
+            "classic" => Some(Variant::Classic),
+            "static" => Some(Variant::Static),
+            _ => None,
+        }
+    }
+}
+
+/// A finished game, with start and end times in seconds since the Unix epoch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameSession {
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub outcome: Outcome,
+    pub variant: Variant,
+    pub config: GameConfig,
+    pub hidden_code: Code,
+    pub rounds: Vec<Round>,
+    /// Guesses skipped without being played, each with how many rounds had
+    /// been played before it.
+    pub skipped: Vec<(usize, SkipReason)>,
+}
+
+impl GameSession {
+    /// Records the game on `board`, which started at `started_at` and ends now.
+    pub fn from_board(board: &Board, started_at: u64, outcome: Outcome) -> Self {
+        Self {
+            started_at,
+            ended_at: now(),
+            outcome,
+            variant: Variant::Classic,
+            config: board.config().clone(),
+            hidden_code: board.hidden_code().clone(),
+            rounds: board.rounds().to_vec(),
+            skipped: board.skipped(),
+        }
+    }
+
+    /// Returns how many guesses the game used, skipped ones included.
+    pub fn guesses(&self) -> usize {
+        self.rounds.len() + self.skipped.len()
+    }
+
+    /// Returns `guesses` scaled to the default game by how much there was to
+    /// find out, the bits in a code of each size, so that games of different
+    /// sizes compare. A game with a single possible code is left as it is.
+    ///
+    /// ```
+    /// use mastermind::board::SkipReason;
+    /// use mastermind::history::{GameSession, Outcome, Variant};
+    /// use mastermind::{Code, GameConfig};
+    ///
+    /// let config = GameConfig {
+    ///     code_length: 8,
+    ///     ..GameConfig::default()
+    /// };
+    /// let session = GameSession {
+    ///     started_at: 0,
+    ///     ended_at: 0,
+    ///     outcome: Outcome::Lost,
+    ///     variant: Variant::Classic,
+    ///     hidden_code: Code::parse(String::from("rgbyrgby"), &config).unwrap(),
+    ///     config,
+    ///     rounds: Vec::new(),
+    ///     skipped: vec![(0, SkipReason::Timeout); 6],
+    /// };
+    /// // Twice the pegs hold twice the bits, so six guesses count as three.
+    /// assert_eq!(session.guesses(), 6);
+    /// assert_eq!(session.adjusted_guesses(), 3.0);
+    /// ```
+    pub fn adjusted_guesses(&self) -> f64 {
+        let bits =
+            |config: &GameConfig| config.code_length as f64 * (config.colors.len() as f64).log2();
+        let guesses = self.guesses() as f64;
+        let played = bits(&self.config);
+        if played > 0.0 {
+            guesses * bits(&GameConfig::default()) / played
+        } else {
+            guesses
+        }
+    }
+}
+
+/// A game as the history writes it, its codes in the letters of the game's
+/// palette. Skipped guesses are written only when there are some.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedSession {
+    started_at: u64,
+    ended_at: u64,
+    outcome: Outcome,
+    // Games recorded before variants existed were all classic.
+    #[serde(default)]
+    variant: Variant,
+    config: GameConfig,
+    hidden_code: String,
+    rounds: Vec<SavedRound>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    skipped: Vec<SavedSkip>,
+    // The help the player had, written only when it was not the usual.
+    #[serde(default = "on", skip_serializing_if = "is_on")]
+    assists: bool,
+    #[serde(default, skip_serializing_if = "is_off")]
+    coach: bool,
+    #[serde(default, skip_serializing_if = "is_off")]
+    cheat: bool,
+}
+
+#[cfg(feature = "serde")]
+fn on() -> bool {
+    true
+}
+
+#[cfg(feature = "serde")]
+fn is_on(flag: &bool) -> bool {
+    *flag
+}
+
+#[cfg(feature = "serde")]
+fn is_off(flag: &bool) -> bool {
+    !flag
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for GameSession {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        let palette = &self.config.palette;
+        SavedSession {
+            started_at: self.started_at,
+            ended_at: self.ended_at,
+            outcome: self.outcome,
+            variant: self.variant,
+            config: self.config.clone(),
+            hidden_code: self.hidden_code.letters(palette),
+            rounds: self
+                .rounds
+                .iter()
+                .map(|round| SavedRound::new(round, palette))
+                .collect(),
+            skipped: saved_skips(&self.skipped),
+            assists: self.config.assists,
+            coach: self.config.coach,
+            cheat: self.config.cheat_mode,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for GameSession {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let saved = SavedSession::deserialize(deserializer)?;
+        let config = GameConfig {
+            assists: saved.assists,
+            coach: saved.coach,
+            cheat_mode: saved.cheat,
+            ..saved.config
+        };
+        let read = || -> std::result::Result<GameSession, GameError> {
+            let rounds = saved
+                .rounds
+                .into_iter()
+                .map(|round| round.read(&config.palette))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            Ok(GameSession {
+                started_at: saved.started_at,
+                ended_at: saved.ended_at,
+                outcome: saved.outcome,
+                variant: saved.variant,
+                hidden_code: Code::from_saved_letters(&saved.hidden_code, &config.palette)?,
+                skipped: read_skips(saved.skipped, rounds.len())?,
+                rounds,
+                config,
+            })
+        };
+        read().map_err(D::Error::custom)
+    }
+}
+
+/// Consecutive wins with one code length and number of colors, broken by any
+/// game lost or abandoned. Only classic games count.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Streak {
+    pub current: usize,
+    pub best: usize,
+}
+
+impl Streak {
+    /// Follows `sessions`, oldest first, counting only games of `code_length`
+    /// pegs in `color_count` colors.
+    ///
+    /// ```
+    /// use mastermind::history::{GameSession, Outcome, Streak, Variant};
+    /// use mastermind::{Code, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let game = |outcome| GameSession {
+    ///     started_at: 0,
+    ///     ended_at: 0,
+    ///     outcome,
+    ///     variant: Variant::Classic,
+    ///     config: config.clone(),
+    ///     hidden_code: String::from("rgby").try_into().unwrap(),
+    ///     rounds: Vec::new(),
+    ///     skipped: Vec::new(),
+    /// };
+    /// let other = GameSession {
+    ///     config: GameConfig {
+    ///         code_length: 5,
+    ///         ..config.clone()
+    ///     },
+    ///     ..game(Outcome::Lost)
+    /// };
+    /// let sessions = [
+    ///     game(Outcome::Won),
+    ///     game(Outcome::Won),
+    ///     game(Outcome::Won),
+    ///     game(Outcome::Abandoned),
+    ///     game(Outcome::Won),
+    ///     // A loss with other rules leaves this streak alone.
+    ///     other,
+    ///     game(Outcome::Won),
+    /// ];
+    ///
+    /// let streak = Streak::from_sessions(&sessions, 4, 4);
+    /// assert_eq!(streak, Streak { current: 2, best: 3 });
+    /// assert_eq!(streak.to_string(), "current streak: 2, best: 3");
+    /// assert_eq!(Streak::from_sessions(&sessions, 5, 4), Streak::default());
+    /// ```
+    pub fn from_sessions<'a>(
+        sessions: impl IntoIterator<Item = &'a GameSession>,
+        code_length: usize,
+        color_count: usize,
+    ) -> Streak {
+        let mut streak = Streak::default();
+        for session in sessions
+            .into_iter()
+            .filter(|s| s.variant == Variant::Classic)
+            .filter(|s| s.config.code_length == code_length && s.config.colors.len() == color_count)
+        {
+            if session.outcome == Outcome::Won {
+                streak.current += 1;
+                streak.best = streak.best.max(streak.current);
+            } else {
+                streak.current = 0;
+            }
+        }
+        streak
+    }
+}
+
+impl fmt::Display for Streak {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "current streak: {}, best: {}", self.current, self.best)
+    }
+}
+
+/// The headline facts about one recorded game; `id` counts games from 1 in the
+/// order they were recorded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameSummary {
+    pub id: usize,
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub outcome: Outcome,
+    pub variant: Variant,
+    pub guesses: usize,
+    pub code_length: usize,
+    pub color_count: usize,
+}
+
+impl GameSummary {
+    /// Returns the summary of `session`, numbered `id`.
+    pub fn of(id: usize, session: &GameSession) -> GameSummary {
+        GameSummary {
+            id,
+            started_at: session.started_at,
+            ended_at: session.ended_at,
+            outcome: session.outcome,
+            variant: session.variant,
+            guesses: session.guesses(),
+            code_length: session.config.code_length,
+            color_count: session.config.colors.len(),
+        }
+    }
+}
+
+/// Running totals over every game a history has recorded, kept apart from
+/// the games so that those dropped past its cap still count.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Totals {
+    pub games: usize,
+    pub wins: usize,
+    /// Games lost, forfeit ones included.
+    pub losses: usize,
+    pub abandoned: usize,
+    /// Guesses used over every game, skipped ones included.
+    pub guesses: usize,
+    /// Time played over every game.
+    pub seconds: u64,
+}
+
+impl Totals {
+    /// Returns the totals over `sessions`.
+    pub fn of<'a>(sessions: impl IntoIterator<Item = &'a GameSession>) -> Totals {
+        let mut totals = Totals::default();
+        for session in sessions {
+            totals.add(session);
+        }
+        totals
+    }
+
+    /// Counts `session` in.
+    pub fn add(&mut self, session: &GameSession) {
+        self.games += 1;
+        match session.outcome {
+            Outcome::Won => self.wins += 1,
+            Outcome::Abandoned => self.abandoned += 1,
+            Outcome::Lost | Outcome::Forfeit => self.losses += 1,
+        }
+        self.guesses += session.guesses();
+        self.seconds += session.ended_at.saturating_sub(session.started_at);
+    }
+}
+
+/// Finished games stored in a file, oldest first, with their `Totals` in a
+/// second file beside it. Only the newest `history_cap()` games are kept.
+#[cfg(all(feature = "serde", not(feature = "sqlite")))]
+pub struct History {
+    path: PathBuf,
+}
+
+#[cfg(all(feature = "serde", not(feature = "sqlite")))]
+impl History {
+    /// Returns `history.jsonl` in `player`'s profile directory.
+    pub fn default_path(player: Option<&str>) -> Option<PathBuf> {
+        Some(profile_dir(player)?.join("history.jsonl"))
+    }
+
+    pub fn open(path: &Path) -> History {
+        History {
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// Returns the file the totals are kept in: `history.totals.json` for
+    /// `history.jsonl`.
+    fn totals_path(&self) -> PathBuf {
+        self.path.with_extension("totals.json")
+    }
+
+    /// Appends `session`, counts it in the totals and drops the oldest games
+    /// past the cap. The game is written with a single call, so a crash
+    /// cannot leave half a game behind for the next reader.
+    pub fn record(&self, session: &GameSession) -> Result<()> {
+        let mut totals = self.totals()?;
+        totals.add(session);
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(format!("{}\n", serde_json::to_string(session)?).as_bytes())?;
+        std::fs::write(self.totals_path(), serde_json::to_string(&totals)?)?;
+        self.trim()
+    }
+
+    /// Drops all but the newest `history_cap()` games. Those kept are written
+    /// beside the history first and moved into place, so a crash part way
+    /// through loses none of them.
+    fn trim(&self) -> Result<()> {
+        let text = std::fs::read_to_string(&self.path)?;
+        let games: Vec<&str> = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        let Some(excess) = games.len().checked_sub(history_cap()).filter(|&n| n > 0) else {
+            return Ok(());
+        };
+        let kept: String = games[excess..]
+            .iter()
+            .map(|game| format!("{}\n", game))
+            .collect();
+        let partial = self.path.with_extension("jsonl.partial");
+        std::fs::write(&partial, kept)?;
+        std::fs::rename(&partial, &self.path)?;
+        Ok(())
+    }
+
+    /// Returns the totals over every game recorded. A history written before
+    /// totals were kept has dropped no games, so they are counted from it.
+    pub fn totals(&self) -> Result<Totals> {
+        match std::fs::read_to_string(self.totals_path()) {
+            Ok(text) => Ok(serde_json::from_str(&text).map_err(GameError::from)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Totals::of(&self.sessions()?)),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Reads every recorded game; a missing file means none have been played.
+    pub fn sessions(&self) -> Result<Vec<GameSession>> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line).map_err(GameError::from)?))
+            .collect()
+    }
+
+    /// Returns the last `n` games recorded, most recent first.
+    ///
+    /// ```
+    /// use mastermind::history::{GameSession, History, Outcome};
+    /// use mastermind::{Board, Code, GameConfig};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let history = History::open(&dir.path().join("history.jsonl"));
+    /// assert!(history.query_recent(5).unwrap().is_empty());
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let mut board = Board::new(config.clone(), code("rgby"));
+    /// board.guess(code("rrgg"));
+    /// let lost = GameSession::from_board(&board, 100, Outcome::Forfeit);
+    /// board.guess(code("rgby"));
+    /// let won = GameSession::from_board(&board, 200, Outcome::Won);
+    /// for session in [&lost, &won, &won] {
+    ///     history.record(session).unwrap();
+    /// }
+    /// assert_eq!(history.sessions().unwrap(), [lost, won.clone(), won]);
+    ///
+    /// let recent = history.query_recent(2).unwrap();
+    /// let ids: Vec<_> = recent.iter().map(|game| game.id).collect();
+    /// assert_eq!(ids, [3, 2]);
+    /// assert_eq!(recent[0].outcome, Outcome::Won);
+    /// assert_eq!(recent[0].guesses, 2);
+    /// assert_eq!(history.query_recent(10).unwrap()[2].outcome, Outcome::Forfeit);
+    /// ```
+    pub fn query_recent(&self, n: usize) -> Result<Vec<GameSummary>> {
+        let sessions = self.sessions()?;
+        // Games dropped past the cap keep their numbers.
+        let dropped = self.totals()?.games.saturating_sub(sessions.len());
+        Ok(sessions
+            .iter()
+            .enumerate()
+            .rev()
+            .take(n)
+            .map(|(i, session)| GameSummary::of(dropped + i + 1, session))
+            .collect())
+    }
+}
+
+/// The tables of the history database. Each game's rounds are numbered from
+/// 1 in the order they were played; a skipped guess is a round with no guess
+/// or score, only the reason it was skipped.
+#[cfg(feature = "sqlite")]
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS games (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        started_at INTEGER NOT NULL,
+        ended_at INTEGER NOT NULL,
+        outcome TEXT NOT NULL,
+        variant TEXT NOT NULL,
+        hidden_code TEXT NOT NULL,
+        config_json TEXT NOT NULL,
+        assists INTEGER NOT NULL DEFAULT 1,
+        coach INTEGER NOT NULL DEFAULT 0,
+        cheat INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE TABLE IF NOT EXISTS rounds (
+        game_id INTEGER NOT NULL REFERENCES games (id),
+        round_number INTEGER NOT NULL,
+        guess TEXT,
+        black INTEGER,
+        white INTEGER,
+        skip_reason TEXT,
+        PRIMARY KEY (game_id, round_number)
+    );
+    CREATE TABLE IF NOT EXISTS totals (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        totals_json TEXT NOT NULL
+    );
+";
+
+/// Columns added to `games` since it was first created, with their
+/// definitions, for databases made before them.
+#[cfg(feature = "sqlite")]
+const ADDED_COLUMNS: [(&str, &str); 3] = [
+    ("assists", "INTEGER NOT NULL DEFAULT 1"),
+    ("coach", "INTEGER NOT NULL DEFAULT 0"),
+    ("cheat", "INTEGER NOT NULL DEFAULT 0"),
+];
+
+/// Finished games stored in an SQLite database, oldest first, with their
+/// `Totals`. Only the newest `history_cap()` games are kept. The database is
+/// opened when first needed, and only created once a game is recorded.
+#[cfg(feature = "sqlite")]
+pub struct History {
+    path: PathBuf,
+    db: OnceCell<rusqlite::Connection>,
+}
+
+#[cfg(feature = "sqlite")]
+impl History {
+    /// Returns `history.db` in `player`'s profile directory.
+    pub fn default_path(player: Option<&str>) -> Option<PathBuf> {
+        Some(profile_dir(player)?.join("history.db"))
+    }
+
+    /// Opens the database at `path`; `":memory:"` keeps the games only as
+    /// long as the `History`.
+    pub fn open(path: &Path) -> History {
+        History {
+            path: path.to_path_buf(),
+            db: OnceCell::new(),
+        }
+    }
+
+    /// Returns the connection to the database, creating the database and its
+    /// tables if they do not exist yet, and adding any columns they lack.
+    fn db(&self) -> Result<&rusqlite::Connection> {
+        if let Some(db) = self.db.get() {
+            return Ok(db);
+        }
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let db = rusqlite::Connection::open(&self.path)?;
+        db.execute_batch(SCHEMA)?;
+        for (column, definition) in ADDED_COLUMNS {
+            let present: bool = db.query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('games') WHERE name = ?1",
+                [column],
+                |row| row.get(0),
+            )?;
+            if !present {
+                db.execute_batch(&format!(
+                    "ALTER TABLE games ADD COLUMN {} {}",
+                    column, definition
+                ))?;
+            }
+        }
+        Ok(self.db.get_or_init(|| db))
+    }
+
+    /// Returns whether nothing has been recorded: there is no database yet.
+    fn is_unwritten(&self) -> bool {
+        self.db.get().is_none() && !self.path.exists()
+    }
+
+    /// Adds `session` and its rounds, counts it in the totals and drops the
+    /// oldest games past the cap, all in one transaction, so a crash cannot
+    /// leave half a game behind for the next reader.
+    pub fn record(&self, session: &GameSession) -> Result<()> {
+        let palette = &session.config.palette;
+        let mut totals = self.totals()?;
+        totals.add(session);
+        let transaction = self.db()?.unchecked_transaction()?;
+        transaction.execute(
+            "INSERT INTO games (started_at, ended_at, outcome, variant, hidden_code, config_json,
+                 assists, coach, cheat)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            (
+                session.started_at,
+                session.ended_at,
+                session.outcome.name(),
+                session.variant.name(),
+                session.hidden_code.letters(palette),
+                serde_json::to_string(&session.config)?,
+                session.config.assists,
+                session.config.coach,
+                session.config.cheat_mode,
+            ),
+        )?;
+        let game_id = transaction.last_insert_rowid();
+        let mut insert = transaction.prepare(
+            "INSERT INTO rounds (game_id, round_number, guess, black, white, skip_reason)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        let outcomes = with_skipped(session.rounds.clone(), &session.skipped);
+        for (number, outcome) in (1..).zip(outcomes) {
+            match outcome {
+                RoundOutcome::Guessed(round) => insert.execute((
+                    game_id,
+                    number,
+                    round.guess().letters(palette),
+                    round.score().black_count(),
+                    round.score().white_count(),
+                    None::<&str>,
+                ))?,
+                RoundOutcome::Skipped { reason } => insert.execute((
+                    game_id,
+                    number,
+                    None::<String>,
+                    None::<usize>,
+                    None::<usize>,
+                    reason.name(),
+                ))?,
+            };
+        }
+        drop(insert);
+        transaction.execute(
+            "INSERT OR REPLACE INTO totals (id, totals_json) VALUES (1, ?1)",
+            [serde_json::to_string(&totals)?],
+        )?;
+        let dropped = "SELECT id FROM games ORDER BY id DESC LIMIT -1 OFFSET ?1";
+        transaction.execute(
+            &format!("DELETE FROM rounds WHERE game_id IN ({})", dropped),
+            [history_cap()],
+        )?;
+        transaction.execute(
+            &format!("DELETE FROM games WHERE id IN ({})", dropped),
+            [history_cap()],
+        )?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Returns the totals over every game recorded. A database written
+    /// before totals were kept has dropped no games, so they are counted
+    /// from it.
+    pub fn totals(&self) -> Result<Totals> {
+        if self.is_unwritten() {
+            return Ok(Totals::default());
+        }
+        let saved: Option<String> = self
+            .db()?
+            .query_row("SELECT totals_json FROM totals WHERE id = 1", (), |row| {
+                row.get(0)
+            })
+            .optional()?;
+        match saved {
+            Some(json) => Ok(serde_json::from_str(&json).map_err(GameError::from)?),
+            None => Ok(Totals::of(&self.sessions()?)),
+        }
+    }
+
+    /// Reads every recorded game.
+    pub fn sessions(&self) -> Result<Vec<GameSession>> {
+        if self.is_unwritten() {
+            return Ok(Vec::new());
+        }
+        let db = self.db()?;
+        let mut games = db.prepare(
+            "SELECT id, started_at, ended_at, outcome, variant, hidden_code, config_json,
+                 assists, coach, cheat
+             FROM games ORDER BY id",
+        )?;
+        let mut rounds = db.prepare(
+            "SELECT guess, black, white, skip_reason FROM rounds
+             WHERE game_id = ?1 ORDER BY round_number",
+        )?;
+        let mut sessions = Vec::new();
+        let mut rows = games.query(())?;
+        while let Some(row) = rows.next()? {
+            let config = GameConfig {
+                assists: row.get(7)?,
+                coach: row.get(8)?,
+                cheat_mode: row.get(9)?,
+                ..serde_json::from_str(&row.get::<_, String>(6)?).map_err(GameError::from)?
+            };
+            let mut session = GameSession {
+                started_at: row.get(1)?,
+                ended_at: row.get(2)?,
+                outcome: read_name(row.get(3)?, Outcome::from_name, "outcome")?,
+                variant: read_name(row.get(4)?, Variant::from_name, "variant")?,
+                hidden_code: Code::from_saved_letters(&row.get::<_, String>(5)?, &config.palette)?,
+                rounds: Vec::new(),
+                skipped: Vec::new(),
+                config,
+            };
+            let mut played = rounds.query([row.get::<_, i64>(0)?])?;
+            while let Some(round) = played.next()? {
+                match (
+                    round.get::<_, Option<String>>(0)?,
+                    round.get(1)?,
+                    round.get(2)?,
+                ) {
+                    (Some(guess), Some(blacks), Some(whites)) => {
+                        let guess = Code::from_saved_letters(&guess, &session.config.palette)?;
+                        let score = Score::from_counts(blacks, whites, guess.len())?;
+                        session.rounds.push(Round::new(guess, score));
+                    }
+                    _ => {
+                        let reason =
+                            read_name(round.get(3)?, SkipReason::from_name, "skip reason")?;
+                        session.skipped.push((session.rounds.len(), reason));
+                    }
+                }
+            }
+            sessions.push(session);
+        }
+        Ok(sessions)
+    }
+
+    /// Returns the last `n` games recorded, most recent first.
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use mastermind::history::{GameSession, History, Outcome};
+    /// use mastermind::{Board, Code, GameConfig};
+    ///
+    /// let history = History::open(Path::new(":memory:"));
+    /// assert!(history.query_recent(5).unwrap().is_empty());
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let mut board = Board::new(config.clone(), code("rgby"));
+    /// board.guess(code("rrgg"));
+    /// let lost = GameSession::from_board(&board, 100, Outcome::Forfeit);
+    /// board.guess(code("rgby"));
+    /// let won = GameSession::from_board(&board, 200, Outcome::Won);
+    /// for session in [&lost, &won, &won] {
+    ///     history.record(session).unwrap();
+    /// }
+    /// assert_eq!(history.sessions().unwrap(), [lost, won.clone(), won]);
+    ///
+    /// let recent = history.query_recent(2).unwrap();
+    /// let ids: Vec<_> = recent.iter().map(|game| game.id).collect();
+    /// assert_eq!(ids, [3, 2]);
+    /// assert_eq!(recent[0].outcome, Outcome::Won);
+    /// assert_eq!(recent[0].guesses, 2);
+    /// assert_eq!(history.query_recent(10).unwrap()[2].outcome, Outcome::Forfeit);
+    /// ```
+    pub fn query_recent(&self, n: usize) -> Result<Vec<GameSummary>> {
+        if self.is_unwritten() {
+            return Ok(Vec::new());
+        }
+        let mut games = self.db()?.prepare(
+            "SELECT id, started_at, ended_at, outcome, variant, config_json,
+                 (SELECT COUNT(*) FROM rounds WHERE game_id = games.id)
+             FROM games ORDER BY id DESC LIMIT ?1",
+        )?;
+        let mut rows = games.query([n])?;
+        let mut recent = Vec::new();
+        while let Some(row) = rows.next()? {
+            let config: GameConfig =
+                serde_json::from_str(&row.get::<_, String>(5)?).map_err(GameError::from)?;
+            recent.push(GameSummary {
+                id: row.get(0)?,
+                started_at: row.get(1)?,
+                ended_at: row.get(2)?,
+                outcome: read_name(row.get(3)?, Outcome::from_name, "outcome")?,
+                variant: read_name(row.get(4)?, Variant::from_name, "variant")?,
+                guesses: row.get(6)?,
+                code_length: config.code_length,
+                color_count: config.colors.len(),
+            });
+        }
+        Ok(recent)
+    }
+}
+
+/// Reads a name stored in the database with `from_name`, refusing one it
+/// does not know.
+#[cfg(feature = "sqlite")]
+fn read_name<T>(name: String, from_name: fn(&str) -> Option<T>, what: &str) -> Result<T> {
+    from_name(&name).ok_or_else(|| {
+        GameError::ParseError(format!("\"{}\" is not a known {}", name, what)).into()
+    })
+}
+
+#[cfg(feature = "serde")]
+impl History {
+    /// Returns the streak for games of `code_length` pegs in `color_count`
+    /// colors.
+    pub fn streak(&self, code_length: usize, color_count: usize) -> Result<Streak> {
+        Ok(Streak::from_sessions(
+            &self.sessions()?,
+            code_length,
+            color_count,
+        ))
+    }
+}
@@ -0,0 +1,388 @@
+//! Helpers for reading what the player typed.
+
+use std::io::{BufRead, Write};
+use std::sync::mpsc::{self, Receiver};
+
+#[cfg(feature = "async")]
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use crate::code::Code;
+use crate::config::GameConfig;
+use crate::error::GameError;
+
+/// The longest line, in bytes including its line ending, read by default.
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 256;
+
+/// Reads one line into `buf` like `BufRead::read_line`, but never buffers more
+/// than `limit` bytes of it.
+///
+/// A longer line is consumed through its newline, so the next read starts on a
+/// fresh line, and reported as `GameError::LineTooLong`.
+///
+/// ```
+/// use std::io::BufReader;
+///
+/// use mastermind::input::read_line_limited;
+/// use mastermind::GameError;
+///
+/// let huge = "r".repeat(10_000_000);
+/// let input = format!("{}\nrgby\n", huge);
+/// let mut reader = BufReader::with_capacity(64, input.as_bytes());
+/// let mut line = String::new();
+/// assert!(matches!(
+///     read_line_limited(&mut reader, &mut line, 256),
+///     Err(GameError::LineTooLong { limit: 256 })
+/// ));
+/// // Nothing of the long line is kept.
+/// assert!(line.is_empty());
+///
+/// // The next line reads as usual.
+/// assert_eq!(read_line_limited(&mut reader, &mut line, 256).unwrap(), 5);
+/// assert_eq!(line, "rgby\n");
+/// assert_eq!(read_line_limited(&mut reader, &mut line, 256).unwrap(), 0);
+/// ```
+pub fn read_line_limited<R: BufRead + ?Sized>(
+    reader: &mut R,
+    buf: &mut String,
+    limit: usize,
+) -> Result<usize, GameError> {
+    let mut line = LimitedLine::new(limit);
+    loop {
+        let available = reader.fill_buf().map_err(GameError::Io)?;
+        if available.is_empty() {
+            break;
+        }
+        let (used, done) = line.take(available);
+        reader.consume(used);
+        if done {
+            break;
+        }
+    }
+    line.finish(buf)
+}
+
+/// Like `read_line_limited`, but reads `reader` asynchronously.
+///
+/// ```
+/// use mastermind::input::read_line_limited_async;
+/// use mastermind::GameError;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let input = format!("{}\nrgby\n", "r".repeat(1_000));
+/// let mut reader = input.as_bytes();
+/// let mut line = String::new();
+/// assert!(matches!(
+///     read_line_limited_async(&mut reader, &mut line, 256).await,
+///     Err(GameError::LineTooLong { limit: 256 })
+/// ));
+/// assert_eq!(read_line_limited_async(&mut reader, &mut line, 256).await.unwrap(), 5);
+/// assert_eq!(line, "rgby\n");
+/// # });
+/// ```
+#[cfg(feature = "async")]
+pub async fn read_line_limited_async<R: AsyncBufRead + Unpin + ?Sized>(
+    reader: &mut R,
+    buf: &mut String,
+    limit: usize,
+) -> Result<usize, GameError> {
+    let mut line = LimitedLine::new(limit);
+    loop {
+        let available = reader.fill_buf().await.map_err(GameError::Io)?;
+        if available.is_empty() {
+            break;
+        }
+        let (used, done) = line.take(available);
+        reader.consume(used);
+        if done {
+            break;
+        }
+    }
+    line.finish(buf)
+}
+
+/// A line being read by `read_line_limited`, a buffer at a time.
+struct LimitedLine {
+    line: Vec<u8>,
+    limit: usize,
+    too_long: bool,
+    read: usize,
+}
+
+impl LimitedLine {
+    fn new(limit: usize) -> LimitedLine {
+        LimitedLine {
+            line: Vec::new(),
+            limit,
+            too_long: false,
+            read: 0,
+        }
+    }
+
+    /// Keeps what `available` holds of the line, unless that makes it too
+    /// long, and returns how many bytes it used and whether the line ended.
+    fn take(&mut self, available: &[u8]) -> (usize, bool) {
+        let (used, done) = match available.iter().position(|&b| b == b'\n') {
+            Some(i) => (i + 1, true),
+            None => (available.len(), false),
+        };
+        if !self.too_long && self.line.len() + used <= self.limit {
+            self.line.extend_from_slice(&available[..used]);
+        } else if !self.too_long {
+            self.too_long = true;
+            self.line = Vec::new();
+        }
+        self.read += used;
+        (used, done)
+    }
+
+    /// Appends the line to `buf` and returns how many bytes were read.
+    fn finish(self, buf: &mut String) -> Result<usize, GameError> {
+        if self.too_long {
+            return Err(GameError::LineTooLong { limit: self.limit });
+        }
+        let line = String::from_utf8(self.line)
+            .map_err(|_| GameError::ParseError(String::from("input is not valid UTF-8")))?;
+        buf.push_str(&line);
+        Ok(self.read)
+    }
+}
+
+/// Strips a UTF-8 byte order mark, the line ending (`\n` or `\r\n`) and any
+/// surrounding whitespace from a line of input.
+///
+/// Every place that parses typed or piped input goes through this first, so
+/// positions reported in parse errors refer to the normalized text.
+///
+/// ```
+/// use mastermind::input::normalize_input;
+/// use mastermind::{Code, GameConfig};
+///
+/// assert_eq!(normalize_input("rgby\r\n"), "rgby");
+/// assert_eq!(normalize_input("\t  rgby  \n"), "rgby");
+/// assert_eq!(normalize_input("\u{feff}rgby\r\n"), "rgby");
+/// assert_eq!(normalize_input("r g b y\n"), "r g b y");
+///
+/// // A padded line parses as the code it holds, and errors point into the
+/// // trimmed text.
+/// let config = GameConfig::default();
+/// let parse = |s: &str| Code::parse(String::from(s), &config);
+/// assert_eq!(parse("\u{feff} \trgby \r\n").unwrap(), parse("rgby").unwrap());
+/// assert_eq!(
+///     parse("\t  rgxy\r\n").unwrap_err().to_string(),
+///     parse("rgxy").unwrap_err().to_string()
+/// );
+/// ```
+pub fn normalize_input(line: &str) -> &str {
+    line.strip_prefix('\u{feff}').unwrap_or(line).trim()
+}
+
+/// Expands a guess written in terms of `last_guess` into the letters of the
+/// code it stands for, leaving any other input as it is:
+///
+/// - `!` plays `last_guess` again, and `!3=y` plays it with the third peg
+///   made yellow.
+/// - `~2143` plays the pegs of `last_guess` in the order given, here its
+///   second, first, fourth then third. For codes of more than nine pegs the
+///   positions are separated by spaces or commas.
+///
+/// Characters the palette uses for pegs are never read as macros.
+///
+/// ```
+/// use mastermind::input::expand_macro;
+/// use mastermind::{Code, GameConfig};
+///
+/// let config = GameConfig::default();
+/// let last = Code::parse(String::from("rgby"), &config).unwrap();
+/// let expand = |input| expand_macro(input, Some(&last), &config);
+///
+/// assert_eq!(expand("!").unwrap(), "rgby");
+/// assert_eq!(expand("!3=y").unwrap(), "rgyy");
+/// assert_eq!(expand("! 1 = b").unwrap(), "bgby");
+/// assert_eq!(expand("~2143").unwrap(), "gryb");
+/// assert_eq!(expand("~1234").unwrap(), "rgby");
+/// assert_eq!(expand("~ 4, 3, 2, 1").unwrap(), "ybgr");
+/// assert_eq!(expand("rrgg").unwrap(), "rrgg");
+///
+/// let error = |input| expand(input).unwrap_err().to_string();
+/// assert_eq!(error("!5=y"), "parse error: position 5 is not from 1 to 4");
+/// assert_eq!(error("!0=y"), "parse error: position 0 is not from 1 to 4");
+/// assert_eq!(error("!3=x"), "parse error: 'x' is not a color, expected one of: r g b y");
+/// assert_eq!(error("!3"), "parse error: expected '!' alone or like '!3=y'");
+/// assert_eq!(error("~213"), "parse error: '~' needs each position from 1 to 4 once");
+/// assert_eq!(error("~2113"), "parse error: '~' needs each position from 1 to 4 once");
+/// assert_eq!(error("~21x3"), "parse error: '~' needs each position from 1 to 4 once");
+/// assert_eq!(
+///     expand_macro("!", None, &config).unwrap_err().to_string(),
+///     "parse error: there is no previous guess to repeat"
+/// );
+/// assert_eq!(expand_macro("rgby", None, &config).unwrap(), "rgby");
+/// ```
+pub fn expand_macro(
+    input: &str,
+    last_guess: Option<&Code>,
+    config: &GameConfig,
+) -> Result<String, GameError> {
+    let invalid = |message: String| Err(GameError::ParseError(message));
+    let Some(first) = input.chars().next() else {
+        return Ok(input.to_string());
+    };
+    if !matches!(first, '!' | '~') || config.palette.value_of(first).is_some() {
+        return Ok(input.to_string());
+    }
+    let Some(last) = last_guess else {
+        return invalid(String::from("there is no previous guess to repeat"));
+    };
+    let mut colors = last.colors().to_vec();
+    let rest: String = input[1..].chars().filter(|c| !c.is_whitespace()).collect();
+    if first == '!' {
+        if !rest.is_empty() {
+            let Some((position, letter)) = rest.split_once('=') else {
+                return invalid(String::from("expected '!' alone or like '!3=y'"));
+            };
+            let index = match position.parse::<usize>() {
+                Ok(n) if (1..=colors.len()).contains(&n) => n - 1,
+                _ => {
+                    return invalid(format!(
+                        "position {} is not from 1 to {}",
+                        position,
+                        colors.len()
+                    ))
+                }
+            };
+            let mut letters = letter.chars();
+            let color = match (letters.next(), letters.next()) {
+                (Some(c), None) => config
+                    .palette
+                    .value_of(c)
+                    .filter(|color| config.colors.contains(color)),
+                _ => None,
+            };
+            let Some(color) = color else {
+                let expected: Vec<String> = config
+                    .colors
+                    .iter()
+                    .map(|color| config.palette.style(color).letter.to_string())
+                    .collect();
+                return invalid(format!(
+                    "'{}' is not a color, expected one of: {}",
+                    letter,
+                    expected.join(" ")
+                ));
+            };
+            colors[index] = color;
+        }
+    } else {
+        let spread = input[1..].contains(|c: char| c == ',' || c.is_whitespace());
+        let positions: Vec<Option<usize>> = if spread {
+            input[1..]
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|part| !part.is_empty())
+                .map(|part| part.parse().ok())
+                .collect()
+        } else {
+            rest.chars()
+                .map(|c| c.to_digit(10).map(|d| d as usize))
+                .collect()
+        };
+        let mut seen = vec![false; colors.len()];
+        let mut order = Vec::with_capacity(colors.len());
+        for position in positions {
+            match position {
+                Some(n) if (1..=colors.len()).contains(&n) && !seen[n - 1] => {
+                    seen[n - 1] = true;
+                    order.push(n - 1);
+                }
+                _ => {
+                    return invalid(format!(
+                        "'~' needs each position from 1 to {} once",
+                        colors.len()
+                    ))
+                }
+            }
+        }
+        if order.len() != colors.len() {
+            return invalid(format!(
+                "'~' needs each position from 1 to {} once",
+                colors.len()
+            ));
+        }
+        colors = order
+            .into_iter()
+            .map(|i| last.colors()[i].clone())
+            .collect();
+    }
+    Ok(colors
+        .iter()
+        .map(|color| config.palette.style(color).letter)
+        .collect())
+}
+
+/// Prints `question` to `output` and reads the normalized answer from `input`,
+/// or `None` at the end of input.
+pub(crate) fn prompt<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    question: &str,
+) -> crate::Result<Option<String>> {
+    write!(output, "{}", question)?;
+    output.flush()?;
+    let mut line = String::new();
+    if read_line_limited(input, &mut line, DEFAULT_MAX_LINE_LENGTH)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(normalize_input(&line).to_string()))
+}
+
+/// Reads `reader` on its own thread, sending each normalized line, so callers
+/// can stop waiting for input with `Receiver::recv_timeout`.
+///
+/// A line longer than `limit` bytes arrives as an error and reading goes on;
+/// any other error is sent last. The channel closes at the end of input.
+///
+/// ```
+/// use std::io::Cursor;
+///
+/// use mastermind::input::spawn_line_reader;
+/// use mastermind::{Board, Code, GameConfig, GameError, OutputLevel};
+///
+/// let input = format!("{}\nrgby\n", "r".repeat(1_000_000));
+/// let lines = spawn_line_reader(Cursor::new(input), 32);
+/// assert!(matches!(lines.recv().unwrap(), Err(GameError::LineTooLong { limit: 32 })));
+/// assert_eq!(lines.recv().unwrap().unwrap(), "rgby");
+/// assert!(lines.recv().is_err());
+///
+/// // At the guess prompt, the long line is turned away and the game goes on.
+/// let input = format!("{}\nrgby\n", "r".repeat(1_000_000));
+/// let lines = spawn_line_reader(Cursor::new(input), 32);
+/// let config = GameConfig {
+///     output_level: OutputLevel::Quiet,
+///     ..GameConfig::default()
+/// };
+/// let hidden = Code::parse(String::from("rgby"), &config).unwrap();
+/// let mut board = Board::new(config, hidden);
+/// assert!(!board.get_input_timeout(&lines, None).unwrap());
+/// assert_eq!(board.guesses_used(), 0);
+/// assert!(board.get_input_timeout(&lines, None).unwrap());
+/// ```
+pub fn spawn_line_reader<R: BufRead + Send + 'static>(
+    mut reader: R,
+    limit: usize,
+) -> Receiver<Result<String, GameError>> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || loop {
+        let mut line = String::new();
+        let sent = match read_line_limited(&mut reader, &mut line, limit) {
+            Ok(0) => break,
+            Ok(_) => sender.send(Ok(normalize_input(&line).to_string())),
+            Err(e @ GameError::LineTooLong { .. }) => sender.send(Err(e)),
+            Err(e) => {
+                let _ = sender.send(Err(e));
+                break;
+            }
+        };
+        if sent.is_err() {
+            break;
+        }
+    });
+    receiver
+}
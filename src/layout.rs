@@ -0,0 +1,122 @@
+//! Fitting output to the width of the terminal.
+//!
+//! The width is decided once, from `--width` or the terminal, and handed to
+//! `set_width`; output that is not a terminal is laid out for
+//! `DEFAULT_WIDTH` columns. Everything else here is a plain function of the
+//! text and a width, so layouts can be checked without a terminal.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The width assumed when output is not a terminal.
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// What `truncate` ends a shortened line with.
+const ELLIPSIS: char = '…';
+
+/// The width to lay output out for, as set with `set_width`.
+static WIDTH: AtomicUsize = AtomicUsize::new(DEFAULT_WIDTH);
+
+/// Lays output out for `width` columns from now on.
+pub fn set_width(width: usize) {
+    WIDTH.store(width, Ordering::Relaxed);
+}
+
+/// Returns the width output is laid out for: `DEFAULT_WIDTH` unless changed
+/// with `set_width`.
+pub fn width() -> usize {
+    WIDTH.load(Ordering::Relaxed)
+}
+
+/// Returns how many columns `text` takes up on a terminal, not counting ANSI
+/// escape sequences.
+///
+/// ```
+/// use mastermind::layout::visible_width;
+///
+/// assert_eq!(visible_width("r g b"), 5);
+/// assert_eq!(visible_width("\x1b[38;5;9mr\x1b[39m g"), 3);
+/// ```
+pub fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(char::is_ascii_alphabetic);
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Shortens the plain line `text` to at most `width` columns, ending it with
+/// an ellipsis when anything had to be cut.
+///
+/// ```
+/// use mastermind::layout::truncate;
+///
+/// assert_eq!(truncate("pos1: r,b  pos2: g", 80), "pos1: r,b  pos2: g");
+/// assert_eq!(truncate("pos1: r,b  pos2: g", 12), "pos1: r,b  …");
+/// assert_eq!(truncate("pos1: r,b  pos2: g", 18), "pos1: r,b  pos2: g");
+/// assert_eq!(truncate("pos1", 0), "");
+/// ```
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut short: String = text.chars().take(width - 1).collect();
+    short.push(ELLIPSIS);
+    short
+}
+
+/// Breaks `text` into lines at most `width` columns wide, between words.
+/// Each line keeps the indent of the first, and a word too long for any line
+/// gets a line to itself. Escape sequences take up no room.
+///
+/// ```
+/// use mastermind::layout::wrap;
+///
+/// assert_eq!(
+///     wrap(" correct color, wrong position: w", 20),
+///     [" correct color,", " wrong position: w"]
+/// );
+/// assert_eq!(wrap(" good luck!", 80), [" good luck!"]);
+/// assert_eq!(wrap("a \x1b[4mbc\x1b[24m d", 4), ["a \x1b[4mbc\x1b[24m", "d"]);
+/// assert_eq!(wrap("", 10), [""]);
+/// ```
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let indent = &text[..text.len() - text.trim_start().len()];
+    let mut lines = Vec::new();
+    let mut line = indent.to_string();
+    for word in text.split_whitespace() {
+        let fits = visible_width(&line) + 1 + visible_width(word) <= width;
+        if line.len() > indent.len() && !fits {
+            lines.push(line);
+            line = indent.to_string();
+        }
+        if line.len() > indent.len() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines.push(line);
+    lines
+}
+
+/// Returns how many columns `column_width` wide, `gap` apart, fit side by
+/// side in `width`. There is always at least one; below that they stack.
+///
+/// ```
+/// use mastermind::layout::columns_that_fit;
+///
+/// assert_eq!(columns_that_fit(33, 3, 120), 3);
+/// assert_eq!(columns_that_fit(33, 3, 80), 2);
+/// assert_eq!(columns_that_fit(33, 3, 60), 1);
+/// assert_eq!(columns_that_fit(33, 3, 20), 1);
+/// ```
+pub fn columns_that_fit(column_width: usize, gap: usize, width: usize) -> usize {
+    ((width + gap) / (column_width + gap)).max(1)
+}
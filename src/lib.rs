@@ -0,0 +1,129 @@
+//! Core types for playing and inspecting games of mastermind.
+//!
+//! Without the `std` feature, which every other feature turns on, the crate
+//! is `no_std`, and only `embedded`, the rules of the game in fixed-capacity
+//! collections, and `random` are built.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+pub mod adaptive;
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "serde")]
+pub mod autosave;
+#[cfg(feature = "bench")]
+pub mod bench;
+#[cfg(feature = "std")]
+pub mod board;
+#[cfg(feature = "std")]
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod challenge;
+#[cfg(feature = "std")]
+pub mod coach;
+#[cfg(feature = "std")]
+pub mod code;
+#[cfg(feature = "std")]
+pub mod codemaker;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod daily;
+#[cfg(feature = "std")]
+pub mod date;
+#[cfg(feature = "std")]
+pub mod deduction;
+#[cfg(feature = "std")]
+pub mod display;
+pub mod embedded;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "std")]
+pub mod events;
+#[cfg(feature = "std")]
+#[cfg_attr(
+    all(target_arch = "x86_64", target_feature = "sse4.1"),
+    path = "exact_sse41.rs"
+)]
+#[cfg_attr(
+    not(all(target_arch = "x86_64", target_feature = "sse4.1")),
+    path = "exact_scalar.rs"
+)]
+mod exact;
+#[cfg(feature = "serde")]
+pub mod export;
+#[cfg(feature = "std")]
+pub mod format;
+#[cfg(feature = "std")]
+pub mod guesser;
+#[cfg(feature = "std")]
+pub mod highscore;
+#[cfg(feature = "std")]
+pub mod history;
+#[cfg(feature = "std")]
+pub mod input;
+#[cfg(feature = "std")]
+pub mod layout;
+#[cfg(feature = "std")]
+pub mod menu;
+#[cfg(feature = "std")]
+pub mod multi;
+#[cfg(feature = "std")]
+pub mod palette;
+#[cfg(feature = "std")]
+pub mod paths;
+#[cfg(feature = "server")]
+pub mod race;
+pub mod random;
+#[cfg(feature = "serde")]
+pub mod report;
+#[cfg(feature = "std")]
+pub mod save;
+#[cfg(feature = "std")]
+pub mod score;
+#[cfg(feature = "tui")]
+pub mod selector;
+#[cfg(feature = "std")]
+pub mod solver;
+#[cfg(feature = "std")]
+pub mod static_game;
+#[cfg(feature = "std")]
+pub mod timetrial;
+#[cfg(feature = "std")]
+pub mod tournament;
+
+#[cfg(feature = "std")]
+pub use board::{Board, Round, RoundOutcome, SkipReason};
+#[cfg(feature = "std")]
+pub use builder::BoardBuilder;
+#[cfg(feature = "std")]
+pub use code::{Code, CodeSpace, Color, LazyCodeSpace};
+#[cfg(feature = "std")]
+pub use config::{DisplayMode, GameConfig, OutputLevel};
+#[cfg(feature = "std")]
+pub use error::GameError;
+#[cfg(feature = "std")]
+pub use format::OutputFormatter;
+#[cfg(feature = "std")]
+pub use highscore::{HighScore, HighScoreBoard, TimeTrialBoard, TimeTrialScore};
+#[cfg(feature = "serde")]
+pub use history::History;
+#[cfg(feature = "std")]
+pub use history::{GameSession, GameSummary, Outcome};
+#[cfg(feature = "std")]
+pub use menu::{run_menu, MenuChoice};
+#[cfg(feature = "std")]
+pub use palette::{Palette, PegValue};
+pub use random::Rng;
+#[cfg(feature = "std")]
+pub use score::{Score, ScoreDetail, ScoreHistogram};
+#[cfg(feature = "std")]
+pub use solver::{
+    find_contradiction, CachedSolver, DeterministicSolver, ExpectedSizeSolver,
+    IncrementalCandidateSet, MostPartsSolver, OpeningSolver, PerceptualSolver, Solver,
+};
+
+/// The Result type for mastermind.
+#[cfg(feature = "std")]
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
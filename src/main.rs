@@ -1,278 +1,2255 @@
-use std::collections::HashSet;
-use std::fmt;
-use std::io::Write;
-
-use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent},
-    style::Stylize,
-    terminal,
+//! The `mastermind` command. Subcommands and flags whose feature is off are
+//! left out of the command line altogether.
+
+#[cfg(feature = "serde")]
+use std::fs::OpenOptions;
+#[cfg(feature = "server")]
+use std::io::BufReader;
+use std::io::{IsTerminal, Write};
+#[cfg(feature = "server")]
+use std::net::{TcpListener, TcpStream};
+#[cfg(feature = "serde")]
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use chrono::NaiveDate;
+#[cfg(feature = "serde")]
+use clap::ValueEnum;
+use clap::{Args, Parser, Subcommand};
+use terminal_size::{terminal_size, Width};
+#[cfg(feature = "async")]
+use tokio::{io::AsyncBufRead, runtime::Handle};
+
+#[cfg(feature = "server")]
+use mastermind::autosave::EXIT_INTERRUPTED;
+#[cfg(all(feature = "serde", feature = "async"))]
+use mastermind::input::read_line_limited_async;
+#[cfg(feature = "server")]
+use mastermind::race::{host_race_until, join_race, Ending, DEFAULT_GRACE, DEFAULT_PORT};
+#[cfg(feature = "serde")]
+use mastermind::{
+    adaptive::{next_settings, Bounds, GameResult, Settings},
+    autosave::{autosave_path, game_id, leftover_games, recovery_dir, Autosave, RecoveryFile},
+    daily::{daily_code, DailyLog, DailyRecord},
+    date,
+    events::JsonLinesSink,
+    export::{write_csv, write_json},
+    history::{set_history_cap, Streak},
+    input::normalize_input,
+    paths::{is_valid_player_name, players},
+    report::{read_transcripts, Report},
+    save::{load_game, ScoreCheck},
+    HighScore, HighScoreBoard, History, TimeTrialBoard, TimeTrialScore,
 };
+use mastermind::{
+    analysis::{analyze, rank_openings, review_game, GameDifficulty},
+    board::WIDE_MIN_WIDTH,
+    challenge::Challenge,
+    coach::Coach,
+    code::MAX_CODE_LENGTH,
+    codemaker::AdversarialCodemaker,
+    deduction::ProgressScale,
+    display::{color_enabled, set_color, set_theme, swatch, ColorChoice, Theme},
+    events::GameEvent,
+    guesser::{run_guesser, GuesserOutcome},
+    history::{self, Variant},
+    input::{spawn_line_reader, DEFAULT_MAX_LINE_LENGTH},
+    layout::{set_width, width, DEFAULT_WIDTH},
+    multi::MultiGame,
+    palette::Palette,
+    run_menu,
+    solver::{describe_buckets, score_buckets, strategies, strategy, Strategy},
+    static_game::{collect_guesses, parse_guesses, play_static},
+    timetrial::{run_time_trial, TimeTrialResult},
+    Board, Code, DeterministicSolver, DisplayMode, GameConfig, GameError, GameSession,
+    LazyCodeSpace, MenuChoice, OpeningSolver, Outcome, OutputFormatter, OutputLevel, Result, Rng,
+    Round, Solver,
+};
+
+/// Play mastermind in the terminal, or study it.
+///
+/// With no command, plays a game: against a code typed in by a second
+/// player, or one given with --hidden-code, --random and the like. Run with
+/// no arguments at all in a terminal, offers a menu instead.
+#[derive(Parser)]
+#[command(name = "mastermind", version)]
+struct Cli {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[command(flatten)]
+    play: PlayArgs,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Options that apply to every command.
+#[derive(Args)]
+struct CommonArgs {
+    /// Start from a set of rules.
+    #[arg(long, global = true, value_parser = ["classic", "super"])]
+    preset: Option<String>,
+    /// The number of pegs in a code.
+    #[arg(long = "length", global = true, value_name = "PEGS")]
+    code_length: Option<usize>,
+    /// Play with only the first N colors.
+    #[arg(long, global = true, value_name = "N")]
+    colors: Option<usize>,
+    /// Type pegs with these letters instead of the classic colors.
+    #[arg(long, global = true, value_name = "LETTERS")]
+    palette: Option<String>,
+    /// End the game after this many guesses.
+    #[arg(long, global = true, value_name = "N")]
+    max_guesses: Option<usize>,
+    /// Pick random codes from this seed, to play the same ones again.
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+    #[cfg(feature = "serde")]
+    /// Keep scores and history in this player's profile.
+    #[arg(long, global = true, value_name = "NAME", value_parser = player_name)]
+    player: Option<String>,
+    #[cfg(feature = "serde")]
+    /// Keep only the newest N games in the history; older ones still count
+    /// in its totals.
+    #[arg(long, global = true, value_name = "N")]
+    history_cap: Option<usize>,
+    /// When to paint output: auto, always or never.
+    #[arg(long, global = true, value_name = "WHEN")]
+    color: Option<ColorChoice>,
+    /// Never paint output; the same as --color never.
+    #[arg(long, global = true, conflicts_with = "color")]
+    no_color: bool,
+    /// The colors to paint pegs with.
+    #[arg(long, global = true)]
+    theme: Option<Theme>,
+    /// Lay output out for a terminal this many columns wide.
+    #[arg(long, global = true, value_name = "COLUMNS")]
+    width: Option<usize>,
+    /// Reject input lines longer than this.
+    #[arg(long, global = true, value_name = "BYTES")]
+    max_line_length: Option<usize>,
+    /// Print only what the game cannot do without.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Print more about each round.
+    #[arg(short, long, global = true)]
+    verbose: bool,
+}
+
+/// Options for how a game is played and shown.
+#[derive(Args, Default)]
+struct SessionArgs {
+    /// Point out guesses that could not be the code.
+    #[arg(long)]
+    coach: bool,
+    /// Explain each score in words.
+    #[arg(long)]
+    explain: bool,
+    /// Score only how many colors match, wherever they are.
+    #[arg(long)]
+    anagram_mode: bool,
+    /// Turn off hints, candidate lists and other help.
+    #[arg(long)]
+    no_assists: bool,
+    /// Show the hidden code, for practice; the game is no record.
+    #[arg(long)]
+    cheat: bool,
+    /// Print each round with this template instead of the board.
+    #[arg(long, value_name = "TEMPLATE")]
+    format: Option<String>,
+    /// Draw pegs as wide blocks of color.
+    #[arg(long)]
+    wide: bool,
+    /// Draw pegs as letters, emoji or symbols.
+    #[arg(long, value_name = "MODE")]
+    display_mode: Option<DisplayMode>,
+    /// Mark the pegs each guess changed from the one before.
+    #[arg(long)]
+    diff_guesses: bool,
+    /// Show the colors still possible at each position.
+    #[arg(long)]
+    show_position_candidates: bool,
+    /// List up to N codes still possible after each guess.
+    #[arg(long, value_name = "N")]
+    show_candidates: Option<usize>,
+    /// Show how far the possible codes have been narrowed down.
+    #[arg(long)]
+    progress: bool,
+    /// Draw the progress bar on this scale; implies --progress.
+    #[arg(long, value_name = "SCALE")]
+    progress_scale: Option<ProgressScale>,
+    /// Print only the rounds, without the board or prompts.
+    #[arg(long)]
+    no_prompt: bool,
+    #[cfg(feature = "serde")]
+    /// Append every game event to FILE as JSON lines.
+    #[arg(long, value_name = "FILE")]
+    events: Option<String>,
+    #[cfg(feature = "serde")]
+    /// Save the game after every guess, to recover it if the program dies.
+    #[arg(long)]
+    autosave: bool,
+    /// Skip a guess not made within this many seconds.
+    #[arg(long, value_name = "SECONDS")]
+    guess_timeout: Option<u64>,
+    /// Review every guess once the game is over.
+    #[arg(long)]
+    analysis: bool,
+}
+
+/// How a solver picks its guesses.
+#[derive(Args)]
+struct SolverArgs {
+    /// The solver strategy.
+    #[arg(long, default_value = "most-parts")]
+    strategy: String,
+    /// Break ties between guesses the same way every time.
+    #[arg(long)]
+    deterministic: bool,
+    /// Open with this guess instead of the solver's choice.
+    #[arg(long, value_name = "CODE")]
+    first_guess: Option<String>,
+}
+
+/// Options for the game played when no command is given, and one-off tasks
+/// that work on its rules.
+#[derive(Args)]
+struct PlayArgs {
+    #[command(flatten)]
+    session: SessionArgs,
+    #[command(flatten)]
+    solver: SolverArgs,
+    /// Play against this code.
+    #[arg(long, value_name = "CODE")]
+    hidden_code: Option<String>,
+    /// Play against the code in this environment variable.
+    #[arg(long, value_name = "VAR")]
+    hidden_code_env: Option<String>,
+    /// Play against a random code.
+    #[arg(long)]
+    random: bool,
+    /// Who makes the code: a fixed one, or an adversary that changes it to
+    /// stay ahead.
+    #[arg(long, value_parser = ["fixed", "adversarial"])]
+    codemaker: Option<String>,
+    #[cfg(feature = "serde")]
+    /// Play a duel: MAKER types the code, or gives it with --hidden-code,
+    /// and BREAKER guesses it. The game is recorded to both profiles.
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["MAKER", "BREAKER"],
+        value_parser = player_name,
+        conflicts_with_all = ["hidden_code_env", "random", "codemaker", "load"],
+    )]
+    duel: Option<Vec<String>>,
+    #[cfg(feature = "serde")]
+    /// Resume the game saved in FILE.
+    #[arg(long, value_name = "FILE")]
+    load: Option<String>,
+    #[cfg(feature = "serde")]
+    /// Refuse a save whose scores do not match the rules.
+    #[arg(long, requires = "load", conflicts_with = "migrate")]
+    strict_load: bool,
+    #[cfg(feature = "serde")]
+    /// Rescore a save made under older rules, and write it back.
+    #[arg(long, requires = "load")]
+    migrate: bool,
+    #[cfg(feature = "serde")]
+    /// Offer games left unfinished by a run that died, even when input is
+    /// not a terminal.
+    #[arg(long)]
+    recover: bool,
+    #[cfg(feature = "serde")]
+    /// Pick the rules from how recent games went.
+    #[arg(long)]
+    adaptive: bool,
+    #[cfg(feature = "serde")]
+    /// The easiest rules --adaptive picks, as PEGSxCOLORS.
+    #[arg(long, requires = "adaptive", value_name = "SETTINGS")]
+    adaptive_floor: Option<Settings>,
+    #[cfg(feature = "serde")]
+    /// The hardest rules --adaptive picks, as PEGSxCOLORS.
+    #[arg(long, requires = "adaptive", value_name = "SETTINGS")]
+    adaptive_ceiling: Option<Settings>,
+    /// Play N boards at once, each with its own code, with the same guesses.
+    #[arg(long, value_name = "N")]
+    boards: Option<usize>,
+    #[cfg(feature = "serde")]
+    /// Print the best scores instead of playing.
+    #[arg(long)]
+    high_scores: bool,
+    /// Print every valid code instead of playing.
+    #[arg(long)]
+    print_all_codes: bool,
+    #[cfg(feature = "serde")]
+    /// Print the codes as JSON objects.
+    #[arg(long, alias = "json-output", requires = "print_all_codes")]
+    json: bool,
+    /// Rate how hard --hidden-code, or a random code, is to crack.
+    #[arg(long)]
+    difficulty_score: bool,
+    /// Show how the scores of CODE would split the possible codes.
+    #[arg(long, value_name = "CODE")]
+    what_if: Option<String>,
+    #[cfg(feature = "serde")]
+    /// Show a game exported by a Mastermind website.
+    #[arg(long, value_name = "FILE")]
+    import: Option<String>,
+    /// Play the solver against every code and report how it did.
+    #[arg(long)]
+    analyze: bool,
+    /// Fail if --analyze needs more than this many guesses for any code.
+    #[arg(long, requires = "analyze", value_name = "GUESSES")]
+    assert_max: Option<usize>,
+    /// Let the solver guess a code you think of, scoring its guesses.
+    #[arg(long)]
+    guesser: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[cfg(feature = "serde")]
+    /// Play the daily puzzle, the same for everyone.
+    Daily(DailyArgs),
+    /// Share a puzzle as a challenge code, or play one.
+    #[command(subcommand)]
+    Challenge(ChallengeCommand),
+    /// Write every guess down first, then name the code.
+    Static(StaticArgs),
+    /// Crack as many codes as you can before time runs out.
+    Timetrial(TimeTrialArgs),
+    #[cfg(feature = "server")]
+    /// Host a race over the network.
+    Host(HostArgs),
+    #[cfg(feature = "server")]
+    /// Race in a game someone else is hosting.
+    Join(JoinArgs),
+    #[cfg(feature = "serde")]
+    /// Show recent games and streaks.
+    Stats {
+        #[command(subcommand)]
+        command: Option<StatsCommand>,
+    },
+    #[cfg(feature = "serde")]
+    /// List player profiles.
+    Players,
+    #[cfg(feature = "serde")]
+    /// Summarize a directory of transcripts.
+    Report(ReportArgs),
+    #[cfg(feature = "serde")]
+    /// Review a saved game round by round.
+    AnalyzeTranscript(AnalyzeTranscriptArgs),
+    /// Rank the best first guesses.
+    AnalyzeOpenings(AnalyzeOpeningsArgs),
+    /// Play every strategy against every code.
+    CompareStrategies(CompareStrategiesArgs),
+    /// List the color themes.
+    Themes,
+    #[cfg(feature = "bench")]
+    /// Time the engine on built-in workloads.
+    Bench(BenchArgs),
+}
+
+#[cfg(feature = "serde")]
+#[derive(Args)]
+struct DailyArgs {
+    /// Play the puzzle of an earlier day, as YYYY-MM-DD.
+    #[arg(long)]
+    date: Option<NaiveDate>,
+    /// Show which recent puzzles have been played instead.
+    #[arg(long, conflicts_with = "date")]
+    list: bool,
+    #[command(flatten)]
+    session: SessionArgs,
+}
+
+#[derive(Subcommand)]
+enum ChallengeCommand {
+    /// Print a challenge code for --hidden-code, or a random code.
+    Create {
+        /// The code to challenge others with.
+        #[arg(long, value_name = "CODE")]
+        hidden_code: Option<String>,
+    },
+    /// Play the puzzle a challenge code stands for.
+    Play {
+        /// The challenge code.
+        code: String,
+        #[command(flatten)]
+        session: SessionArgs,
+    },
+}
+
+#[derive(Args)]
+struct StaticArgs {
+    /// Play against this code instead of a random one.
+    #[arg(long, value_name = "CODE")]
+    hidden_code: Option<String>,
+    /// Type in this many guesses.
+    #[arg(long, value_name = "N")]
+    guesses: Option<usize>,
+    /// Read the guesses from FILE, one per line.
+    #[arg(long, value_name = "FILE")]
+    guesses_file: Option<String>,
+}
+
+#[derive(Args)]
+struct TimeTrialArgs {
+    /// How long the trial lasts.
+    #[arg(long, default_value_t = 3)]
+    minutes: u64,
+}
+
+#[cfg(feature = "server")]
+#[derive(Args)]
+struct HostArgs {
+    /// The port to listen on.
+    #[arg(long, default_value_t = DEFAULT_PORT)]
+    port: u16,
+    /// How many players to wait for.
+    #[arg(long, default_value_t = 2)]
+    racers: usize,
+    /// How many seconds a dropped player has to reconnect.
+    #[arg(long, value_name = "SECONDS")]
+    grace: Option<u64>,
+}
+
+#[cfg(feature = "server")]
+#[derive(Args)]
+struct JoinArgs {
+    /// The host's address, as HOST:PORT.
+    address: String,
+    /// Take back a place after losing the connection.
+    #[arg(long, value_name = "TOKEN")]
+    resume: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Subcommand)]
+enum StatsCommand {
+    /// Write every recorded game to a file.
+    Export {
+        /// The file format.
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+        /// The file to write.
+        #[arg(long, value_name = "FILE")]
+        out: PathBuf,
+    },
+}
+
+#[cfg(feature = "serde")]
+/// The formats `stats export` writes.
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Args)]
+struct ReportArgs {
+    /// The directory of transcripts.
+    dir: PathBuf,
+    /// Only count games played on or after this date.
+    #[arg(long)]
+    since: Option<NaiveDate>,
+    /// Only count games played on or before this date.
+    #[arg(long)]
+    until: Option<NaiveDate>,
+    /// Print the report as JSON.
+    #[arg(long, alias = "json-output")]
+    json: bool,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Args)]
+struct AnalyzeTranscriptArgs {
+    /// The saved game.
+    file: PathBuf,
+    /// Refuse a save whose scores do not match the rules.
+    #[arg(long, conflicts_with = "migrate")]
+    strict_load: bool,
+    /// Rescore a save made under older rules, and write it back.
+    #[arg(long)]
+    migrate: bool,
+}
+
+#[derive(Args)]
+struct AnalyzeOpeningsArgs {
+    #[command(flatten)]
+    solver: SolverArgs,
+    /// How many openings to list.
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+    /// Also play each opening out against every code.
+    #[arg(long)]
+    deep: bool,
+    /// Print CSV instead of a table.
+    #[arg(long)]
+    csv: bool,
+}
+
+#[derive(Args)]
+struct CompareStrategiesArgs {
+    #[command(flatten)]
+    solver: SolverArgs,
+}
 
-/// The Result type for mastermind.
-pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+#[cfg(feature = "bench")]
+#[derive(Args)]
+struct BenchArgs {
+    /// How many times to run each workload.
+    #[arg(long, default_value_t = 5)]
+    iterations: usize,
+    /// Compare against the medians saved in FILE.
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+    /// Save the medians to FILE.
+    #[arg(long, value_name = "FILE")]
+    save_baseline: Option<PathBuf>,
+}
 
-#[derive(Clone, Eq, PartialEq, Hash)]
-enum Color {
-    Red,
-    Green,
-    Blue,
-    Yellow,
+#[cfg(feature = "serde")]
+/// Checks a `--player` name, which becomes a directory name.
+fn player_name(name: &str) -> std::result::Result<String, String> {
+    if is_valid_player_name(name) {
+        Ok(name.to_string())
+    } else {
+        Err(String::from(
+            "player names may only use letters, digits, '-' and '_'",
+        ))
+    }
 }
 
-impl fmt::Display for Color {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let c = match self {
-            Color::Red => "r".red(),
-            Color::Green => "g".green(),
-            Color::Blue => "b".blue(),
-            Color::Yellow => "y".yellow(),
+impl CommonArgs {
+    fn output_level(&self) -> OutputLevel {
+        if self.quiet {
+            OutputLevel::Quiet
+        } else if self.verbose {
+            OutputLevel::Verbose
+        } else {
+            OutputLevel::default()
+        }
+    }
+
+    fn max_line_length(&self) -> usize {
+        self.max_line_length.unwrap_or(DEFAULT_MAX_LINE_LENGTH)
+    }
+
+    /// Returns the `--player` given; without `serde` there are no profiles.
+    fn player(&self) -> Option<&str> {
+        #[cfg(feature = "serde")]
+        return self.player.as_deref();
+        #[cfg(not(feature = "serde"))]
+        None
+    }
+
+    /// Returns the `--seed` given, or a fresh one.
+    fn seed(&self) -> u64 {
+        self.seed.unwrap_or_else(Rng::entropy_seed)
+    }
+
+    /// Builds the game settings the flags ask for, with the rules `session`
+    /// changes.
+    fn config(&self, session: &SessionArgs) -> Result<GameConfig> {
+        let mut config = GameConfig {
+            output_level: self.output_level(),
+            coach: session.coach,
+            explain: session.explain,
+            anagram_mode: session.anagram_mode,
+            max_guesses: self.max_guesses,
+            assists: !session.no_assists,
+            cheat_mode: session.cheat,
+            display_mode: session.display_mode.unwrap_or_default(),
+            ..match self.preset.as_deref() {
+                Some("super") => GameConfig::super_mastermind(),
+                _ => GameConfig::default(),
+            }
         };
-        write!(f, "{}", c)
+        if let Some(length) = self.code_length {
+            if !(1..=MAX_CODE_LENGTH).contains(&length) {
+                return Err(format!("--length must be from 1 to {}", MAX_CODE_LENGTH).into());
+            }
+            config.code_length = length;
+        }
+        if let Some(letters) = &self.palette {
+            config.palette = Palette::from_letters(letters)?;
+            config.colors = config.palette.values();
+        }
+        if let Some(count) = self.colors {
+            if !(1..=config.colors.len()).contains(&count) {
+                return Err(format!("--colors must be from 1 to {}", config.colors.len()).into());
+            }
+            config.colors.truncate(count);
+        }
+        if self.max_guesses == Some(0) {
+            return Err("--max-guesses must be at least 1".into());
+        }
+        if session.guess_timeout == Some(0) {
+            return Err("--guess-timeout must be at least 1 second".into());
+        }
+        // Solvers and candidate lists count every code, so there must be few
+        // enough to count.
+        LazyCodeSpace::try_new(&config)?;
+        Ok(config)
+    }
+}
+
+impl SolverArgs {
+    /// Parses `--first-guess` under `config`.
+    fn first_guess(&self, config: &GameConfig) -> Result<Option<Code>> {
+        self.first_guess
+            .as_ref()
+            .map(|code| Code::parse(code.clone(), config))
+            .transpose()
     }
 }
 
-#[derive(Clone)]
-struct Code {
-    positional: [Color; 4],
-    set: HashSet<Color>,
+#[cfg(feature = "serde")]
+/// Picks how a save is checked from `--strict-load` and `--migrate`.
+fn score_check(strict_load: bool, migrate: bool) -> ScoreCheck {
+    match (strict_load, migrate) {
+        (true, _) => ScoreCheck::Strict,
+        (false, true) => ScoreCheck::Migrate,
+        (false, false) => ScoreCheck::Warn,
+    }
 }
 
-impl Code {
-    fn score(&self, other: Code) -> Score {
-        let score: Vec<ScoreDetail> = self
-            .positional
-            .iter()
-            .zip(other.positional.iter())
-            .map(|(s, o)| {
-                if s == o {
-                    ScoreDetail::ColorAndPositionCorrect
-                } else if self.set.contains(o) {
-                    ScoreDetail::ColorCorrect
-                } else {
-                    ScoreDetail::Empty
-                }
-            })
-            .collect();
-        Score(
-            score[0].clone(),
-            score[1].clone(),
-            score[2].clone(),
-            score[3].clone(),
-        )
+#[cfg(not(feature = "async"))]
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Plays on a blocking thread, which waits on the runtime only for guesses
+/// with a time limit.
+#[cfg(feature = "async")]
+#[tokio::main]
+async fn main() {
+    match tokio::task::spawn_blocking(|| run().map_err(|e| e.to_string())).await {
+        // Exits without dropping the runtime, which would wait for a read of
+        // stdin left behind by a guess that ran out of time.
+        Ok(Ok(())) => std::process::exit(0),
+        Ok(Err(e)) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
     }
 }
 
-impl fmt::Display for Code {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{} {} {} {}",
-            self.positional[0], self.positional[1], self.positional[2], self.positional[3]
+fn run() -> Result<()> {
+    let bare = std::env::args().len() == 1;
+    let cli = Cli::parse();
+    let common = &cli.common;
+    let color = if common.no_color {
+        ColorChoice::Never
+    } else {
+        common.color.unwrap_or_default()
+    };
+    let no_color = std::env::var("NO_COLOR").ok();
+    set_color(color.resolve(std::io::stdout().is_terminal(), no_color.as_deref()));
+    if common.width == Some(0) {
+        return Err("--width must be at least 1".into());
+    }
+    set_width(
+        common
+            .width
+            .or_else(terminal_width)
+            .unwrap_or(DEFAULT_WIDTH),
+    );
+    if let Some(theme) = common.theme {
+        set_theme(theme);
+    }
+    #[cfg(feature = "serde")]
+    if let Some(cap) = common.history_cap {
+        if cap == 0 {
+            return Err("--history-cap must be at least 1".into());
+        }
+        set_history_cap(cap);
+    }
+    let player = common.player();
+
+    let Some(command) = &cli.command else {
+        return run_default(common, &cli.play, bare);
+    };
+    let no_session = SessionArgs::default();
+    match command {
+        #[cfg(feature = "serde")]
+        Command::Daily(daily) if daily.list => print_daily_calendar(player),
+        #[cfg(feature = "serde")]
+        Command::Daily(daily) => {
+            let config = common.config(&daily.session)?;
+            let date = daily.date.unwrap_or_else(date::today);
+            play_daily(common, &daily.session, config, date)
+        }
+        Command::Challenge(ChallengeCommand::Create { hidden_code }) => {
+            let config = common.config(&no_session)?;
+            print_challenge(&config, hidden_code.as_deref(), common.seed())
+        }
+        Command::Challenge(ChallengeCommand::Play { code, session }) => {
+            let config = common.config(session)?;
+            play_challenge(common, session, config, code)
+        }
+        Command::Static(args) => {
+            let config = common.config(&no_session)?;
+            play_static_game(&config, args, common.seed(), player)
+        }
+        Command::Timetrial(args) => {
+            let config = common.config(&no_session)?;
+            play_time_trial(
+                &config,
+                args.minutes,
+                common.max_line_length(),
+                common.seed(),
+                player,
+            )
+        }
+        #[cfg(feature = "server")]
+        Command::Host(args) => {
+            let config = common.config(&no_session)?;
+            let grace = args.grace.map_or(DEFAULT_GRACE, Duration::from_secs);
+            host_game(
+                &config,
+                args.port,
+                args.racers,
+                grace,
+                common.max_line_length(),
+                common.seed(),
+            )
+        }
+        #[cfg(feature = "server")]
+        Command::Join(args) => join_game(
+            &args.address,
+            args.resume.as_deref(),
+            common.max_line_length(),
+        ),
+        #[cfg(feature = "serde")]
+        Command::Stats { command: None } => print_stats(player),
+        #[cfg(feature = "serde")]
+        Command::Stats {
+            command: Some(StatsCommand::Export { format, out }),
+        } => export_history(out, *format, player),
+        #[cfg(feature = "serde")]
+        Command::Players => print_players(),
+        #[cfg(feature = "serde")]
+        Command::Report(args) => {
+            let (transcripts, unreadable) = read_transcripts(&args.dir, args.since, args.until)?;
+            let report = Report::new(&transcripts, unreadable);
+            if args.json {
+                println!("{}", serde_json::to_string(&report)?);
+            } else {
+                report.write_text(&mut std::io::stdout())?;
+            }
+            Ok(())
+        }
+        #[cfg(feature = "serde")]
+        Command::AnalyzeTranscript(args) => {
+            let check = score_check(args.strict_load, args.migrate);
+            let board = load_game(&args.file, check, &mut std::io::stdout())?;
+            print_review(&board);
+            Ok(())
+        }
+        Command::AnalyzeOpenings(args) => {
+            let config = common.config(&no_session)?;
+            let strategy = strategy(&args.solver.strategy)?;
+            print_openings(
+                &config,
+                strategy,
+                args.solver.deterministic,
+                args.top,
+                args.deep,
+                args.csv,
+            );
+            Ok(())
+        }
+        Command::CompareStrategies(args) => {
+            let config = common.config(&no_session)?;
+            let first_guess = args.solver.first_guess(&config)?;
+            print_strategies(&config, first_guess.as_ref(), args.solver.deterministic);
+            Ok(())
+        }
+        Command::Themes => {
+            print_themes();
+            Ok(())
+        }
+        #[cfg(feature = "bench")]
+        Command::Bench(args) => run_bench(args),
+    }
+}
+
+/// Runs the one-off task a flag asks for, or else plays a game.
+fn run_default(common: &CommonArgs, play: &PlayArgs, bare: bool) -> Result<()> {
+    let config = common.config(&play.session)?;
+    if play.print_all_codes {
+        #[cfg(feature = "serde")]
+        if play.json {
+            return print_all_codes_json(&config);
+        }
+        return print_all_codes(&config);
+    }
+    #[cfg(feature = "serde")]
+    if play.high_scores {
+        return print_high_scores(common.player());
+    }
+    #[cfg(feature = "serde")]
+    if let Some(path) = &play.import {
+        return print_import(&config, Path::new(path));
+    }
+    if let Some(code) = &play.what_if {
+        return print_what_if(&config, code);
+    }
+    let first_guess = play.solver.first_guess(&config)?;
+    if play.analyze {
+        let strategy = strategy(&play.solver.strategy)?;
+        return print_analysis(
+            &config,
+            strategy,
+            play.solver.deterministic,
+            first_guess.as_ref(),
+            play.assert_max,
+        );
+    }
+    if play.guesser {
+        return guess_players_code(&config, &play.solver, first_guess);
+    }
+    if play.difficulty_score {
+        return print_difficulty(&config, play.hidden_code.as_deref(), common.seed());
+    }
+    if let Some(count) = play.boards {
+        return play_boards(config, count, common.seed());
+    }
+    play_game(common, play, config, bare)
+}
+
+/// Shows the game exported by a Mastermind website at `path`.
+#[cfg(feature = "serde")]
+fn print_import(config: &GameConfig, path: &Path) -> Result<()> {
+    let board = Board::from_wordle_json(&std::fs::read_to_string(path)?, config)?;
+    board.print()?;
+    match board.last_round() {
+        Some(round) if round.wins() => println!("solved in {} guesses", board.rounds().len()),
+        _ => println!(
+            "unsolved, the code was {}",
+            board.hidden_code().display(&config.palette)
+        ),
+    }
+    Ok(())
+}
+
+/// Shows how the scores of `code` split every code under `config`.
+fn print_what_if(config: &GameConfig, code: &str) -> Result<()> {
+    if !config.assists {
+        return Err("--what-if is an assist, and is disabled by --no-assists".into());
+    }
+    let code = Code::parse(code.to_string(), config)?;
+    let space = LazyCodeSpace::new(config).collect::<Vec<_>>();
+    println!("{}", describe_buckets(&score_buckets(&code, &space)));
+    Ok(())
+}
+
+/// Lets the solver crack a code the player thinks of, asking for the score
+/// of each guess.
+fn guess_players_code(
+    config: &GameConfig,
+    solver: &SolverArgs,
+    first_guess: Option<Code>,
+) -> Result<()> {
+    let strategy = strategy(&solver.strategy)?;
+    let stdin = std::io::stdin();
+    let outcome = run_guesser(&mut stdin.lock(), &mut std::io::stdout(), config, || {
+        OpeningSolver::new(
+            new_solver(&strategy, config, solver.deterministic),
+            first_guess.clone(),
         )
+    })?;
+    if let GuesserOutcome::Solved(guesses) = outcome {
+        println!("solved in {} guesses", guesses);
     }
+    Ok(())
 }
 
-impl TryFrom<String> for Code {
-    type Error = Box<dyn std::error::Error>;
+/// A board ready to play, with what finishing it has to record.
+struct Game {
+    board: Board,
+    started_at: u64,
+    /// The seed the hidden code was picked with, if it was picked at random.
+    random_seed: Option<u64>,
+    /// The day of the daily puzzle being played.
+    #[cfg(feature = "serde")]
+    daily_date: Option<NaiveDate>,
+    /// Where the game is saved after every guess.
+    #[cfg(feature = "serde")]
+    recovery: Option<RecoveryFile>,
+    /// The profile of the player guessing, in a duel, in place of `--player`.
+    #[cfg(feature = "serde")]
+    breaker: Option<String>,
+    /// The profile of the player who chose the code, in a duel.
+    #[cfg(feature = "serde")]
+    maker: Option<String>,
+}
 
-    fn try_from(s: String) -> Result<Self> {
-        let mut set = HashSet::new();
-        let pos: Vec<Color> = s
-            .chars()
-            .filter_map(|c| match c {
-                '(' | ')' | ',' => None,
-                'r' => {
-                    set.insert(Color::Red);
-                    Some(Color::Red)
-                }
-                'g' => {
-                    set.insert(Color::Green);
-                    Some(Color::Green)
-                }
-                'b' => {
-                    set.insert(Color::Blue);
-                    Some(Color::Blue)
-                }
-                'y' => {
-                    set.insert(Color::Yellow);
-                    Some(Color::Yellow)
-                }
-                _ => None,
-            })
-            .collect();
-        match pos.len() {
-            x if x < 4 => return Err(String::from("not enough characters").into()),
-            x if x > 4 => return Err(String::from("too many characters").into()),
-            _ => (),
-        }
-        Ok(Self {
-            positional: [
-                pos[0].clone(),
-                pos[1].clone(),
-                pos[2].clone(),
-                pos[3].clone(),
-            ],
-            set,
-        })
+impl Game {
+    fn new(board: Board) -> Game {
+        Game {
+            board,
+            started_at: history::now(),
+            random_seed: None,
+            #[cfg(feature = "serde")]
+            daily_date: None,
+            #[cfg(feature = "serde")]
+            recovery: None,
+            #[cfg(feature = "serde")]
+            breaker: None,
+            #[cfg(feature = "serde")]
+            maker: None,
+        }
+    }
+
+    /// Records the game to `maker`'s profile as well as the player's, with
+    /// `breaker`'s in place of the player's when given.
+    #[cfg(feature = "serde")]
+    fn duel(self, maker: Option<String>, breaker: Option<String>) -> Game {
+        Game {
+            maker,
+            breaker,
+            ..self
+        }
+    }
+
+    /// Starts a game against a code nobody has seen, chosen using `seed`.
+    fn random(config: GameConfig, seed: u64, no_prompt: bool) -> Game {
+        Game {
+            random_seed: Some(seed),
+            ..Game::new(random_board(config, seed, no_prompt))
+        }
     }
 }
 
-struct Board {
-    hidden_code: Code,
-    rounds: Vec<Round>,
+/// Plays the game the flags ask for when no command is given: a game left
+/// unfinished, a saved one, or a new one against a code from the flags, the
+/// menu or the player at the keyboard.
+fn play_game(common: &CommonArgs, play: &PlayArgs, config: GameConfig, bare: bool) -> Result<()> {
+    let session = &play.session;
+    #[cfg(feature = "serde")]
+    let player = common.player();
+    let seed = common.seed();
+    #[cfg(feature = "serde")]
+    let config = if play.adaptive {
+        adaptive_config(config, play, player)?
+    } else {
+        config
+    };
+    #[cfg(feature = "serde")]
+    let loading = play.load.is_some();
+    #[cfg(not(feature = "serde"))]
+    let loading = false;
+    let adversarial = play.codemaker.as_deref() == Some("adversarial");
+    if adversarial
+        && (loading
+            || play.hidden_code.is_some()
+            || play.hidden_code_env.is_some()
+            || session.cheat)
+    {
+        return Err(
+            "--codemaker adversarial picks its own code, and cannot be used with a \
+                    set code, --load or --cheat"
+                .into(),
+        );
+    }
+    #[cfg(feature = "serde")]
+    {
+        // A game left behind by a run that died is offered back before
+        // starting another, unless this run asks for a particular game.
+        let chosen = loading || adversarial || play.duel.is_some();
+        if !chosen && (play.recover || std::io::stdin().is_terminal()) {
+            if let Some((board, file)) = offer_recovery(player)? {
+                let game = Game {
+                    recovery: Some(file),
+                    ..Game::new(board)
+                };
+                return play_to_the_end(common, session, game, seed);
+            }
+        }
+        if let Some(path) = &play.load {
+            let check = score_check(play.strict_load, play.migrate);
+            let board = load_game(Path::new(path), check, &mut std::io::stdout())?;
+            return play_to_the_end(common, session, Game::new(board), seed);
+        }
+    }
+    let game = if adversarial {
+        let codemaker = AdversarialCodemaker::new(&config);
+        let mut board = start_board(config, codemaker.hidden(), session.no_prompt);
+        board.set_codemaker(Box::new(codemaker));
+        Game::new(board)
+    } else if bare && std::io::stdin().is_terminal() {
+        let stdin = std::io::stdin();
+        match run_menu(&mut stdin.lock(), &mut std::io::stdout(), config)? {
+            MenuChoice::Random(config) => Game::random(config, seed, session.no_prompt),
+            #[cfg(feature = "serde")]
+            MenuChoice::Daily(config) => {
+                return play_daily(common, session, config, date::today());
+            }
+            #[cfg(feature = "serde")]
+            MenuChoice::Stats => return print_stats(player),
+            #[cfg(not(feature = "serde"))]
+            MenuChoice::Daily(_) | MenuChoice::Stats => {
+                return Err("this build keeps no records, rebuild with --features serde".into());
+            }
+            #[cfg(feature = "serde")]
+            MenuChoice::Duel {
+                config,
+                maker,
+                breaker,
+            } => Game::new(Board::init(config)?).duel(maker, breaker),
+            #[cfg(not(feature = "serde"))]
+            MenuChoice::Duel { config, .. } => Game::new(Board::init(config)?),
+            MenuChoice::Quit => return Ok(()),
+        }
+    } else if let Some(hidden) = &play.hidden_code {
+        let hidden = Code::parse(hidden.clone(), &config)?;
+        Game::new(start_board(config, hidden, session.no_prompt))
+    } else if let Some(var) = &play.hidden_code_env {
+        let hidden = Code::from_env(var, &config)?;
+        Game::new(start_board(config, hidden, session.no_prompt))
+    } else if play.random {
+        Game::random(config, seed, session.no_prompt)
+    } else {
+        Game::new(Board::init(config)?)
+    };
+    #[cfg(feature = "serde")]
+    let game = match play.duel.as_deref() {
+        Some([maker, breaker]) => game.duel(Some(maker.clone()), Some(breaker.clone())),
+        _ => game,
+    };
+    play_to_the_end(common, session, game, seed)
 }
 
-impl Board {
-    fn get_input(&mut self) -> Result<bool> {
-        println!("{}", &self);
-        let mut buffer = String::new();
+/// Plays the daily puzzle for `date`.
+#[cfg(feature = "serde")]
+fn play_daily(
+    common: &CommonArgs,
+    session: &SessionArgs,
+    config: GameConfig,
+    date: NaiveDate,
+) -> Result<()> {
+    let player = common.player();
+    let board = daily_board(config, date, player, session.no_prompt)?;
+    let game = Game {
+        daily_date: Some(date),
+        ..Game::new(board)
+    };
+    play_to_the_end(common, session, game, common.seed())
+}
 
-        print!("guess: ");
-        std::io::stdout().flush()?;
-        std::io::stdin().read_line(&mut buffer)?;
+/// Plays the puzzle the challenge code `code` stands for, with the rules it
+/// carries and the rest of `config`.
+fn play_challenge(
+    common: &CommonArgs,
+    session: &SessionArgs,
+    config: GameConfig,
+    code: &str,
+) -> Result<()> {
+    let challenge = Challenge::decode(code)?;
+    let config = GameConfig {
+        colors: challenge.config.colors,
+        code_length: challenge.config.code_length,
+        max_guesses: challenge.config.max_guesses,
+        allow_duplicates: challenge.config.allow_duplicates,
+        anagram_mode: challenge.config.anagram_mode,
+        ..config
+    };
+    let board = start_board(config, challenge.hidden, session.no_prompt);
+    play_to_the_end(common, session, Game::new(board), common.seed())
+}
 
-        let code: Code = buffer.try_into()?;
-        let round = Round {
-            input_code: code.clone(),
-            score: self.hidden_code.score(code),
+/// Sets `board` up to show rounds the way `session` asks.
+fn configure_board(board: &mut Board, common: &CommonArgs, session: &SessionArgs) -> Result<()> {
+    let formatter = session
+        .format
+        .as_deref()
+        .map(OutputFormatter::new)
+        .transpose()?;
+    board.set_output_format(formatter);
+    board.set_max_line_length(common.max_line_length());
+    board.set_diff_guesses(session.diff_guesses);
+    board.set_show_position_candidates(session.show_position_candidates);
+    board.set_show_candidates(session.show_candidates);
+    board.set_no_prompt(session.no_prompt);
+    let width = common.width.or_else(terminal_width);
+    if session.progress || session.progress_scale.is_some() {
+        board.set_progress(Some(session.progress_scale.unwrap_or_default()), width);
+    }
+    if session.wide {
+        board.set_wide(Some(width.unwrap_or(0).max(WIDE_MIN_WIDTH)));
+    } else {
+        board.set_wide(width.filter(|w| *w > 80));
+    }
+    #[cfg(feature = "serde")]
+    if let Some(path) = &session.events {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        board.set_event_sink(Some(Box::new(JsonLinesSink::new(file))));
+    }
+    Ok(())
+}
+
+/// Keeps the game in progress saved: for Ctrl-C, and with `--autosave` after
+/// every round too.
+#[cfg(feature = "serde")]
+struct Saves {
+    autosave: Autosave,
+    recovery: Option<RecoveryFile>,
+}
+
+#[cfg(feature = "serde")]
+impl Saves {
+    /// Starts saving `board`, to `recovery` if it is already kept in a
+    /// recovery file, or to a new one named by `started_at` and `seed` with
+    /// `--autosave`.
+    fn start(
+        board: &Board,
+        recovery: Option<RecoveryFile>,
+        autosave: bool,
+        player: Option<&str>,
+        started_at: u64,
+        seed: u64,
+    ) -> Result<Saves> {
+        let saves = Saves {
+            autosave: Autosave::new(),
+            recovery: recovery.or_else(|| {
+                autosave
+                    .then(|| recovery_dir(player))
+                    .flatten()
+                    .map(|dir| RecoveryFile::new(&dir, &game_id(started_at, seed)))
+            }),
         };
+        if let Some(path) = autosave_path(player) {
+            saves.autosave.install(path)?;
+        }
+        saves.update(board)?;
+        Ok(saves)
+    }
 
-        self.rounds.push(round);
+    fn update(&self, board: &Board) -> Result<()> {
+        self.autosave.update(board);
+        if let Some(file) = &self.recovery {
+            file.save(board)?;
+        }
+        Ok(())
+    }
 
-        match self.rounds.last() {
-            Some(rs) => Ok(rs.wins()),
-            None => Ok(false),
+    /// Forgets the game, which is over.
+    fn finish(&self) -> Result<()> {
+        self.autosave.clear();
+        if let Some(file) = &self.recovery {
+            file.remove()?;
         }
+        Ok(())
     }
+}
 
-    fn init() -> Result<Self> {
-        let mut buffer = String::new();
-        println!(" to begin you will need to input hidden code.");
-        println!(
-            " codes can be one of four letters:\n {} {} {} {}",
-            "r".red(),
-            "g".green(),
-            "b".blue(),
-            "y".yellow()
-        );
-        print!("hidden code: ");
-        std::io::stdout().flush()?;
+/// Reads guesses until `game` is won, lost or given up, then records how it
+/// went. `seed` names the recovery file when `--autosave` starts one.
+#[cfg_attr(not(feature = "serde"), allow(unused_variables))]
+fn play_to_the_end(
+    common: &CommonArgs,
+    session: &SessionArgs,
+    game: Game,
+    seed: u64,
+) -> Result<()> {
+    let mut board = game.board;
+    let started_at = game.started_at;
+    #[cfg(feature = "serde")]
+    let player = game.breaker.as_deref().or(common.player());
+    #[cfg(not(feature = "serde"))]
+    let player = common.player();
+    #[cfg(feature = "serde")]
+    let maker = game.maker.as_deref();
+    #[cfg(not(feature = "serde"))]
+    let maker = None;
+    configure_board(&mut board, common, session)?;
+    board.emit(GameEvent::GameStarted {
+        config: board.config().clone(),
+        hidden_code: board.hidden_code().clone(),
+        seed: game.random_seed,
+    });
+    #[cfg(feature = "serde")]
+    let saves = Saves::start(
+        &board,
+        game.recovery,
+        session.autosave,
+        player,
+        started_at,
+        seed,
+    )?;
 
-        terminal::enable_raw_mode()?;
-        while let Event::Key(KeyEvent { code, .. }) = event::read()? {
-            match code {
-                KeyCode::Enter => {
-                    break;
-                }
-                KeyCode::Char(c) => {
-                    buffer.push(c);
+    let show_board = |board: &Board| {
+        board.output_format().is_none()
+            && common.output_level() > OutputLevel::Quiet
+            && !session.no_prompt
+    };
+    // With a time limit per guess, input is read on its own thread so waiting
+    // for it can be given up.
+    #[cfg(not(feature = "async"))]
+    let timed_lines = session.guess_timeout.map(|_| {
+        spawn_line_reader(
+            std::io::BufReader::new(std::io::stdin()),
+            common.max_line_length(),
+        )
+    });
+    let guess_timeout = session.guess_timeout.map(Duration::from_secs);
+    let outcome = loop {
+        #[cfg(not(feature = "async"))]
+        let input = match &timed_lines {
+            Some(lines) => board.get_input_timeout(lines, guess_timeout),
+            None => board.get_input(),
+        };
+        #[cfg(feature = "async")]
+        let input = match guess_timeout {
+            Some(timeout) => Handle::current()
+                .block_on(board.get_input_async(Some(timeout)))
+                .map(|state| state.won),
+            None => board.get_input(),
+        };
+        let won = match input {
+            Ok(won) => won,
+            Err(e) => {
+                #[cfg(feature = "serde")]
+                saves.finish()?;
+                // Leaving mid-game, say by closing input, breaks any streak,
+                // even when every guess used so far was skipped.
+                if board.guesses_used() > 0 {
+                    record_history(&board, started_at, Outcome::Abandoned, player, maker)?;
                 }
-                _ => {}
+                return Err(e);
             }
+        };
+        #[cfg(feature = "serde")]
+        saves.update(&board)?;
+        if won {
+            break Outcome::Won;
         }
-        terminal::disable_raw_mode()?;
+        if board.is_forfeit() {
+            break Outcome::Forfeit;
+        }
+        if Some(board.guesses_used()) == board.config().max_guesses {
+            break Outcome::Lost;
+        }
+    };
+
+    #[cfg(feature = "serde")]
+    saves.finish()?;
+    if outcome != Outcome::Forfeit && show_board(&board) {
+        board.print()?;
+    }
+    let palette = &board.config().palette;
+    match outcome {
+        Outcome::Won => println!("congratulations, you win!"),
+        Outcome::Forfeit => println!(
+            "you forfeit, the code was {}",
+            board.hidden_code().display(palette)
+        ),
+        _ => {
+            println!(
+                "out of guesses, the code was {}",
+                board.hidden_code().display(palette)
+            );
+            if let Some(best) = board.best_round() {
+                println!(
+                    "closest guess: {} ({}b{}w)",
+                    best.guess().display(palette),
+                    best.score().black_count(),
+                    best.score().white_count()
+                );
+            }
+        }
+    }
+    board.emit(GameEvent::GameOver {
+        outcome,
+        guesses: board.guesses_used(),
+    });
+    record_history(&board, started_at, outcome, player, maker)?;
+    #[cfg(feature = "serde")]
+    if let Some(date) = game.daily_date {
+        record_daily(&board, date, outcome, player)?;
+    }
+    if session.analysis {
+        print_review(&board);
+    }
+    if outcome == Outcome::Won {
+        #[cfg(not(feature = "async"))]
+        let mut timed_input = timed_lines;
+        #[cfg(feature = "async")]
+        let mut timed_input = board.take_async_input();
+        record_high_score(&board, player, timed_input.as_mut())?;
+    }
+    Ok(())
+}
+
+/// Writes every game in `player`'s history to `path` in `format`.
+#[cfg(feature = "serde")]
+fn export_history(path: &Path, format: ExportFormat, player: Option<&str>) -> Result<()> {
+    let history = History::default_path(player).ok_or("cannot find a data directory")?;
+    let sessions = History::open(&history).sessions()?;
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    match format {
+        ExportFormat::Csv => write_csv(&sessions, &mut file)?,
+        ExportFormat::Json => write_json(&sessions, &mut file)?,
+    }
+    file.flush()?;
+    println!("exported {} games to {}", sessions.len(), path.display());
+    Ok(())
+}
+
+/// Lists every player profile.
+#[cfg(feature = "serde")]
+fn print_players() -> Result<()> {
+    let players = players()?;
+    if players.is_empty() {
+        println!("no player profiles yet");
+    }
+    for name in players {
+        println!("{}", name);
+    }
+    Ok(())
+}
+
+/// Prints the ten best scores for every code length and color count played.
+#[cfg(feature = "serde")]
+fn print_high_scores(player: Option<&str>) -> Result<()> {
+    let path = HighScoreBoard::default_path(player).ok_or("cannot find a data directory")?;
+    let scores = HighScoreBoard::load(&path)?;
+    let path = TimeTrialBoard::default_path(player).ok_or("cannot find a data directory")?;
+    let trials = TimeTrialBoard::load(&path)?;
+    if scores.scores().is_empty() && trials.scores().is_empty() {
+        println!("no high scores yet");
+    }
+    for (code_length, color_count) in scores.configs() {
+        println!("{} pegs, {} colors:", code_length, color_count);
+        for (rank, score) in scores.top(code_length, color_count, 10).iter().enumerate() {
+            println!(
+                "{:>3}. {:<16} {:>3} guesses  {}",
+                rank + 1,
+                score.name,
+                score.guesses,
+                score.date
+            );
+        }
+    }
 
-        println!("\n great.\n");
+    for (code_length, color_count, seconds) in trials.configs() {
         println!(
-            r#"score is represented with three different colors:\n
- correct color, correct position: {}
- correct color, wrong position: {}
- wrong color, wrong position: {}
- good luck!"#,
-            " ".on_cyan(),
-            " ".on_white(),
-            " ".on_red(),
+            "time trial, {}:{:02}, {} pegs, {} colors:",
+            seconds / 60,
+            seconds % 60,
+            code_length,
+            color_count
         );
+        for (rank, score) in trials
+            .top(code_length, color_count, seconds, 10)
+            .iter()
+            .enumerate()
+        {
+            println!(
+                "{:>3}. {:<16} {:>3} codes {:>4} guesses  {}",
+                rank + 1,
+                score.name,
+                score.solved,
+                score.guesses,
+                score.date
+            );
+        }
+    }
+    Ok(())
+}
 
-        Ok(Self {
-            hidden_code: buffer.try_into()?,
-            rounds: Vec::new(),
-        })
+/// Lists every valid code, one per line, drawn in the config's display mode.
+fn print_all_codes(config: &GameConfig) -> Result<()> {
+    let mut out = std::io::stdout().lock();
+    for code in LazyCodeSpace::new(config) {
+        let pegs: Vec<String> = code
+            .colors()
+            .iter()
+            .map(|color| color.glyph(&config.palette, config.display_mode))
+            .collect();
+        writeln!(out, "{}", pegs.join(" "))?;
+    }
+    Ok(())
+}
+
+/// Lists every valid code as JSON objects, one per line.
+#[cfg(feature = "serde")]
+fn print_all_codes_json(config: &GameConfig) -> Result<()> {
+    let mut out = std::io::stdout().lock();
+    for code in LazyCodeSpace::new(config) {
+        let object = serde_json::json!({ "code": code.letters(&config.palette) });
+        writeln!(out, "{}", object)?;
+    }
+    Ok(())
+}
+
+/// Builds `strategy`'s solver, wrapped in a `DeterministicSolver` for
+/// `--deterministic`.
+fn new_solver(strategy: &Strategy, config: &GameConfig, deterministic: bool) -> Box<dyn Solver> {
+    let solver = (strategy.new)(config);
+    if deterministic {
+        Box::new(DeterministicSolver::new(solver))
+    } else {
+        solver
     }
 }
 
-impl fmt::Display for Board {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut lines = Vec::new();
-        for round in &self.rounds {
-            let s = format!("| {} |", round);
-            lines.push(s);
+/// Plays `strategy` against every code and prints how many guesses it needed,
+/// failing if the worst case is more than `assert_max`.
+fn print_analysis(
+    config: &GameConfig,
+    strategy: Strategy,
+    deterministic: bool,
+    first_guess: Option<&Code>,
+    assert_max: Option<usize>,
+) -> Result<()> {
+    let report = analyze(config, first_guess, || {
+        new_solver(&strategy, config, deterministic)
+    });
+    match &report.first_guess {
+        Some(code) => println!("first guess: {}", code.display(&config.palette)),
+        None => println!("first guess: solver's choice"),
+    }
+    println!("games: {}", report.games);
+    println!("worst case: {} guesses", report.worst_case);
+    println!("average: {:.3} guesses", report.average);
+    if report.unsolved > 0 {
+        println!("unsolved: {}", report.unsolved);
+    }
+    if let Some(max) = assert_max {
+        if report.worst_case > max || report.unsolved > 0 {
+            return Err(format!(
+                "worst case of {} guesses exceeds --assert-max {}",
+                report.worst_case, max
+            )
+            .into());
         }
-        if self.rounds.len() > 0 {
-            write!(f, "\n{}\n", "=".repeat(21))?;
-            write!(f, "{}\n", lines.join("\n"))?;
-            write!(f, "{}\n", "=".repeat(21))?;
+    }
+    Ok(())
+}
+
+/// Prints every theme's name beside a swatch of its colors and key pegs, or
+/// only the names when color is off.
+fn print_themes() {
+    for theme in Theme::ALL {
+        if color_enabled() {
+            println!("{:<14} {}", theme.name(), swatch(theme));
+        } else {
+            println!("{}", theme.name());
         }
-        Ok(())
     }
 }
 
-#[derive(Clone)]
-enum ScoreDetail {
-    ColorCorrect,
-    ColorAndPositionCorrect,
-    Empty,
+/// Times the built-in workloads and prints their medians, compared against
+/// the baseline at `--baseline` if given, then saves them to
+/// `--save-baseline` if given.
+#[cfg(feature = "bench")]
+fn run_bench(args: &BenchArgs) -> Result<()> {
+    use mastermind::bench::{measure, report, workloads, Baseline};
+
+    let baseline = args
+        .baseline
+        .as_ref()
+        .map(|path| -> Result<Baseline> {
+            let saved = std::fs::read_to_string(path)?;
+            Ok(serde_json::from_str(&saved).map_err(GameError::from)?)
+        })
+        .transpose()?;
+    let measurements: Vec<_> = workloads()
+        .iter()
+        .map(|workload| measure(workload, 1, args.iterations))
+        .collect();
+    print!(
+        "{}",
+        report(&measurements, baseline.as_ref().map(|b| b.0.as_slice()))
+    );
+    if let Some(path) = &args.save_baseline {
+        std::fs::write(path, serde_json::to_string(&Baseline(measurements))?)?;
+    }
+    Ok(())
+}
+
+/// Plays every strategy against every code and prints how each did.
+fn print_strategies(config: &GameConfig, first_guess: Option<&Code>, deterministic: bool) {
+    println!(
+        "{:<14} {:>10} {:>8} {:>8}",
+        "strategy", "worst case", "average", "unsolved"
+    );
+    let strategies = strategies();
+    let reports = map_each(&strategies, |strategy| {
+        analyze(config, first_guess, || {
+            new_solver(strategy, config, deterministic)
+        })
+    });
+    for (strategy, report) in strategies.iter().zip(reports) {
+        println!(
+            "{:<14} {:>10} {:>8.3} {:>8}",
+            strategy.name, report.worst_case, report.average, report.unsolved
+        );
+    }
+}
+
+/// Returns `task` done to each of `items`, in order, with a thread for each
+/// item.
+#[cfg(feature = "parallel")]
+fn map_each<T: Sync, R: Send>(items: &[T], task: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let task = &task;
+    std::thread::scope(|scope| {
+        let threads: Vec<_> = items
+            .iter()
+            .map(|item| scope.spawn(move || task(item)))
+            .collect();
+        threads
+            .into_iter()
+            .map(|thread| {
+                thread
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect()
+    })
+}
+
+/// Returns `task` done to each of `items`, in order.
+#[cfg(not(feature = "parallel"))]
+fn map_each<T, R>(items: &[T], task: impl Fn(&T) -> R) -> Vec<R> {
+    items.iter().map(task).collect()
 }
 
-impl fmt::Display for ScoreDetail {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let c = match self {
-            ScoreDetail::ColorCorrect => " ".on_white(),
-            ScoreDetail::ColorAndPositionCorrect => " ".on_cyan(),
-            ScoreDetail::Empty => " ".on_red(),
+/// Prints the `top` best first guesses as a table or CSV. With `deep`, each is
+/// also played out by `strategy` against every code for its worst case.
+fn print_openings(
+    config: &GameConfig,
+    strategy: Strategy,
+    deterministic: bool,
+    top: usize,
+    deep: bool,
+    csv: bool,
+) {
+    let letters = |code: &Code| code.letters(&config.palette);
+    if csv {
+        print!("guess,worst_case,expected");
+        println!("{}", if deep { ",deep_worst_case" } else { "" });
+    } else {
+        print!("{:<8} {:>10} {:>9}", "guess", "worst case", "expected");
+        println!("{}", if deep { "  guesses needed" } else { "" });
+    }
+    let openings: Vec<_> = rank_openings(config).into_iter().take(top).collect();
+    let deep_worst = map_each(&openings, |opening| {
+        deep.then(|| {
+            analyze(config, Some(&opening.guess), || {
+                new_solver(&strategy, config, deterministic)
+            })
+            .worst_case
+        })
+    });
+    for (opening, deep_worst) in openings.iter().zip(deep_worst) {
+        let guess = letters(&opening.guess);
+        if csv {
+            print!("{},{},{:.3}", guess, opening.worst_case, opening.expected);
+            match deep_worst {
+                Some(worst) => println!(",{}", worst),
+                None => println!(),
+            }
+        } else {
+            print!(
+                "{:<8} {:>10} {:>9.3}",
+                guess, opening.worst_case, opening.expected
+            );
+            match deep_worst {
+                Some(worst) => println!("  {:>14}", worst),
+                None => println!(),
+            }
+        }
+    }
+}
+
+/// Prints a round-by-round review of the guesses on `board`.
+fn print_review(board: &Board) {
+    let letters = |code: &Code| code.letters(&board.config().palette);
+    println!(
+        "{:>5}  {:<8} {:>6} {:>6}  {:<8} {:>4}  verdict",
+        "round", "guess", "before", "worst", "best", "left"
+    );
+    for (i, (round, review)) in board.rounds().iter().zip(review_game(board)).enumerate() {
+        println!(
+            "{:>5}  {:<8} {:>6} {:>6}  {:<8} {:>4}  {}",
+            i + 1,
+            letters(round.guess()),
+            review.candidates_before,
+            review.worst_case,
+            letters(&review.best_guess),
+            review.best_worst_case,
+            review.verdict
+        );
+    }
+    if let Some(summary) = board.coaching().and_then(Coach::summary) {
+        println!("{}", summary);
+    }
+}
+
+/// Prints the ten most recently finished games.
+#[cfg(feature = "serde")]
+fn print_stats(player: Option<&str>) -> Result<()> {
+    let path = History::default_path(player).ok_or("cannot find a data directory")?;
+    let history = History::open(&path);
+    let recent = history.query_recent(10)?;
+    if recent.is_empty() {
+        println!("no games played yet");
+    }
+    for game in recent {
+        let variant = match game.variant {
+            Variant::Classic => String::new(),
+            other => format!(" ({})", other.name()),
         };
-        write!(f, "{}", c)
+        println!(
+            "{:>4}. {}  {:<9} in {:>2} guesses  {} pegs, {} colors{}",
+            game.id,
+            date::day_of(game.started_at),
+            game.outcome.name(),
+            game.guesses,
+            game.code_length,
+            game.color_count,
+            variant
+        );
+    }
+    let sessions = history.sessions()?;
+    let mut configs: Vec<(usize, usize)> = sessions
+        .iter()
+        .filter(|s| s.variant == Variant::Classic)
+        .map(|s| (s.config.code_length, s.config.colors.len()))
+        .collect();
+    configs.sort();
+    configs.dedup();
+    for (code_length, color_count) in configs {
+        println!(
+            "{} pegs, {} colors: {}",
+            code_length,
+            color_count,
+            Streak::from_sessions(&sessions, code_length, color_count)
+        );
+    }
+    Ok(())
+}
+
+/// Adds the game on `board` to the history file and, if it was finished, shows
+/// the streak it leaves. In a duel, the game is also added to `maker`'s
+/// history, as the codemaker saw it.
+#[cfg(feature = "serde")]
+fn record_history(
+    board: &Board,
+    started_at: u64,
+    outcome: Outcome,
+    player: Option<&str>,
+    maker: Option<&str>,
+) -> Result<()> {
+    if let Some(maker) = maker {
+        let session = GameSession::from_board(board, started_at, outcome.for_codemaker());
+        record_session(&session, Some(maker))?;
+    }
+    let Some(path) = History::default_path(player) else {
+        return Ok(());
+    };
+    let history = History::open(&path);
+    history.record(&GameSession::from_board(board, started_at, outcome))?;
+    if outcome != Outcome::Abandoned {
+        let config = board.config();
+        println!(
+            "{}",
+            history.streak(config.code_length, config.colors.len())?
+        );
+    }
+    Ok(())
+}
+
+/// Keeps no history: there is nowhere to keep it without `serde`.
+#[cfg(not(feature = "serde"))]
+fn record_history(
+    _board: &Board,
+    _started_at: u64,
+    _outcome: Outcome,
+    _player: Option<&str>,
+    _maker: Option<&str>,
+) -> Result<()> {
+    Ok(())
+}
+
+/// Marks the puzzle for `date` as played with the result on `board` and shows
+/// the daily streak.
+#[cfg(feature = "serde")]
+fn record_daily(
+    board: &Board,
+    date: NaiveDate,
+    outcome: Outcome,
+    player: Option<&str>,
+) -> Result<()> {
+    let Some(path) = DailyLog::default_path(player) else {
+        return Ok(());
+    };
+    let mut log = DailyLog::load(&path)?;
+    let today = date::today();
+    log.add(DailyRecord {
+        date,
+        played_on: today,
+        outcome,
+        guesses: board.guesses_used(),
+    });
+    log.save(&path)?;
+    println!("daily {}", log.streak(today));
+    Ok(())
+}
+
+/// Picks the settings for an adaptive game from the games `player` has
+/// recorded, continuing from the settings of the latest, and announces them.
+/// With no games yet, `config` is where play starts.
+#[cfg(feature = "serde")]
+fn adaptive_config(
+    config: GameConfig,
+    args: &PlayArgs,
+    player: Option<&str>,
+) -> Result<GameConfig> {
+    let defaults = Bounds::default();
+    let bounds = Bounds {
+        floor: args.adaptive_floor.unwrap_or(defaults.floor),
+        ceiling: args.adaptive_ceiling.unwrap_or(defaults.ceiling),
+    };
+    if bounds.floor.code_length > bounds.ceiling.code_length
+        || bounds.floor.color_count > bounds.ceiling.color_count
+    {
+        return Err(format!(
+            "--adaptive-floor {} is above --adaptive-ceiling {}",
+            bounds.floor, bounds.ceiling
+        )
+        .into());
     }
+    let results: Vec<GameResult> = match History::default_path(player) {
+        Some(path) => History::open(&path)
+            .sessions()?
+            .iter()
+            .filter_map(GameResult::from_session)
+            .collect(),
+        None => Vec::new(),
+    };
+    let current = results
+        .last()
+        .map_or_else(|| Settings::of(&config), |game| game.settings);
+    let next = next_settings(&results, current, &bounds);
+    println!(
+        "adaptive difficulty: {} pegs, {} colors",
+        next.code_length, next.color_count
+    );
+    Ok(next.apply(config))
 }
 
-struct Score(ScoreDetail, ScoreDetail, ScoreDetail, ScoreDetail);
+/// Plays static Mastermind: the guesses come from `--guesses-file` or are
+/// typed in, all are scored at once, and then the player names the code.
+fn play_static_game(
+    config: &GameConfig,
+    args: &StaticArgs,
+    seed: u64,
+    player: Option<&str>,
+) -> Result<()> {
+    let hidden = match &args.hidden_code {
+        Some(hidden) => Code::parse(hidden.clone(), config)?,
+        None => Code::random(config, &mut Rng::seeded(seed)),
+    };
+    let started_at = history::now();
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    let mut output = std::io::stdout();
+    let guesses = match (&args.guesses_file, args.guesses) {
+        (Some(path), count) => {
+            let guesses = parse_guesses(&std::fs::read_to_string(path)?, config)?;
+            if count.is_some_and(|count| count != guesses.len()) || guesses.is_empty() {
+                return Err(format!("{} lists {} guesses", path, guesses.len()).into());
+            }
+            guesses
+        }
+        (None, Some(0)) | (None, None) => {
+            return Err("'static' needs --guesses with at least 1, or --guesses-file".into())
+        }
+        (None, Some(count)) => match collect_guesses(&mut input, &mut output, config, count)? {
+            Some(guesses) => guesses,
+            None => return Ok(()),
+        },
+    };
 
-impl Score {
-    fn wins(&self) -> bool {
-        match self {
-            Score(
-                ScoreDetail::ColorAndPositionCorrect,
-                ScoreDetail::ColorAndPositionCorrect,
-                ScoreDetail::ColorAndPositionCorrect,
-                ScoreDetail::ColorAndPositionCorrect,
-            ) => true,
-            _ => false,
+    let result = play_static(&mut input, &mut output, config, &hidden, &guesses)?;
+    let outcome = match &result.answer {
+        None => Outcome::Abandoned,
+        Some(_) if result.won(&hidden) => {
+            println!("that's it, you win!");
+            Outcome::Won
+        }
+        Some(_) => {
+            println!(
+                "not quite, the code was {}",
+                hidden.display(&config.palette)
+            );
+            Outcome::Lost
         }
+    };
+    let mut rounds = result.rounds;
+    rounds.extend(
+        result
+            .answer
+            .map(|answer| Round::from_guess(answer, &hidden)),
+    );
+    record_session(
+        &GameSession {
+            started_at,
+            ended_at: history::now(),
+            outcome,
+            variant: Variant::Static,
+            config: config.clone(),
+            hidden_code: hidden,
+            rounds,
+            skipped: Vec::new(),
+        },
+        player,
+    )
+}
+
+/// Adds `session` to `player`'s history.
+#[cfg(feature = "serde")]
+fn record_session(session: &GameSession, player: Option<&str>) -> Result<()> {
+    match History::default_path(player) {
+        Some(path) => History::open(&path).record(session),
+        None => Ok(()),
     }
 }
 
-impl fmt::Display for Score {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} {} {} {}", self.0, self.1, self.2, self.3)
+/// Keeps no history: there is nowhere to keep it without `serde`.
+#[cfg(not(feature = "serde"))]
+fn record_session(_session: &GameSession, _player: Option<&str>) -> Result<()> {
+    Ok(())
+}
+
+/// Prints a challenge code for `hidden`, or for a code picked from `seed`
+/// if none is given, under `config`.
+fn print_challenge(config: &GameConfig, hidden: Option<&str>, seed: u64) -> Result<()> {
+    let mut rng = Rng::seeded(seed);
+    let hidden = match hidden {
+        Some(hidden) => Code::parse(hidden.to_string(), config)?,
+        None => Code::random(config, &mut rng),
+    };
+    let salt = rng.below(256) as u8;
+    let code = Challenge::new(config.clone(), hidden).encode(salt)?;
+    println!("{}", code);
+    println!("play it with: mastermind challenge play {}", code);
+    Ok(())
+}
+
+/// Prints how hard the code given in `hidden`, or else chosen using `seed`,
+/// is to crack, from 0.00 to 1.00.
+fn print_difficulty(config: &GameConfig, hidden: Option<&str>, seed: u64) -> Result<()> {
+    let hidden = match hidden {
+        Some(hidden) => Code::parse(hidden.to_string(), config)?,
+        None => Code::random(config, &mut Rng::seeded(seed)),
+    };
+    println!("{:.2}", GameDifficulty::score(&hidden, config));
+    Ok(())
+}
+
+/// Shows which of the last 30 daily puzzles have been played.
+#[cfg(feature = "serde")]
+fn print_daily_calendar(player: Option<&str>) -> Result<()> {
+    let path = DailyLog::default_path(player).ok_or("cannot find a data directory")?;
+    let log = DailyLog::load(&path)?;
+    let today = date::today();
+    println!("{}", log.calendar(today, 30));
+    println!("daily {}", log.streak(today));
+    Ok(())
+}
+
+/// Where guesses with a time limit are read from, and so where anything typed
+/// after the last of them is.
+#[cfg(not(feature = "async"))]
+type TimedInput = Receiver<std::result::Result<String, GameError>>;
+#[cfg(feature = "async")]
+type TimedInput = Box<dyn AsyncBufRead + Unpin + Send>;
+
+/// Reads the next normalized line from `input`, or `None` at the end of it.
+#[cfg(all(feature = "serde", not(feature = "async")))]
+fn read_timed_line(input: &mut TimedInput) -> Option<String> {
+    input.recv().ok()?.ok()
+}
+
+#[cfg(all(feature = "serde", feature = "async"))]
+fn read_timed_line(input: &mut TimedInput) -> Option<String> {
+    let mut line = String::new();
+    match Handle::current().block_on(read_line_limited_async(
+        input,
+        &mut line,
+        DEFAULT_MAX_LINE_LENGTH,
+    )) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(normalize_input(&line).to_string()),
+    }
+}
+
+/// Offers to save the win on `board` if it beats the best score for its rules.
+/// The name is read from `timed_input` when guesses had a time limit, and from
+/// stdin otherwise.
+#[cfg(feature = "serde")]
+fn record_high_score(
+    board: &Board,
+    player: Option<&str>,
+    timed_input: Option<&mut TimedInput>,
+) -> Result<()> {
+    // A game played with the code on view is no record.
+    if board.config().cheat_mode {
+        return Ok(());
+    }
+    let Some(path) = HighScoreBoard::default_path(player) else {
+        return Ok(());
+    };
+    let mut scores = HighScoreBoard::load(&path)?;
+    let guesses = board.guesses_used() as u32;
+    if !scores.is_new_best(board.config(), guesses) {
+        return Ok(());
     }
+    let mut typed = String::new();
+    let name = match player {
+        Some(name) => {
+            println!("that's a new best!");
+            name
+        }
+        None => {
+            print!("that's a new best! enter your name: ");
+            std::io::stdout().flush()?;
+            match timed_input {
+                Some(input) => match read_timed_line(input) {
+                    Some(line) => typed = line,
+                    None => return Ok(()),
+                },
+                None => {
+                    if std::io::stdin().read_line(&mut typed)? == 0 {
+                        return Ok(());
+                    }
+                }
+            }
+            match normalize_input(&typed) {
+                "" => "anonymous",
+                name => name,
+            }
+        }
+    };
+    scores.add(HighScore {
+        name: name.to_string(),
+        guesses,
+        code_length: board.config().code_length,
+        color_count: board.config().colors.len(),
+        date: date::today(),
+    });
+    scores.save(&path)
 }
 
-struct Round {
-    input_code: Code,
-    score: Score,
+/// Keeps no high scores: there is nowhere to keep them without `serde`.
+#[cfg(not(feature = "serde"))]
+fn record_high_score(
+    _board: &Board,
+    _player: Option<&str>,
+    _timed_input: Option<&mut TimedInput>,
+) -> Result<()> {
+    Ok(())
 }
 
-impl Round {
-    fn wins(&self) -> bool {
-        self.score.wins()
+/// Hosts a race between `racers` players, listening on `port`, for a code
+/// chosen using `seed` that nobody playing sees. Dropped players keep their
+/// place for `grace`, and lines longer than `max_line_length` are rejected.
+/// Ctrl-C tells the players the race is off and exits with
+/// `EXIT_INTERRUPTED`; a second one quits straight away.
+#[cfg(feature = "server")]
+fn host_game(
+    config: &GameConfig,
+    port: u16,
+    racers: usize,
+    grace: Duration,
+    max_line_length: usize,
+    seed: u64,
+) -> Result<()> {
+    if racers == 0 {
+        return Err("--racers must be at least 1".into());
     }
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    println!(
+        "waiting for {} players to join on port {}",
+        racers,
+        listener.local_addr()?.port()
+    );
+    let hidden = Code::random(config, &mut Rng::seeded(seed));
+    let (stop, stopped) = std::sync::mpsc::channel();
+    ctrlc::set_handler(move || {
+        if stop.send(()).is_err() {
+            std::process::exit(EXIT_INTERRUPTED);
+        }
+    })?;
+    let result = host_race_until(
+        &listener,
+        racers,
+        config,
+        &hidden,
+        grace,
+        max_line_length,
+        stopped,
+    )?;
+    let Some(result) = result else {
+        drop(listener);
+        println!(
+            "race stopped; the code was {}",
+            hidden.display(&config.palette)
+        );
+        std::process::exit(EXIT_INTERRUPTED);
+    };
+    match result.winner {
+        Some(winner) => println!(
+            "player {} won in {} guesses",
+            winner,
+            result.guesses[winner - 1]
+        ),
+        None => println!("nobody cracked it"),
+    }
+    println!("the code was {}", hidden.display(&config.palette));
+    Ok(())
+}
+
+/// Plays in the race hosted at `address`, guessing from stdin, or takes back
+/// the place with session token `resume`.
+#[cfg(feature = "server")]
+fn join_game(address: &str, resume: Option<&str>, max_line_length: usize) -> Result<()> {
+    let stream = TcpStream::connect(address)?;
+    if resume.is_none() {
+        println!("joined, waiting for the race to start");
+    }
+    let input = BufReader::new(std::io::stdin());
+    match join_race(
+        stream,
+        input,
+        &mut std::io::stdout(),
+        resume,
+        max_line_length,
+    )? {
+        Ending::Finished { .. } | Ending::SessionExpired | Ending::Stopped => {}
+        Ending::HostGone => println!("the host went away"),
+    }
+    Ok(())
+}
+
+/// Plays `count` boards at once against random codes chosen using `seed`.
+fn play_boards(config: GameConfig, count: usize, seed: u64) -> Result<()> {
+    if count == 0 {
+        return Err("--boards must be at least 1".into());
+    }
+    let mut rng = Rng::seeded(seed);
+    let hidden = (0..count)
+        .map(|_| Code::random(&config, &mut rng))
+        .collect();
+    let mut game = MultiGame::new(config, hidden);
+    let stdin = std::io::stdin();
+    let width = width();
+    let outcome = game.play(&mut stdin.lock(), &mut std::io::stdout(), width)?;
+    println!("{}", game.render(width));
+    match outcome {
+        Some(Outcome::Won) => println!("congratulations, you solved every board!"),
+        Some(Outcome::Lost | Outcome::Abandoned | Outcome::Forfeit) => {
+            let codes: Vec<String> = game
+                .boards()
+                .iter()
+                .map(|board| {
+                    board
+                        .hidden_code()
+                        .display(&board.config().palette)
+                        .to_string()
+                })
+                .collect();
+            println!("out of guesses, the codes were {}", codes.join(", "));
+        }
+        None => {}
+    }
+    Ok(())
 }
 
-impl fmt::Display for Round {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} | {}", self.input_code, self.score)
+/// Plays a time trial of `minutes` against random codes chosen using `seed`,
+/// then offers to record it if it is a new best.
+fn play_time_trial(
+    config: &GameConfig,
+    minutes: u64,
+    max_line_length: usize,
+    seed: u64,
+    player: Option<&str>,
+) -> Result<()> {
+    if minutes == 0 {
+        return Err("--minutes must be at least 1".into());
     }
+    // Read on a separate thread so the deadline holds while waiting for a guess.
+    let lines = spawn_line_reader(std::io::BufReader::new(std::io::stdin()), max_line_length);
+    let limit = Duration::from_secs(minutes * 60);
+    let result = run_time_trial(
+        &lines,
+        &mut std::io::stdout(),
+        config,
+        &mut Rng::seeded(seed),
+        limit,
+    )?;
+    println!("{}", result);
+    if let Some(average) = result.average() {
+        println!("{} guesses, {:.2} per code", result.guesses, average);
+    }
+    record_time_trial(config, &result, limit, &lines, player)
 }
 
-fn main() -> Result<()> {
-    let mut board = Board::init()?;
+/// Offers to save the time trial `result`, played for `limit`, if it is a
+/// new best. The name is read from `lines`.
+#[cfg(feature = "serde")]
+fn record_time_trial(
+    config: &GameConfig,
+    result: &TimeTrialResult,
+    limit: Duration,
+    lines: &Receiver<std::result::Result<String, GameError>>,
+    player: Option<&str>,
+) -> Result<()> {
+    let Some(path) = TimeTrialBoard::default_path(player) else {
+        return Ok(());
+    };
+    let mut scores = TimeTrialBoard::load(&path)?;
+    if !scores.is_new_best(config, result) {
+        return Ok(());
+    }
+    let name = match player {
+        Some(name) => {
+            println!("that's a new best!");
+            name.to_string()
+        }
+        None => {
+            print!("that's a new best! enter your name: ");
+            std::io::stdout().flush()?;
+            let Ok(Ok(name)) = lines.recv() else {
+                return Ok(());
+            };
+            if name.is_empty() {
+                String::from("anonymous")
+            } else {
+                name
+            }
+        }
+    };
+    scores.add(TimeTrialScore {
+        name,
+        solved: result.solved,
+        guesses: result.guesses,
+        seconds: limit.as_secs(),
+        code_length: config.code_length,
+        color_count: config.colors.len(),
+        date: date::today(),
+    });
+    scores.save(&path)
+}
 
+/// Keeps no time trials: there is nowhere to keep them without `serde`.
+#[cfg(not(feature = "serde"))]
+fn record_time_trial(
+    _config: &GameConfig,
+    _result: &TimeTrialResult,
+    _limit: Duration,
+    _lines: &Receiver<std::result::Result<String, GameError>>,
+    _player: Option<&str>,
+) -> Result<()> {
+    Ok(())
+}
+
+/// Starts a game against a hidden code nobody has seen, chosen using `seed`.
+fn random_board(config: GameConfig, seed: u64, no_prompt: bool) -> Board {
+    let hidden = Code::random(&config, &mut Rng::seeded(seed));
+    start_board(config, hidden, no_prompt)
+}
+
+/// Lists the games autosaved by runs that died, and asks whether to resume
+/// one, discard them all, or start a new game.
+#[cfg(feature = "serde")]
+fn offer_recovery(player: Option<&str>) -> Result<Option<(Board, RecoveryFile)>> {
+    let Some(dir) = recovery_dir(player) else {
+        return Ok(None);
+    };
+    let mut leftovers = leftover_games(&dir)?;
+    if leftovers.is_empty() {
+        return Ok(None);
+    }
+    println!(
+        "found {} unfinished game{}:",
+        leftovers.len(),
+        if leftovers.len() == 1 { "" } else { "s" }
+    );
+    for (i, leftover) in leftovers.iter().enumerate() {
+        println!("{:>3}. {}", i + 1, leftover.describe());
+    }
     loop {
-        if board.get_input()? {
-            println!("{}", board);
-            println!("congratulations, you win!");
-            break;
+        print!("resume one by number, 'd' to discard them, or press enter for a new game: ");
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        if std::io::stdin().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        match normalize_input(&line) {
+            "" => return Ok(None),
+            "d" => {
+                for leftover in &leftovers {
+                    leftover.file.remove()?;
+                }
+                println!("discarded");
+                return Ok(None);
+            }
+            answer => match answer.parse::<usize>() {
+                Ok(n) if (1..=leftovers.len()).contains(&n) => {
+                    let leftover = leftovers.swap_remove(n - 1);
+                    return Ok(Some((leftover.board, leftover.file)));
+                }
+                _ => println!("'{}' is not one of the games listed", answer),
+            },
         }
     }
-    return Ok(());
+}
+
+/// Starts a game against `hidden`. With `no_prompt` the scoring legend is
+/// left out too, so that only rounds are printed.
+fn start_board(config: GameConfig, hidden: Code, no_prompt: bool) -> Board {
+    if no_prompt {
+        Board::new(config, hidden)
+    } else {
+        Board::start(config, hidden)
+    }
+}
+
+/// Starts the daily puzzle for `date`, unless `player` has already played it
+/// or it is still to come.
+#[cfg(feature = "serde")]
+fn daily_board(
+    config: GameConfig,
+    date: NaiveDate,
+    player: Option<&str>,
+    no_prompt: bool,
+) -> Result<Board> {
+    if let Some(path) = DailyLog::default_path(player) {
+        DailyLog::load(&path)?.check_playable(date, date::today())?;
+    }
+    let hidden = daily_code(&config, date);
+    Ok(start_board(config, hidden, no_prompt))
+}
+
+/// The width of the terminal stdout is attached to, if it is one.
+fn terminal_width() -> Option<usize> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    terminal_size().map(|(Width(width), _)| usize::from(width))
 }
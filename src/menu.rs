@@ -0,0 +1,201 @@
+//! The start menu shown when the game is launched without arguments.
+
+use std::io::{BufRead, Write};
+
+use crate::config::GameConfig;
+use crate::input::prompt;
+use crate::paths::is_valid_player_name;
+use crate::Result;
+
+/// The longest code the custom game prompt accepts.
+const MAX_CODE_LENGTH: usize = 8;
+
+/// What the player picked from the start menu.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MenuChoice {
+    /// Play against a randomly chosen hidden code.
+    Random(GameConfig),
+    /// Play today's daily puzzle.
+    Daily(GameConfig),
+    /// Have one player type a hidden code for another to guess, with the
+    /// game recorded to the profiles named for either, if any.
+    Duel {
+        config: GameConfig,
+        maker: Option<String>,
+        breaker: Option<String>,
+    },
+    /// Show the games played so far.
+    Stats,
+    Quit,
+}
+
+/// Shows the start menu on `output` and reads choices from `input` until one
+/// is made. Games start from `base`; the custom game entry adjusts its length,
+/// colors and guess limit. Running out of input counts as quitting.
+///
+/// ```
+/// use mastermind::{run_menu, GameConfig, MenuChoice};
+///
+/// let mut input = "x\n3\n6\n3\n10\n".as_bytes();
+/// let mut output = Vec::new();
+/// let choice = run_menu(&mut input, &mut output, GameConfig::default()).unwrap();
+///
+/// let MenuChoice::Random(config) = choice else { panic!("expected a game") };
+/// assert_eq!(config.code_length, 6);
+/// assert_eq!(config.colors.len(), 3);
+/// assert_eq!(config.max_guesses, Some(10));
+///
+/// let mut choose = |line: &str| {
+///     run_menu(&mut line.as_bytes(), &mut Vec::new(), GameConfig::default()).unwrap()
+/// };
+/// assert_eq!(choose("2\n"), MenuChoice::Daily(GameConfig::default()));
+/// assert_eq!(choose("5\n"), MenuChoice::Stats);
+/// assert_eq!(
+///     choose("4\nalice\n\n"),
+///     MenuChoice::Duel {
+///         config: GameConfig::default(),
+///         maker: Some(String::from("alice")),
+///         breaker: None,
+///     }
+/// );
+/// ```
+pub fn run_menu<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    base: GameConfig,
+) -> Result<MenuChoice> {
+    loop {
+        writeln!(output, " 1) quick game")?;
+        writeln!(output, " 2) daily puzzle")?;
+        writeln!(output, " 3) custom game")?;
+        writeln!(output, " 4) duel")?;
+        writeln!(output, " 5) stats")?;
+        writeln!(output, " q) quit")?;
+        let Some(line) = prompt(input, output, "choice: ")? else {
+            return Ok(MenuChoice::Quit);
+        };
+        match line.as_str() {
+            "1" => return Ok(MenuChoice::Random(base)),
+            "2" => return Ok(MenuChoice::Daily(base)),
+            "3" => match custom_config(input, output, base.clone())? {
+                Some(config) => return Ok(MenuChoice::Random(config)),
+                None => return Ok(MenuChoice::Quit),
+            },
+            "4" => {
+                let Some(maker) =
+                    ask_player(input, output, "codemaker's profile [blank for none]: ")?
+                else {
+                    return Ok(MenuChoice::Quit);
+                };
+                let Some(breaker) =
+                    ask_player(input, output, "codebreaker's profile [blank for none]: ")?
+                else {
+                    return Ok(MenuChoice::Quit);
+                };
+                return Ok(MenuChoice::Duel {
+                    config: base,
+                    maker,
+                    breaker,
+                });
+            }
+            "5" => return Ok(MenuChoice::Stats),
+            "q" => return Ok(MenuChoice::Quit),
+            _ => writeln!(output, "please pick 1 to 5 or q")?,
+        }
+    }
+}
+
+/// Walks through the custom game settings, or returns `None` if input runs out.
+fn custom_config<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    mut config: GameConfig,
+) -> Result<Option<GameConfig>> {
+    let palette = GameConfig::default().colors;
+
+    let question = format!(
+        "code length [1-{}, {}]: ",
+        MAX_CODE_LENGTH, config.code_length
+    );
+    let Some(length) = ask_number(input, output, &question, 1, MAX_CODE_LENGTH)? else {
+        return Ok(None);
+    };
+    config.code_length = length.unwrap_or(config.code_length);
+
+    let question = format!(
+        "number of colors [2-{}, {}]: ",
+        palette.len(),
+        config.colors.len()
+    );
+    let Some(colors) = ask_number(input, output, &question, 2, palette.len())? else {
+        return Ok(None);
+    };
+    if let Some(count) = colors {
+        config.colors = palette[..count].to_vec();
+    }
+
+    let Some(guesses) = ask_number(
+        input,
+        output,
+        "maximum guesses [blank for unlimited]: ",
+        1,
+        usize::MAX,
+    )?
+    else {
+        return Ok(None);
+    };
+    config.max_guesses = guesses;
+
+    Ok(Some(config))
+}
+
+/// Asks for a number in `min..=max` until one is given. The inner `None` means
+/// the player left the answer blank; the outer one that input ran out.
+fn ask_number<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    question: &str,
+    min: usize,
+    max: usize,
+) -> Result<Option<Option<usize>>> {
+    loop {
+        let Some(line) = prompt(input, output, question)? else {
+            return Ok(None);
+        };
+        if line.is_empty() {
+            return Ok(Some(None));
+        }
+        match line.parse::<usize>() {
+            Ok(n) if (min..=max).contains(&n) => return Ok(Some(Some(n))),
+            _ if max == usize::MAX => {
+                writeln!(output, "please enter a number of at least {}", min)?
+            }
+            _ => writeln!(output, "please enter a number from {} to {}", min, max)?,
+        }
+    }
+}
+
+/// Asks for a player profile name until a valid one is given. The inner
+/// `None` means the player left the answer blank; the outer one that input
+/// ran out.
+fn ask_player<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    question: &str,
+) -> Result<Option<Option<String>>> {
+    loop {
+        let Some(line) = prompt(input, output, question)? else {
+            return Ok(None);
+        };
+        if line.is_empty() {
+            return Ok(Some(None));
+        }
+        if is_valid_player_name(&line) {
+            return Ok(Some(Some(line)));
+        }
+        writeln!(
+            output,
+            "player names may only use letters, digits, '-' and '_'"
+        )?;
+    }
+}
@@ -0,0 +1,209 @@
+//! Several games against different hidden codes, played with one stream of
+//! guesses.
+
+use std::io::{BufRead, Write};
+
+use crate::board::Board;
+use crate::code::Code;
+use crate::config::GameConfig;
+use crate::history::Outcome;
+use crate::input::prompt;
+use crate::layout::columns_that_fit;
+use crate::Result;
+
+/// The gap between boards drawn side by side.
+const COLUMN_GAP: &str = "   ";
+
+/// Boards that share every guess: each guess is scored on every board not yet
+/// solved, and a board stops taking guesses once it is solved. The game is won
+/// when every board is solved.
+///
+/// ```
+/// use mastermind::multi::MultiGame;
+/// use mastermind::{Code, GameConfig};
+///
+/// let config = GameConfig {
+///     max_guesses: Some(3),
+///     ..GameConfig::default()
+/// };
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let mut game = MultiGame::new(config.clone(), vec![code("rgby"), code("yyyy")]);
+/// // One extra guess for the second board.
+/// assert_eq!(game.max_guesses(), Some(4));
+///
+/// game.guess(code("rgby"));
+/// assert_eq!(game.solved_at(), [Some(1), None]);
+/// game.guess(code("yyyy"));
+/// // The first board was locked, so it never saw the second guess.
+/// assert_eq!(game.boards()[0].rounds().len(), 1);
+/// assert_eq!(game.outcome(), Some(mastermind::Outcome::Won));
+/// ```
+pub struct MultiGame {
+    config: GameConfig,
+    boards: Vec<Board>,
+    solved_at: Vec<Option<usize>>,
+    guesses: usize,
+}
+
+impl MultiGame {
+    /// Starts one board per code in `hidden_codes`, all with `config`.
+    pub fn new(config: GameConfig, hidden_codes: Vec<Code>) -> Self {
+        let boards: Vec<Board> = hidden_codes
+            .into_iter()
+            .map(|code| Board::new(config.clone(), code))
+            .collect();
+        Self {
+            config,
+            solved_at: vec![None; boards.len()],
+            boards,
+            guesses: 0,
+        }
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn boards(&self) -> &[Board] {
+        &self.boards
+    }
+
+    /// Returns, for each board, the guess number that solved it.
+    pub fn solved_at(&self) -> &[Option<usize>] {
+        &self.solved_at
+    }
+
+    /// Returns how many guesses have been made.
+    pub fn guesses(&self) -> usize {
+        self.guesses
+    }
+
+    /// The guess budget: the configured maximum plus one more for every board
+    /// after the first, or `None` when guesses are unlimited.
+    pub fn max_guesses(&self) -> Option<usize> {
+        self.config
+            .max_guesses
+            .map(|max| max + self.boards.len().saturating_sub(1))
+    }
+
+    /// Scores `code` on every board not yet solved.
+    pub fn guess(&mut self, code: Code) {
+        self.guesses += 1;
+        for (board, solved) in self.boards.iter_mut().zip(&mut self.solved_at) {
+            if solved.is_none() && board.guess(code.clone()).wins() {
+                *solved = Some(self.guesses);
+            }
+        }
+    }
+
+    /// Returns how the game ended, or `None` while it is still going.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if self.solved_at.iter().all(Option::is_some) {
+            Some(Outcome::Won)
+        } else if Some(self.guesses) == self.max_guesses() {
+            Some(Outcome::Lost)
+        } else {
+            None
+        }
+    }
+
+    /// Draws the boards side by side, each round on its own row, starting a
+    /// new row of boards when the next would not fit in `width` columns. Too
+    /// narrow for two, the boards are stacked one above the other.
+    ///
+    /// ```
+    /// use mastermind::multi::MultiGame;
+    /// use mastermind::{Code, GameConfig};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let hidden = vec![code("rgby"), code("yyyy"), code("bbgg")];
+    /// let mut game = MultiGame::new(config.clone(), hidden);
+    /// game.guess(code("rrgg"));
+    ///
+    /// // A heading and a round for each row of boards.
+    /// assert_eq!(game.render(80).lines().count(), 2);
+    /// assert_eq!(game.render(40).lines().count(), 5);
+    /// ```
+    pub fn render(&self, width: usize) -> String {
+        let length = self.config.code_length;
+        // A round is drawn as "<code> | <pegs>", each peg one character apart.
+        let column_width = 4 * length + 1;
+        let per_row = columns_that_fit(column_width, COLUMN_GAP.len(), width);
+        let mut rows = Vec::new();
+        for (first, boards) in self.boards.chunks(per_row).enumerate() {
+            let first = first * per_row;
+            let headings: Vec<String> = boards
+                .iter()
+                .enumerate()
+                .map(|(i, _)| {
+                    let heading = match self.solved_at[first + i] {
+                        Some(at) => format!("board {} (solved in {})", first + i + 1, at),
+                        None => format!("board {}", first + i + 1),
+                    };
+                    format!("{:<width$}", heading, width = column_width)
+                })
+                .collect();
+            if !rows.is_empty() {
+                rows.push(String::new());
+            }
+            rows.push(headings.join(COLUMN_GAP).trim_end().to_string());
+            for round in 0..self.guesses {
+                let cells: Vec<String> = boards
+                    .iter()
+                    .map(|board| match board.rounds().get(round) {
+                        Some(round) => round.display(&self.config.palette).to_string(),
+                        None => " ".repeat(column_width),
+                    })
+                    .collect();
+                rows.push(cells.join(COLUMN_GAP).trim_end().to_string());
+            }
+        }
+        rows.join("\n")
+    }
+
+    /// Plays the game, reading guesses from `input` and drawing the boards for a
+    /// terminal `width` columns wide on `output`.
+    ///
+    /// ```
+    /// use mastermind::multi::MultiGame;
+    /// use mastermind::{Code, GameConfig, Outcome};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let mut game = MultiGame::new(config.clone(), vec![code("rgby"), code("yyyy")]);
+    /// let mut output = Vec::new();
+    /// let outcome = game
+    ///     .play(&mut "rgby\nnope\nyyyy\n".as_bytes(), &mut output, 80)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(outcome, Some(Outcome::Won));
+    /// assert_eq!(game.guesses(), 2);
+    /// ```
+    pub fn play<R: BufRead, W: Write>(
+        &mut self,
+        input: &mut R,
+        output: &mut W,
+        width: usize,
+    ) -> Result<Option<Outcome>> {
+        loop {
+            if let Some(outcome) = self.outcome() {
+                return Ok(Some(outcome));
+            }
+            if self.guesses > 0 {
+                writeln!(output, "{}\n", self.render(width))?;
+            }
+            let question = match self.max_guesses() {
+                Some(max) => format!("guess {} of {}: ", self.guesses + 1, max),
+                None => String::from("guess: "),
+            };
+            let Some(line) = prompt(input, output, &question)? else {
+                return Ok(None);
+            };
+            match Code::parse(line, &self.config) {
+                Ok(code) => self.guess(code),
+                Err(e) => writeln!(output, "{}", e)?,
+            }
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! The pegs a game is played with.
+//!
+//! A peg is a `PegValue`, a small index into a `Palette` that says how the
+//! value is typed, named and drawn. The eight classic colors are the default
+//! palette, and `Color` names a peg value by them, so `Color::Red` is the
+//! classic palette's first value. A game played with other symbols, such as
+//! digits, brings its own palette in `GameConfig::palette`.
+//!
+//! Nothing about a peg value says which palette it belongs to, so anything
+//! that draws, parses or saves pegs for a game is handed the game's palette.
+//! `PegValue`'s own accessors, and `Display` and `FromStr` for codes, always
+//! use the classic one.
+
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use crate::display::Shade;
+use crate::error::GameError;
+
+/// One peg: an index into a `Palette`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct PegValue(u8);
+
+/// How a palette shows one peg value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PegStyle {
+    /// The character typed for the value, and written for it in saves.
+    pub letter: char,
+    /// The value's lowercase full name.
+    pub name: String,
+    /// The shape drawn for it in `DisplayMode::Symbols`.
+    pub symbol: char,
+    /// The picture drawn for it in `DisplayMode::Emoji`.
+    pub emoji: String,
+    /// The terminal color it is drawn in; `None` leaves it to the theme.
+    pub shade: Option<Shade>,
+    /// Where it sits in CIE L*a*b* space, if it is a color at all.
+    pub lab: Option<[f64; 3]>,
+}
+
+/// The styles of every peg value a game may use, in value order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    pegs: Vec<PegStyle>,
+}
+
+/// The classic colors, in value order: letter, name, symbol, emoji, and L*a*b*
+/// under a D65 white point for the sRGB shade each stands for.
+const CLASSIC: [(char, &str, char, &str, [f64; 3]); 8] = [
+    ('r', "red", '●', "🔴", [53.24, 80.09, 67.20]),
+    ('g', "green", '▲', "🟢", [46.23, -51.70, 49.90]),
+    ('b', "blue", '■', "🔵", [32.30, 79.19, -107.86]),
+    ('y', "yellow", '◆', "🟡", [97.14, -21.55, 94.48]),
+    ('p', "pink", '★', "🟣", [65.49, 64.24, -10.65]),
+    ('n', "brown", '✚', "🟤", [37.52, 49.69, 30.54]),
+    ('w', "white", '○', "⚪", [100.0, 0.0, 0.0]),
+    ('k', "black", '◇', "⚫", [0.0, 0.0, 0.0]),
+];
+
+impl Palette {
+    /// Makes a palette of `pegs`, which must be between 1 and 256 values with
+    /// no letter used twice.
+    pub fn new(pegs: Vec<PegStyle>) -> Result<Palette, GameError> {
+        if pegs.is_empty() || pegs.len() > usize::from(u8::MAX) + 1 {
+            return Err(GameError::InvalidSetting {
+                setting: "palette",
+                reason: String::from("must have from 1 to 256 pegs"),
+            });
+        }
+        if let Some((i, peg)) = pegs
+            .iter()
+            .enumerate()
+            .find(|(i, peg)| pegs[..*i].iter().any(|other| other.letter == peg.letter))
+        {
+            return Err(GameError::InvalidSetting {
+                setting: "palette",
+                reason: format!(
+                    "'{}' is used for peg {} and an earlier one",
+                    peg.letter,
+                    i + 1
+                ),
+            });
+        }
+        Ok(Palette { pegs })
+    }
+
+    /// Returns the eight classic colors.
+    pub fn classic() -> Palette {
+        let pegs = CLASSIC
+            .iter()
+            .map(|&(letter, name, symbol, emoji, lab)| PegStyle {
+                letter,
+                name: name.to_string(),
+                symbol,
+                emoji: emoji.to_string(),
+                shade: None,
+                lab: Some(lab),
+            })
+            .collect();
+        Palette { pegs }
+    }
+
+    /// Makes a palette with one peg per character of `letters`, each drawn as
+    /// its own letter.
+    ///
+    /// ```
+    /// use mastermind::palette::Palette;
+    ///
+    /// let digits = Palette::from_letters("0123456789").unwrap();
+    /// assert_eq!(digits.len(), 10);
+    /// assert_eq!(digits.value_of('7').map(|peg| digits.style(&peg).name.clone()), Some(String::from("7")));
+    /// assert!(Palette::from_letters("abca").is_err());
+    /// assert!(Palette::from_letters("a b").is_err());
+    /// assert!(Palette::from_letters("").is_err());
+    /// ```
+    pub fn from_letters(letters: &str) -> Result<Palette, GameError> {
+        if let Some(c) = letters
+            .chars()
+            .find(|c| c.is_whitespace() || matches!(c, '(' | ')' | ','))
+        {
+            return Err(GameError::InvalidSetting {
+                setting: "palette",
+                reason: format!("'{}' cannot be typed as a peg", c),
+            });
+        }
+        Palette::new(
+            letters
+                .chars()
+                .map(|letter| PegStyle {
+                    letter,
+                    name: letter.to_string(),
+                    symbol: letter,
+                    emoji: letter.to_string(),
+                    shade: None,
+                    lab: None,
+                })
+                .collect(),
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.pegs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pegs.is_empty()
+    }
+
+    /// Returns every value of the palette, in order.
+    pub fn values(&self) -> Vec<PegValue> {
+        (0..self.pegs.len()).map(PegValue::from_index).collect()
+    }
+
+    /// Returns the style of `value`, or one made up from its index if the
+    /// palette is too short to have it.
+    ///
+    /// ```
+    /// use mastermind::palette::Palette;
+    /// use mastermind::PegValue;
+    ///
+    /// let digits = Palette::from_letters("0123").unwrap();
+    /// assert_eq!(digits.style(&PegValue::new(2)).letter, '2');
+    /// assert_eq!(Palette::classic().style(&PegValue::new(2)).letter, 'b');
+    /// assert_eq!(digits.style(&PegValue::new(12)).name, "peg 13");
+    /// ```
+    pub fn style(&self, value: &PegValue) -> Cow<'_, PegStyle> {
+        match self.pegs.get(value.index()) {
+            Some(style) => Cow::Borrowed(style),
+            None => {
+                let letter = char::from_digit(value.0.into(), 36).unwrap_or('?');
+                Cow::Owned(PegStyle {
+                    letter,
+                    name: format!("peg {}", value.index() + 1),
+                    symbol: letter,
+                    emoji: letter.to_string(),
+                    shade: None,
+                    lab: None,
+                })
+            }
+        }
+    }
+
+    /// Returns the value typed as `c`, if any.
+    pub fn value_of(&self, c: char) -> Option<PegValue> {
+        self.pegs
+            .iter()
+            .position(|peg| peg.letter == c)
+            .map(PegValue::from_index)
+    }
+
+    /// Returns whether this is the classic palette.
+    pub fn is_classic(&self) -> bool {
+        *self == *classic()
+    }
+
+    /// Returns the letters of every value, in order.
+    pub fn letters(&self) -> String {
+        self.pegs.iter().map(|peg| peg.letter).collect()
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::classic()
+    }
+}
+
+/// The classic palette, made once.
+pub(crate) fn classic() -> &'static Palette {
+    static CLASSIC_PALETTE: OnceLock<Palette> = OnceLock::new();
+    CLASSIC_PALETTE.get_or_init(Palette::classic)
+}
+
+impl PegValue {
+    /// Returns the indices of `values` as bytes, without copying them.
+    pub(crate) fn as_bytes(values: &[PegValue]) -> &[u8] {
+        // SAFETY: a `PegValue` is a transparent wrapper around one byte.
+        unsafe { std::slice::from_raw_parts(values.as_ptr() as *const u8, values.len()) }
+    }
+
+    /// Returns the value at `index` of a palette.
+    pub const fn new(index: u8) -> PegValue {
+        PegValue(index)
+    }
+
+    /// Returns the value at `index` of a palette.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is past the 256 values a palette can hold.
+    pub fn from_index(index: usize) -> PegValue {
+        PegValue(u8::try_from(index).expect("a palette holds at most 256 pegs"))
+    }
+
+    /// Returns where this value sits in its palette.
+    pub fn index(&self) -> usize {
+        usize::from(self.0)
+    }
+
+    /// Returns the letter the classic palette types this value with.
+    pub fn letter(&self) -> char {
+        classic().style(self).letter
+    }
+
+    /// Returns the classic palette's lowercase name for this value.
+    pub fn name(&self) -> String {
+        classic().style(self).name.clone()
+    }
+
+    /// Returns the value the classic palette types as `c`, if any.
+    pub fn from_letter(c: char) -> Option<PegValue> {
+        classic().value_of(c)
+    }
+}
+
+/// Written as the classic letter, like everything `PegValue` shows on its own.
+#[cfg(feature = "serde")]
+impl serde::Serialize for PegValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.letter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PegValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let letter = String::deserialize(deserializer)?;
+        let mut chars = letter.chars();
+        match (chars.next().and_then(PegValue::from_letter), chars.next()) {
+            (Some(value), None) => Ok(value),
+            _ => Err(serde::de::Error::custom(format!(
+                "\"{}\" is not a color",
+                letter
+            ))),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Where the game keeps files between runs.
+
+use std::path::PathBuf;
+
+/// Returns `$XDG_DATA_HOME/mastermind`, falling back to `~/.local/share` when
+/// `XDG_DATA_HOME` is unset.
+pub fn data_dir() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+        })?;
+    Some(data_home.join("mastermind"))
+}
+
+/// Returns whether `name` can be used as a player profile: letters, digits,
+/// `-` and `_` only, so it is always a plain directory name.
+pub fn is_valid_player_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Returns the directory for `player`'s files: `players/<name>` in the data
+/// directory, or the data directory itself for the default player.
+///
+/// ```
+/// # #[cfg(feature = "serde")] {
+/// use mastermind::history::{GameSession, History, Outcome, Variant};
+/// use mastermind::paths::profile_dir;
+/// use mastermind::{Code, GameConfig};
+///
+/// let data = tempfile::tempdir().unwrap();
+/// std::env::set_var("XDG_DATA_HOME", data.path());
+///
+/// let alice = History::open(&History::default_path(Some("alice")).unwrap());
+/// alice
+///     .record(&GameSession {
+///         started_at: 0,
+///         ended_at: 0,
+///         outcome: Outcome::Won,
+///         variant: Variant::Classic,
+///         config: GameConfig::default(),
+///         hidden_code: String::from("rgby").try_into().unwrap(),
+///         rounds: Vec::new(),
+///         skipped: Vec::new(),
+///     })
+///     .unwrap();
+///
+/// // Nobody else's games are affected.
+/// assert_eq!(alice.sessions().unwrap().len(), 1);
+/// for other in [Some("bob"), None] {
+///     let history = History::open(&History::default_path(other).unwrap());
+///     assert!(history.sessions().unwrap().is_empty());
+/// }
+/// assert_eq!(mastermind::paths::players().unwrap(), ["alice"]);
+/// assert_eq!(profile_dir(None).unwrap(), data.path().join("mastermind"));
+/// # }
+/// ```
+pub fn profile_dir(player: Option<&str>) -> Option<PathBuf> {
+    let dir = data_dir()?;
+    Some(match player {
+        Some(name) => dir.join("players").join(name),
+        None => dir,
+    })
+}
+
+/// Returns the name of every player with a profile, sorted.
+pub fn players() -> std::io::Result<Vec<String>> {
+    let Some(dir) = data_dir() else {
+        return Ok(Vec::new());
+    };
+    let entries = match std::fs::read_dir(dir.join("players")) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut names = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            if let Some(name) = entry
+                .file_name()
+                .to_str()
+                .filter(|n| is_valid_player_name(n))
+            {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
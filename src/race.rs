@@ -0,0 +1,994 @@
+//! Races over the network: the host picks the hidden code itself and scores
+//! every player's guesses, so nobody playing knows the code.
+//!
+//! Players send each guess as a line of text. The host answers with one line
+//! of JSON per `Message`: a player's own scores go only to that player, and
+//! the others only hear how many guesses each has made, never the guesses.
+//!
+//! Each player gets a session token when the race starts. A player whose
+//! connection drops can connect again and send `resume <token>` to take their
+//! place back, as long as they return within the host's grace period.
+//!
+//! A host told to stop tells every player the race is off, closes their
+//! connections and stops listening.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use crate::board::Round;
+use crate::code::Code;
+use crate::config::{DisplayMode, GameConfig};
+use crate::error::GameError;
+use crate::input::{normalize_input, read_line_limited};
+use crate::palette::{self, Palette};
+use crate::random::Rng;
+use crate::save::SavedRound;
+use crate::Result;
+
+/// The port `mastermind host` listens on unless told otherwise.
+pub const DEFAULT_PORT: u16 = 7654;
+
+/// How long the host keeps a dropped player's place unless told otherwise.
+pub const DEFAULT_GRACE: Duration = Duration::from_secs(120);
+
+/// Something the host tells a player. Players are numbered from 1 in the
+/// order they joined.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// Every player has joined and the race is on. `token` takes the
+    /// player's place back after a dropped connection.
+    Welcome {
+        player: usize,
+        config: GameConfig,
+        token: String,
+    },
+    /// The player is back in the race after a dropped connection, with their
+    /// own rounds so far and how many guesses each player has made.
+    Resumed {
+        player: usize,
+        config: GameConfig,
+        rounds: Vec<Round>,
+        progress: Vec<usize>,
+    },
+    /// The session asked to resume is unknown or its grace period ran out.
+    Expired,
+    /// The player's own guess and its score, as their `guesses`th guess.
+    Scored { round: Round, guesses: usize },
+    /// The player's last line was not a guess that counts.
+    Rejected { reason: String },
+    /// Another player has now made `guesses` guesses.
+    Progress { player: usize, guesses: usize },
+    /// The race is over: `winner` cracked the code in `guesses` guesses, or
+    /// nobody did.
+    Finished {
+        winner: Option<usize>,
+        guesses: usize,
+        hidden_code: Code,
+    },
+    /// The host stopped the race before it finished.
+    Stopped,
+}
+
+impl Message {
+    /// Writes the message as a line of JSON, with a scored guess or the
+    /// hidden code in `palette`'s letters. The rounds of `Resumed` are
+    /// written in the letters of the config sent with them.
+    pub fn to_json_with(&self, palette: &Palette) -> String {
+        serde_json::to_string(&WireMessage::new(self, palette)).expect("messages always serialize")
+    }
+
+    /// Reads a message back from the JSON `to_json_with` writes with
+    /// `palette`.
+    ///
+    /// ```
+    /// use mastermind::race::Message;
+    /// use mastermind::{Code, GameConfig, Palette};
+    ///
+    /// let palette = Palette::from_letters("0123").unwrap();
+    /// let config = GameConfig {
+    ///     colors: palette.values(),
+    ///     palette,
+    ///     ..GameConfig::default()
+    /// };
+    /// let finished = Message::Finished {
+    ///     winner: None,
+    ///     guesses: 0,
+    ///     hidden_code: Code::from_letters("3012", &config).unwrap(),
+    /// };
+    /// let json = finished.to_json_with(&config.palette);
+    /// assert!(json.contains(r#""hidden_code":"3012""#));
+    /// let read = Message::from_json_with(&json, &config.palette);
+    /// assert_eq!(read.unwrap(), finished);
+    /// // The digits are not classic letters.
+    /// assert!(serde_json::from_str::<Message>(&json).is_err());
+    /// ```
+    pub fn from_json_with(s: &str, palette: &Palette) -> std::result::Result<Self, GameError> {
+        serde_json::from_str::<WireMessage>(s)?.read(palette)
+    }
+}
+
+/// A message as it goes over the wire, its codes in a palette's letters.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(tag = "message", rename_all = "snake_case")]
+enum WireMessage {
+    Welcome {
+        player: usize,
+        config: GameConfig,
+        token: String,
+    },
+    Resumed {
+        player: usize,
+        config: GameConfig,
+        rounds: Vec<SavedRound>,
+        progress: Vec<usize>,
+    },
+    Expired,
+    Scored {
+        round: SavedRound,
+        guesses: usize,
+    },
+    Rejected {
+        reason: String,
+    },
+    Progress {
+        player: usize,
+        guesses: usize,
+    },
+    Finished {
+        winner: Option<usize>,
+        guesses: usize,
+        hidden_code: String,
+    },
+    Stopped,
+}
+
+impl WireMessage {
+    fn new(message: &Message, palette: &Palette) -> WireMessage {
+        match message {
+            Message::Welcome {
+                player,
+                config,
+                token,
+            } => WireMessage::Welcome {
+                player: *player,
+                config: config.clone(),
+                token: token.clone(),
+            },
+            Message::Resumed {
+                player,
+                config,
+                rounds,
+                progress,
+            } => WireMessage::Resumed {
+                player: *player,
+                config: config.clone(),
+                rounds: rounds
+                    .iter()
+                    .map(|round| SavedRound::new(round, &config.palette))
+                    .collect(),
+                progress: progress.clone(),
+            },
+            Message::Expired => WireMessage::Expired,
+            Message::Scored { round, guesses } => WireMessage::Scored {
+                round: SavedRound::new(round, palette),
+                guesses: *guesses,
+            },
+            Message::Rejected { reason } => WireMessage::Rejected {
+                reason: reason.clone(),
+            },
+            Message::Progress { player, guesses } => WireMessage::Progress {
+                player: *player,
+                guesses: *guesses,
+            },
+            Message::Finished {
+                winner,
+                guesses,
+                hidden_code,
+            } => WireMessage::Finished {
+                winner: *winner,
+                guesses: *guesses,
+                hidden_code: hidden_code.letters(palette),
+            },
+            Message::Stopped => WireMessage::Stopped,
+        }
+    }
+
+    fn read(self, palette: &Palette) -> std::result::Result<Message, GameError> {
+        Ok(match self {
+            WireMessage::Welcome {
+                player,
+                config,
+                token,
+            } => Message::Welcome {
+                player,
+                config,
+                token,
+            },
+            WireMessage::Resumed {
+                player,
+                config,
+                rounds,
+                progress,
+            } => Message::Resumed {
+                player,
+                rounds: rounds
+                    .into_iter()
+                    .map(|round| round.read(&config.palette))
+                    .collect::<std::result::Result<_, _>>()?,
+                config,
+                progress,
+            },
+            WireMessage::Expired => Message::Expired,
+            WireMessage::Scored { round, guesses } => Message::Scored {
+                round: round.read(palette)?,
+                guesses,
+            },
+            WireMessage::Rejected { reason } => Message::Rejected { reason },
+            WireMessage::Progress { player, guesses } => Message::Progress { player, guesses },
+            WireMessage::Finished {
+                winner,
+                guesses,
+                hidden_code,
+            } => Message::Finished {
+                winner,
+                guesses,
+                hidden_code: Code::from_saved_letters(&hidden_code, palette)?,
+            },
+            WireMessage::Stopped => Message::Stopped,
+        })
+    }
+}
+
+/// Messages on their own are written with the classic letters.
+///
+/// ```
+/// use mastermind::race::Message;
+///
+/// let message = Message::Progress { player: 2, guesses: 3 };
+/// let json = serde_json::to_string(&message).unwrap();
+/// assert_eq!(json, r#"{"message":"progress","player":2,"guesses":3}"#);
+/// assert_eq!(serde_json::from_str::<Message>(&json).unwrap(), message);
+/// assert!(serde_json::from_str::<Message>(r#"{"message":"cheat"}"#).is_err());
+/// ```
+impl serde::Serialize for Message {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        WireMessage::new(self, palette::classic()).serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Message {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        WireMessage::deserialize(deserializer)?
+            .read(palette::classic())
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// How a race ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RaceResult {
+    /// The player who cracked the code, or `None` if every player ran out of
+    /// guesses or left first.
+    pub winner: Option<usize>,
+    /// How many guesses each player made, in player order.
+    pub guesses: Vec<usize>,
+}
+
+/// What the host hears from the connections it has accepted, each known by
+/// the number it was accepted as.
+enum Event {
+    Connected(usize, TcpStream),
+    Line(usize, std::result::Result<String, GameError>),
+    Closed(usize),
+    /// The host was told to stop.
+    Stop,
+}
+
+/// The host's view of one player.
+struct Seat {
+    token: String,
+    /// The connection the player is on, with its number, unless dropped.
+    connection: Option<(usize, TcpStream)>,
+    /// When the connection dropped, while it is down.
+    dropped_at: Option<Instant>,
+    rounds: Vec<Round>,
+    /// Out of the race: out of guesses, or gone for longer than the grace
+    /// period.
+    done: bool,
+}
+
+impl Seat {
+    /// Sends `message` with pegs in `palette`'s letters, treating the
+    /// connection as dropped if it fails.
+    fn send(&mut self, message: &Message, palette: &Palette) {
+        let Some((_, stream)) = &mut self.connection else {
+            return;
+        };
+        if writeln!(stream, "{}", message.to_json_with(palette)).is_err() {
+            self.drop_connection();
+        }
+    }
+
+    fn drop_connection(&mut self) {
+        self.connection = None;
+        self.dropped_at = Some(Instant::now());
+    }
+
+    fn is_on(&self, id: usize) -> bool {
+        matches!(self.connection, Some((on, _)) if on == id)
+    }
+}
+
+/// Sends `message` down `stream` and closes it, ignoring a connection that
+/// has already gone.
+fn hang_up(mut stream: TcpStream, message: &Message, palette: &Palette) {
+    let _ = writeln!(stream, "{}", message.to_json_with(palette));
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+/// Waits on `listener` for `players` players, then races them to crack
+/// `hidden_code`. Each player's guesses are scored as they arrive; the first
+/// to win ends the race for everyone. A player who drops keeps their place
+/// for `grace`, then forfeits. Lines longer than `max_line_length` bytes are
+/// rejected without being buffered.
+///
+/// ```
+/// use std::io::{BufRead, BufReader, Write};
+/// use std::net::{TcpListener, TcpStream};
+/// use std::time::Duration;
+///
+/// use mastermind::race::{host_race, Message, RaceResult};
+/// use mastermind::{Code, GameConfig};
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let address = listener.local_addr().unwrap();
+/// let host = {
+///     let (config, hidden) = (config.clone(), code("rgby"));
+///     let grace = Duration::from_secs(60);
+///     std::thread::spawn(move || host_race(&listener, 2, &config, &hidden, grace, 64).unwrap())
+/// };
+///
+/// // A scripted player, keeping every line the host sends them.
+/// struct Player(TcpStream, BufReader<TcpStream>, String);
+/// impl Player {
+///     fn guess(&mut self, guess: &str) {
+///         writeln!(self.0, "{}", guess).unwrap();
+///     }
+///     fn hear(&mut self) -> Message {
+///         let mut line = String::new();
+///         self.1.read_line(&mut line).unwrap();
+///         self.2.push_str(&line);
+///         serde_json::from_str::<Message>(&line).unwrap()
+///     }
+/// }
+/// let join = || {
+///     let stream = TcpStream::connect(address).unwrap();
+///     Player(stream.try_clone().unwrap(), BufReader::new(stream), String::new())
+/// };
+/// let (mut alice, mut bob) = (join(), join());
+/// assert!(matches!(alice.hear(), Message::Welcome { player: 1, .. }));
+/// assert!(matches!(bob.hear(), Message::Welcome { player: 2, .. }));
+///
+/// alice.guess("rrgg");
+/// assert!(matches!(alice.hear(), Message::Scored { guesses: 1, .. }));
+/// assert_eq!(bob.hear(), Message::Progress { player: 1, guesses: 1 });
+/// bob.guess("nope");
+/// assert!(matches!(bob.hear(), Message::Rejected { .. }));
+/// bob.guess(&"r".repeat(100_000));
+/// assert_eq!(
+///     bob.hear(),
+///     Message::Rejected { reason: String::from("input line is longer than 64 bytes") }
+/// );
+/// bob.guess("bbyy");
+/// assert!(matches!(bob.hear(), Message::Scored { guesses: 1, .. }));
+/// assert_eq!(alice.hear(), Message::Progress { player: 2, guesses: 1 });
+/// bob.guess("rgby");
+/// assert!(matches!(bob.hear(), Message::Scored { guesses: 2, .. }));
+/// let finished = Message::Finished {
+///     winner: Some(2),
+///     guesses: 2,
+///     hidden_code: code("rgby"),
+/// };
+/// assert_eq!(bob.hear(), finished);
+/// assert_eq!(alice.hear(), Message::Progress { player: 2, guesses: 2 });
+/// assert_eq!(alice.hear(), finished);
+///
+/// // Neither ever saw the other's guesses.
+/// assert!(!alice.2.contains("bbyy") && !alice.2.contains("nope"));
+/// assert!(!bob.2.contains("rrgg"));
+/// assert_eq!(
+///     host.join().unwrap(),
+///     RaceResult { winner: Some(2), guesses: vec![1, 2] }
+/// );
+/// ```
+///
+/// A player whose connection drops resumes with their token, and finds their
+/// rounds as they left them; one who takes too long has forfeited.
+///
+/// ```
+/// # use std::io::{BufRead, BufReader, Write};
+/// # use std::net::{TcpListener, TcpStream};
+/// # use std::time::Duration;
+/// # use mastermind::race::{host_race, Message, RaceResult};
+/// # use mastermind::{Code, GameConfig};
+/// # let config = GameConfig::default();
+/// # let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// # struct Player(TcpStream, BufReader<TcpStream>);
+/// # impl Player {
+/// #     fn say(&mut self, line: &str) {
+/// #         writeln!(self.0, "{}", line).unwrap();
+/// #     }
+/// #     fn hear(&mut self) -> Message {
+/// #         let mut line = String::new();
+/// #         self.1.read_line(&mut line).unwrap();
+/// #         serde_json::from_str::<Message>(&line).unwrap()
+/// #     }
+/// # }
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let address = listener.local_addr().unwrap();
+/// let join = || {
+///     let stream = TcpStream::connect(address).unwrap();
+///     Player(stream.try_clone().unwrap(), BufReader::new(stream))
+/// };
+/// let host = {
+///     let (config, hidden) = (config.clone(), code("rgby"));
+///     let grace = Duration::from_millis(500);
+///     std::thread::spawn(move || host_race(&listener, 2, &config, &hidden, grace, 64).unwrap())
+/// };
+/// let (mut alice, mut bob) = (join(), join());
+/// let Message::Welcome { token: alices, .. } = alice.hear() else { panic!() };
+/// let Message::Welcome { token: bobs, .. } = bob.hear() else { panic!() };
+///
+/// alice.say("rrgg");
+/// alice.hear();
+/// bob.hear();
+/// // Alice's Wi-Fi blips.
+/// drop(alice);
+/// bob.say("bbyy");
+/// bob.hear();
+///
+/// let mut alice = join();
+/// alice.say(&format!("resume {}", alices));
+/// match alice.hear() {
+///     Message::Resumed { player, rounds, progress, .. } => {
+///         assert_eq!(player, 1);
+///         assert_eq!(rounds.len(), 1);
+///         assert_eq!(rounds[0].guess(), &code("rrgg"));
+///         assert_eq!(progress, [1, 1]);
+///     }
+///     other => panic!("{:?}", other),
+/// }
+/// alice.say("rgbb");
+/// assert!(matches!(alice.hear(), Message::Scored { guesses: 2, .. }));
+/// assert_eq!(bob.hear(), Message::Progress { player: 1, guesses: 2 });
+///
+/// // Bob drops for longer than the grace period and has forfeited.
+/// drop(bob);
+/// std::thread::sleep(Duration::from_millis(800));
+/// let mut bob = join();
+/// bob.say(&format!("resume {}", bobs));
+/// assert_eq!(bob.hear(), Message::Expired);
+///
+/// alice.say("rgby");
+/// alice.hear();
+/// assert!(matches!(alice.hear(), Message::Finished { winner: Some(1), .. }));
+/// assert_eq!(host.join().unwrap().winner, Some(1));
+/// ```
+pub fn host_race(
+    listener: &TcpListener,
+    players: usize,
+    config: &GameConfig,
+    hidden_code: &Code,
+    grace: Duration,
+    max_line_length: usize,
+) -> Result<RaceResult> {
+    // Nothing can send on a channel whose sender is gone, so this race runs
+    // to the end.
+    let (_, never) = mpsc::channel();
+    let result = host_race_until(
+        listener,
+        players,
+        config,
+        hidden_code,
+        grace,
+        max_line_length,
+        never,
+    )?;
+    Ok(result.expect("a race nobody can stop runs to the end"))
+}
+
+/// Hosts a race like `host_race`, stopping early when `stop` hears. Every
+/// player and waiting connection is then sent `Message::Stopped` and hung up
+/// on, the listener is no longer accepted on, and the result is `None`.
+///
+/// ```
+/// use std::io::{BufRead, BufReader, Read};
+/// use std::net::{TcpListener, TcpStream};
+/// use std::sync::mpsc;
+/// use std::time::Duration;
+///
+/// use mastermind::race::{host_race_until, Message};
+/// use mastermind::{Code, GameConfig};
+///
+/// let config = GameConfig::default();
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let address = listener.local_addr().unwrap();
+/// let (stop, stopped) = mpsc::channel();
+/// let host = std::thread::spawn(move || {
+///     let hidden = "rgby".parse::<Code>().unwrap();
+///     let grace = Duration::from_secs(60);
+///     host_race_until(&listener, 2, &config, &hidden, grace, 64, stopped).unwrap()
+/// });
+///
+/// // One player of two has joined when the host is stopped.
+/// let mut alice = BufReader::new(TcpStream::connect(address).unwrap());
+/// std::thread::sleep(Duration::from_millis(100));
+/// stop.send(()).unwrap();
+/// assert_eq!(host.join().unwrap(), None);
+///
+/// let mut line = String::new();
+/// alice.read_line(&mut line).unwrap();
+/// assert_eq!(serde_json::from_str::<Message>(&line).unwrap(), Message::Stopped);
+/// // Then the host hangs up.
+/// assert_eq!(alice.read_to_string(&mut line).unwrap(), 0);
+/// ```
+pub fn host_race_until(
+    listener: &TcpListener,
+    players: usize,
+    config: &GameConfig,
+    hidden_code: &Code,
+    grace: Duration,
+    max_line_length: usize,
+    stop: mpsc::Receiver<()>,
+) -> Result<Option<RaceResult>> {
+    let (events, received) = mpsc::channel();
+    {
+        let events = events.clone();
+        std::thread::spawn(move || {
+            if stop.recv().is_ok() {
+                let _ = events.send(Event::Stop);
+            }
+        });
+    }
+    let address = listener.local_addr()?;
+    let listener = listener.try_clone()?;
+    std::thread::spawn(move || {
+        for (id, stream) in listener.incoming().enumerate() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let Ok(reader) = stream.try_clone() else {
+                continue;
+            };
+            if events.send(Event::Connected(id, stream)).is_err() {
+                break;
+            }
+            let events = events.clone();
+            std::thread::spawn(move || read_lines(id, reader, max_line_length, events));
+        }
+    });
+
+    let mut rng = Rng::from_entropy();
+    let mut seats: Vec<Seat> = Vec::new();
+    // Connections made once the race is full, waiting to say whose place
+    // they are resuming.
+    let mut waiting: Vec<(usize, TcpStream)> = Vec::new();
+    let mut winner = None;
+    let mut stopped = false;
+    while !stopped
+        && winner.is_none()
+        && (seats.len() < players || seats.iter().any(|seat| !seat.done))
+    {
+        let deadline = seats
+            .iter()
+            .filter(|seat| !seat.done)
+            .filter_map(|seat| seat.dropped_at)
+            .min()
+            .map(|dropped| (dropped + grace).saturating_duration_since(Instant::now()));
+        let event = match deadline {
+            Some(wait) => received.recv_timeout(wait),
+            None => received.recv().map_err(RecvTimeoutError::from),
+        };
+        let event = match event {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => {
+                for seat in &mut seats {
+                    if seat.dropped_at.is_some_and(|at| at.elapsed() >= grace) {
+                        seat.done = true;
+                    }
+                }
+                continue;
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        match event {
+            Event::Stop => stopped = true,
+            Event::Connected(id, stream) if seats.len() < players => {
+                seats.push(Seat {
+                    token: format!("{:016x}", rng.next_u64()),
+                    connection: Some((id, stream)),
+                    dropped_at: None,
+                    rounds: Vec::new(),
+                    done: false,
+                });
+                if seats.len() == players {
+                    for (i, seat) in seats.iter_mut().enumerate() {
+                        let token = seat.token.clone();
+                        seat.send(
+                            &Message::Welcome {
+                                player: i + 1,
+                                config: config.clone(),
+                                token,
+                            },
+                            &config.palette,
+                        );
+                    }
+                }
+            }
+            Event::Connected(id, stream) => waiting.push((id, stream)),
+            Event::Closed(id) => {
+                waiting.retain(|(waiting, _)| *waiting != id);
+                if let Some(seat) = seats.iter_mut().find(|seat| seat.is_on(id)) {
+                    seat.drop_connection();
+                }
+            }
+            Event::Line(id, line) => {
+                if let Some(at) = waiting.iter().position(|(waiting, _)| *waiting == id) {
+                    let (id, stream) = waiting.remove(at);
+                    resume(&mut seats, id, stream, line, config, grace);
+                } else if let Some(player) = seats.iter().position(|seat| seat.is_on(id)) {
+                    if seats.len() < players {
+                        seats[player].send(
+                            &Message::Rejected {
+                                reason: String::from("the race has not started yet"),
+                            },
+                            &config.palette,
+                        );
+                    } else if guess(&mut seats, player, line, config, hidden_code) {
+                        winner = Some(player);
+                    }
+                }
+            }
+        }
+    }
+    if stopped {
+        let connections = seats
+            .into_iter()
+            .filter_map(|seat| seat.connection)
+            .chain(waiting);
+        for (_, stream) in connections {
+            hang_up(stream, &Message::Stopped, &config.palette);
+        }
+        // The accepting thread holds its own handle on the listener, and only
+        // lets go when a connection finds nobody left to hear about it.
+        drop(received);
+        let _ = TcpStream::connect(reachable(address));
+        return Ok(None);
+    }
+    let finished = Message::Finished {
+        winner: winner.map(|i| i + 1),
+        guesses: winner.map_or(0, |i| seats[i].rounds.len()),
+        hidden_code: hidden_code.clone(),
+    };
+    for seat in &mut seats {
+        seat.send(&finished, &config.palette);
+    }
+    Ok(Some(RaceResult {
+        winner: winner.map(|i| i + 1),
+        guesses: seats.iter().map(|seat| seat.rounds.len()).collect(),
+    }))
+}
+
+/// The address to connect to for a listener bound to `address`, which may
+/// be on every interface rather than one that can be connected to.
+fn reachable(mut address: SocketAddr) -> SocketAddr {
+    if address.ip().is_unspecified() {
+        address.set_ip(match address {
+            SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+            SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+        });
+    }
+    address
+}
+
+/// Sends each line, of at most `limit` bytes, read from connection `id` to
+/// `events`, then that it closed.
+fn read_lines(id: usize, stream: TcpStream, limit: usize, events: mpsc::Sender<Event>) {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        let event = match read_line_limited(&mut reader, &mut line, limit) {
+            Ok(0) | Err(GameError::Io(_)) => break,
+            Ok(_) => Event::Line(id, Ok(normalize_input(&line).to_string())),
+            Err(e) => Event::Line(id, Err(e)),
+        };
+        if events.send(event).is_err() {
+            return;
+        }
+    }
+    let _ = events.send(Event::Closed(id));
+}
+
+/// Puts connection `id` back in the place whose token `line` names, if that
+/// player has not forfeited, and brings them up to date.
+fn resume(
+    seats: &mut [Seat],
+    id: usize,
+    mut stream: TcpStream,
+    line: std::result::Result<String, GameError>,
+    config: &GameConfig,
+    grace: Duration,
+) {
+    let token = line.ok();
+    let token = token
+        .as_deref()
+        .and_then(|line| line.strip_prefix("resume "));
+    let progress: Vec<usize> = seats.iter().map(|seat| seat.rounds.len()).collect();
+    let place = seats.iter_mut().enumerate().find(|(_, seat)| {
+        Some(seat.token.as_str()) == token
+            && !seat.done
+            && seat.dropped_at.is_none_or(|at| at.elapsed() < grace)
+    });
+    let Some((player, seat)) = place else {
+        let _ = writeln!(
+            stream,
+            "{}",
+            Message::Expired.to_json_with(palette::classic())
+        );
+        return;
+    };
+    // The old connection may not have been noticed dropping yet.
+    seat.connection = Some((id, stream));
+    seat.dropped_at = None;
+    let rounds = seat.rounds.clone();
+    seat.send(
+        &Message::Resumed {
+            player: player + 1,
+            config: config.clone(),
+            rounds,
+            progress,
+        },
+        &config.palette,
+    );
+}
+
+/// Scores `line` as the next guess of `player`, telling the others how many
+/// guesses they have made. Returns whether it won.
+fn guess(
+    seats: &mut [Seat],
+    player: usize,
+    line: std::result::Result<String, GameError>,
+    config: &GameConfig,
+    hidden_code: &Code,
+) -> bool {
+    let seat = &mut seats[player];
+    if seat.done {
+        seat.send(
+            &Message::Rejected {
+                reason: String::from("you are out of guesses"),
+            },
+            &config.palette,
+        );
+        return false;
+    }
+    let code = match line
+        .map_err(Box::from)
+        .and_then(|line| Code::parse(line, config))
+    {
+        Ok(code) => code,
+        Err(e) => {
+            seat.send(
+                &Message::Rejected {
+                    reason: e.to_string(),
+                },
+                &config.palette,
+            );
+            return false;
+        }
+    };
+    let round = Round::from_guess(code, hidden_code);
+    let wins = round.wins();
+    seat.rounds.push(round.clone());
+    let guesses = seat.rounds.len();
+    if !wins && Some(guesses) == config.max_guesses {
+        seat.done = true;
+    }
+    seat.send(&Message::Scored { round, guesses }, &config.palette);
+    for (i, other) in seats.iter_mut().enumerate() {
+        if i != player {
+            other.send(
+                &Message::Progress {
+                    player: player + 1,
+                    guesses,
+                },
+                &config.palette,
+            );
+        }
+    }
+    wins
+}
+
+/// How taking part in a race ended for one player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ending {
+    /// The race finished, and this player did or did not win it.
+    Finished { won: bool },
+    /// The connection closed before the race finished; the player can try to
+    /// resume.
+    HostGone,
+    /// The host no longer had the session asked to resume.
+    SessionExpired,
+    /// The host stopped the race before it finished.
+    Stopped,
+}
+
+/// Plays in the race hosted at the other end of `stream`: sends each line of
+/// `input` as a guess and writes what the host says to `output` until the race
+/// is over. With `resume`, first asks to take back the place with that
+/// session token. Lines of `input` longer than `max_line_length` bytes are
+/// sent as blank lines.
+pub fn join_race<R: BufRead + Send + 'static, W: Write>(
+    stream: TcpStream,
+    mut input: R,
+    output: &mut W,
+    resume: Option<&str>,
+    max_line_length: usize,
+) -> Result<Ending> {
+    let mut guesses = stream.try_clone()?;
+    if let Some(token) = resume {
+        writeln!(guesses, "resume {}", token)?;
+    }
+    std::thread::spawn(move || loop {
+        let mut line = String::new();
+        match read_line_limited(&mut input, &mut line, max_line_length) {
+            Ok(0) | Err(GameError::Io(_)) => break,
+            Ok(_) | Err(_) => {}
+        }
+        if writeln!(guesses, "{}", normalize_input(&line)).is_err() {
+            break;
+        }
+    });
+    let mut me = 0;
+    // The race's palette arrives with the first message.
+    let mut palette = Palette::classic();
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let message = Message::from_json_with(&line, &palette)?;
+        if let Message::Welcome { config, .. } | Message::Resumed { config, .. } = &message {
+            palette = config.palette.clone();
+        }
+        writeln!(output, "{}", describe(&message, me, &palette))?;
+        match message {
+            Message::Welcome { player, .. } | Message::Resumed { player, .. } => me = player,
+            Message::Expired => return Ok(Ending::SessionExpired),
+            Message::Stopped => return Ok(Ending::Stopped),
+            Message::Finished { winner, .. } => {
+                return Ok(Ending::Finished {
+                    won: winner == Some(me),
+                })
+            }
+            _ => {}
+        }
+    }
+    Ok(Ending::HostGone)
+}
+
+/// Describes `message` to player `me`, with pegs in `palette`'s letters.
+///
+/// ```
+/// use mastermind::display::set_color;
+/// use mastermind::race::{describe, Message};
+/// use mastermind::{Code, Palette};
+///
+/// set_color(false);
+/// let classic = Palette::classic();
+/// let progress = Message::Progress { player: 2, guesses: 3 };
+/// assert_eq!(describe(&progress, 1, &classic), "player 2 has made 3 guesses");
+///
+/// let finished = Message::Finished {
+///     winner: Some(2),
+///     guesses: 4,
+///     hidden_code: "rgby".parse::<Code>().unwrap(),
+/// };
+/// assert_eq!(describe(&finished, 2, &classic), "you cracked it first, in 4 guesses!");
+/// assert_eq!(
+///     describe(&finished, 1, &classic),
+///     "player 2 cracked it first, in 4 guesses; the code was r g b y"
+/// );
+/// let digits = Palette::from_letters("0123").unwrap();
+/// assert!(describe(&finished, 1, &digits).ends_with("the code was 0 1 2 3"));
+/// ```
+pub fn describe(message: &Message, me: usize, palette: &Palette) -> String {
+    match message {
+        Message::Welcome {
+            player,
+            config,
+            token,
+        } => {
+            let letters: Vec<String> = config
+                .colors
+                .iter()
+                .map(|c| c.glyph(&config.palette, DisplayMode::Letters))
+                .collect();
+            format!(
+                "you are player {}; crack the {}-peg code of {} before anyone else\n\
+                 if your connection drops, rejoin with --resume {}",
+                player,
+                config.code_length,
+                letters.join(" "),
+                token
+            )
+        }
+        Message::Resumed {
+            player,
+            config,
+            rounds,
+            progress,
+        } => {
+            let mut lines = vec![format!("resumed as player {}", player)];
+            lines.extend(
+                rounds
+                    .iter()
+                    .map(|round| round.display(&config.palette).to_string()),
+            );
+            lines.extend(
+                progress
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| i + 1 != *player)
+                    .map(|(i, guesses)| {
+                        let progress = Message::Progress {
+                            player: i + 1,
+                            guesses: *guesses,
+                        };
+                        describe(&progress, me, palette)
+                    }),
+            );
+            lines.join("\n")
+        }
+        Message::Expired => {
+            String::from("that session has expired, so the race went on without you")
+        }
+        Message::Scored { round, .. } => round.display(palette).to_string(),
+        Message::Rejected { reason } => reason.clone(),
+        Message::Progress { player, guesses } => format!(
+            "player {} has made {} {}",
+            player,
+            guesses,
+            if *guesses == 1 { "guess" } else { "guesses" }
+        ),
+        Message::Finished {
+            winner: Some(winner),
+            guesses,
+            ..
+        } if *winner == me => format!("you cracked it first, in {} guesses!", guesses),
+        Message::Finished {
+            winner: Some(winner),
+            guesses,
+            hidden_code,
+        } => format!(
+            "player {} cracked it first, in {} guesses; the code was {}",
+            winner,
+            guesses,
+            hidden_code.display(palette)
+        ),
+        Message::Finished { hidden_code, .. } => {
+            format!(
+                "nobody cracked it; the code was {}",
+                hidden_code.display(palette)
+            )
+        }
+        Message::Stopped => String::from("the host stopped the race"),
+    }
+}
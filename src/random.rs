@@ -0,0 +1,51 @@
+//! A small pseudo-random number generator for choosing hidden codes.
+//!
+//! This is SplitMix64: fast, statistically decent and fully reproducible from a
+//! seed, which is all a game needs. It is not suitable for anything
+//! security-sensitive.
+
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a generator that always produces the same sequence for `seed`.
+    pub fn seeded(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Creates a generator seeded by the operating system.
+    #[cfg(feature = "rand")]
+    pub fn from_entropy() -> Self {
+        Self::seeded(Self::entropy_seed())
+    }
+
+    /// Returns a seed drawn from the operating system, for callers that want
+    /// to record the seed they play with.
+    #[cfg(feature = "rand")]
+    pub fn entropy_seed() -> u64 {
+        rand::random()
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in `0..n`. `n` must not be zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        // Rejection sampling keeps the result uniform when n does not divide 2^64.
+        let n = n as u64;
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return (x % n) as usize;
+            }
+        }
+    }
+}
@@ -0,0 +1,381 @@
+//! Summing up a directory of saved games: how each setting went, how many
+//! guesses wins took, which first guesses worked, and how often guesses
+//! ignored what earlier feedback had shown.
+//!
+//! Each file is read the way `analyze-transcript` reads one, with
+//! `save::load_game`, and its guesses judged by `analysis::review_game`.
+//! Saves carry no date, so a game is dated by when its file was last written.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use chrono::NaiveDate;
+
+use crate::analysis::{review_game, Verdict};
+use crate::board::Board;
+use crate::date::day_of;
+use crate::save::{load_game, ScoreCheck};
+use crate::Result;
+
+/// How many first guesses the report lists.
+pub const OPENINGS_LISTED: usize = 5;
+
+/// One saved game and the day its file was last written.
+pub struct Transcript {
+    pub path: PathBuf,
+    pub date: NaiveDate,
+    pub board: Board,
+}
+
+/// A file that could not be read as a game, and why.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Unreadable {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// Reads every file under `dir`, and the directories in it, in path order.
+/// Games dated outside `since` to `until`, inclusive, are left out; files that
+/// are not games are returned separately.
+pub fn read_transcripts(
+    dir: &Path,
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+) -> Result<(Vec<Transcript>, Vec<Unreadable>)> {
+    let mut paths = Vec::new();
+    collect_files(dir, &mut paths)?;
+    paths.sort();
+    let mut transcripts = Vec::new();
+    let mut unreadable = Vec::new();
+    for path in paths {
+        let modified = std::fs::metadata(&path)?.modified()?;
+        let seconds = modified
+            .duration_since(UNIX_EPOCH)
+            .map(|age| age.as_secs())
+            .unwrap_or_default();
+        let date = day_of(seconds);
+        if since.is_some_and(|since| date < since) || until.is_some_and(|until| date > until) {
+            continue;
+        }
+        match load_game(&path, ScoreCheck::Warn, &mut io::sink()) {
+            Ok(board) => transcripts.push(Transcript { path, date, board }),
+            Err(e) => unreadable.push(Unreadable {
+                path,
+                reason: e.to_string(),
+            }),
+        }
+    }
+    Ok((transcripts, unreadable))
+}
+
+fn collect_files(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, paths)?;
+        } else {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Games played and won.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub games: usize,
+    pub wins: usize,
+}
+
+impl Tally {
+    fn add(&mut self, won: bool) {
+        self.games += 1;
+        self.wins += usize::from(won);
+    }
+
+    /// Returns the percentage of games won, or 0 with no games.
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        100.0 * self.wins as f64 / self.games as f64
+    }
+}
+
+/// Totals over a set of saved games.
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use mastermind::report::Report;
+/// use mastermind::report::Transcript;
+/// use mastermind::{Board, Code, GameConfig};
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let game = |hidden: &str, guesses: &[&str]| {
+///     let mut board = Board::new(config.clone(), code(hidden));
+///     for guess in guesses {
+///         board.guess(code(guess));
+///     }
+///     let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+///     Transcript { path: "game.json".into(), date, board }
+/// };
+/// let report = Report::new(
+///     &[
+///         game("rgby", &["rrgg", "rgby"]),
+///         game("yyyy", &["rrgg", "bbbb", "yyyy"]),
+///         // Once rrgg scored nothing, rgrg could not have been the code.
+///         game("bbyy", &["rrgg", "rgrg"]),
+///     ],
+///     Vec::new(),
+/// );
+/// assert_eq!((report.total.games, report.total.wins), (3, 2));
+/// assert_eq!(report.guesses_to_win.get(&2), Some(&1));
+/// assert_eq!(report.guesses_to_win.get(&3), Some(&1));
+/// assert_eq!(report.openings["rrgg"].games, 3);
+/// assert_eq!(report.rounds, 7);
+/// assert_eq!(report.already_impossible, 1);
+///
+/// let mut text = Vec::new();
+/// report.write_text(&mut text).unwrap();
+/// let text = String::from_utf8(text).unwrap();
+/// assert!(text.starts_with("games: 3, won 2 (67%)\n"), "{}", text);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    pub total: Tally,
+    /// Games by code length, then number of colors.
+    pub settings: BTreeMap<(usize, usize), Tally>,
+    /// How many wins took each number of guesses.
+    pub guesses_to_win: BTreeMap<usize, usize>,
+    /// Games by their first guess, written as letters.
+    pub openings: BTreeMap<String, Tally>,
+    /// Guesses made, over every game.
+    pub rounds: usize,
+    /// Guesses that could not have been the code, given earlier feedback.
+    pub already_impossible: usize,
+    /// Guesses that left more codes in the worst case than the best would have.
+    pub inefficient: usize,
+    pub unreadable: Vec<Unreadable>,
+}
+
+impl Report {
+    /// Totals up `transcripts`, reviewing every guess of each.
+    pub fn new(transcripts: &[Transcript], unreadable: Vec<Unreadable>) -> Report {
+        let mut report = Report {
+            unreadable,
+            ..Report::default()
+        };
+        for transcript in transcripts {
+            let board = &transcript.board;
+            let config = board.config();
+            let won = board.last_round().is_some_and(|round| round.wins());
+            report.total.add(won);
+            report
+                .settings
+                .entry((config.code_length, config.colors.len()))
+                .or_default()
+                .add(won);
+            if won {
+                *report
+                    .guesses_to_win
+                    .entry(board.rounds().len())
+                    .or_default() += 1;
+            }
+            if let Some(first) = board.rounds().first() {
+                let letters = first
+                    .guess()
+                    .colors()
+                    .iter()
+                    .map(|color| config.palette.style(color).letter)
+                    .collect();
+                report.openings.entry(letters).or_default().add(won);
+            }
+            report.rounds += board.rounds().len();
+            for review in review_game(board) {
+                match review.verdict {
+                    Verdict::AlreadyImpossible => report.already_impossible += 1,
+                    Verdict::Inefficient => report.inefficient += 1,
+                    Verdict::Fine => {}
+                }
+            }
+        }
+        report
+    }
+
+    /// Returns the most played first guesses, most played first, at most
+    /// `OPENINGS_LISTED` of them.
+    pub fn top_openings(&self) -> Vec<(&str, Tally)> {
+        let mut openings: Vec<(&str, Tally)> = self
+            .openings
+            .iter()
+            .map(|(guess, tally)| (guess.as_str(), *tally))
+            .collect();
+        openings.sort_by(|a, b| b.1.games.cmp(&a.1.games).then(a.0.cmp(b.0)));
+        openings.truncate(OPENINGS_LISTED);
+        openings
+    }
+
+    /// Writes the report as tables.
+    pub fn write_text<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(
+            out,
+            "games: {}, won {} ({:.0}%)",
+            self.total.games,
+            self.total.wins,
+            self.total.win_rate()
+        )?;
+        if !self.settings.is_empty() {
+            writeln!(out, "\n{:<20} {:>5} {:>5}", "settings", "games", "won")?;
+            for ((length, colors), tally) in &self.settings {
+                let name = format!("{} pegs, {} colors", length, colors);
+                writeln!(
+                    out,
+                    "{:<20} {:>5} {:>4.0}%",
+                    name,
+                    tally.games,
+                    tally.win_rate()
+                )?;
+            }
+        }
+        if !self.guesses_to_win.is_empty() {
+            writeln!(out, "\n{:<20} {:>5}", "guesses to win", "games")?;
+            for (guesses, games) in &self.guesses_to_win {
+                writeln!(out, "{:<20} {:>5}  {}", guesses, games, "#".repeat(*games))?;
+            }
+        }
+        let openings = self.top_openings();
+        if !openings.is_empty() {
+            writeln!(out, "\n{:<20} {:>5} {:>5}", "first guess", "games", "won")?;
+            for (guess, tally) in openings {
+                writeln!(
+                    out,
+                    "{:<20} {:>5} {:>4.0}%",
+                    guess,
+                    tally.games,
+                    tally.win_rate()
+                )?;
+            }
+        }
+        if self.rounds > 0 {
+            let share = |n: usize| 100.0 * n as f64 / self.rounds as f64;
+            writeln!(out, "\nhabits, over {} guesses:", self.rounds)?;
+            writeln!(
+                out,
+                "  {} ({:.0}%) could not have been the code, given earlier feedback",
+                self.already_impossible,
+                share(self.already_impossible)
+            )?;
+            writeln!(
+                out,
+                "  {} ({:.0}%) could leave more codes than the best guess",
+                self.inefficient,
+                share(self.inefficient)
+            )?;
+        }
+        if !self.unreadable.is_empty() {
+            writeln!(
+                out,
+                "\nskipped {} unreadable file{}:",
+                self.unreadable.len(),
+                if self.unreadable.len() == 1 { "" } else { "s" }
+            )?;
+            for file in &self.unreadable {
+                writeln!(out, "  {}: {}", file.path.display(), file.reason)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Written with each setting, winning guess count, first guess and unreadable
+/// file as an object of its own, in the order the text report lists them.
+impl serde::Serialize for Report {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        SavedReport {
+            games: self.total.games,
+            wins: self.total.wins,
+            settings: self
+                .settings
+                .iter()
+                .map(|(&(code_length, colors), tally)| SavedSetting {
+                    code_length,
+                    colors,
+                    games: tally.games,
+                    wins: tally.wins,
+                })
+                .collect(),
+            guesses_to_win: self
+                .guesses_to_win
+                .iter()
+                .map(|(&guesses, &games)| SavedGuessCount { guesses, games })
+                .collect(),
+            first_guesses: self
+                .top_openings()
+                .into_iter()
+                .map(|(guess, tally)| SavedOpening {
+                    guess,
+                    games: tally.games,
+                    wins: tally.wins,
+                })
+                .collect(),
+            rounds: self.rounds,
+            already_impossible: self.already_impossible,
+            inefficient: self.inefficient,
+            unreadable: self
+                .unreadable
+                .iter()
+                .map(|file| SavedUnreadable {
+                    path: file.path.display().to_string(),
+                    reason: &file.reason,
+                })
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(serde::Serialize)]
+struct SavedReport<'a> {
+    games: usize,
+    wins: usize,
+    settings: Vec<SavedSetting>,
+    guesses_to_win: Vec<SavedGuessCount>,
+    first_guesses: Vec<SavedOpening<'a>>,
+    rounds: usize,
+    already_impossible: usize,
+    inefficient: usize,
+    unreadable: Vec<SavedUnreadable<'a>>,
+}
+
+#[derive(serde::Serialize)]
+struct SavedSetting {
+    code_length: usize,
+    colors: usize,
+    games: usize,
+    wins: usize,
+}
+
+#[derive(serde::Serialize)]
+struct SavedGuessCount {
+    guesses: usize,
+    games: usize,
+}
+
+#[derive(serde::Serialize)]
+struct SavedOpening<'a> {
+    guess: &'a str,
+    games: usize,
+    wins: usize,
+}
+
+#[derive(serde::Serialize)]
+struct SavedUnreadable<'a> {
+    path: String,
+    reason: &'a str,
+}
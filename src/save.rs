@@ -0,0 +1,488 @@
+//! Loading saved games, and checking the scores they store against the ones
+//! the hidden code gives today.
+//!
+//! Saves keep each round's score as well as its guess. A save written before
+//! a change to the scoring rules, or edited by hand, can hold scores that the
+//! hidden code no longer gives, so every score is worked out again on loading.
+
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::io::Write;
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::board::SkipReason;
+use crate::board::{Board, Round, RoundOutcome};
+use crate::code::{Code, Color};
+#[cfg(feature = "serde")]
+use crate::config::GameConfig;
+#[cfg(feature = "serde")]
+use crate::error::GameError;
+#[cfg(feature = "serde")]
+use crate::palette::Palette;
+use crate::score::Score;
+#[cfg(feature = "serde")]
+use crate::Result;
+
+/// The version of the save format a saved `Board` is written in. Saves from before
+/// versions were written count as version 1; version 2 saves were scored, or
+/// rescored, by the current rules.
+pub const FORMAT_VERSION: usize = 2;
+
+/// A round whose saved score is not the one its guess gets today.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScoreMismatch {
+    /// The round, counting from 1.
+    pub round: usize,
+    pub guess: Code,
+    pub saved: Score,
+    pub recomputed: Score,
+}
+
+impl fmt::Display for ScoreMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let compact = |score: &Score| format!("{}b{}w", score.black_count(), score.white_count());
+        write!(
+            f,
+            "round {} ({}): saved as {} but scores {}",
+            self.round,
+            self.guess
+                .colors()
+                .iter()
+                .map(Color::letter)
+                .collect::<String>(),
+            compact(&self.saved),
+            compact(&self.recomputed)
+        )
+    }
+}
+
+/// Returns every round of `board` whose score is not the one its guess gets
+/// against the hidden code.
+///
+/// ```
+/// # #[cfg(feature = "serde")] {
+/// use mastermind::save::score_mismatches;
+/// use mastermind::Board;
+///
+/// // Saved when a repeated color could earn a white peg as well as a black one.
+/// let saved = r#"{
+///     "config": {"colors": ["r", "g", "b", "y"], "code_length": 4},
+///     "hidden_code": "rrgb",
+///     "rounds": [
+///         {"guess": "rrrr", "score": {"blacks": 2, "whites": 2}},
+///         {"guess": "grbr", "score": {"blacks": 1, "whites": 3}}
+///     ]
+/// }"#;
+/// let board: Board = serde_json::from_str(saved).unwrap();
+/// let mismatches = score_mismatches(&board);
+/// assert_eq!(mismatches.len(), 1);
+/// assert_eq!(
+///     mismatches[0].to_string(),
+///     "round 1 (rrrr): saved as 2b2w but scores 2b0w"
+/// );
+/// # }
+/// ```
+pub fn score_mismatches(board: &Board) -> Vec<ScoreMismatch> {
+    board
+        .rounds()
+        .iter()
+        .enumerate()
+        .filter_map(|(i, round)| {
+            let recomputed = board.hidden_code().score(round.guess());
+            (recomputed != *round.score()).then(|| ScoreMismatch {
+                round: i + 1,
+                guess: round.guess().clone(),
+                saved: round.score().clone(),
+                recomputed,
+            })
+        })
+        .collect()
+}
+
+/// Returns `board` with every round scored again against the hidden code.
+/// Skipped guesses stay where they were.
+pub fn rescore(board: &Board) -> Board {
+    let outcomes = board
+        .outcomes()
+        .iter()
+        .map(|outcome| match outcome {
+            RoundOutcome::Guessed(round) => RoundOutcome::Guessed(Round::from_guess(
+                round.guess().clone(),
+                board.hidden_code(),
+            )),
+            skipped => skipped.clone(),
+        })
+        .collect();
+    Board::from_parts(
+        board.config().clone(),
+        board.hidden_code().clone(),
+        outcomes,
+    )
+}
+
+/// What to do about saved scores that disagree with the hidden code.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScoreCheck {
+    /// Warn about each one, then carry on with the scores as saved.
+    #[default]
+    Warn,
+    /// Refuse to load the save.
+    Strict,
+    /// Rescore every round and write the save back in the current format.
+    Migrate,
+}
+
+/// Loads the game saved at `path`, checking its scores as `check` says and
+/// writing any warnings to `out`.
+///
+/// ```
+/// use mastermind::save::{load_game, ScoreCheck};
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("save.json");
+/// std::fs::write(
+///     &path,
+///     r#"{"config": {"colors": ["r", "g", "b", "y"], "code_length": 4}, "hidden_code": "rrgb",
+///         "rounds": [{"guess": "rrrr", "score": {"blacks": 2, "whites": 2}}]}"#,
+/// )
+/// .unwrap();
+///
+/// let mut out = Vec::new();
+/// let board = load_game(&path, ScoreCheck::Warn, &mut out).unwrap();
+/// assert_eq!(board.rounds()[0].score().white_count(), 2);
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "warning: round 1 (rrrr): saved as 2b2w but scores 2b0w\n"
+/// );
+///
+/// let Err(strict) = load_game(&path, ScoreCheck::Strict, &mut Vec::new()) else {
+///     panic!("loaded despite the mismatch");
+/// };
+/// assert!(strict.to_string().contains("1 round scored differently"));
+///
+/// let mut out = Vec::new();
+/// let board = load_game(&path, ScoreCheck::Migrate, &mut out).unwrap();
+/// assert_eq!(board.rounds()[0].score().white_count(), 0);
+/// let migrated: serde_json::Value =
+///     serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+/// assert_eq!(migrated["version"], 2);
+/// let mut out = Vec::new();
+/// load_game(&path, ScoreCheck::Strict, &mut out).unwrap();
+/// assert!(out.is_empty());
+/// ```
+#[cfg(feature = "serde")]
+pub fn load_game<W: Write>(path: &Path, check: ScoreCheck, out: &mut W) -> Result<Board> {
+    let text = std::fs::read_to_string(path)?;
+    let board: Board = serde_json::from_str(&text).map_err(GameError::from)?;
+    let mismatches = score_mismatches(&board);
+    if mismatches.is_empty() {
+        return Ok(board);
+    }
+    match check {
+        ScoreCheck::Warn => {
+            for mismatch in &mismatches {
+                writeln!(out, "warning: {}", mismatch)?;
+            }
+            Ok(board)
+        }
+        ScoreCheck::Strict => {
+            let lines: Vec<String> = mismatches.iter().map(ToString::to_string).collect();
+            Err(format!(
+                "{} has {} round{} scored differently from the hidden code:\n  {}",
+                path.display(),
+                mismatches.len(),
+                if mismatches.len() == 1 { "" } else { "s" },
+                lines.join("\n  ")
+            )
+            .into())
+        }
+        ScoreCheck::Migrate => {
+            let board = rescore(&board);
+            std::fs::write(path, serde_json::to_string(&board)?)?;
+            writeln!(
+                out,
+                "rescored {} of {} rounds in {}",
+                mismatches.len(),
+                board.rounds().len(),
+                path.display()
+            )?;
+            Ok(board)
+        }
+    }
+}
+
+/// A round as saves write it, its guess in the letters of the game's palette.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SavedRound {
+    guess: String,
+    score: Score,
+}
+
+#[cfg(feature = "serde")]
+impl SavedRound {
+    pub(crate) fn new(round: &Round, palette: &Palette) -> SavedRound {
+        SavedRound {
+            guess: round.guess().letters(palette),
+            score: round.score().clone(),
+        }
+    }
+
+    /// Reads the round back, refusing a score with more key pegs than the
+    /// guess has pegs.
+    pub(crate) fn read(self, palette: &Palette) -> std::result::Result<Round, GameError> {
+        let guess = Code::from_saved_letters(&self.guess, palette)?;
+        let score = Score::from_counts(
+            self.score.black_count(),
+            self.score.white_count(),
+            guess.len(),
+        )?;
+        Ok(Round::new(guess, score))
+    }
+}
+
+/// A skipped guess, written as the number of rounds played before it.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SavedSkip {
+    after: usize,
+    reason: SkipReason,
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn saved_skips(skipped: &[(usize, SkipReason)]) -> Vec<SavedSkip> {
+    skipped
+        .iter()
+        .map(|&(after, reason)| SavedSkip { after, reason })
+        .collect()
+}
+
+/// Reads skipped guesses back in order, refusing one after more rounds than
+/// were played.
+#[cfg(feature = "serde")]
+pub(crate) fn read_skips(
+    skipped: Vec<SavedSkip>,
+    rounds: usize,
+) -> std::result::Result<Vec<(usize, SkipReason)>, GameError> {
+    if skipped.iter().any(|skip| skip.after > rounds) {
+        return Err(GameError::ParseError(String::from("invalid skipped guess")));
+    }
+    let mut read: Vec<_> = skipped
+        .into_iter()
+        .map(|skip| (skip.after, skip.reason))
+        .collect();
+    read.sort_by_key(|(after, _)| *after);
+    Ok(read)
+}
+
+/// Puts `skipped`, each after the number of `rounds` it gives, back among
+/// the rounds.
+#[cfg(feature = "serde")]
+pub(crate) fn with_skipped(
+    rounds: Vec<Round>,
+    skipped: &[(usize, SkipReason)],
+) -> Vec<RoundOutcome> {
+    let mut outcomes = Vec::with_capacity(rounds.len() + skipped.len());
+    let mut skipped = skipped.iter().peekable();
+    for (index, round) in rounds.into_iter().enumerate() {
+        while let Some((_, reason)) = skipped.next_if(|(before, _)| *before <= index) {
+            outcomes.push(RoundOutcome::Skipped { reason: *reason });
+        }
+        outcomes.push(RoundOutcome::Guessed(round));
+    }
+    outcomes.extend(skipped.map(|(_, reason)| RoundOutcome::Skipped { reason: *reason }));
+    outcomes
+}
+
+/// A board as saves write it. Skipped guesses are written only when there
+/// are some, so older readers still understand games without them.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct SavedBoard {
+    #[serde(default)]
+    version: Option<usize>,
+    config: GameConfig,
+    hidden_code: String,
+    rounds: Vec<SavedRound>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    skipped: Vec<SavedSkip>,
+}
+
+/// Codes are written in the letters of the game's palette.
+///
+/// ```
+/// use mastermind::board::SkipReason;
+/// use mastermind::{Board, Code, GameConfig, Palette};
+///
+/// let palette = Palette::from_letters("0123456789").unwrap();
+/// let digits = GameConfig {
+///     colors: palette.values()[..6].to_vec(),
+///     palette,
+///     ..GameConfig::default()
+/// };
+/// let code = |s| Code::from_letters(s, &digits).unwrap();
+/// let mut board = Board::new(digits.clone(), code("5052"));
+/// board.skip(SkipReason::Timeout);
+/// board.guess(code("0123"));
+/// let json = serde_json::to_string(&board).unwrap();
+/// assert!(json.contains(r#""hidden_code":"5052""#));
+/// assert!(json.contains(r#""guess":"0123""#));
+/// assert!(json.contains(r#""skipped":[{"after":0,"reason":"timeout"}]"#));
+///
+/// let loaded: Board = serde_json::from_str(&json).unwrap();
+/// assert_eq!(loaded.config(), &digits);
+/// assert_eq!(loaded.outcomes(), board.outcomes());
+///
+/// // Games saved before guesses could be skipped have none.
+/// let mut fresh = Board::new(digits.clone(), code("5052"));
+/// fresh.guess(code("0123"));
+/// assert!(!serde_json::to_string(&fresh).unwrap().contains("skipped"));
+/// ```
+#[cfg(feature = "serde")]
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let palette = &self.config().palette;
+        SavedBoard {
+            version: Some(FORMAT_VERSION),
+            config: self.config().clone(),
+            hidden_code: self.hidden_code().letters(palette),
+            rounds: self
+                .rounds()
+                .iter()
+                .map(|round| SavedRound::new(round, palette))
+                .collect(),
+            skipped: saved_skips(&self.skipped()),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Every code must have as many pegs as the config says.
+///
+/// ```
+/// use mastermind::Board;
+///
+/// let short = r#"{
+///     "config": {"colors": ["r", "g", "b", "y"], "code_length": 4},
+///     "hidden_code": "rgb",
+///     "rounds": []
+/// }"#;
+/// let Err(error) = serde_json::from_str::<Board>(short) else {
+///     panic!("loaded a code that is too short");
+/// };
+/// assert!(error.to_string().contains(r#"code "rgb" does not have 4 pegs"#));
+/// ```
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let saved = SavedBoard::deserialize(deserializer)?;
+        if saved
+            .version
+            .is_some_and(|version| version > FORMAT_VERSION)
+        {
+            return Err(D::Error::custom(format!(
+                "unsupported save format version; up to {} can be read",
+                FORMAT_VERSION
+            )));
+        }
+        let config = saved.config;
+        let hidden_code = Code::from_saved_letters(&saved.hidden_code, &config.palette)
+            .map_err(D::Error::custom)?;
+        let rounds = saved
+            .rounds
+            .into_iter()
+            .map(|round| round.read(&config.palette))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(D::Error::custom)?;
+        let codes = std::iter::once(&hidden_code).chain(rounds.iter().map(Round::guess));
+        for code in codes {
+            if code.len() != config.code_length {
+                return Err(D::Error::custom(format!(
+                    "code \"{}\" does not have {} pegs",
+                    code.letters(&config.palette),
+                    config.code_length
+                )));
+            }
+        }
+        let skipped = read_skips(saved.skipped, rounds.len()).map_err(D::Error::custom)?;
+        let outcomes = with_skipped(rounds, &skipped);
+        Ok(Board::from_parts(config, hidden_code, outcomes))
+    }
+}
+
+/// A game as Mastermind websites export it.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct WordleExport {
+    code: String,
+    guesses: Vec<WordleGuess>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct WordleGuess {
+    guess: String,
+    exact: usize,
+    inexact: usize,
+}
+
+#[cfg(feature = "serde")]
+impl Board {
+    /// Reads a game exported by Mastermind websites in the form
+    /// `{"code": "1234", "guesses": [{"guess": "1122", "exact": 2, "inexact": 1}]}`,
+    /// where each digit is a color's position in `config.colors` counting from 1.
+    ///
+    /// Every guess is rescored against the code, and a recorded score that
+    /// disagrees is an error.
+    ///
+    /// ```
+    /// use mastermind::{Board, GameConfig};
+    ///
+    /// let exported = r#"{
+    ///     "code": "1234",
+    ///     "guesses": [
+    ///         {"guess": "1122", "exact": 1, "inexact": 1},
+    ///         {"guess": "1243", "exact": 2, "inexact": 2},
+    ///         {"guess": "1234", "exact": 4, "inexact": 0}
+    ///     ]
+    /// }"#;
+    /// let board = Board::from_wordle_json(exported, &GameConfig::default()).unwrap();
+    ///
+    /// let scores: Vec<_> = board
+    ///     .rounds()
+    ///     .iter()
+    ///     .map(|round| (round.score().black_count(), round.score().white_count()))
+    ///     .collect();
+    /// assert_eq!(scores, [(1, 1), (2, 2), (4, 0)]);
+    /// assert!(board.last_round().unwrap().wins());
+    /// ```
+    pub fn from_wordle_json(s: &str, config: &GameConfig) -> std::result::Result<Board, GameError> {
+        let export: WordleExport = serde_json::from_str(s)?;
+        let mut board = Board::new(
+            config.clone(),
+            Code::from_numeric_string(&export.code, config)?,
+        );
+        for (i, entry) in export.guesses.iter().enumerate() {
+            let guess = Code::from_numeric_string(&entry.guess, config)?;
+            let score = board.guess(guess).score();
+            if (score.black_count(), score.white_count()) != (entry.exact, entry.inexact) {
+                return Err(GameError::ParseError(format!(
+                    "guess {} was recorded as {} exact and {} inexact but scores {} and {}",
+                    i + 1,
+                    entry.exact,
+                    entry.inexact,
+                    score.black_count(),
+                    score.white_count()
+                )));
+            }
+        }
+        Ok(board)
+    }
+}
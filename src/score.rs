@@ -0,0 +1,522 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::board::Board;
+use crate::display::{color_enabled, paint_key};
+use crate::error::GameError;
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ScoreDetail {
+    ColorCorrect,
+    ColorAndPositionCorrect,
+    Empty,
+}
+
+impl ScoreDetail {
+    /// Returns the letter this key peg is written as without color.
+    pub fn letter(&self) -> char {
+        match self {
+            ScoreDetail::ColorCorrect => 'w',
+            ScoreDetail::ColorAndPositionCorrect => 'b',
+            ScoreDetail::Empty => '-',
+        }
+    }
+}
+
+/// A colored blank with color, or a letter without.
+impl fmt::Display for ScoreDetail {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if color_enabled() {
+            return write!(f, "{}", paint_key(self, " "));
+        }
+        write!(f, "{}", self.letter())
+    }
+}
+
+/// The key pegs a guess earned: how many pegs matched exactly (black) and how
+/// many more had a correct color in the wrong position (white).
+///
+/// Scores order by blacks, and only on equal blacks by whites: one black beats
+/// any number of whites, so 1b0w ranks above 0b4w. The order ignores code
+/// length; only `is_win` needs it.
+///
+/// ```
+/// use mastermind::Score;
+///
+/// let score = |b, w| Score::from_counts(b, w, 4).unwrap();
+/// assert!(score(1, 0) > score(0, 4));
+/// assert!(score(2, 1) > score(2, 0));
+/// assert_eq!(score(1, 2).total_pegs(), 3);
+///
+/// // Over every score of every length from 3 to 8, the order is a total
+/// // order: antisymmetric, transitive, and agreeing with equality.
+/// for length in 3..=8 {
+///     let scores: Vec<Score> = (0..=length)
+///         .flat_map(|b| (0..=length - b).map(move |w| (b, w)))
+///         .map(|(b, w)| Score::from_counts(b, w, length).unwrap())
+///         .collect();
+///     for a in &scores {
+///         for b in &scores {
+///             assert_eq!(a.cmp(b), b.cmp(a).reverse());
+///             assert_eq!(a.cmp(b).is_eq(), a == b);
+///             for c in &scores {
+///                 if a <= b && b <= c {
+///                     assert!(a <= c);
+///                 }
+///             }
+///         }
+///     }
+///     let best = scores.iter().max().unwrap();
+///     assert!(best.is_win(length));
+///     assert_eq!(scores.iter().filter(|s| s.is_win(length)).count(), 1);
+/// }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Score {
+    blacks: usize,
+    whites: usize,
+}
+
+impl Score {
+    /// Returns the number of pegs with the right color in the right position.
+    pub fn black_count(&self) -> usize {
+        self.blacks
+    }
+
+    /// Returns the number of pegs with the right color in the wrong position.
+    pub fn white_count(&self) -> usize {
+        self.whites
+    }
+
+    /// Builds a score from its black and white peg counts for a code of `length` pegs.
+    pub fn from_counts(blacks: usize, whites: usize, length: usize) -> Result<Score, GameError> {
+        if blacks.saturating_add(whites) > length {
+            return Err(GameError::ParseError(format!(
+                "{} black and {} white pegs is more than {} pegs",
+                blacks, whites, length
+            )));
+        }
+        Ok(Score { blacks, whites })
+    }
+
+    /// Returns the black and white peg counts packed into a byte each.
+    ///
+    /// Counts above 255 cannot happen for any code short enough to play, so
+    /// they saturate rather than wrap.
+    pub fn as_compact_u8(&self) -> (u8, u8) {
+        let byte = |n: usize| u8::try_from(n).unwrap_or(u8::MAX);
+        (byte(self.blacks), byte(self.whites))
+    }
+
+    /// Rebuilds a score from `as_compact_u8` for a code of `length` pegs.
+    ///
+    /// ```
+    /// use mastermind::Score;
+    ///
+    /// for length in 1..=5 {
+    ///     for blacks in 0..=length {
+    ///         for whites in 0..=length - blacks {
+    ///             let score = Score::from_counts(blacks, whites, length).unwrap();
+    ///             let (b, w) = score.as_compact_u8();
+    ///             assert_eq!(Score::from_compact_u8(b, w, length).unwrap(), score);
+    ///         }
+    ///     }
+    /// }
+    /// assert!(Score::from_compact_u8(3, 2, 4).is_err());
+    /// ```
+    pub fn from_compact_u8(black: u8, white: u8, length: usize) -> Result<Score, GameError> {
+        Self::from_counts(usize::from(black), usize::from(white), length)
+    }
+
+    /// Reads a score shared as Wordle-style emoji, one square per peg: 🟩 for
+    /// the right color in the right position, 🟨 for the right color in the
+    /// wrong position, and ⬛ or ⬜ for neither. Only the counts matter, so
+    /// the squares may be in any order; whitespace between them is ignored.
+    ///
+    /// ```
+    /// use mastermind::{Score, ScoreDetail};
+    ///
+    /// let score = Score::from_wordle_emoji("🟨🟩⬛⬜").unwrap();
+    /// assert_eq!((score.black_count(), score.white_count()), (1, 1));
+    /// assert_eq!(score.to_wordle_emoji(4), "🟩🟨⬛⬛");
+    /// assert!(Score::from_wordle_emoji("🟩🟥").is_err());
+    /// assert!(Score::from_wordle_emoji("").is_err());
+    ///
+    /// // The squares mean what the keys of the same name mean here.
+    /// let keys = Score::from_wordle_emoji("🟩🟨⬛").unwrap().details(3);
+    /// assert_eq!(
+    ///     keys,
+    ///     [
+    ///         ScoreDetail::ColorAndPositionCorrect,
+    ///         ScoreDetail::ColorCorrect,
+    ///         ScoreDetail::Empty
+    ///     ]
+    /// );
+    ///
+    /// // Every one of the 81 four-square strings reads back the same after a
+    /// // round trip, and the 15 scores they make each write out one way.
+    /// let squares = ['🟩', '🟨', '⬛'];
+    /// let mut scores = std::collections::HashSet::new();
+    /// for n in 0..81 {
+    ///     let shared: String = (0..4).map(|i| squares[n / 3usize.pow(i) % 3]).collect();
+    ///     let score = Score::from_wordle_emoji(&shared).unwrap();
+    ///     let emoji = score.to_wordle_emoji(4);
+    ///     assert_eq!(Score::from_wordle_emoji(&emoji).unwrap(), score);
+    ///     scores.insert(emoji);
+    /// }
+    /// assert_eq!(scores.len(), 15);
+    /// ```
+    pub fn from_wordle_emoji(s: &str) -> Result<Score, GameError> {
+        let (mut blacks, mut whites, mut length) = (0, 0, 0);
+        for (position, c) in s.chars().enumerate() {
+            match c {
+                c if c.is_whitespace() => continue,
+                '🟩' => blacks += 1,
+                '🟨' => whites += 1,
+                '⬛' | '⬜' => {}
+                found => {
+                    return Err(GameError::InvalidCharacter {
+                        input: s.to_string(),
+                        position,
+                        found,
+                        expected: vec!['🟩', '🟨', '⬛', '⬜'],
+                    })
+                }
+            }
+            length += 1;
+        }
+        if length == 0 {
+            return Err(GameError::ParseError(String::from(
+                "a Wordle-style score needs at least one square",
+            )));
+        }
+        Self::from_counts(blacks, whites, length)
+    }
+
+    /// Writes the score for a `length`-peg code as Wordle-style emoji: 🟩 for
+    /// each black peg, then 🟨 for each white, then ⬛ for the rest.
+    pub fn to_wordle_emoji(&self, length: usize) -> String {
+        self.details(length)
+            .iter()
+            .map(|detail| match detail {
+                ScoreDetail::ColorAndPositionCorrect => '🟩',
+                ScoreDetail::ColorCorrect => '🟨',
+                ScoreDetail::Empty => '⬛',
+            })
+            .collect()
+    }
+
+    /// Returns one key per peg of a `length`-peg code: blacks, then whites, then empties.
+    pub fn details(&self, length: usize) -> Vec<ScoreDetail> {
+        (0..length.max(self.total_pegs()))
+            .map(|i| {
+                if i < self.blacks {
+                    ScoreDetail::ColorAndPositionCorrect
+                } else if i < self.blacks + self.whites {
+                    ScoreDetail::ColorCorrect
+                } else {
+                    ScoreDetail::Empty
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the score with every black key made white and every white key
+    /// made black.
+    ///
+    /// ```
+    /// use mastermind::{Score, ScoreDetail};
+    ///
+    /// let score = Score::from_counts(3, 1, 5).unwrap();
+    /// assert_eq!(score.complement(), Score::from_counts(1, 3, 5).unwrap());
+    ///
+    /// let scored = |keys: &[ScoreDetail]| keys.iter().filter(|k| **k != ScoreDetail::Empty).count();
+    /// for blacks in 0..=4 {
+    ///     for whites in 0..=4 - blacks {
+    ///         let score = Score::from_counts(blacks, whites, 4).unwrap();
+    ///         assert_eq!(score.complement().complement(), score);
+    ///         assert_eq!(scored(&score.complement().details(4)), scored(&score.details(4)));
+    ///         assert_eq!(scored(&score.mirror(4)), scored(&score.details(4)));
+    ///     }
+    /// }
+    /// ```
+    pub fn complement(&self) -> Score {
+        Score {
+            blacks: self.whites,
+            whites: self.blacks,
+        }
+    }
+
+    /// Returns the keys of `details` in reverse order: empties, then whites,
+    /// then blacks. A score only counts its keys, so the reordering can only
+    /// be seen in the keys, not in another `Score`.
+    ///
+    /// ```
+    /// use mastermind::{Score, ScoreDetail};
+    ///
+    /// let score = Score::from_counts(1, 2, 4).unwrap();
+    /// assert_eq!(
+    ///     score.mirror(4),
+    ///     [
+    ///         ScoreDetail::Empty,
+    ///         ScoreDetail::ColorCorrect,
+    ///         ScoreDetail::ColorCorrect,
+    ///         ScoreDetail::ColorAndPositionCorrect,
+    ///     ]
+    /// );
+    /// ```
+    pub fn mirror(&self, length: usize) -> Vec<ScoreDetail> {
+        let mut keys = self.details(length);
+        keys.reverse();
+        keys
+    }
+
+    /// Returns whether this score means every peg of a `length`-peg code matched.
+    pub fn is_win(&self, length: usize) -> bool {
+        self.blacks == length
+    }
+
+    /// Returns how many key pegs were placed, black and white together.
+    pub fn total_pegs(&self) -> usize {
+        self.blacks + self.whites
+    }
+
+    pub(crate) fn fmt_pegs(&self, f: &mut fmt::Formatter, length: usize) -> fmt::Result {
+        let pegs: Vec<String> = self
+            .details(length)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        write!(f, "{}", pegs.join(" "))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.blacks
+            .cmp(&other.blacks)
+            .then(self.whites.cmp(&other.whites))
+    }
+}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Reads `{"blacks": n, "whites": m}`, refusing more key pegs than any code
+/// can have. Where the score's code is known, its length is checked too.
+///
+/// ```
+/// use mastermind::Score;
+///
+/// let score = Score::from_counts(2, 1, 4).unwrap();
+/// let json = serde_json::to_string(&score).unwrap();
+/// assert_eq!(json, r#"{"blacks":2,"whites":1}"#);
+/// assert_eq!(serde_json::from_str::<Score>(&json).unwrap(), score);
+/// assert!(serde_json::from_str::<Score>(r#"{"blacks":200,"whites":200}"#).is_err());
+/// assert!(serde_json::from_str::<Score>(r#"{"blacks":-1,"whites":0}"#).is_err());
+/// ```
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Score {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct KeyPegs {
+            blacks: usize,
+            whites: usize,
+        }
+        let pegs = KeyPegs::deserialize(deserializer)?;
+        Score::from_counts(pegs.blacks, pegs.whites, crate::code::MAX_CODE_LENGTH)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_pegs(f, self.total_pegs())
+    }
+}
+
+impl FromStr for Score {
+    type Err = GameError;
+
+    /// Parses a score written as key pegs the way `Display` and
+    /// `Score::details` write them, or as counts: `2b1w` as `{score_compact}`
+    /// writes them, or `(2, 1)` for black then white.
+    ///
+    /// ```
+    /// use mastermind::Score;
+    ///
+    /// let score: Score = "2b1w".parse().unwrap();
+    /// assert_eq!((score.black_count(), score.white_count()), (2, 1));
+    /// assert_eq!("(2, 1)".parse::<Score>().unwrap(), score);
+    /// assert!("2b1x".parse::<Score>().is_err());
+    /// assert!("(2)".parse::<Score>().is_err());
+    ///
+    /// for length in 1..=6 {
+    ///     for blacks in 0..=length {
+    ///         for whites in 0..=length - blacks {
+    ///             let score = Score::from_counts(blacks, whites, length).unwrap();
+    ///             let pegs: Vec<String> = score.details(length).iter().map(ToString::to_string).collect();
+    ///             let texts = [
+    ///                 score.to_string(),
+    ///                 pegs.join(" "),
+    ///                 pegs.concat(),
+    ///                 format!("{}b{}w", blacks, whites),
+    ///                 format!("({}, {})", blacks, whites),
+    ///             ];
+    ///             for text in texts {
+    ///                 assert_eq!(text.parse::<Score>().unwrap(), score, "{:?}", text);
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let invalid = || GameError::ParseError(format!("\"{}\" is not a score", s));
+        let count = |n: &str| n.trim().parse::<usize>().map_err(|_| invalid());
+        if let Some(inner) = trimmed.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+            if inner.chars().any(|c| c.is_ascii_digit()) {
+                let (blacks, whites) = inner.split_once(',').ok_or_else(invalid)?;
+                return Ok(Score {
+                    blacks: count(blacks)?,
+                    whites: count(whites)?,
+                });
+            }
+        }
+        if trimmed.starts_with(|c: char| c.is_ascii_digit()) {
+            let (blacks, whites) = trimmed.split_once('b').ok_or_else(invalid)?;
+            let whites = whites.strip_suffix('w').ok_or_else(invalid)?;
+            return Ok(Score {
+                blacks: count(blacks)?,
+                whites: count(whites)?,
+            });
+        }
+
+        let details = [
+            ScoreDetail::ColorAndPositionCorrect,
+            ScoreDetail::ColorCorrect,
+            ScoreDetail::Empty,
+        ];
+        let (mut blacks, mut whites) = (0, 0);
+        let mut rest = s;
+        while let Some(c) = rest.chars().next() {
+            // With color support a peg is a painted space, so pegs are
+            // matched before whitespace is skipped.
+            if let Some((detail, text)) = details
+                .iter()
+                .map(|detail| (detail, detail.to_string()))
+                .find(|(_, text)| rest.starts_with(text.as_str()))
+            {
+                match detail {
+                    ScoreDetail::ColorAndPositionCorrect => blacks += 1,
+                    ScoreDetail::ColorCorrect => whites += 1,
+                    ScoreDetail::Empty => {}
+                }
+                rest = &rest[text.len()..];
+            } else if c.is_whitespace() || matches!(c, '(' | ')' | ',') {
+                rest = &rest[c.len_utf8()..];
+            } else {
+                return Err(invalid());
+            }
+        }
+        Ok(Score { blacks, whites })
+    }
+}
+
+/// The black and white peg counts of every round of a game, in the order
+/// they were played.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScoreHistogram {
+    code_length: usize,
+    entries: Vec<(usize, usize)>,
+}
+
+impl ScoreHistogram {
+    /// Collects the score of each round played on `board`.
+    ///
+    /// ```
+    /// use mastermind::{Board, Code, GameConfig, ScoreHistogram};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let mut board = Board::new(config.clone(), code("rgby"));
+    /// for guess in ["rrgg", "gbyr", "rgby"] {
+    ///     board.guess(code(guess));
+    /// }
+    ///
+    /// let histogram = ScoreHistogram::from_board(&board);
+    /// assert_eq!(histogram.len(), board.rounds().len());
+    /// assert_eq!(histogram.entries(), [(1, 1), (0, 4), (4, 0)]);
+    /// assert_eq!(histogram.entries().last(), Some(&(config.code_length, 0)));
+    /// ```
+    pub fn from_board(board: &Board) -> ScoreHistogram {
+        ScoreHistogram {
+            code_length: board.config().code_length,
+            entries: board
+                .rounds()
+                .iter()
+                .map(|round| (round.score().black_count(), round.score().white_count()))
+                .collect(),
+        }
+    }
+
+    /// Returns each round's black and white peg counts, oldest first.
+    pub fn entries(&self) -> &[(usize, usize)] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Draws one row per round, with a bar as long as the pegs the round
+    /// earned: a `b` for each black peg, then a `w` for each white one.
+    ///
+    /// ```
+    /// use mastermind::{Board, Code, GameConfig, ScoreHistogram};
+    ///
+    /// let config = GameConfig::default();
+    /// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+    /// let mut board = Board::new(config.clone(), code("rrgb"));
+    /// for guess in ["yyyy", "rgyy", "grbr", "rrgb"] {
+    ///     board.guess(code(guess));
+    /// }
+    ///
+    /// let mut chart = Vec::new();
+    /// ScoreHistogram::from_board(&board).print(&mut chart).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(chart).unwrap(),
+    ///     "1 |      | 0b0w\n\
+    ///      2 | bw   | 1b1w\n\
+    ///      3 | bwww | 1b3w\n\
+    ///      4 | bbbb | 4b0w\n"
+    /// );
+    /// ```
+    pub fn print(&self, mut writer: impl Write) -> io::Result<()> {
+        let number_width = self.entries.len().to_string().len();
+        for (i, (blacks, whites)) in self.entries.iter().enumerate() {
+            let bar = format!("{}{}", "b".repeat(*blacks), "w".repeat(*whites));
+            writeln!(
+                writer,
+                "{:>number_width$} | {:<bar_width$} | {}b{}w",
+                i + 1,
+                bar,
+                blacks,
+                whites,
+                number_width = number_width,
+                bar_width = self.code_length,
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+//! Picking a code with the arrow keys instead of typing it.
+
+use std::io::Write;
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    terminal::{self, ClearType},
+    QueueableCommand,
+};
+
+use crate::code::{Code, Color};
+use crate::config::{DisplayMode, GameConfig};
+use crate::error::GameError;
+use crate::palette::Palette;
+use crate::Result;
+
+/// What a key press did to a `ColorSelector`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SelectorAction {
+    /// The selection may have changed; keep reading keys.
+    Continue,
+    /// Enter was pressed on this code.
+    Confirm(Code),
+    /// Escape was pressed.
+    Cancel,
+}
+
+/// A code being picked one position at a time: left and right move between
+/// positions, up and down cycle the color at the focused one.
+///
+/// ```
+/// use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+/// use mastermind::selector::{ColorSelector, SelectorAction};
+/// use mastermind::{Color, GameConfig};
+///
+/// let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+/// let mut selector = ColorSelector::new(&GameConfig::default());
+/// for code in [KeyCode::Up, KeyCode::Right, KeyCode::Down, KeyCode::Right] {
+///     assert_eq!(selector.handle_key(key(code)), SelectorAction::Continue);
+/// }
+/// assert_eq!(selector.focus(), 2);
+///
+/// // Up steps forward through the colors; down wraps round to the last.
+/// let SelectorAction::Confirm(code) = selector.handle_key(key(KeyCode::Enter)) else {
+///     panic!("enter confirms");
+/// };
+/// assert_eq!(code.colors(), [Color::Green, Color::Yellow, Color::Red, Color::Red]);
+/// assert_eq!(selector.handle_key(key(KeyCode::Esc)), SelectorAction::Cancel);
+/// ```
+pub struct ColorSelector {
+    colors: Vec<Color>,
+    palette: Palette,
+    selected: Vec<usize>,
+    focus: usize,
+}
+
+impl ColorSelector {
+    /// Starts with every position on the first color of `config`.
+    pub fn new(config: &GameConfig) -> ColorSelector {
+        ColorSelector {
+            colors: config.colors.clone(),
+            palette: config.palette.clone(),
+            selected: vec![0; config.code_length],
+            focus: 0,
+        }
+    }
+
+    /// Returns the position that up and down change.
+    pub fn focus(&self) -> usize {
+        self.focus
+    }
+
+    /// Returns the code as currently selected.
+    pub fn code(&self) -> Code {
+        Code::unchecked_new(
+            self.selected
+                .iter()
+                .map(|&i| self.colors[i].clone())
+                .collect(),
+        )
+    }
+
+    /// Applies one key press.
+    pub fn handle_key(&mut self, key: KeyEvent) -> SelectorAction {
+        let count = self.colors.len();
+        let length = self.selected.len();
+        match key.code {
+            KeyCode::Left => self.focus = self.focus.saturating_sub(1),
+            KeyCode::Right => self.focus = (self.focus + 1).min(length.saturating_sub(1)),
+            KeyCode::Up => self.selected[self.focus] = (self.selected[self.focus] + 1) % count,
+            KeyCode::Down => {
+                self.selected[self.focus] = (self.selected[self.focus] + count - 1) % count
+            }
+            KeyCode::Enter => return SelectorAction::Confirm(self.code()),
+            KeyCode::Esc => return SelectorAction::Cancel,
+            _ => {}
+        }
+        SelectorAction::Continue
+    }
+
+    /// Reads keys from the terminal in raw mode, redrawing the selection with
+    /// the focused peg in brackets, until Enter returns the code or Escape
+    /// returns `None`.
+    pub fn run(&mut self) -> Result<Option<Code>> {
+        terminal::enable_raw_mode()?;
+        let result = self.read_keys();
+        terminal::disable_raw_mode()?;
+        println!();
+        result
+    }
+
+    fn read_keys(&mut self) -> Result<Option<Code>> {
+        let mut out = std::io::stdout();
+        loop {
+            self.draw(&mut out)?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            // Raw mode turns Ctrl-C into a key press rather than a signal.
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                return Err(GameError::Io(std::io::ErrorKind::Interrupted.into()).into());
+            }
+            match self.handle_key(key) {
+                SelectorAction::Continue => {}
+                SelectorAction::Confirm(code) => return Ok(Some(code)),
+                SelectorAction::Cancel => return Ok(None),
+            }
+        }
+    }
+
+    fn draw<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        out.queue(cursor::MoveToColumn(0))?
+            .queue(terminal::Clear(ClearType::CurrentLine))?;
+        for (position, color) in self.code().colors().iter().enumerate() {
+            let glyph = color.glyph(&self.palette, DisplayMode::Letters);
+            if position == self.focus {
+                write!(out, "[{}]", glyph)?;
+            } else {
+                write!(out, " {} ", glyph)?;
+            }
+        }
+        out.flush()
+    }
+}
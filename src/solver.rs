@@ -0,0 +1,853 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::board::Round;
+use crate::code::{Code, CodeSpace, Color, LazyCodeSpace};
+use crate::config::GameConfig;
+use crate::deduction::CandidateSet;
+use crate::error::GameError;
+use crate::palette::Palette;
+use crate::score::Score;
+
+/// A strategy for choosing guesses from the feedback received so far.
+pub trait Solver {
+    /// Returns the next code to guess given the rounds played so far, or `None` if
+    /// no code is consistent with them.
+    fn next_guess(&mut self, rounds: &[Round]) -> Option<Code>;
+
+    /// Returns the codes the solver still considers possible after its last guess.
+    fn candidates(&self) -> Vec<&Code>;
+}
+
+impl<S: Solver + ?Sized> Solver for Box<S> {
+    fn next_guess(&mut self, rounds: &[Round]) -> Option<Code> {
+        (**self).next_guess(rounds)
+    }
+
+    fn candidates(&self) -> Vec<&Code> {
+        (**self).candidates()
+    }
+}
+
+/// A solver that can be chosen by name, with `--strategy` or `strategy`.
+#[derive(Clone, Copy)]
+pub struct Strategy {
+    pub name: &'static str,
+    pub new: fn(&GameConfig) -> Box<dyn Solver>,
+}
+
+/// Returns every strategy, in the order they are listed and compared.
+pub fn strategies() -> Vec<Strategy> {
+    vec![
+        Strategy {
+            name: "most-parts",
+            new: |config| Box::new(MostPartsSolver::new(config)),
+        },
+        Strategy {
+            name: "expected-size",
+            new: |config| Box::new(ExpectedSizeSolver::new(config)),
+        },
+        Strategy {
+            name: "perceptual",
+            new: |config| Box::new(PerceptualSolver::new(config)),
+        },
+    ]
+}
+
+/// Returns the strategy called `name`.
+///
+/// ```
+/// use mastermind::analysis::analyze;
+/// use mastermind::solver::{strategies, strategy};
+/// use mastermind::GameConfig;
+///
+/// let config = GameConfig {
+///     code_length: 3,
+///     ..GameConfig::default()
+/// };
+/// let expected_size = strategy("expected-size").unwrap();
+/// let report = analyze(&config, None, || (expected_size.new)(&config));
+/// assert_eq!(report.unsolved, 0);
+///
+/// assert_eq!(strategy("expected-size").unwrap().name, "expected-size");
+/// let Err(e) = strategy("minimax") else { panic!() };
+/// assert_eq!(
+///     e.to_string(),
+///     "invalid strategy: no strategy 'minimax', expected one of: most-parts expected-size perceptual"
+/// );
+/// ```
+pub fn strategy(name: &str) -> Result<Strategy, GameError> {
+    let all = strategies();
+    all.iter()
+        .find(|strategy| strategy.name == name)
+        .copied()
+        .ok_or_else(|| {
+            let names: Vec<&str> = all.iter().map(|strategy| strategy.name).collect();
+            GameError::InvalidSetting {
+                setting: "strategy",
+                reason: format!(
+                    "no strategy '{}', expected one of: {}",
+                    name,
+                    names.join(" ")
+                ),
+            }
+        })
+}
+
+/// Returns the codes in `space` that are consistent with every round in `rounds`.
+pub fn consistent_candidates<'a>(space: &'a CodeSpace, rounds: &[Round]) -> Vec<&'a Code> {
+    space
+        .iter()
+        .filter(|code| rounds.iter().all(|round| code.is_consistent_with(round)))
+        .collect()
+}
+
+/// Returns the colors of `config` that appear in none of `candidates`.
+///
+/// Because it works from the candidates rather than from individual rounds, a
+/// color only counts as eliminated once the combined feedback rules it out.
+pub fn eliminated_colors(config: &GameConfig, candidates: &[&Code]) -> Vec<Color> {
+    config
+        .colors
+        .iter()
+        .filter(|color| !candidates.iter().any(|code| code.contains(color)))
+        .cloned()
+        .collect()
+}
+
+/// Returns, for each of the `length` positions, the colors that some code in
+/// `candidates` has there, in `Color` order.
+///
+/// ```
+/// use mastermind::solver::position_colors;
+/// use mastermind::{Code, Color, GameConfig};
+///
+/// let config = GameConfig {
+///     code_length: 3,
+///     ..GameConfig::default()
+/// };
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let candidates = [code("rgy"), code("bgy"), code("rgr")];
+/// assert_eq!(
+///     position_colors(&candidates, 3),
+///     [
+///         vec![Color::Red, Color::Blue],
+///         vec![Color::Green],
+///         vec![Color::Red, Color::Yellow],
+///     ]
+/// );
+///
+/// // With one candidate left, each position has just that code's color.
+/// let secret = code("byr");
+/// let colors = position_colors([&secret], 3);
+/// assert!(colors.iter().zip(secret.colors()).all(|(c, s)| c == &[s.clone()]));
+/// ```
+pub fn position_colors<'a>(
+    candidates: impl IntoIterator<Item = &'a Code>,
+    length: usize,
+) -> Vec<Vec<Color>> {
+    let mut positions = vec![BTreeSet::new(); length];
+    for code in candidates {
+        for (colors, color) in positions.iter_mut().zip(code.colors()) {
+            colors.insert(color.clone());
+        }
+    }
+    positions
+        .into_iter()
+        .map(|colors| colors.into_iter().collect())
+        .collect()
+}
+
+/// Describes `positions` as a line such as `pos1: r,b  pos2: g` in the letters
+/// of `palette`, listing the colors at a position only when there are at most
+/// `max_listed` of them and otherwise just how many there are.
+///
+/// ```
+/// use mastermind::solver::describe_position_colors;
+/// use mastermind::{Color, Palette};
+///
+/// let positions = [
+///     vec![Color::Red, Color::Blue],
+///     vec![Color::Green],
+///     vec![Color::Red, Color::Green, Color::Yellow],
+/// ];
+/// let classic = Palette::classic();
+/// assert_eq!(
+///     describe_position_colors(&positions, 3, &classic),
+///     "pos1: r,b  pos2: g  pos3: r,g,y"
+/// );
+/// assert_eq!(
+///     describe_position_colors(&positions, 2, &classic),
+///     "pos1: r,b  pos2: g  pos3: 3 colors"
+/// );
+/// let digits = Palette::from_letters("0123").unwrap();
+/// assert_eq!(
+///     describe_position_colors(&positions, 3, &digits),
+///     "pos1: 0,2  pos2: 1  pos3: 0,1,3"
+/// );
+/// ```
+pub fn describe_position_colors(
+    positions: &[Vec<Color>],
+    max_listed: usize,
+    palette: &Palette,
+) -> String {
+    let parts: Vec<String> = positions
+        .iter()
+        .enumerate()
+        .map(|(i, colors)| {
+            if colors.len() > max_listed {
+                format!("pos{}: {} colors", i + 1, colors.len())
+            } else {
+                let letters: Vec<String> = colors
+                    .iter()
+                    .map(|c| palette.style(c).letter.to_string())
+                    .collect();
+                format!("pos{}: {}", i + 1, letters.join(","))
+            }
+        })
+        .collect();
+    parts.join("  ")
+}
+
+/// Groups `candidates` by the score each would give `guess`, returning how many
+/// fall under each score, most black pegs first and then most white pegs.
+///
+/// ```
+/// use mastermind::solver::score_buckets;
+/// use mastermind::{Code, GameConfig};
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let candidates = [code("rgby"), code("rgyb"), code("yyyy"), code("gggg")];
+///
+/// let buckets = score_buckets(&code("rgby"), &candidates);
+/// let counts: Vec<_> = buckets
+///     .iter()
+///     .map(|(score, n)| (score.black_count(), score.white_count(), *n))
+///     .collect();
+/// assert_eq!(counts, [(4, 0, 1), (2, 2, 1), (1, 0, 2)]);
+/// ```
+pub fn score_buckets<'a>(
+    guess: &Code,
+    candidates: impl IntoIterator<Item = &'a Code>,
+) -> Vec<(Score, usize)> {
+    let mut buckets: HashMap<Score, usize> = HashMap::new();
+    for candidate in candidates {
+        *buckets.entry(guess.score(candidate)).or_insert(0) += 1;
+    }
+    let mut buckets: Vec<(Score, usize)> = buckets.into_iter().collect();
+    buckets.sort_by_key(|(score, _)| {
+        (
+            std::cmp::Reverse(score.black_count()),
+            std::cmp::Reverse(score.white_count()),
+        )
+    });
+    buckets
+}
+
+/// Describes `buckets` as a histogram line such as `2b0w: 3, 1b1w: 7`, followed
+/// by the largest and expected bucket sizes.
+pub fn describe_buckets(buckets: &[(Score, usize)]) -> String {
+    let parts: Vec<String> = buckets
+        .iter()
+        .map(|(score, n)| format!("{}b{}w: {}", score.black_count(), score.white_count(), n))
+        .collect();
+    let total: usize = buckets.iter().map(|(_, n)| n).sum();
+    let worst = buckets.iter().map(|(_, n)| *n).max().unwrap_or(0);
+    let squares: usize = buckets.iter().map(|(_, n)| n * n).sum();
+    let expected = if total == 0 {
+        0.0
+    } else {
+        squares as f64 / total as f64
+    };
+    format!(
+        "{}\nworst case: {}, expected: {:.2}",
+        parts.join(", "),
+        worst,
+        expected
+    )
+}
+
+/// Finds a smallest set of rounds that no code in `config`'s code space could
+/// have produced together, trying single rounds, then pairs, then triples.
+///
+/// Returns the rounds' indices in ascending order, all of `rounds` when the
+/// conflict needs more than three of them, or `None` when some code is
+/// consistent with every round.
+pub fn find_contradiction(config: &GameConfig, rounds: &[Round]) -> Option<Vec<usize>> {
+    if !CandidateSet::from_rounds(config, rounds).is_contradictory() {
+        return None;
+    }
+    let space = CodeSpace::new(config);
+    let consistent: Vec<Vec<bool>> = rounds
+        .iter()
+        .map(|round| {
+            space
+                .iter()
+                .map(|code| code.is_consistent_with(round))
+                .collect()
+        })
+        .collect();
+    let conflicting = |subset: &[usize]| {
+        (0..space.len()).all(|code| !subset.iter().all(|&round| consistent[round][code]))
+    };
+    let n = rounds.len();
+    for i in 0..n {
+        if conflicting(&[i]) {
+            return Some(vec![i]);
+        }
+    }
+    for i in 0..n {
+        for j in i + 1..n {
+            if conflicting(&[i, j]) {
+                return Some(vec![i, j]);
+            }
+        }
+    }
+    for i in 0..n {
+        for j in i + 1..n {
+            for k in j + 1..n {
+                if conflicting(&[i, j, k]) {
+                    return Some(vec![i, j, k]);
+                }
+            }
+        }
+    }
+    Some((0..n).collect())
+}
+
+/// The codes still consistent with the feedback seen so far, narrowed in place as
+/// each new round arrives instead of being refiltered from the whole code space.
+pub struct IncrementalCandidateSet {
+    candidates: Vec<Code>,
+}
+
+impl IncrementalCandidateSet {
+    pub fn new(codes: impl IntoIterator<Item = Code>) -> Self {
+        Self {
+            candidates: codes.into_iter().collect(),
+        }
+    }
+
+    /// Drops every candidate that would not have given `score` for `guess`.
+    pub fn filter_in_place(&mut self, guess: &Code, score: &Score) {
+        self.candidates
+            .retain(|candidate| candidate.score(guess) == *score);
+    }
+
+    pub fn candidates(&self) -> &[Code] {
+        &self.candidates
+    }
+
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+}
+
+/// Picks the guess that splits the remaining candidates into the most distinct
+/// scores, breaking ties lexicographically.
+///
+/// Candidates are narrowed incrementally, so successive calls are expected to pass
+/// the same game's rounds as it grows; a history that does not extend the last
+/// one starts over.
+///
+/// ```
+/// use mastermind::analysis::analyze;
+/// use mastermind::solver::{MostPartsSolver, Solver};
+/// use mastermind::{Board, Code, GameConfig};
+///
+/// // Every code of the standard game is solved within 6 guesses.
+/// let config = GameConfig::default();
+/// let report = analyze(&config, None, || MostPartsSolver::new(&config));
+/// assert_eq!(report.unsolved, 0);
+/// assert!(report.worst_case <= 6);
+///
+/// // One game, played out by hand.
+/// let hidden = Code::parse(String::from("ybgr"), &config).unwrap();
+/// let mut board = Board::new(config.clone(), hidden);
+/// let mut solver = MostPartsSolver::new(&config);
+/// while !board.last_round().is_some_and(|round| round.wins()) {
+///     let guess = solver.next_guess(board.rounds()).unwrap();
+///     board.guess(guess);
+/// }
+/// assert!(board.rounds().len() <= 6);
+/// assert_eq!(solver.candidates().len(), 1);
+/// ```
+pub struct MostPartsSolver {
+    space: LazyCodeSpace,
+    candidates: IncrementalCandidateSet,
+    observed: Vec<Round>,
+}
+
+impl MostPartsSolver {
+    pub fn new(config: &GameConfig) -> Self {
+        let space = LazyCodeSpace::new(config);
+        let candidates = IncrementalCandidateSet::new(space.clone());
+        Self {
+            space,
+            candidates,
+            observed: Vec::new(),
+        }
+    }
+
+    fn observe(&mut self, rounds: &[Round]) {
+        if !rounds.starts_with(&self.observed) {
+            self.candidates = IncrementalCandidateSet::new(self.space.clone());
+            self.observed.clear();
+        }
+        for round in &rounds[self.observed.len()..] {
+            self.candidates
+                .filter_in_place(round.guess(), round.score());
+        }
+        self.observed = rounds.to_vec();
+    }
+}
+
+impl Solver for MostPartsSolver {
+    fn next_guess(&mut self, rounds: &[Round]) -> Option<Code> {
+        self.observe(rounds);
+        let candidates = self.candidates.candidates();
+        if candidates.len() <= 2 {
+            return candidates.first().cloned();
+        }
+
+        let guesses: Box<dyn Iterator<Item = Code>> = if self.observed.is_empty() {
+            Box::new(opening_shapes(&self.space).into_iter())
+        } else {
+            Box::new(self.space.clone())
+        };
+        let mut best: Option<(Code, usize)> = None;
+        for guess in guesses {
+            let parts = score_buckets(&guess, candidates).len();
+            if best.as_ref().is_none_or(|(_, most)| parts > *most) {
+                best = Some((guess, parts));
+            }
+            // Every candidate on its own cannot be beaten.
+            if parts == candidates.len() {
+                break;
+            }
+        }
+        best.map(|(code, _)| code)
+    }
+
+    fn candidates(&self) -> Vec<&Code> {
+        self.candidates.candidates().iter().collect()
+    }
+}
+
+/// Picks guesses as `MostPartsSolver` does, but among guesses that split the
+/// candidates into equally many parts prefers the one that looks most unlike
+/// every earlier guess, peg by peg, by `Color::perceptual_distance`. A guess
+/// that stands out is easier to tell apart on the board.
+///
+/// ```
+/// use mastermind::analysis::analyze;
+/// use mastermind::{GameConfig, PerceptualSolver};
+///
+/// let config = GameConfig {
+///     code_length: 3,
+///     ..GameConfig::default()
+/// };
+/// let report = analyze(&config, None, || PerceptualSolver::new(&config));
+/// assert_eq!(report.unsolved, 0);
+/// assert!(report.worst_case <= 5);
+/// ```
+pub struct PerceptualSolver {
+    inner: MostPartsSolver,
+    palette: Palette,
+}
+
+impl PerceptualSolver {
+    pub fn new(config: &GameConfig) -> Self {
+        Self {
+            inner: MostPartsSolver::new(config),
+            palette: config.palette.clone(),
+        }
+    }
+}
+
+/// Returns how unlike `guess` is to the most similar guess of `rounds`,
+/// summing the perceptual distance in `palette` between the colors at each
+/// position.
+fn distinctness(guess: &Code, rounds: &[Round], palette: &Palette) -> f64 {
+    rounds
+        .iter()
+        .map(|round| {
+            guess
+                .colors()
+                .iter()
+                .zip(round.guess().colors())
+                .map(|(a, b)| a.perceptual_distance(b, palette))
+                .sum::<f64>()
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+impl Solver for PerceptualSolver {
+    fn next_guess(&mut self, rounds: &[Round]) -> Option<Code> {
+        if rounds.is_empty() {
+            return self.inner.next_guess(rounds);
+        }
+        self.inner.observe(rounds);
+        let candidates = self.inner.candidates.candidates();
+        if candidates.len() <= 2 {
+            return candidates.first().cloned();
+        }
+
+        let mut best: Option<(Code, usize, f64)> = None;
+        for guess in self.inner.space.clone() {
+            let parts = score_buckets(&guess, candidates).len();
+            let distinct = distinctness(&guess, rounds, &self.palette);
+            let better = best.as_ref().is_none_or(|(_, most, farthest)| {
+                parts > *most || (parts == *most && distinct > *farthest)
+            });
+            if better {
+                best = Some((guess, parts, distinct));
+            }
+        }
+        best.map(|(code, _, _)| code)
+    }
+
+    fn candidates(&self) -> Vec<&Code> {
+        self.inner.candidates()
+    }
+}
+
+/// Picks the guess that leaves the fewest candidates on average: the one whose
+/// score buckets have the smallest sum of squared sizes. Ties go to the guess
+/// that comes first in the code space.
+///
+/// ```
+/// use mastermind::analysis::analyze;
+/// use mastermind::solver::ExpectedSizeSolver;
+/// use mastermind::GameConfig;
+///
+/// let config = GameConfig {
+///     code_length: 3,
+///     ..GameConfig::default()
+/// };
+/// let report = analyze(&config, None, || ExpectedSizeSolver::new(&config));
+/// assert_eq!(report.unsolved, 0);
+/// assert!(report.worst_case <= 5);
+/// ```
+pub struct ExpectedSizeSolver {
+    inner: MostPartsSolver,
+}
+
+impl ExpectedSizeSolver {
+    pub fn new(config: &GameConfig) -> Self {
+        Self {
+            inner: MostPartsSolver::new(config),
+        }
+    }
+}
+
+impl Solver for ExpectedSizeSolver {
+    fn next_guess(&mut self, rounds: &[Round]) -> Option<Code> {
+        self.inner.observe(rounds);
+        let candidates = self.inner.candidates.candidates();
+        if candidates.len() <= 2 {
+            return candidates.first().cloned();
+        }
+
+        let guesses: Box<dyn Iterator<Item = Code>> = if rounds.is_empty() {
+            Box::new(opening_shapes(&self.inner.space).into_iter())
+        } else {
+            Box::new(self.inner.space.clone())
+        };
+        let mut best: Option<(Code, usize)> = None;
+        for guess in guesses {
+            let squares = score_buckets(&guess, candidates)
+                .iter()
+                .map(|(_, n)| n * n)
+                .sum();
+            if best.as_ref().is_none_or(|(_, fewest)| squares < *fewest) {
+                best = Some((guess, squares));
+            }
+            // Every candidate on its own cannot be beaten.
+            if squares == candidates.len() {
+                break;
+            }
+        }
+        best.map(|(code, _)| code)
+    }
+
+    fn candidates(&self) -> Vec<&Code> {
+        self.inner.candidates()
+    }
+}
+
+/// Returns the first code of `space` with each way of splitting its pegs among
+/// colors: "rrrr", "rrrg", "rrgg", "rrgb", "rgby" and so on.
+///
+/// Before any feedback every code is a candidate, so renaming colors or moving
+/// pegs around gives a guess the same number of parts. Only these codes need
+/// scoring for an opening, which keeps large spaces such as Super Mastermind's
+/// 32768 codes from being scored against each other in full.
+fn opening_shapes(space: &LazyCodeSpace) -> Vec<Code> {
+    fn split(rest: usize, largest: usize, parts: &mut Vec<usize>, out: &mut Vec<Vec<usize>>) {
+        if rest == 0 {
+            out.push(parts.clone());
+            return;
+        }
+        for size in (1..=rest.min(largest)).rev() {
+            parts.push(size);
+            split(rest - size, size, parts, out);
+            parts.pop();
+        }
+    }
+
+    let mut splits = Vec::new();
+    split(
+        space.code_length(),
+        space.code_length(),
+        &mut Vec::new(),
+        &mut splits,
+    );
+    let mut codes: Vec<Code> = splits
+        .into_iter()
+        .filter(|parts| parts.len() <= space.colors().len())
+        .map(|parts| {
+            let colors = parts
+                .iter()
+                .zip(space.colors())
+                .flat_map(|(&size, color)| std::iter::repeat_n(color.clone(), size))
+                .collect();
+            Code::new(colors)
+        })
+        .collect();
+    codes.sort();
+    codes
+}
+
+/// Wraps another solver and reports its candidates in `Code` order, so that
+/// assertions about them do not depend on how the inner solver stores them.
+///
+/// ```
+/// use mastermind::{Board, Code, DeterministicSolver, GameConfig, MostPartsSolver, Solver};
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let mut board = Board::new(config.clone(), code("ybgr"));
+/// let mut solver = DeterministicSolver::new(MostPartsSolver::new(&config));
+/// board.guess(code("rgby"));
+/// board.guess(code("grby"));
+/// solver.next_guess(board.rounds());
+/// let letters: Vec<String> = solver
+///     .candidates()
+///     .iter()
+///     .map(|code| code.colors().iter().map(|color| color.letter()).collect())
+///     .collect();
+/// // In palette order, r before g before b before y, however the inner
+/// // solver happened to keep them.
+/// assert_eq!(letters, ["byrg", "bygr", "ybrg", "ybgr"]);
+/// ```
+pub struct DeterministicSolver<S> {
+    inner: S,
+    candidates: BTreeSet<Code>,
+}
+
+impl<S: Solver> DeterministicSolver<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            candidates: BTreeSet::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Solver> Solver for DeterministicSolver<S> {
+    fn next_guess(&mut self, rounds: &[Round]) -> Option<Code> {
+        let guess = self.inner.next_guess(rounds);
+        self.candidates = self.inner.candidates().into_iter().cloned().collect();
+        guess
+    }
+
+    fn candidates(&self) -> Vec<&Code> {
+        self.candidates.iter().collect()
+    }
+}
+
+/// Wraps another solver, playing `opening` as the first guess when there is one
+/// and leaving every later guess to the inner solver.
+pub struct OpeningSolver<S> {
+    inner: S,
+    opening: Option<Code>,
+}
+
+impl<S: Solver> OpeningSolver<S> {
+    pub fn new(inner: S, opening: Option<Code>) -> Self {
+        Self { inner, opening }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Solver> Solver for OpeningSolver<S> {
+    fn next_guess(&mut self, rounds: &[Round]) -> Option<Code> {
+        match &self.opening {
+            Some(opening) if rounds.is_empty() => Some(opening.clone()),
+            _ => self.inner.next_guess(rounds),
+        }
+    }
+
+    fn candidates(&self) -> Vec<&Code> {
+        self.inner.candidates()
+    }
+}
+
+/// Wraps another solver and remembers its answer for each position it has
+/// been asked about, so that playing many games with one solver asks the
+/// inner solver once per position rather than once per game.
+///
+/// Positions are keyed by the rounds played, sorted, so the key does not
+/// depend on the order they were played in. A position found in the cache
+/// is answered without asking the inner solver, which therefore must answer
+/// from the rounds it is given alone, as the solvers here do, and not from
+/// what it was asked before.
+///
+/// ```
+/// use mastermind::solver::CachedSolver;
+/// use mastermind::{Board, Code, GameConfig, MostPartsSolver, Solver};
+///
+/// let config = GameConfig::default();
+/// let mut cached = CachedSolver::new(MostPartsSolver::new(&config));
+/// for hidden in ["rgby", "rgyb"] {
+///     let hidden = Code::parse(String::from(hidden), &config).unwrap();
+///     let mut board = Board::new(config.clone(), hidden);
+///     while !board.last_round().is_some_and(|round| round.wins()) {
+///         board.guess(cached.next_guess(board.rounds()).unwrap());
+///     }
+/// }
+/// // The second game opened like the first, so its first guess was cached.
+/// assert!(cached.hits() >= 1);
+/// ```
+pub struct CachedSolver<S> {
+    inner: S,
+    cache: HashMap<Vec<(Code, Score)>, CachedGuess>,
+    /// The codes still possible at the position last asked about.
+    candidates: Vec<Code>,
+    hits: usize,
+    misses: usize,
+}
+
+/// What the inner solver answered for one position.
+struct CachedGuess {
+    guess: Option<Code>,
+    /// The codes it then considered possible.
+    candidates: Vec<Code>,
+}
+
+impl<S: Solver> CachedSolver<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            cache: HashMap::new(),
+            candidates: Vec::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns how many guesses came from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Returns how many guesses the inner solver had to make.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Returns the share of guesses that came from the cache, or 0 before
+    /// any were asked for.
+    pub fn hit_rate(&self) -> f64 {
+        let asked = self.hits + self.misses;
+        if asked == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / asked as f64
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Solver> Solver for CachedSolver<S> {
+    fn next_guess(&mut self, rounds: &[Round]) -> Option<Code> {
+        let mut key: Vec<(Code, Score)> = rounds
+            .iter()
+            .map(|round| (round.guess().clone(), round.score().clone()))
+            .collect();
+        key.sort();
+        if let Some(cached) = self.cache.get(&key) {
+            self.hits += 1;
+            self.candidates.clone_from(&cached.candidates);
+            return cached.guess.clone();
+        }
+        self.misses += 1;
+        let guess = self.inner.next_guess(rounds);
+        self.candidates = self.inner.candidates().into_iter().cloned().collect();
+        self.cache.insert(
+            key,
+            CachedGuess {
+                guess: guess.clone(),
+                candidates: self.candidates.clone(),
+            },
+        );
+        guess
+    }
+
+    fn candidates(&self) -> Vec<&Code> {
+        self.candidates.iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::analyze;
+    use crate::board::Board;
+
+    #[test]
+    fn every_strategy_solves_the_default_game_in_six() {
+        let config = GameConfig::default();
+        for strategy in strategies() {
+            let report = analyze(&config, None, || (strategy.new)(&config));
+            assert_eq!(report.unsolved, 0, "{}", strategy.name);
+            assert!(report.worst_case <= 6, "{}", strategy.name);
+        }
+    }
+
+    #[test]
+    fn cached_solver_guesses_as_its_inner_solver_does() {
+        let config = GameConfig::default();
+        let mut plain = MostPartsSolver::new(&config);
+        let mut cached = CachedSolver::new(MostPartsSolver::new(&config));
+        let codes = LazyCodeSpace::new(&config).len();
+        for hidden in LazyCodeSpace::new(&config) {
+            let mut board = Board::new(config.clone(), hidden);
+            while !board.last_round().is_some_and(|round| round.wins()) {
+                let guess = cached.next_guess(board.rounds()).unwrap();
+                assert_eq!(Some(&guess), plain.next_guess(board.rounds()).as_ref());
+                assert_eq!(cached.candidates(), plain.candidates());
+                board.guess(guess);
+            }
+        }
+        // Every code is won from a position no other code reaches, so the
+        // inner solver is asked at least once per code, and the hit rate is
+        // at most one less one over the average number of guesses.
+        assert!(cached.misses() >= codes);
+        assert!(cached.hit_rate() > 0.65, "{}", cached.hit_rate());
+    }
+}
@@ -0,0 +1,242 @@
+//! Static Mastermind: every guess is written down before any is scored, the
+//! scores are revealed together, and the player has one answer to name the
+//! code.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+
+use crate::board::Round;
+use crate::code::{Code, LazyCodeSpace};
+use crate::config::GameConfig;
+use crate::deduction::CandidateSet;
+use crate::input::{normalize_input, prompt};
+use crate::Result;
+
+/// How well a set of guesses pins down the hidden code when all of their
+/// scores are seen at once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuessSetReport {
+    /// The most codes that could share one set of scores.
+    pub worst_case: usize,
+    /// How many different sets of scores the guesses can get.
+    pub outcomes: usize,
+}
+
+impl GuessSetReport {
+    /// Returns whether the scores always leave exactly one code, so a player
+    /// who reads them correctly can always name it.
+    pub fn is_sufficient(&self) -> bool {
+        self.worst_case <= 1
+    }
+}
+
+/// Works out how well `guesses` tell apart every code valid under `config`.
+///
+/// ```
+/// use mastermind::static_game::analyze_guess_set;
+/// use mastermind::{Code, GameConfig};
+///
+/// let config = GameConfig::default();
+/// let codes = |s: &str| -> Vec<Code> {
+///     s.split(' ')
+///         .map(|c| Code::parse(String::from(c), &config).unwrap())
+///         .collect()
+/// };
+///
+/// // Four well chosen guesses separate all 256 codes.
+/// let report = analyze_guess_set(&config, &codes("bryb rggb rrry ygyr"));
+/// assert!(report.is_sufficient());
+/// assert_eq!(report.outcomes, 256);
+///
+/// // Four guesses of one color each only count the colors.
+/// let report = analyze_guess_set(&config, &codes("rrrr gggg bbbb yyyy"));
+/// assert!(!report.is_sufficient());
+/// assert_eq!(report.outcomes, 35);
+/// assert_eq!(report.worst_case, 24);
+/// ```
+pub fn analyze_guess_set(config: &GameConfig, guesses: &[Code]) -> GuessSetReport {
+    let mut groups = HashMap::new();
+    for hidden in LazyCodeSpace::new(config) {
+        let scores: Vec<_> = guesses.iter().map(|guess| hidden.score(guess)).collect();
+        *groups.entry(scores).or_insert(0) += 1;
+    }
+    GuessSetReport {
+        worst_case: groups.values().copied().max().unwrap_or(0),
+        outcomes: groups.len(),
+    }
+}
+
+/// How a static game went.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StaticResult {
+    /// Each guess with its score, in the order they were written down.
+    pub rounds: Vec<Round>,
+    /// The final answer, or `None` if input ended before one was given.
+    pub answer: Option<Code>,
+    /// How many codes fit every score revealed.
+    pub candidates: usize,
+    pub report: GuessSetReport,
+}
+
+impl StaticResult {
+    pub fn won(&self, hidden: &Code) -> bool {
+        self.answer.as_ref() == Some(hidden)
+    }
+}
+
+/// Reads `count` guesses from `input`, asking again for any that is not a
+/// valid code or repeats one already written down. Returns `None` if input
+/// ends first.
+pub fn collect_guesses<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    config: &GameConfig,
+    count: usize,
+) -> Result<Option<Vec<Code>>> {
+    let mut guesses: Vec<Code> = Vec::with_capacity(count);
+    while guesses.len() < count {
+        let question = format!("guess {} of {}: ", guesses.len() + 1, count);
+        let Some(line) = prompt(input, output, &question)? else {
+            return Ok(None);
+        };
+        match Code::parse(line, config) {
+            Ok(code) if guesses.contains(&code) => writeln!(
+                output,
+                "{} is already written down",
+                code.display(&config.palette)
+            )?,
+            Ok(code) => guesses.push(code),
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+    }
+    Ok(Some(guesses))
+}
+
+/// Scores every one of `guesses` against `hidden` and shows them together,
+/// then reads the player's final answer from `input`.
+///
+/// ```
+/// use mastermind::static_game::play_static;
+/// use mastermind::{Code, GameConfig};
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let hidden = code("rgby");
+///
+/// let guesses = [code("bryb"), code("rggb"), code("rrry"), code("ygyr")];
+/// let mut output = Vec::new();
+/// let answers = "rgbx\nrgby\n";
+/// let result = play_static(&mut answers.as_bytes(), &mut output, &config, &hidden, &guesses).unwrap();
+/// assert!(result.won(&hidden));
+/// assert_eq!(result.candidates, 1);
+/// assert!(result.report.is_sufficient());
+/// let output = String::from_utf8(output).unwrap();
+/// assert!(output.contains("1 code fits every score"));
+///
+/// // With one guess many codes are left, and a wrong answer loses.
+/// let mut output = Vec::new();
+/// let result = play_static(&mut "rgyb\n".as_bytes(), &mut output, &config, &hidden, &guesses[..1]).unwrap();
+/// assert!(!result.won(&hidden));
+/// assert!(result.candidates > 1);
+/// assert!(!result.report.is_sufficient());
+/// ```
+pub fn play_static<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    config: &GameConfig,
+    hidden: &Code,
+    guesses: &[Code],
+) -> Result<StaticResult> {
+    let rounds: Vec<Round> = guesses
+        .iter()
+        .map(|guess| Round::from_guess(guess.clone(), hidden))
+        .collect();
+    writeln!(output, "the scores:")?;
+    for (number, round) in rounds.iter().enumerate() {
+        let pegs: Vec<String> = round
+            .score()
+            .details(config.code_length)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        writeln!(
+            output,
+            "{:>3}. {} | {}",
+            number + 1,
+            round.guess().display(&config.palette),
+            pegs.join(" ")
+        )?;
+    }
+    let candidates = CandidateSet::from_rounds(config, &rounds).len();
+    writeln!(
+        output,
+        "{} {} every score",
+        candidates,
+        if candidates == 1 {
+            "code fits"
+        } else {
+            "codes fit"
+        }
+    )?;
+    let report = analyze_guess_set(config, guesses);
+    if report.is_sufficient() {
+        writeln!(output, "these guesses tell every code apart")?;
+    } else {
+        writeln!(
+            output,
+            "these guesses could leave up to {} codes to choose from",
+            report.worst_case
+        )?;
+    }
+
+    let answer = loop {
+        let Some(line) = prompt(input, output, "your answer: ")? else {
+            break None;
+        };
+        match Code::parse(line, config) {
+            Ok(code) => break Some(code),
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+    };
+    Ok(StaticResult {
+        rounds,
+        answer,
+        candidates,
+        report,
+    })
+}
+
+/// Reads guesses for a static game from `text`, one code per line, skipping
+/// blank lines. Every guess must be a valid code and appear only once.
+///
+/// ```
+/// use mastermind::static_game::parse_guesses;
+/// use mastermind::GameConfig;
+///
+/// let config = GameConfig::default();
+/// assert_eq!(parse_guesses("rrgg\n\nrgby\n", &config).unwrap().len(), 2);
+/// assert!(parse_guesses("rrgg\nrrgg\n", &config).is_err());
+/// assert!(parse_guesses("rrgx\n", &config).is_err());
+/// ```
+pub fn parse_guesses(text: &str, config: &GameConfig) -> Result<Vec<Code>> {
+    let mut seen = HashSet::new();
+    let mut guesses = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = normalize_input(line);
+        if line.is_empty() {
+            continue;
+        }
+        let code = Code::parse(line.to_string(), config)
+            .map_err(|e| format!("line {}: {}", number + 1, e))?;
+        if !seen.insert(code.clone()) {
+            return Err(format!(
+                "line {}: {} is listed twice",
+                number + 1,
+                code.display(&config.palette)
+            )
+            .into());
+        }
+        guesses.push(code);
+    }
+    Ok(guesses)
+}
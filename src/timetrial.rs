@@ -0,0 +1,150 @@
+//! Solving as many codes as possible before time runs out.
+
+use std::fmt;
+use std::io::Write;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use crate::board::Board;
+use crate::code::Code;
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::random::Rng;
+use crate::Result;
+
+/// How a time trial went. Only codes solved before time ran out count; the
+/// guesses spent on a code left unsolved are not included.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeTrialResult {
+    pub solved: usize,
+    pub guesses: usize,
+    pub limit: Duration,
+}
+
+impl TimeTrialResult {
+    /// The mean number of guesses per solved code, or `None` if none were.
+    pub fn average(&self) -> Option<f64> {
+        (self.solved > 0).then(|| self.guesses as f64 / self.solved as f64)
+    }
+}
+
+/// Formats the result for sharing, like `7 codes in 3:00`.
+impl fmt::Display for TimeTrialResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let seconds = self.limit.as_secs();
+        let codes = if self.solved == 1 { "code" } else { "codes" };
+        write!(
+            f,
+            "{} {} in {}:{:02}",
+            self.solved,
+            codes,
+            seconds / 60,
+            seconds % 60
+        )
+    }
+}
+
+/// Plays random codes from `rng` one after another until `limit` has passed,
+/// reading guesses from `lines` (see `input::spawn_line_reader`) and writing
+/// progress to `output`. A code that runs out of `config.max_guesses` is
+/// revealed and play moves on to the next. The trial also ends if `lines`
+/// closes.
+///
+/// ```
+/// use std::sync::mpsc;
+/// use std::time::Duration;
+///
+/// use mastermind::timetrial::run_time_trial;
+/// use mastermind::{Color, GameConfig, Rng};
+///
+/// // With one color every code is "r".
+/// let config = GameConfig {
+///     colors: vec![Color::Red],
+///     code_length: 2,
+///     ..GameConfig::default()
+/// };
+/// let (sender, lines) = mpsc::channel();
+/// for line in ["rr", "oops", "rr"] {
+///     sender.send(Ok(String::from(line))).unwrap();
+/// }
+///
+/// // Time runs out while waiting for a third guess.
+/// let limit = Duration::from_millis(50);
+/// let mut output = Vec::new();
+/// let result = run_time_trial(&lines, &mut output, &config, &mut Rng::seeded(1), limit).unwrap();
+/// assert_eq!((result.solved, result.guesses), (2, 2));
+/// assert_eq!(result.to_string(), "2 codes in 0:00");
+///
+/// // Closing the input ends the trial early.
+/// drop(sender);
+/// let limit = Duration::from_secs(180);
+/// let result = run_time_trial(&lines, &mut output, &config, &mut Rng::seeded(1), limit).unwrap();
+/// assert_eq!(result.to_string(), "0 codes in 3:00");
+/// ```
+pub fn run_time_trial<W: Write>(
+    lines: &Receiver<std::result::Result<String, GameError>>,
+    output: &mut W,
+    config: &GameConfig,
+    rng: &mut Rng,
+    limit: Duration,
+) -> Result<TimeTrialResult> {
+    let deadline = Instant::now() + limit;
+    let mut result = TimeTrialResult {
+        solved: 0,
+        guesses: 0,
+        limit,
+    };
+    'codes: loop {
+        let mut board = Board::new(config.clone(), Code::random(config, rng));
+        loop {
+            write!(
+                output,
+                "code {}, guess {}: ",
+                result.solved + 1,
+                board.rounds().len() + 1
+            )?;
+            output.flush()?;
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = match lines.recv_timeout(remaining) {
+                Ok(Ok(line)) => line,
+                Ok(Err(e @ GameError::LineTooLong { .. })) => {
+                    writeln!(output, "{}", e)?;
+                    continue;
+                }
+                Ok(Err(e)) => return Err(e.into()),
+                Err(RecvTimeoutError::Timeout) => {
+                    writeln!(output, "\ntime's up!")?;
+                    break 'codes;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    writeln!(output)?;
+                    break 'codes;
+                }
+            };
+            let code = match Code::parse(line, config) {
+                Ok(code) => code,
+                Err(e) => {
+                    writeln!(output, "{}", e)?;
+                    continue;
+                }
+            };
+            let round = board.guess(code);
+            writeln!(output, "{}", round)?;
+            if round.wins() {
+                result.solved += 1;
+                result.guesses += board.rounds().len();
+                writeln!(output, "solved! next code")?;
+                continue 'codes;
+            }
+            if Some(board.rounds().len()) == config.max_guesses {
+                writeln!(
+                    output,
+                    "out of guesses, the code was {}; next code",
+                    board.hidden_code()
+                )?;
+                continue 'codes;
+            }
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,150 @@
+//! A session of games played under one guess budget.
+
+use crate::board::{Board, Round};
+use crate::code::Code;
+use crate::config::GameConfig;
+use crate::history::Outcome;
+
+/// Limits that apply across every game of a session.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SessionConfig {
+    /// The most guesses all games together may use; `None` means unlimited.
+    pub max_total_guesses: Option<usize>,
+}
+
+/// One finished game of a session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TournamentResult {
+    pub hidden_code: Code,
+    pub rounds: Vec<Round>,
+    /// `Outcome::Forfeit` when the game was given up, or cut short by the
+    /// session running out of guesses.
+    pub outcome: Outcome,
+}
+
+/// Plays games one after another, stopping any game that would go over the
+/// session's guess budget.
+///
+/// ```
+/// use mastermind::tournament::{SessionConfig, TournamentRunner};
+/// use mastermind::{Code, GameConfig, Outcome};
+///
+/// let config = GameConfig::default();
+/// let code = |s: &str| Code::parse(String::from(s), &config).unwrap();
+/// let session = SessionConfig {
+///     max_total_guesses: Some(5),
+/// };
+/// let mut runner = TournamentRunner::new(config.clone(), session);
+/// // Each game takes three guesses to win.
+/// let script = |rounds: &[_]| ["rrrr", "gggg", "rgby"].get(rounds.len()).map(|s| code(s));
+///
+/// let first = runner.play_game(code("rgby"), script);
+/// assert_eq!((first.outcome, first.rounds.len()), (Outcome::Won, 3));
+///
+/// let second = runner.play_game(code("rgby"), script);
+/// assert_eq!((second.outcome, second.rounds.len()), (Outcome::Forfeit, 2));
+/// assert_eq!(runner.remaining_guesses(), Some(0));
+///
+/// // A guesser with nothing to say forfeits too.
+/// let mut runner = TournamentRunner::new(config.clone(), SessionConfig::default());
+/// assert_eq!(runner.play_game(code("rgby"), |_| None).outcome, Outcome::Forfeit);
+/// ```
+pub struct TournamentRunner {
+    game_config: GameConfig,
+    session_config: SessionConfig,
+    guesses_used: usize,
+    results: Vec<TournamentResult>,
+}
+
+impl TournamentRunner {
+    pub fn new(game_config: GameConfig, session_config: SessionConfig) -> TournamentRunner {
+        TournamentRunner {
+            game_config,
+            session_config,
+            guesses_used: 0,
+            results: Vec::new(),
+        }
+    }
+
+    /// Changes the rules for the games still to come, for instance to the
+    /// settings `adaptive::next_settings` picks from the results so far.
+    ///
+    /// ```
+    /// use mastermind::adaptive::{next_settings, Bounds, GameResult, Settings};
+    /// use mastermind::tournament::{SessionConfig, TournamentRunner};
+    /// use mastermind::{Code, GameConfig};
+    ///
+    /// let mut runner = TournamentRunner::new(GameConfig::default(), SessionConfig::default());
+    /// let mut played = Vec::new();
+    /// for _ in 0..3 {
+    ///     let config = runner.game_config().clone();
+    ///     let hidden = Code::all_of(config.colors[0].clone(), config.code_length);
+    ///     let result = runner.play_game(hidden.clone(), |_| Some(hidden.clone()));
+    ///     played.push(GameResult {
+    ///         settings: Settings::of(&config),
+    ///         outcome: result.outcome,
+    ///         guesses: result.rounds.len(),
+    ///     });
+    ///     let next = next_settings(&played, Settings::of(&config), &Bounds::default());
+    ///     runner.set_game_config(next.apply(config));
+    /// }
+    /// // Three wins in one guess each earn a fifth color.
+    /// assert_eq!(runner.game_config().colors.len(), 5);
+    /// ```
+    pub fn set_game_config(&mut self, game_config: GameConfig) {
+        self.game_config = game_config;
+    }
+
+    pub fn game_config(&self) -> &GameConfig {
+        &self.game_config
+    }
+
+    /// Returns how many guesses every game so far has used.
+    pub fn guesses_used(&self) -> usize {
+        self.guesses_used
+    }
+
+    /// Returns how many guesses are left in the session, or `None` when the
+    /// session has no limit.
+    pub fn remaining_guesses(&self) -> Option<usize> {
+        self.session_config
+            .max_total_guesses
+            .map(|max| max.saturating_sub(self.guesses_used))
+    }
+
+    /// Returns every game played so far, in order.
+    pub fn results(&self) -> &[TournamentResult] {
+        &self.results
+    }
+
+    /// Plays a game against `hidden`, asking `next_guess` for each guess given
+    /// the rounds so far. The game is lost if it runs out of its own guesses,
+    /// and forfeited if `next_guess` gives up or the session runs out.
+    pub fn play_game<F>(&mut self, hidden: Code, mut next_guess: F) -> &TournamentResult
+    where
+        F: FnMut(&[Round]) -> Option<Code>,
+    {
+        let mut board = Board::new(self.game_config.clone(), hidden);
+        let outcome = loop {
+            if self.remaining_guesses() == Some(0) {
+                break Outcome::Forfeit;
+            }
+            if Some(board.rounds().len()) == self.game_config.max_guesses {
+                break Outcome::Lost;
+            }
+            let Some(guess) = next_guess(board.rounds()) else {
+                break Outcome::Forfeit;
+            };
+            self.guesses_used += 1;
+            if board.guess(guess).wins() {
+                break Outcome::Won;
+            }
+        };
+        self.results.push(TournamentResult {
+            hidden_code: board.hidden_code().clone(),
+            rounds: board.rounds().to_vec(),
+            outcome,
+        });
+        &self.results[self.results.len() - 1]
+    }
+}
@@ -0,0 +1,45 @@
+//! The crate builds with each feature on its own and with the combinations
+//! that matter, as `cargo hack` would check them.
+
+use std::process::Command;
+
+/// The `--features` lists checked, each with the default features off.
+const COMBINATIONS: [&str; 14] = [
+    "",
+    "std",
+    "rand",
+    "rand,color",
+    "rand,tui",
+    "rand,serde",
+    "rand,sqlite",
+    "rand,server",
+    "rand,ws",
+    "rand,parallel",
+    "rand,readline",
+    "rand,async",
+    "rand,serde,async",
+    "rand,serde,bench",
+];
+
+#[test]
+fn every_combination_builds() {
+    for features in COMBINATIONS {
+        let status = Command::new(env!("CARGO"))
+            .args([
+                "check",
+                "--quiet",
+                "--no-default-features",
+                "--features",
+                features,
+            ])
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .env("CARGO_TARGET_DIR", env!("CARGO_TARGET_TMPDIR"))
+            .status()
+            .unwrap();
+        assert!(
+            status.success(),
+            "does not build with --features '{}'",
+            features
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! The SQLite history gives back every game as it was recorded.
+
+use std::path::Path;
+
+use mastermind::board::SkipReason;
+use mastermind::history::Variant;
+use mastermind::{Board, Code, GameConfig, GameSession, History, Outcome, Palette};
+
+#[test]
+fn sessions_read_back_with_skipped_guesses() {
+    let palette = Palette::from_letters("0123456789").unwrap();
+    let config = GameConfig {
+        colors: palette.values()[..6].to_vec(),
+        palette,
+        ..GameConfig::default()
+    };
+    let code = |s| Code::from_letters(s, &config).unwrap();
+    let mut board = Board::new(config.clone(), code("5052"));
+    board.skip(SkipReason::Timeout);
+    board.guess(code("0123"));
+    board.skip(SkipReason::Timeout);
+    board.skip(SkipReason::Timeout);
+    board.guess(code("5052"));
+    let won = GameSession::from_board(&board, 100, Outcome::Won);
+    let fresh = GameSession {
+        variant: Variant::Static,
+        rounds: Vec::new(),
+        skipped: vec![(0, SkipReason::Timeout)],
+        ..won.clone()
+    };
+
+    let history = History::open(Path::new(":memory:"));
+    history.record(&won).unwrap();
+    history.record(&fresh).unwrap();
+    assert_eq!(history.sessions().unwrap(), [won, fresh]);
+
+    let recent = history.query_recent(5).unwrap();
+    let guesses: Vec<_> = recent.iter().map(|game| game.guesses).collect();
+    assert_eq!(guesses, [1, 5]);
+    assert_eq!(recent[0].variant, Variant::Static);
+    assert_eq!((recent[1].code_length, recent[1].color_count), (4, 6));
+}
+
+#[test]
+fn each_database_is_separate() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("profile/history.db");
+    let game = GameSession::from_board(
+        &Board::new(
+            GameConfig::default(),
+            String::from("rgby").try_into().unwrap(),
+        ),
+        0,
+        Outcome::Abandoned,
+    );
+    // Nothing is created until a game is recorded.
+    assert!(History::open(&path).sessions().unwrap().is_empty());
+    assert!(!path.exists());
+    History::open(&path).record(&game).unwrap();
+    // The game was committed to the file, not kept by the connection.
+    assert_eq!(History::open(&path).sessions().unwrap(), [game]);
+    assert!(History::open(Path::new(":memory:"))
+        .sessions()
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn databases_from_before_totals_still_read() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("history.db");
+    let old = rusqlite::Connection::open(&path).unwrap();
+    old.execute_batch(
+        "CREATE TABLE games (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            started_at INTEGER NOT NULL,
+            ended_at INTEGER NOT NULL,
+            outcome TEXT NOT NULL,
+            variant TEXT NOT NULL,
+            hidden_code TEXT NOT NULL,
+            config_json TEXT NOT NULL
+        );
+        INSERT INTO games (started_at, ended_at, outcome, variant, hidden_code, config_json)
+        VALUES (0, 60, 'lost', 'classic', 'rgby', '{\"colors\":[\"r\",\"g\",\"b\",\"y\"],\"code_length\":4}');",
+    )
+    .unwrap();
+    drop(old);
+
+    let history = History::open(&path);
+    let totals = history.totals().unwrap();
+    assert_eq!((totals.games, totals.losses, totals.seconds), (1, 1, 60));
+    let game = GameSession {
+        config: GameConfig {
+            cheat_mode: true,
+            ..GameConfig::default()
+        },
+        ..history.sessions().unwrap()[0].clone()
+    };
+    history.record(&game).unwrap();
+    assert_eq!(history.totals().unwrap().games, 2);
+    assert!(history.sessions().unwrap()[1].config.cheat_mode);
+}
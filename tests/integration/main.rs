@@ -0,0 +1,838 @@
+//! End-to-end games replayed from golden files.
+//!
+//! Each file in this directory is a transcript of one run of the binary. The
+//! first line is the command, `$ mastermind <args>`; lines starting with `> `
+//! are fed to stdin in order, with the line ending the file has, and every
+//! other line is expected on stdout.
+//! Colors are turned back into the letters the build without the `color`
+//! feature prints before comparing, so the same transcripts work for both.
+//! Likewise a build without the `serde` feature keeps no records, so the
+//! streak and high score lines are left out of what it is expected to print.
+
+#[cfg(feature = "serde")]
+use std::io::Write;
+use std::path::Path;
+#[cfg(feature = "serde")]
+use std::process::{self, Stdio};
+
+use assert_cmd::assert::Assert;
+#[cfg(feature = "serde")]
+use assert_cmd::cargo::CommandCargoExt;
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+mod features;
+#[cfg(feature = "sqlite")]
+mod history;
+#[cfg(feature = "serde")]
+mod round_trips;
+
+/// A golden file split into the run it describes and what it should print.
+struct Golden {
+    args: Vec<String>,
+    stdin: String,
+    stdout: String,
+}
+
+impl Golden {
+    fn load(name: &str) -> Golden {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/integration")
+            .join(name);
+        let text = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e));
+        let mut lines = text.split_inclusive('\n');
+        let args = lines
+            .next()
+            .and_then(|line| line.strip_prefix("$ mastermind"))
+            .unwrap_or_else(|| panic!("{} must start with '$ mastermind'", name))
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        let mut stdin = String::new();
+        let mut stdout = String::new();
+        for line in lines {
+            match line.strip_prefix("> ") {
+                Some(input) => {
+                    stdin.push_str(input);
+                    if !input.ends_with('\n') {
+                        stdin.push('\n');
+                    }
+                }
+                None => {
+                    stdout.push_str(line.trim_end_matches(['\r', '\n']));
+                    stdout.push('\n');
+                }
+            }
+        }
+        Golden {
+            args,
+            stdin,
+            stdout,
+        }
+    }
+}
+
+/// The key peg printed as a colored blank, by background color code, and the
+/// letter printed for it without color.
+const KEY_PEGS: [(&str, char); 3] = [("48;5;14", 'b'), ("48;5;15", 'w'), ("48;5;9", '-')];
+
+/// Removes ANSI escape sequences such as `\x1b[38;5;9m` from `s`, writing key
+/// pegs, which are only a background color, as their plain letters.
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::new();
+    let mut background = None;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            match background {
+                Some(letter) if c == ' ' => out.push(letter),
+                _ => out.push(c),
+            }
+            continue;
+        }
+        let mut sequence = String::new();
+        for c in chars.by_ref() {
+            if c.is_ascii_alphabetic() {
+                break;
+            }
+            sequence.push(c);
+        }
+        let sequence = sequence.trim_start_matches('[');
+        if let Some((_, letter)) = KEY_PEGS.iter().find(|(code, _)| *code == sequence) {
+            background = Some(*letter);
+        } else if sequence == "49" || sequence == "0" {
+            background = None;
+        }
+    }
+    out
+}
+
+/// The starts of lines only printed by a build that keeps records.
+const RECORDS: [&str; 2] = ["current streak:", "that's a new best!"];
+
+/// Returns whether this build prints `line` of a transcript.
+fn printed(line: &str) -> bool {
+    cfg!(feature = "serde") || !RECORDS.iter().any(|start| line.starts_with(start))
+}
+
+/// Runs the binary as `name` describes and checks its output line by line.
+fn check(name: &str) {
+    let golden = Golden::load(name);
+    let assert = run(&golden.args, &golden.stdin).success();
+    let actual = strip_ansi(&String::from_utf8_lossy(&assert.get_output().stdout));
+    let actual: Vec<&str> = actual.lines().map(str::trim_end).collect();
+    let expected: Vec<&str> = golden
+        .stdout
+        .lines()
+        .filter(|line| printed(line))
+        .map(str::trim_end)
+        .collect();
+    let differs = predicate::str::diff(expected.join("\n"));
+    if let Some(case) = differs.find_case(false, &actual.join("\n")) {
+        let diff: Vec<String> = case.products().map(ToString::to_string).collect();
+        panic!("{}: stdout differs\n{}", name, diff.join("\n"));
+    }
+}
+
+/// Runs the binary with `args` and a data directory of its own, feeding it
+/// `stdin`, and returns how it went.
+fn run<S: AsRef<std::ffi::OsStr>>(args: &[S], stdin: &str) -> Assert {
+    let data = tempfile::tempdir().unwrap();
+    run_in(data.path(), args, stdin)
+}
+
+/// Like `run`, but keeps the player's files in `data`.
+fn run_in<S: AsRef<std::ffi::OsStr>>(data: &Path, args: &[S], stdin: &str) -> Assert {
+    Command::cargo_bin("mastermind")
+        .unwrap()
+        .args(args)
+        .env("XDG_DATA_HOME", data)
+        .env_remove("NO_COLOR")
+        .write_stdin(stdin)
+        .assert()
+}
+
+/// Starts the binary with `args` and `data` as its data directory, with its
+/// input and output piped, for tests that need to act while it runs.
+#[cfg(feature = "serde")]
+fn spawn(args: &[&str], data: &Path) -> process::Child {
+    process::Command::cargo_bin("mastermind")
+        .unwrap()
+        .args(args)
+        .env("XDG_DATA_HOME", data)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap()
+}
+
+#[test]
+fn print_all_codes_lists_the_whole_code_space() {
+    let lines = |assert: Assert| {
+        let output = assert.success().get_output().stdout.clone();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect::<Vec<_>>()
+    };
+    let codes = lines(run(&["--print-all-codes"], ""));
+    assert_eq!(codes.len(), 4usize.pow(4));
+    assert_eq!(codes[0], "r r r r");
+
+    let args = [
+        "--print-all-codes",
+        "--preset",
+        "super",
+        "--length",
+        "3",
+        "--colors",
+        "5",
+        "--display-mode",
+        "symbols",
+    ];
+    let codes = lines(run(&args, ""));
+    assert_eq!(codes.len(), 5usize.pow(3));
+    assert_eq!(codes[0], "● ● ●");
+    assert_eq!(codes[codes.len() - 1], "★ ★ ★");
+    run(&["--print-all-codes", "--display-mode", "pictures"], "")
+        .failure()
+        .stderr(predicate::str::contains("unknown display mode 'pictures'"));
+}
+
+#[test]
+fn piped_output_has_no_color() {
+    let no_escapes = || predicate::str::contains('\x1b').not();
+    run(&["--hidden-code", "rgby"], "rrgg\nrgby\n")
+        .stdout(predicate::str::contains("| r r g g | b w - - |").and(no_escapes()));
+    run(&["--hidden-code", "rgby", "--wide"], "rrgg\nrgby\n")
+        .stdout(predicate::str::contains("| rrr ggg bbb yyy | b b b b |").and(no_escapes()));
+    run(&["themes"], "").stdout(no_escapes());
+}
+
+#[test]
+#[cfg(feature = "color")]
+fn color_always_paints_piped_output() {
+    run(&["--hidden-code", "rgby", "--color", "always"], "rgby\n")
+        .stdout(predicate::str::contains('\x1b'));
+    run(&["--hidden-code", "rgby", "--color", "never"], "rgby\n")
+        .stdout(predicate::str::contains('\x1b').not());
+}
+
+#[test]
+#[cfg(not(feature = "color"))]
+fn plain_build_never_paints() {
+    run(&["--hidden-code", "rgby", "--color", "always"], "rgby\n").stdout(
+        predicate::str::contains("congratulations, you win!")
+            .and(predicate::str::contains('\x1b').not()),
+    );
+}
+
+#[test]
+fn basic_win() {
+    check("basic_win.txt");
+}
+
+#[test]
+fn max_rounds_loss() {
+    check("max_rounds_loss.txt");
+}
+
+#[test]
+fn auto_solve_knuth() {
+    check("auto_solve_knuth.txt");
+}
+
+#[test]
+fn super_mastermind_win() {
+    check("super_mastermind_win.txt");
+}
+
+/// Solves all 32768 Super Mastermind codes, which takes minutes even in a
+/// release build; run it with `cargo test --release -- --ignored`. The most
+/// guesses allowed can be set with `MASTERMIND_SUPER_MAX_GUESSES`.
+#[test]
+#[ignore = "solves every Super Mastermind code, run with --ignored"]
+fn super_mastermind_solves_every_code() {
+    let max = std::env::var("MASTERMIND_SUPER_MAX_GUESSES").unwrap_or_else(|_| "8".into());
+    run(
+        &["--preset", "super", "--analyze", "--assert-max", &max],
+        "",
+    )
+    .success()
+    .stdout(predicate::str::contains("games: 32768"));
+}
+
+#[test]
+fn no_prompt() {
+    check("no_prompt.txt");
+}
+
+#[test]
+fn show_candidates() {
+    check("show_candidates.txt");
+}
+
+#[test]
+fn crlf_input() {
+    check("crlf_input.txt");
+}
+
+#[test]
+fn crlf_guesses_file() {
+    check("crlf_guesses_file.txt");
+}
+
+#[test]
+fn output_quiet() {
+    check("output_quiet.txt");
+}
+
+#[test]
+fn output_verbose() {
+    check("output_verbose.txt");
+}
+
+#[test]
+fn coach_eliminated() {
+    check("coach_eliminated.txt");
+}
+
+#[test]
+fn format_csv() {
+    check("format_csv.txt");
+}
+
+#[test]
+fn guesser_deterministic() {
+    check("guesser_deterministic.txt");
+}
+
+#[test]
+fn anagram_mode() {
+    check("anagram_mode.txt");
+}
+
+#[test]
+fn wide() {
+    check("wide.txt");
+}
+
+#[test]
+fn parse_errors() {
+    check("parse_errors.txt");
+}
+
+/// Anagram scores and compact scores hand peg counts out as a `u8`, so codes
+/// longer than that are refused up front rather than misreported mid-game.
+#[test]
+fn length_too_long_for_scores() {
+    run(&["--length", "300", "--colors", "1"], "")
+        .failure()
+        .stderr(predicate::str::contains("--length must be from 1 to 255"));
+}
+
+#[test]
+fn what_if_needs_assists() {
+    run(&["--what-if", "rgby", "--no-assists"], "")
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("disabled by --no-assists"));
+}
+
+#[test]
+fn input_macros() {
+    check("input_macros.txt");
+}
+
+#[test]
+fn forfeit() {
+    check("forfeit.txt");
+}
+
+#[test]
+fn adversarial_codemaker() {
+    check("adversarial_codemaker.txt");
+}
+
+#[test]
+fn cheat() {
+    check("cheat.txt");
+}
+
+#[test]
+fn width_60() {
+    check("width_60.txt");
+}
+
+#[test]
+fn width_80() {
+    check("width_80.txt");
+}
+
+#[test]
+fn width_120() {
+    check("width_120.txt");
+}
+
+#[test]
+fn boards_width_60() {
+    check("boards_width_60.txt");
+}
+
+#[test]
+fn boards_width_120() {
+    check("boards_width_120.txt");
+}
+
+#[test]
+fn coach() {
+    check("coach.txt");
+}
+
+#[test]
+fn explain() {
+    check("explain.txt");
+}
+
+#[test]
+fn difficulty_score() {
+    check("difficulty_score.txt");
+}
+
+#[test]
+fn digit_palette() {
+    check("digit_palette.txt");
+}
+
+#[test]
+fn letter_palette() {
+    check("letter_palette.txt");
+}
+
+#[test]
+fn quit_as_code() {
+    check("quit_as_code.txt");
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn report() {
+    check("report.txt");
+}
+
+#[test]
+fn analyze_openings() {
+    check("analyze_openings.txt");
+}
+
+#[test]
+fn time_trial() {
+    check("time_trial.txt");
+}
+
+#[test]
+fn compare_strategies() {
+    check("compare_strategies.txt");
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn report_json_and_dates() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/integration/fixtures/report");
+    let dir = dir.to_str().unwrap();
+    let settings =
+        r#"{"games":3,"wins":2,"settings":[{"code_length":4,"colors":4,"games":2,"wins":2},"#;
+    run(&["report", dir, "--json"], "").stdout(
+        predicate::str::starts_with(settings)
+            .and(predicate::str::contains(r#""already_impossible":1,"#))
+            .and(predicate::str::contains("notes.txt")),
+    );
+    run(&["report", dir, "--since", "2999-01-01"], "").stdout("games: 0, won 0 (0%)\n");
+    run(&["report", dir, "--until", "1970-01-01"], "").stdout("games: 0, won 0 (0%)\n");
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn old_scores_warn() {
+    check("old_scores_warn.txt");
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn old_scores_strict_and_migrate() {
+    let fixture =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/integration/fixtures/old_scoring.json");
+    let fixture = fixture.to_str().unwrap();
+    run(&["analyze-transcript", fixture, "--strict-load"], "").stdout(predicate::str::is_empty());
+
+    let dir = tempfile::tempdir().unwrap();
+    let copy = dir.path().join("old_scoring.json");
+    std::fs::copy(fixture, &copy).unwrap();
+    run(
+        &["analyze-transcript", copy.to_str().unwrap(), "--migrate"],
+        "",
+    )
+    .stdout(predicate::str::starts_with("rescored 1 of 3 rounds in "));
+    let migrated = predicate::str::starts_with(r#"{"version":2,"#).and(predicate::str::contains(
+        r#"{"guess":"rrrr","score":{"blacks":2,"whites":0}}"#,
+    ));
+    let text = std::fs::read_to_string(&copy).unwrap();
+    assert!(migrated.eval(&text), "{}", text);
+    run(
+        &[
+            "analyze-transcript",
+            copy.to_str().unwrap(),
+            "--strict-load",
+        ],
+        "",
+    )
+    .stdout(predicate::str::starts_with("round  guess"));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn autosave_survives_a_killed_game() {
+    let data = tempfile::tempdir().unwrap();
+    let recovery = data.path().join("mastermind/recovery");
+    let mut child = spawn(&["--autosave", "--hidden-code", "rgby"], data.path());
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"rrgg\n").unwrap();
+    let saved_round = || {
+        std::fs::read_dir(&recovery).ok()?.find_map(|entry| {
+            let text = std::fs::read_to_string(entry.ok()?.path()).ok()?;
+            text.contains(r#""guess":"rrgg""#).then_some(())
+        })
+    };
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    while saved_round().is_none() {
+        assert!(
+            std::time::Instant::now() < deadline,
+            "the first round was never autosaved"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    child.kill().unwrap();
+    child.wait().unwrap();
+    drop(stdin);
+
+    run_in(data.path(), &["--recover"], "1\nrgby\n")
+        .success()
+        .stdout(
+            predicate::str::contains(
+                "found 1 unfinished game:\n  1. 4 pegs, 4 colors, 1 round played, ",
+            )
+            .and(predicate::str::contains("| r r g g | b w - - |"))
+            .and(predicate::str::contains("congratulations, you win!")),
+        );
+    assert_eq!(std::fs::read_dir(&recovery).unwrap().count(), 0);
+}
+
+#[test]
+#[cfg(all(unix, feature = "serde"))]
+fn ctrl_c_saves_the_game_for_load() {
+    let data = tempfile::tempdir().unwrap();
+    // Each player's game is saved in their own profile.
+    let profile = data.path().join("mastermind/players/ada");
+    let saved = profile.join("autosave.json");
+    // --autosave writes each round out just after it is handed to the Ctrl-C
+    // handler, so seeing it there means the round is ready to be saved.
+    let recovery = profile.join("recovery");
+    let mut child = spawn(
+        &[
+            "--hidden-code",
+            "rgby",
+            "--no-prompt",
+            "--autosave",
+            "--player",
+            "ada",
+        ],
+        data.path(),
+    );
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"rrgg\n").unwrap();
+    let saved_round = || {
+        std::fs::read_dir(&recovery).ok()?.find_map(|entry| {
+            let text = std::fs::read_to_string(entry.ok()?.path()).ok()?;
+            text.contains(r#""guess":"rrgg""#).then_some(())
+        })
+    };
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    while saved_round().is_none() {
+        assert!(
+            std::time::Instant::now() < deadline,
+            "the first round was never played"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    let status = process::Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let output = child.wait_with_output().unwrap();
+    drop(stdin);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(130), "{}", stderr);
+    let hint = predicate::str::contains(format!("mastermind --load {}", saved.display()));
+    assert!(hint.eval(&stderr), "{}", stderr);
+    assert!(predicate::str::contains(r#""guess":"rrgg""#)
+        .eval(&std::fs::read_to_string(&saved).unwrap()));
+
+    run_in(
+        data.path(),
+        &["--load", saved.to_str().unwrap(), "--no-prompt"],
+        "rgby\n",
+    )
+    .success()
+    .stdout(predicate::str::contains("congratulations, you win!"));
+}
+
+#[test]
+#[cfg(all(unix, feature = "server"))]
+fn ctrl_c_stops_the_race_host() {
+    use std::io::{BufRead, BufReader, Read};
+
+    let data = tempfile::tempdir().unwrap();
+    let mut child = spawn(&["host", "--port", "0", "--racers", "2"], data.path());
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut waiting = String::new();
+    stdout.read_line(&mut waiting).unwrap();
+    let port = waiting.trim().rsplit(' ').next().unwrap();
+    let address = format!("127.0.0.1:{}", port);
+
+    // One racer of two has joined, and the host is still waiting for the other.
+    let mut racer = BufReader::new(std::net::TcpStream::connect(&address).unwrap());
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    let status = process::Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let mut told = String::new();
+    racer.read_line(&mut told).unwrap();
+    assert_eq!(told.trim(), r#"{"message":"stopped"}"#);
+    assert_eq!(racer.read_to_string(&mut told).unwrap(), 0);
+    let mut rest = String::new();
+    stdout.read_to_string(&mut rest).unwrap();
+    assert!(rest.starts_with("race stopped; the code was "), "{}", rest);
+    assert_eq!(child.wait().unwrap().code(), Some(130));
+    // Nobody is listening any more.
+    assert!(std::net::TcpStream::connect(&address).is_err());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn skipped_guesses_count_towards_the_high_score() {
+    let data = tempfile::tempdir().unwrap();
+    let mut child = spawn(
+        &[
+            "--hidden-code",
+            "rgby",
+            "--no-prompt",
+            "--guess-timeout",
+            "1",
+        ],
+        data.path(),
+    );
+    let mut stdin = child.stdin.take().unwrap();
+    // Let the first guess time out before the winning one is typed.
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+    stdin.write_all(b"rgby\ndana\n").unwrap();
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    let stdout = strip_ansi(&String::from_utf8(output.stdout).unwrap());
+    let skipped = predicate::str::contains("time's up, that guess is skipped");
+    assert!(skipped.eval(&stdout), "{}", stdout);
+    let scores = std::fs::read_to_string(data.path().join("mastermind/highscores.json")).unwrap();
+    assert!(
+        predicate::str::contains(r#""guesses":2"#).eval(&scores),
+        "{}",
+        scores
+    );
+}
+
+/// A game played as one player shows up in their stats and nobody else's.
+#[test]
+#[cfg(feature = "serde")]
+fn players_keep_their_stats_apart() {
+    let data = tempfile::tempdir().unwrap();
+    let data = data.path();
+    run_in(
+        data,
+        &["--hidden-code", "rgby", "--no-prompt", "--player", "alice"],
+        "rgby\nal\n",
+    )
+    .success();
+    run_in(data, &["stats", "--player", "alice"], "")
+        .success()
+        .stdout(predicate::str::contains("won       in  1 guesses"));
+    run_in(data, &["stats", "--player", "bob"], "")
+        .success()
+        .stdout("no games played yet\n");
+    run_in(data, &["players"], "").success().stdout("alice\n");
+}
+
+/// A duel is recorded to both players, the codemaker losing when the code is
+/// cracked.
+#[test]
+#[cfg(feature = "serde")]
+fn duels_are_recorded_for_both_players() {
+    let data = tempfile::tempdir().unwrap();
+    let data = data.path();
+    run_in(
+        data,
+        &[
+            "--hidden-code",
+            "rgby",
+            "--no-prompt",
+            "--duel",
+            "alice",
+            "bob",
+        ],
+        "rrgg\nrgby\nbo\n",
+    )
+    .success();
+    run_in(data, &["stats", "--player", "bob"], "")
+        .success()
+        .stdout(predicate::str::contains("won       in  2 guesses"));
+    run_in(data, &["stats", "--player", "alice"], "")
+        .success()
+        .stdout(predicate::str::contains("lost      in  2 guesses"));
+    run_in(data, &["stats"], "")
+        .success()
+        .stdout("no games played yet\n");
+    run_in(data, &["--duel", "alice", "bob", "--random"], "")
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+/// `stats export` writes one row per recorded game, in either format.
+#[test]
+#[cfg(feature = "serde")]
+fn stats_export_writes_every_game() {
+    let data = tempfile::tempdir().unwrap();
+    let data = data.path();
+    run_in(
+        data,
+        &["--hidden-code", "rgby", "--no-prompt"],
+        "rgby\nal\n",
+    )
+    .success();
+    run_in(
+        data,
+        &["--hidden-code", "rgby", "--no-prompt"],
+        "rrrr\nrgby\nbo\n",
+    )
+    .success();
+
+    let csv = data.join("stats.csv");
+    let out = csv.to_str().unwrap();
+    run_in(
+        data,
+        &["stats", "export", "--format", "csv", "--out", out],
+        "",
+    )
+    .success();
+    let csv = std::fs::read_to_string(csv).unwrap();
+    let rows: Vec<&str> = csv.lines().skip(1).collect();
+    assert_eq!(rows.len(), 2, "{}", csv);
+    assert!(rows.iter().all(|row| row.contains(",won,")), "{}", csv);
+
+    let json = data.join("stats.json");
+    run_in(
+        data,
+        &["stats", "export", "--out", json.to_str().unwrap()],
+        "",
+    )
+    .success();
+    let json = std::fs::read_to_string(json).unwrap();
+    assert_eq!(json.matches(r#""outcome":"won""#).count(), 2, "{}", json);
+}
+
+/// With `--history-cap`, only the newest games are kept, and they keep
+/// their numbers.
+#[test]
+#[cfg(feature = "serde")]
+fn history_cap_drops_the_oldest_games() {
+    let data = tempfile::tempdir().unwrap();
+    let data = data.path();
+    for guesses in ["rgby\nal\n", "rrrr\nrgby\n", "rrrr\ngggg\nrgby\n"] {
+        run_in(
+            data,
+            &["--hidden-code", "rgby", "--no-prompt", "--history-cap", "2"],
+            guesses,
+        )
+        .success();
+    }
+    run_in(data, &["stats"], "").success().stdout(
+        predicate::str::contains("   3. ")
+            .and(predicate::str::contains("   2. "))
+            .and(predicate::str::contains("   1. ").not()),
+    );
+
+    let csv = data.join("stats.csv");
+    let out = csv.to_str().unwrap();
+    run_in(
+        data,
+        &["stats", "export", "--format", "csv", "--out", out],
+        "",
+    )
+    .success()
+    .stdout("exported 2 games to ".to_owned() + out + "\n");
+    run_in(data, &["--history-cap", "0", "stats"], "")
+        .failure()
+        .stderr("--history-cap must be at least 1\n");
+}
+
+/// Closing input after a guess timed out abandons the game, which breaks
+/// the streak though no guess was ever scored.
+#[test]
+#[cfg(feature = "serde")]
+fn abandoning_after_a_skipped_guess_breaks_the_streak() {
+    let data = tempfile::tempdir().unwrap();
+    let data = data.path();
+    run_in(
+        data,
+        &["--hidden-code", "rgby", "--no-prompt"],
+        "rgby\nal\n",
+    )
+    .success();
+
+    let mut child = spawn(
+        &[
+            "--hidden-code",
+            "rgby",
+            "--no-prompt",
+            "--guess-timeout",
+            "1",
+        ],
+        data,
+    );
+    let stdin = child.stdin.take().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    let stdout = strip_ansi(&String::from_utf8(output.stdout).unwrap());
+    let skipped = predicate::str::contains("time's up, that guess is skipped");
+    assert!(skipped.eval(&stdout), "{}", stdout);
+
+    run_in(data, &["stats"], "").stdout(predicate::str::contains("current streak: 0, best: 1"));
+}
+
+/// A command's name given as the value of a flag is only that value.
+#[test]
+#[cfg(feature = "serde")]
+fn command_names_are_plain_values_after_flags() {
+    run(
+        &["--player", "stats", "--hidden-code", "rgby", "--no-prompt"],
+        "rgby\n",
+    )
+    .stdout(
+        predicate::str::contains("congratulations, you win!")
+            .and(predicate::str::contains("no games played yet").not()),
+    );
+}
@@ -0,0 +1,257 @@
+//! Every type saved as JSON reads back as what was written, and reading
+//! refuses values that could not have been written.
+
+use std::fmt::Debug;
+
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use mastermind::adaptive::Settings;
+use mastermind::board::SkipReason;
+use mastermind::daily::{DailyLog, DailyRecord};
+use mastermind::events::{EventRecord, GameEvent};
+use mastermind::history::Variant;
+#[cfg(feature = "server")]
+use mastermind::race::Message;
+use mastermind::{
+    Board, Code, Color, GameConfig, GameSession, HighScore, HighScoreBoard, Outcome, Palette,
+    Round, Score, TimeTrialBoard, TimeTrialScore,
+};
+
+/// Writes `value` as JSON and reads it back.
+fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    let json = serde_json::to_string(value).unwrap();
+    serde_json::from_str(&json).unwrap_or_else(|e| panic!("cannot read back {}: {}", json, e))
+}
+
+fn assert_round_trips<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: T) {
+    assert_eq!(round_trip(&value), value);
+}
+
+fn code(s: &str) -> Code {
+    Code::parse(String::from(s), &GameConfig::default()).unwrap()
+}
+
+fn digits() -> GameConfig {
+    let palette = Palette::from_letters("0123456789").unwrap();
+    GameConfig {
+        colors: palette.values()[..6].to_vec(),
+        palette,
+        ..GameConfig::default()
+    }
+}
+
+#[test]
+fn colors() {
+    for color in GameConfig::default().colors {
+        assert_round_trips(color);
+    }
+    assert!(serde_json::from_str::<Color>(r#""rg""#).is_err());
+    assert!(serde_json::from_str::<Color>(r#""?""#).is_err());
+}
+
+#[test]
+fn codes() {
+    assert_eq!(serde_json::to_string(&code("rgby")).unwrap(), r#""rgby""#);
+    assert_round_trips(code("rgby"));
+    assert_round_trips(code("yyyy"));
+    assert!(serde_json::from_str::<Code>(r#""rgbx""#).is_err());
+    assert!(serde_json::from_str::<Code>("4").is_err());
+}
+
+#[test]
+fn scores() {
+    let score = code("rgby").score(&code("rgyb"));
+    assert_eq!(
+        serde_json::to_string(&score).unwrap(),
+        r#"{"blacks":2,"whites":2}"#
+    );
+    assert_round_trips(score);
+    assert!(serde_json::from_str::<Score>(r#"{"blacks":2}"#).is_err());
+    assert!(serde_json::from_str::<Score>(r#"{"blacks":-1,"whites":0}"#).is_err());
+}
+
+#[test]
+fn rounds() {
+    assert_round_trips(Round::from_guess(code("rrgg"), &code("rgby")));
+    // Five key pegs cannot score a guess of four.
+    let overscored = r#"{"guess":"rrgg","score":{"blacks":4,"whites":1}}"#;
+    assert!(serde_json::from_str::<Round>(overscored).is_err());
+}
+
+#[test]
+fn configs() {
+    assert_round_trips(GameConfig::default());
+    assert_round_trips(digits());
+    let unknown = r#"{"colors":["r","x"],"code_length":4}"#;
+    assert!(serde_json::from_str::<GameConfig>(unknown).is_err());
+}
+
+#[test]
+fn settings() {
+    assert_round_trips(Settings::of(&GameConfig::default()));
+    assert_round_trips(Settings::of(&digits()));
+}
+
+#[test]
+fn boards() {
+    let config = digits();
+    let hidden = Code::from_letters("5052", &config).unwrap();
+    let mut board = Board::new(config.clone(), hidden.clone());
+    board.guess(Code::from_letters("0123", &config).unwrap());
+    board.skip(SkipReason::Timeout);
+    board.guess(Code::from_letters("5052", &config).unwrap());
+
+    let read = round_trip(&board);
+    assert_eq!(read.config(), &config);
+    assert_eq!(read.hidden_code(), &hidden);
+    assert_eq!(read.outcomes(), board.outcomes());
+
+    // A code of the wrong length is refused, whichever code it is.
+    let wrong_length = [
+        r#"{"config":{"colors":["r","g","b","y"],"code_length":4},"hidden_code":"rgb","rounds":[]}"#,
+        r#"{"config":{"colors":["r","g","b","y"],"code_length":4},"hidden_code":"rgby",
+            "rounds":[{"guess":"rgbyr","score":{"blacks":4,"whites":0}}]}"#,
+    ];
+    for json in wrong_length {
+        assert!(serde_json::from_str::<Board>(json).is_err(), "{}", json);
+    }
+    let future =
+        r#"{"version":99,"config":{"colors":["r"],"code_length":1},"hidden_code":"r","rounds":[]}"#;
+    assert!(serde_json::from_str::<Board>(future).is_err());
+}
+
+#[test]
+fn sessions() {
+    let config = digits();
+    let mut board = Board::new(config.clone(), Code::from_letters("5052", &config).unwrap());
+    board.skip(SkipReason::Timeout);
+    board.guess(Code::from_letters("0123", &config).unwrap());
+    let session = GameSession::from_board(&board, 100, Outcome::Forfeit);
+    assert_round_trips(session.clone());
+    assert_round_trips(GameSession {
+        variant: Variant::Static,
+        ..session
+    });
+}
+
+#[test]
+fn dates_and_scoreboards() {
+    let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+    assert_eq!(serde_json::to_string(&date).unwrap(), r#""2024-02-29""#);
+    assert_round_trips(date);
+    assert!(serde_json::from_str::<NaiveDate>(r#""2023-02-29""#).is_err());
+
+    let mut high_scores = HighScoreBoard::default();
+    high_scores.add(HighScore {
+        name: String::from("ada"),
+        guesses: 5,
+        code_length: 4,
+        color_count: 6,
+        date,
+    });
+    assert_round_trips(high_scores);
+
+    let mut trials = TimeTrialBoard::default();
+    trials.add(TimeTrialScore {
+        name: String::from("ada"),
+        solved: 3,
+        guesses: 14,
+        seconds: 180,
+        code_length: 4,
+        color_count: 6,
+        date,
+    });
+    assert_round_trips(trials);
+
+    let mut daily = DailyLog::default();
+    daily.add(DailyRecord {
+        date,
+        played_on: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+        outcome: Outcome::Won,
+        guesses: 4,
+    });
+    assert_round_trips(daily);
+}
+
+#[test]
+fn events() {
+    let events = [
+        GameEvent::GameStarted {
+            config: GameConfig::default(),
+            hidden_code: code("rgby"),
+            seed: Some(u64::MAX),
+        },
+        GameEvent::GameStarted {
+            config: GameConfig::default(),
+            hidden_code: code("rgby"),
+            seed: None,
+        },
+        GameEvent::GuessSubmitted {
+            input: String::from("r g b b"),
+            guess: code("rgbb"),
+        },
+        GameEvent::GuessRejected {
+            input: String::from("rgbx"),
+            reason: String::from("'x' is not a color"),
+        },
+        GameEvent::ScoreComputed {
+            guess: code("rgbb"),
+            score: code("rgby").score(&code("rgbb")),
+        },
+        GameEvent::GameOver {
+            outcome: Outcome::Won,
+            guesses: 2,
+        },
+    ];
+    for (sequence, event) in (1..).zip(events) {
+        assert_round_trips(EventRecord {
+            sequence,
+            timestamp_ms: 1_700_000_000_000,
+            event,
+        });
+    }
+    let unknown = r#"{"sequence":1,"timestamp_ms":0,"event":"game_paused"}"#;
+    assert!(serde_json::from_str::<EventRecord>(unknown).is_err());
+}
+
+#[test]
+#[cfg(feature = "server")]
+fn race_messages() {
+    let config = GameConfig::default();
+    let messages = [
+        Message::Welcome {
+            player: 1,
+            config: config.clone(),
+            token: String::from("abc"),
+        },
+        Message::Resumed {
+            player: 2,
+            config,
+            rounds: vec![Round::from_guess(code("rrgg"), &code("rgby"))],
+            progress: vec![3, 1],
+        },
+        Message::Expired,
+        Message::Scored {
+            round: Round::from_guess(code("rgbb"), &code("rgby")),
+            guesses: 2,
+        },
+        Message::Rejected {
+            reason: String::from("not a code"),
+        },
+        Message::Progress {
+            player: 1,
+            guesses: 4,
+        },
+        Message::Finished {
+            winner: None,
+            guesses: 10,
+            hidden_code: code("rgby"),
+        },
+        Message::Stopped,
+    ];
+    for message in messages {
+        assert_round_trips(message);
+    }
+}